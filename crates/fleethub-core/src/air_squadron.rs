@@ -3,6 +3,7 @@ use wasm_bindgen::prelude::*;
 use crate::{
     gear::Gear,
     gear_array::GearArray,
+    plane::{Plane, PlaneMut},
     types::{AirSquadronMode, GearAttr, GearType, SlotSizeVec},
 };
 
@@ -21,6 +22,71 @@ pub struct AirSquadron {
     pub max_slots: SlotSizeVec,
 }
 
+impl AirSquadron {
+    pub fn planes(&self) -> impl Iterator<Item = Plane<'_>> {
+        self.gears
+            .0
+            .iter()
+            .zip(self.slots.iter())
+            .enumerate()
+            .filter_map(|(index, (gear, &slot_size))| {
+                let gear = gear.as_ref()?;
+
+                gear.has_proficiency().then_some(Plane {
+                    index,
+                    gear,
+                    slot_size,
+                })
+            })
+    }
+
+    pub fn planes_mut(&mut self) -> impl Iterator<Item = PlaneMut<'_>> {
+        self.gears
+            .0
+            .iter_mut()
+            .zip(self.slots.iter_mut())
+            .enumerate()
+            .filter_map(|(index, (gear, slot_size))| {
+                let gear = gear.as_mut()?;
+
+                gear.has_proficiency().then_some(PlaneMut {
+                    index,
+                    gear,
+                    slot_size,
+                })
+            })
+    }
+
+    /// 高高度迎撃時の制空値補正
+    pub fn high_altitude_interception_mod<'a>(
+        squadrons: impl IntoIterator<Item = &'a AirSquadron>,
+    ) -> f64 {
+        let count = squadrons
+            .into_iter()
+            .map(|air_squadron| {
+                air_squadron
+                    .gears
+                    .count_attr(GearAttr::HighAltitudeInterceptor)
+            })
+            .sum::<usize>();
+
+        match count {
+            0 => 0.5,
+            1 => 0.8,
+            2 => 1.1,
+            _ => 1.2,
+        }
+    }
+
+    /// 陸上偵察機による航空攻撃の補正
+    pub fn land_base_recon_mod(&self) -> f64 {
+        self.planes()
+            .filter(|plane| plane.gear_type == GearType::LbRecon && plane.slot_size > Some(0))
+            .map(|plane| if plane.los >= 9 { 1.15 } else { 1.125 })
+            .fold(1.0, f64::max)
+    }
+}
+
 #[wasm_bindgen]
 impl AirSquadron {
     pub fn get_gear(&self, key: &str) -> Option<Gear> {
@@ -7,29 +7,45 @@ mod comp_analyzer;
 mod contact_analyzer;
 mod damage_report;
 mod day_cutin_analyzer;
+mod expedition_analyzer;
 mod fleet_cutin_analyzer;
 mod night_cutin_analyzer;
 mod node_attack_analyzer;
+mod routing_analyzer;
 mod ship_analyzer;
 
+use rand::prelude::*;
 use wasm_bindgen::prelude::*;
 
 pub use action_report::*;
+pub(crate) use anti_air_analyzer::calc_ship_anti_air_cutin_chance;
 pub use attack_analyzer::*;
 pub use attack_analyzer_config::*;
 pub use attack_report::*;
 pub use comp_analyzer::*;
 pub use damage_report::*;
+pub use expedition_analyzer::*;
 pub use fleet_cutin_analyzer::*;
 pub use node_attack_analyzer::*;
+pub use routing_analyzer::*;
 pub use ship_analyzer::*;
 
 use crate::{
     battle::BattleComp,
     comp::Comp,
+    org::Org,
     ship::Ship,
-    simulator::{SimulatorResult, SupportShellingSimulator},
-    types::BattleDefinitions,
+    simulator::{
+        AirRaidSimulator, AirRaidSimulatorResult, BattleSimulator, BattleSimulatorResult,
+        EngagementSimulatorResult, LandBaseSimulator, LandBaseSimulatorResult, SimulatorResult,
+        SortieConfig, SortieNode, SortieSimulator, SortieSimulatorResult, SupportConfig,
+        SupportShellingSimulator, SupportSimulator, SupportSimulatorResult, allocate_times,
+        seeded_rng,
+    },
+    types::{
+        BattleDefinitions, BattleType, EngagementRates, FormationRate, GunfitAccuracyInfo,
+        HistoricalBonusRuleSet,
+    },
 };
 
 #[wasm_bindgen]
@@ -41,10 +57,195 @@ impl Analyzer {
     pub fn new(battle_defs: BattleDefinitions) -> Self {
         Self { battle_defs }
     }
+
+    pub fn battle_defs(&self) -> &BattleDefinitions {
+        &self.battle_defs
+    }
+
+    pub fn try_simulate_support_shelling<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        player_comp: &Comp,
+        enemy_comp: &Comp,
+        config: &NodeAttackAnalyzerConfig,
+        times: usize,
+    ) -> anyhow::Result<SimulatorResult> {
+        let mut player = BattleComp::new(player_comp.clone(), config.left.formation);
+        let mut enemy = BattleComp::new(enemy_comp.clone(), config.right.formation);
+
+        SupportShellingSimulator::new(
+            rng,
+            &self.battle_defs,
+            &mut player,
+            &mut enemy,
+            config.engagement,
+            config.node_state,
+        )
+        .run(times)
+    }
+
+    pub fn try_simulate_support<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        player_comp: &Comp,
+        enemy_comp: &Comp,
+        config: &NodeAttackAnalyzerConfig,
+        support_config: SupportConfig,
+        times: usize,
+    ) -> anyhow::Result<SupportSimulatorResult> {
+        let mut player = BattleComp::new(player_comp.clone(), config.left.formation);
+        let mut enemy = BattleComp::new(enemy_comp.clone(), config.right.formation);
+
+        SupportSimulator::new(
+            rng,
+            &self.battle_defs,
+            &mut player,
+            &mut enemy,
+            config.engagement,
+            config.node_state,
+            support_config,
+        )?
+        .run(times)
+    }
+
+    /// `engagement_rates`か`right_formation_rates`を指定した場合は、試行回数を発生率に比例して割り振る
+    pub fn try_simulate_battle<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        player_comp: &Comp,
+        enemy_comp: &Comp,
+        config: &NodeAttackAnalyzerConfig,
+        battle_type: BattleType,
+        times: usize,
+    ) -> anyhow::Result<BattleSimulatorResult> {
+        let simulate = |rng: &mut R, engagement, enemy_formation, times| {
+            let mut player = BattleComp::new(player_comp.clone(), config.left.formation);
+            let mut enemy = BattleComp::new(enemy_comp.clone(), enemy_formation);
+
+            BattleSimulator::new(
+                rng,
+                &self.battle_defs,
+                &mut player,
+                &mut enemy,
+                battle_type,
+                engagement,
+                config.node_state,
+            )
+            .run(times)
+        };
+
+        if config.engagement_rates.is_none() && config.right_formation_rates.is_empty() {
+            return simulate(rng, config.engagement, config.right.formation, times);
+        }
+
+        let engagement_rates = config
+            .engagement_rates
+            .unwrap_or_else(|| EngagementRates::only(config.engagement))
+            .with_saiun(player_comp.has_saiun());
+
+        let formation_rates = if config.right_formation_rates.is_empty() {
+            vec![FormationRate {
+                formation: config.right.formation,
+                rate: 1.0,
+            }]
+        } else {
+            config.right_formation_rates.clone()
+        };
+
+        let cases = engagement_rates
+            .normalized()
+            .flat_map(|(engagement, engagement_rate)| {
+                formation_rates.iter().map(move |formation_rate| {
+                    let rate = engagement_rate * formation_rate.rate.max(0.0);
+                    (engagement, formation_rate.formation, rate)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let weights = cases.iter().map(|(_, _, rate)| *rate).collect::<Vec<_>>();
+
+        anyhow::ensure!(
+            engagement_rates.iter().any(|(_, rate)| rate > 0.0)
+                && weights.iter().sum::<f64>() > 0.0,
+            "交戦形態と陣形の発生率が全て0です"
+        );
+
+        let results = cases
+            .into_iter()
+            .zip(allocate_times(&weights, times))
+            .filter(|(_, times)| *times > 0)
+            .map(|((engagement, formation, _), times)| {
+                let mut result = simulate(rng, engagement, formation, times)?;
+
+                result.engagements = vec![EngagementSimulatorResult {
+                    engagement,
+                    times,
+                    rate: 1.0,
+                    result: result.clone(),
+                }];
+
+                Ok((result, times))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        anyhow::ensure!(!results.is_empty(), "試行回数が0です");
+
+        Ok(BattleSimulatorResult::merge(results))
+    }
+
+    pub fn try_simulate_sortie<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        player_comp: &Comp,
+        nodes: &[SortieNode],
+        config: &SortieConfig,
+        times: usize,
+    ) -> anyhow::Result<SortieSimulatorResult> {
+        let mut player = BattleComp::new(player_comp.clone(), Default::default());
+
+        SortieSimulator::new(rng, &self.battle_defs, &mut player, nodes, config).run(times)
+    }
+
+    pub fn try_simulate_land_base<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        org: &Org,
+        enemy_comp: &Comp,
+        config: &NodeAttackAnalyzerConfig,
+        times: usize,
+    ) -> anyhow::Result<LandBaseSimulatorResult> {
+        let mut enemy = BattleComp::new(enemy_comp.clone(), config.right.formation);
+        let squadrons = vec![org.a1.clone(), org.a2.clone(), org.a3.clone()];
+
+        LandBaseSimulator::new(rng, &self.battle_defs, squadrons, &mut enemy).run(times)
+    }
+
+    pub fn try_simulate_air_raid<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        org: &Org,
+        enemy_comp: &Comp,
+        high_altitude: bool,
+        times: usize,
+    ) -> anyhow::Result<AirRaidSimulatorResult> {
+        let mut enemy = enemy_comp.clone();
+        let squadrons = vec![org.a1.clone(), org.a2.clone(), org.a3.clone()];
+
+        AirRaidSimulator::new(rng, squadrons, &mut enemy, high_altitude).run(times)
+    }
 }
 
 #[wasm_bindgen]
 impl Analyzer {
+    /// マスターデータの特効に加えて適用する定義を追加する
+    pub fn add_historical_bonus_rule_set(&mut self, rule_set: HistoricalBonusRuleSet) {
+        self.battle_defs.historical_bonus_rule_sets.push(rule_set);
+    }
+
+    pub fn clear_historical_bonus_rule_sets(&mut self) {
+        self.battle_defs.historical_bonus_rule_sets.clear();
+    }
+
     pub fn analyze_ship_attack(
         &self,
         config: ShipAnalyzerConfig,
@@ -94,30 +295,122 @@ impl Analyzer {
         CompAnalyzer::new(&self.battle_defs, comp, config).analyze()
     }
 
+    /// フィット砲補正
+    pub fn ship_gunfit_accuracy(&self, ship: &Ship, is_night: bool) -> f64 {
+        self.battle_defs.get_gunfit_accuracy(ship, is_night)
+    }
+
+    /// 装備ごとのフィット砲補正
+    pub fn ship_gunfit_accuracy_info(&self, ship: &Ship, is_night: bool) -> GunfitAccuracyInfo {
+        self.battle_defs.get_gunfit_accuracy_info(ship, is_night)
+    }
+
     pub fn simulate_support_shelling(
         &self,
         player_comp: &Comp,
         enemy_comp: &Comp,
         config: NodeAttackAnalyzerConfig,
         times: usize,
+        seed: Option<u64>,
     ) -> Result<SimulatorResult, JsValue> {
-        use rand::prelude::*;
+        let (mut rng, seed) = seeded_rng(seed);
 
-        let mut rng = SmallRng::from_entropy();
-        let mut player = BattleComp::new(player_comp.clone(), config.left.formation);
-        let mut enemy = BattleComp::new(enemy_comp.clone(), config.right.formation);
+        self.try_simulate_support_shelling(&mut rng, player_comp, enemy_comp, &config, times)
+            .map(|mut result| {
+                result.seed = Some(seed);
+                result
+            })
+            .map_err(|err| JsValue::from(&err.to_string()))
+    }
+
+    /// `support_type`を省略した場合は支援艦隊の編成から種別を判定する
+    pub fn simulate_support(
+        &self,
+        player_comp: &Comp,
+        enemy_comp: &Comp,
+        config: NodeAttackAnalyzerConfig,
+        support_config: SupportConfig,
+        times: usize,
+        seed: Option<u64>,
+    ) -> Result<SupportSimulatorResult, JsValue> {
+        let (mut rng, seed) = seeded_rng(seed);
 
-        let mut simulator = SupportShellingSimulator::new(
+        self.try_simulate_support(
             &mut rng,
-            &self.battle_defs,
-            &mut player,
-            &mut enemy,
-            config.engagement,
-            config.node_state,
-        );
+            player_comp,
+            enemy_comp,
+            &config,
+            support_config,
+            times,
+        )
+        .map(|mut result| {
+            result.seed = Some(seed);
+            result
+        })
+        .map_err(|err| JsValue::from(&err.to_string()))
+    }
 
-        simulator
-            .run(times)
+    pub fn simulate_battle(
+        &self,
+        player_comp: &Comp,
+        enemy_comp: &Comp,
+        config: NodeAttackAnalyzerConfig,
+        battle_type: BattleType,
+        times: usize,
+        seed: Option<u64>,
+    ) -> Result<BattleSimulatorResult, JsValue> {
+        let (mut rng, seed) = seeded_rng(seed);
+
+        self.try_simulate_battle(
+            &mut rng,
+            player_comp,
+            enemy_comp,
+            &config,
+            battle_type,
+            times,
+        )
+        .map(|mut result| {
+            result.seed = Some(seed);
+            result
+        })
+        .map_err(|err| JsValue::from(&err.to_string()))
+    }
+
+    /// 基地航空隊の各波を敵艦隊に対して実行する
+    pub fn simulate_land_base(
+        &self,
+        org: &Org,
+        enemy_comp: &Comp,
+        config: NodeAttackAnalyzerConfig,
+        times: usize,
+        seed: Option<u64>,
+    ) -> Result<LandBaseSimulatorResult, JsValue> {
+        let (mut rng, seed) = seeded_rng(seed);
+
+        self.try_simulate_land_base(&mut rng, org, enemy_comp, &config, times)
+            .map(|mut result| {
+                result.seed = Some(seed);
+                result
+            })
+            .map_err(|err| JsValue::from(&err.to_string()))
+    }
+
+    /// 敵基地空襲に対する防空戦を実行する
+    pub fn simulate_air_raid(
+        &self,
+        org: &Org,
+        enemy_comp: &Comp,
+        high_altitude: bool,
+        times: usize,
+        seed: Option<u64>,
+    ) -> Result<AirRaidSimulatorResult, JsValue> {
+        let (mut rng, seed) = seeded_rng(seed);
+
+        self.try_simulate_air_raid(&mut rng, org, enemy_comp, high_altitude, times)
+            .map(|mut result| {
+                result.seed = Some(seed);
+                result
+            })
             .map_err(|err| JsValue::from(&err.to_string()))
     }
 }
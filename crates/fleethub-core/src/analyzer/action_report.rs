@@ -60,4 +60,27 @@ impl<T: AttackStyleKey> ActionReport<T> {
             is_active: true,
         }
     }
+    /// 発動率で重み付けしたダメージの期待値
+    pub fn expected_damage(&self) -> Option<f64> {
+        self.data
+            .values()
+            .map(|report| {
+                let proc_rate = report.proc_rate?;
+                let damage = report
+                    .damage
+                    .as_ref()?
+                    .damage_density
+                    .iter()
+                    .map(|(damage, rate)| *damage as f64 * rate)
+                    .sum::<f64>();
+
+                Some(proc_rate * damage)
+            })
+            .sum()
+    }
+
+    pub fn sunk_rate(&self) -> Option<f64> {
+        let density = self.damage_state_density.as_ref()?;
+        Some(density.get(&DamageState::Sunk).copied().unwrap_or_default())
+    }
 }
@@ -71,7 +71,10 @@ impl AntiAirAnalyzer<'_> {
                     fixed_shotdown_number: air_defense
                         .fixed_shotdown_number(ship_anti_air_resist, fleet_anti_air_resist),
                     guaranteed: air_defense.guaranteed(),
-                    anti_air_cutin_chance: self.calc_ship_anti_air_cutin_chance(&member),
+                    anti_air_cutin_chance: calc_ship_anti_air_cutin_chance(
+                        self.battle_defs,
+                        &member,
+                    ),
                     anti_air_propellant_barrage_chance: air_defense
                         .anti_air_propellant_barrage_chance(),
                 }
@@ -116,48 +119,52 @@ impl AntiAirAnalyzer<'_> {
     fn get_anti_air_cutin_def(&self, id: u8) -> Option<&AntiAirCutinDef> {
         self.battle_defs.anti_air_cutin.get(&id)
     }
+}
 
-    fn calc_ship_anti_air_cutin_chance(&self, ship: &Ship) -> Vec<(u8, f64)> {
-        let (sequential_cis, normal_cis) = ship
-            .get_possible_anti_air_cutin_ids()
-            .into_iter()
-            .filter_map(|id| self.get_anti_air_cutin_def(id))
-            .partition::<Vec<_>, _>(|aaci| aaci.is_sequential());
-
-        let mut total_sequential_rate = 0.0;
-
-        #[allow(clippy::needless_collect)]
-        let sequential_ci_vec = sequential_cis
-            .into_iter()
-            .filter_map(|aaci| {
-                let current = aaci.rate()?;
-                let actual = ((1.0 - total_sequential_rate) * current).min(1.0);
-                total_sequential_rate += actual;
+/// 艦ごとの対空カットインの種別と発動率
+pub(crate) fn calc_ship_anti_air_cutin_chance(
+    battle_defs: &BattleDefinitions,
+    ship: &Ship,
+) -> Vec<(u8, f64)> {
+    let (sequential_cis, normal_cis) = ship
+        .get_possible_anti_air_cutin_ids()
+        .into_iter()
+        .filter_map(|id| battle_defs.anti_air_cutin.get(&id))
+        .partition::<Vec<_>, _>(|aaci| aaci.is_sequential());
+
+    let mut total_sequential_rate = 0.0;
+
+    #[allow(clippy::needless_collect)]
+    let sequential_ci_vec = sequential_cis
+        .into_iter()
+        .filter_map(|aaci| {
+            let current = aaci.rate()?;
+            let actual = ((1.0 - total_sequential_rate) * current).min(1.0);
+            total_sequential_rate += actual;
 
-                Some((aaci.id, actual))
-            })
-            .collect::<Vec<_>>();
+            Some((aaci.id, actual))
+        })
+        .collect::<Vec<_>>();
 
-        let normal_ci_iter = normal_cis.into_iter().scan(0.0, |prev, aaci| {
-            let current = aaci.rate()?;
+    let normal_ci_iter = normal_cis.into_iter().scan(0.0, |prev, aaci| {
+        let current = aaci.rate()?;
 
-            if current < *prev {
-                return Some((aaci.id, 0.0));
-            }
+        if current < *prev {
+            return Some((aaci.id, 0.0));
+        }
 
-            let mut actual = current - *prev;
+        let mut actual = current - *prev;
 
-            if total_sequential_rate > 0.0 {
-                actual *= 1.0 - total_sequential_rate;
-            }
+        if total_sequential_rate > 0.0 {
+            actual *= 1.0 - total_sequential_rate;
+        }
 
-            *prev = current;
-            Some((aaci.id, actual))
-        });
+        *prev = current;
+        Some((aaci.id, actual))
+    });
 
-        sequential_ci_vec
-            .into_iter()
-            .chain(normal_ci_iter)
-            .collect::<Vec<_>>()
-    }
+    sequential_ci_vec
+        .into_iter()
+        .chain(normal_ci_iter)
+        .collect::<Vec<_>>()
 }
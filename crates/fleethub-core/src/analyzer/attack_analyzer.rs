@@ -10,9 +10,10 @@ use crate::{
     ship::{NightCutinTermParams, Ship},
     types::{
         AswAttackStyle, AswPhase, AttackType, BattleDefinitions, DayPhaseAttackStyle,
-        DayPhaseAttackType, FormationParams, HistoricalParams, NightAttackStyle, NightAttackType,
-        NightPhaseAttackStyle, NightPhaseAttackType, ShellingStyle, ShellingType,
-        SupportShellingStyle, SupportShellingType, TorpedoAttackStyle, TorpedoAttackType,
+        DayPhaseAttackType, FormationParams, HistoricalBonusRef, HistoricalParams,
+        NightAttackStyle, NightAttackType, NightPhaseAttackStyle, NightPhaseAttackType,
+        ShellingStyle, ShellingType, SpecialEnemyModifiers, SupportShellingStyle,
+        SupportShellingType, TorpedoAttackStyle, TorpedoAttackType,
     },
     utils::some_or_return,
 };
@@ -33,6 +34,8 @@ pub struct AttackAnalysis {
     pub attacker_ship_id: u16,
     pub target_ship_id: u16,
     pub historical_params: HistoricalParams,
+    /// 適用された特効の定義
+    pub historical_bonuses: Vec<HistoricalBonusRef>,
 
     pub day: ActionReport<DayPhaseAttackStyle>,
     pub night: ActionReport<NightPhaseAttackStyle>,
@@ -50,12 +53,18 @@ impl AttackAnalyzer<'_> {
         let support_shelling = self.analyze_support_shelling();
 
         let historical_params = self.get_historical_params();
+        let historical_bonuses = self.battle_defs.get_historical_bonus_refs(
+            self.config.node_state,
+            &self.attacker_combat_ship(),
+            &self.target_combat_ship(),
+        );
 
         AttackAnalysis {
             attacker_is_player: self.config.attacker.side().is_player(),
             attacker_ship_id: self.attacker.ship_id,
             target_ship_id: self.target.ship_id,
             historical_params,
+            historical_bonuses,
 
             day,
             night,
@@ -93,6 +102,18 @@ impl AttackAnalyzer<'_> {
         )
     }
 
+    fn get_special_enemy_mods(&self, attack_type: impl Into<AttackType>) -> SpecialEnemyModifiers {
+        self.battle_defs.get_special_enemy_mods(
+            self.attacker,
+            self.target.special_enemy_type(),
+            attack_type,
+        )
+    }
+
+    fn get_gunfit_accuracy(&self, is_night: bool) -> f64 {
+        self.battle_defs.get_gunfit_accuracy(self.attacker, is_night)
+    }
+
     fn get_historical_params(&self) -> HistoricalParams {
         self.battle_defs.get_historical_params(
             self.config.node_state,
@@ -163,6 +184,7 @@ impl AttackAnalyzer<'_> {
         let node_state = self.config.node_state;
         let formation_params = self.get_formation_params(style.to_attack_type());
         let historical_params = self.get_historical_params();
+        let special_enemy_mods = self.get_special_enemy_mods(style.to_attack_type());
 
         let attack_params = DayPhaseAttackParams {
             style: style.clone(),
@@ -171,6 +193,8 @@ impl AttackAnalyzer<'_> {
             target: &self.target_combat_ship(),
             formation_params,
             historical_params,
+            special_enemy_mods,
+            gunfit_accuracy: self.get_gunfit_accuracy(false),
             node_state,
             balloons: self.config.attacker.balloons,
         }
@@ -209,6 +233,8 @@ impl AttackAnalyzer<'_> {
                     target,
                     formation_params,
                     historical_params,
+                    special_enemy_mods: self.get_special_enemy_mods(style.to_attack_type()),
+                    gunfit_accuracy: self.get_gunfit_accuracy(false),
                     node_state,
                     balloons: self.config.attacker.balloons,
                 }
@@ -249,6 +275,8 @@ impl AttackAnalyzer<'_> {
                     target,
                     formation_params,
                     historical_params,
+                    special_enemy_mods: self.get_special_enemy_mods(style.to_attack_type()),
+                    gunfit_accuracy: self.get_gunfit_accuracy(true),
                     night_conditions,
                 }
                 .calc_attack_params();
@@ -348,6 +376,7 @@ impl AttackAnalyzer<'_> {
             engagement,
             formation_params,
             historical_params,
+            special_enemy_mods: self.get_special_enemy_mods(attack_type),
             node_state,
         }
         .calc_attack_params();
@@ -401,6 +430,7 @@ impl AttackAnalyzer<'_> {
         };
 
         let formation_params = self.get_formation_params(attack_type);
+        let special_enemy_mods = self.get_special_enemy_mods(attack_type);
 
         let params = SupportShellingAttackParams {
             attack_type,
@@ -408,6 +438,7 @@ impl AttackAnalyzer<'_> {
             target,
             engagement: self.config.engagement,
             formation_params,
+            special_enemy_mods,
             node_state: self.config.node_state,
         }
         .calc_attack_params();
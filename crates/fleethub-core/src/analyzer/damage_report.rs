@@ -2,8 +2,7 @@ use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 
 use crate::{
-    attack::{Attack, AttackPower, HitRate},
-    attack::{Damage, DefenseParams, HitType},
+    attack::{Attack, Damage, DefenseParams, HitType, HpDistribution},
     types::DamageState,
     utils::Histogram,
 };
@@ -18,13 +17,15 @@ pub struct DamageReport {
     pub critical_damage_max: u16,
     pub normal_scratch_rate: f64,
     pub critical_scratch_rate: f64,
+    /// 合計ダメージの分布、残り耐久を超えるダメージも含む
     pub damage_density: Histogram<u16, f64>,
+    /// 攻撃後の残り耐久の分布
+    pub hp_density: Histogram<u16, f64>,
     pub damage_state_density: Histogram<DamageState, f64>,
 }
 
 impl DamageReport {
     pub fn new(attack: &Attack) -> Option<Self> {
-        let hits = attack.hits;
         let is_cutin = attack.is_cutin;
         let attack_power = attack.attack_power.as_ref()?;
         let defense_params = attack.defense_params.as_ref()?;
@@ -51,34 +52,13 @@ impl DamageReport {
         let normal_scratch_rate = normal.scratch_rate();
         let critical_scratch_rate = critical.scratch_rate();
 
-        let damage_density = attack
-            .hit_rate
-            .as_ref()
-            .map(|hit_rate| {
-                DamageAnalyzer {
-                    attack_power,
-                    defense_params,
-                    hit_rate,
-                    hits,
-                    is_cutin,
-                }
-                .density()
-            })
-            .unwrap_or_default();
-
-        let &DefenseParams {
-            current_hp, max_hp, ..
-        } = defense_params;
-
-        let damage_state_density = damage_density
-            .iter()
-            .map(|(damage, rate)| {
-                (
-                    DamageState::new(max_hp, current_hp.saturating_sub(*damage)),
-                    *rate,
-                )
-            })
-            .collect();
+        let (damage_density, hp_density, damage_state_density) =
+            damage_densities(attack, defense_params)
+                .map(|(damage_density, dist)| {
+                    let damage_state_density = dist.damage_state_density();
+                    (damage_density, dist.hp_density, damage_state_density)
+                })
+                .unwrap_or_default();
 
         Some(Self {
             miss_damage_min,
@@ -90,81 +70,67 @@ impl DamageReport {
             normal_scratch_rate,
             critical_scratch_rate,
             damage_density,
+            hp_density,
             damage_state_density,
         })
     }
 }
 
-struct DamageAnalyzer<'a> {
-    attack_power: &'a AttackPower,
-    hit_rate: &'a HitRate,
-    defense_params: &'a DefenseParams,
-    is_cutin: bool,
-    hits: f64,
-}
-
-impl<'a> DamageAnalyzer<'a> {
-    fn to_damage(&self, hit_type: HitType, current_hp: u16) -> Damage {
-        Damage {
-            hit_type,
-            attack_term: self.attack_power.get_attack_term(hit_type),
-            remaining_ammo_mod: self.attack_power.remaining_ammo_mod,
-            current_hp,
-            basic_defense_power: self.defense_params.basic_defense_power,
-            overkill_protection: self.defense_params.overkill_protection,
-            sinkable: self.defense_params.sinkable,
-            is_cutin: self.is_cutin,
-        }
-    }
-
-    fn once(&self, current_hp: u16) -> Histogram<u16, f64> {
-        self.hit_rate
-            .iter()
-            .map(|(hit_type, rate)| {
-                let damage = self.to_damage(hit_type, current_hp);
-                damage.density() * rate
-            })
-            .sum()
-    }
+/// 合計ダメージと残り耐久の分布
+///
+/// 複数回の攻撃は攻撃直前の耐久ごとのダメージを畳み込み、撃沈後の攻撃は行わない
+/// `hits`の端数は`HpDistribution::apply`と同じく最後の1回が発生する確率として扱う
+fn damage_densities(
+    attack: &Attack,
+    defense_params: &DefenseParams,
+) -> Option<(Histogram<u16, f64>, HpDistribution)> {
+    let hits = attack.hits.max(0.0);
+    let count = hits.ceil() as usize;
+
+    // (残り耐久, 合計ダメージ) の同時分布
+    let mut joint = Histogram::from_iter([((defense_params.current_hp, 0_u16), 1.0)]);
+
+    for i in 0..count {
+        let rate = (hits - i as f64).min(1.0);
+        let mut next = Histogram::new();
+
+        for (&(hp, total), &joint_rate) in joint.iter() {
+            if hp == 0 {
+                next += ((hp, total), joint_rate);
+                continue;
+            }
 
-    fn density(&self) -> Histogram<u16, f64> {
-        let hp1 = self.defense_params.current_hp;
-        let mut density1 = self.once(hp1);
+            if rate < 1.0 {
+                next += ((hp, total), joint_rate * (1.0 - rate));
+            }
 
-        if self.hits <= 1.0 {
-            return density1;
+            for (damage, damage_rate) in attack.damage_density(hp)? {
+                next += (
+                    (hp.saturating_sub(damage), total.saturating_add(damage)),
+                    joint_rate * rate * damage_rate,
+                );
+            }
         }
 
-        let max_hits = self.hits.ceil() as usize;
-        let max_hits_rate = self.hits.fract();
+        joint = next;
+    }
 
-        for h in 1..max_hits {
-            let density2 = density1
-                .iter()
-                .flat_map(|(&damage_value1, rate1)| {
-                    let hp2 = hp1.saturating_sub(damage_value1);
-                    let density2 = self.once(hp2);
+    let mut damage_density = Histogram::new();
+    let mut dist = HpDistribution::from_defense_params(defense_params);
+    dist.hp_density = Histogram::new();
 
-                    density2.into_iter().map(move |(damage_value2, rate2)| {
-                        (damage_value1 + damage_value2, rate1 * rate2)
-                    })
-                })
-                .collect::<Histogram<u16, f64>>();
+    joint.into_iter().for_each(|((hp, total), rate)| {
+        damage_density += (total, rate);
+        dist.hp_density += (hp, rate);
+    });
 
-            if h == max_hits && max_hits_rate > 0.0 {
-                density1 = density1 * (1.0 - max_hits_rate) + density2 * max_hits_rate;
-            } else {
-                density1 = density2
-            }
-        }
-
-        density1
-    }
+    Some((damage_density, dist))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::attack::{AttackPower, HitRate};
 
     #[test]
     fn test_damage_report() {
@@ -202,4 +168,73 @@ mod test {
         assert_eq!(result.normal_damage_min, (1000.0 - 500.0 * 1.3) as u16);
         assert_eq!(result.normal_damage_max, (1000.0 - 500.0 * 0.7) as u16);
     }
+
+    fn assert_close<K: Eq + std::hash::Hash + std::fmt::Debug>(
+        left: &Histogram<K, f64>,
+        right: &Histogram<K, f64>,
+    ) {
+        assert_eq!(left.len(), right.len());
+        left.iter().for_each(|(key, rate)| {
+            let other = right.get(key).copied().unwrap_or_default();
+            assert!((rate - other).abs() < 1e-10, "{key:?}: {rate} != {other}");
+        });
+    }
+
+    #[test]
+    fn test_damage_report_hits() {
+        let attack = |hits: f64| Attack {
+            attack_power: Some(AttackPower {
+                normal: 40.0,
+                critical: 60.0,
+                remaining_ammo_mod: 1.0,
+                ..Default::default()
+            }),
+            defense_params: Some(DefenseParams {
+                basic_defense_power: 20.0,
+                current_hp: 40,
+                max_hp: 40,
+                sinkable: true,
+                overkill_protection: false,
+            }),
+            hit_rate: Some(HitRate {
+                normal: 0.6,
+                critical: 0.2,
+                total: 0.8,
+            }),
+            hits,
+            is_cutin: false,
+        };
+
+        let expected_damage = |report: &DamageReport| {
+            report
+                .damage_density
+                .iter()
+                .map(|(&damage, &rate)| damage as f64 * rate)
+                .sum::<f64>()
+        };
+
+        let single = DamageReport::new(&attack(1.0)).unwrap();
+        let double = DamageReport::new(&attack(2.0)).unwrap();
+        let partial = DamageReport::new(&attack(1.5)).unwrap();
+
+        // 1回の攻撃のダメージ分布はそのまま
+        assert_eq!(
+            single.damage_density,
+            attack(1.0).damage_density(40).unwrap()
+        );
+
+        // 残り耐久の分布と一致する
+        let mut dist = HpDistribution::new(40, 40);
+        dist.apply(&attack(2.0)).unwrap();
+        assert_close(&double.hp_density, &dist.hp_density);
+        assert_close(&double.damage_state_density, &dist.damage_state_density());
+
+        // 合計ダメージは残り耐久で頭打ちにならない
+        assert!(double.damage_density.keys().any(|&damage| damage > 40));
+        assert!(expected_damage(&double) > 40.0 - dist.expected_hp());
+
+        // 端数の攻撃回数は最後の1回が発生する確率として扱う
+        assert!(expected_damage(&single) < expected_damage(&partial));
+        assert!(expected_damage(&partial) < expected_damage(&double));
+    }
 }
@@ -0,0 +1,303 @@
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::{
+    fleet::Fleet,
+    master_data::{ExpeditionGreatSuccessType, ExpeditionResources, MasterExpedition},
+    ship::Ship,
+    types::{MoraleState, gear_id},
+};
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct ExpeditionRequirementResult {
+    pub name: String,
+    pub value: f64,
+    pub required: f64,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct ExpeditionReport {
+    pub id: u16,
+    pub name: String,
+    pub duration: u32,
+    pub requirements: Vec<ExpeditionRequirementResult>,
+    /// 全ての条件を満たす
+    pub passed: bool,
+    pub great_success_rate: f64,
+    /// 大成功率と遠征ボーナスを反映した1回あたりの期待値
+    pub income: ExpeditionResources,
+    pub income_per_hour: ExpeditionResources,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct ExpeditionAnalysis {
+    pub sparkle_count: usize,
+    pub expedition_bonus: f64,
+    pub expeditions: Vec<ExpeditionReport>,
+}
+
+fn count_drums(ship: &Ship) -> usize {
+    ship.gears.count(gear_id!("ドラム缶(輸送用)"))
+}
+
+/// 艦隊が各遠征の条件を満たすかと、大成功率、収入を求める
+pub struct ExpeditionAnalyzer<'a> {
+    pub fleet: &'a Fleet,
+}
+
+impl ExpeditionAnalyzer<'_> {
+    fn ships(&self) -> impl Iterator<Item = &Ship> {
+        self.fleet.ships.values()
+    }
+
+    fn flagship(&self) -> Option<&Ship> {
+        self.fleet.ships.get(0)
+    }
+
+    fn sparkle_count(&self) -> usize {
+        self.ships()
+            .filter(|ship| ship.morale_state() == MoraleState::Sparkle)
+            .count()
+    }
+
+    fn drum_count(&self) -> usize {
+        self.ships().map(count_drums).sum()
+    }
+
+    fn check_requirements(
+        &self,
+        expedition: &MasterExpedition,
+    ) -> Vec<ExpeditionRequirementResult> {
+        let requirements = &expedition.requirements;
+        let mut results = vec![];
+
+        let mut push = |name: String, value: f64, required: f64| {
+            results.push(ExpeditionRequirementResult {
+                name,
+                value,
+                required,
+                passed: value >= required,
+            });
+        };
+
+        let flagship_level = self.flagship().map_or(0, |ship| ship.level);
+
+        if let Some(required) = requirements.flagship_level {
+            push(
+                "flagship_level".into(),
+                flagship_level.into(),
+                required.into(),
+            );
+        }
+
+        if !requirements.flagship_ship_types.is_empty() {
+            let matched = self
+                .flagship()
+                .is_some_and(|ship| requirements.flagship_ship_types.contains(&ship.stype()));
+
+            push("flagship_ship_type".into(), matched.into(), 1.0);
+        }
+
+        if let Some(required) = requirements.ship_count {
+            push(
+                "ship_count".into(),
+                self.ships().count() as f64,
+                required as f64,
+            );
+        }
+
+        for requirement in requirements.ship_types.iter() {
+            let count = self
+                .ships()
+                .filter(|ship| requirement.ship_types.contains(&ship.stype()))
+                .count();
+
+            let name = requirement
+                .ship_types
+                .iter()
+                .map(|stype| stype.to_string())
+                .collect::<Vec<_>>()
+                .join("|");
+
+            push(
+                format!("ship_type:{name}"),
+                count as f64,
+                requirement.count as f64,
+            );
+        }
+
+        for requirement in requirements.stats.iter() {
+            let key = requirement.stat.key();
+            let value = self.fleet.sum_ship_stat_by(key).unwrap_or_default();
+            push(key.into(), value, requirement.min);
+        }
+
+        if let Some(required) = requirements.drum_carriers {
+            let carriers = self.ships().filter(|ship| count_drums(ship) > 0).count();
+            push("drum_carriers".into(), carriers as f64, required as f64);
+        }
+
+        if let Some(required) = requirements.drum_count {
+            push(
+                "drum_count".into(),
+                self.drum_count() as f64,
+                required as f64,
+            );
+        }
+
+        results
+    }
+
+    /// 大成功率 (判定は暫定)
+    fn great_success_rate(&self, expedition: &MasterExpedition) -> f64 {
+        let ship_count = self.ships().count();
+        let sparkle_count = self.sparkle_count();
+
+        if ship_count == 0 {
+            return 0.0;
+        }
+
+        let base = 20.0 + 15.0 * sparkle_count as f64;
+
+        let percent = match expedition.great_success_type {
+            ExpeditionGreatSuccessType::Regular => {
+                if sparkle_count < ship_count {
+                    return 0.0;
+                }
+
+                base
+            }
+            ExpeditionGreatSuccessType::Drum => {
+                let required = expedition.great_success_drum_count.unwrap_or_default();
+
+                if self.drum_count() >= required {
+                    base + 20.0
+                } else {
+                    base - 15.0
+                }
+            }
+            ExpeditionGreatSuccessType::FlagshipLevel => {
+                let level = self.flagship().map_or(0, |ship| ship.level) as f64;
+                base - 5.0 + (level.sqrt() + level / 10.0).floor()
+            }
+        };
+
+        (percent / 100.0).clamp(0.0, 1.0)
+    }
+
+    pub fn analyze_expedition(
+        &self,
+        expedition: &MasterExpedition,
+        expedition_bonus: f64,
+    ) -> ExpeditionReport {
+        let requirements = self.check_requirements(expedition);
+        let passed = requirements.iter().all(|requirement| requirement.passed);
+
+        let great_success_rate = if passed {
+            self.great_success_rate(expedition)
+        } else {
+            0.0
+        };
+
+        let income = if passed {
+            expedition
+                .rewards
+                .scale((1.0 + expedition_bonus) * (1.0 + 0.5 * great_success_rate))
+        } else {
+            ExpeditionResources::default()
+        };
+
+        let hours = expedition.duration as f64 / 60.0;
+        let income_per_hour = if hours > 0.0 {
+            income.scale(1.0 / hours)
+        } else {
+            ExpeditionResources::default()
+        };
+
+        ExpeditionReport {
+            id: expedition.id,
+            name: expedition.name.clone(),
+            duration: expedition.duration,
+            requirements,
+            passed,
+            great_success_rate,
+            income,
+            income_per_hour,
+        }
+    }
+
+    pub fn analyze(&self, expeditions: &[MasterExpedition]) -> ExpeditionAnalysis {
+        let expedition_bonus = self.fleet.expedition_bonus();
+
+        ExpeditionAnalysis {
+            sparkle_count: self.sparkle_count(),
+            expedition_bonus,
+            expeditions: expeditions
+                .iter()
+                .map(|expedition| self.analyze_expedition(expedition, expedition_bonus))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expedition_analyzer() {
+        let expedition: MasterExpedition = serde_json::from_value(serde_json::json!({
+            "id": 2,
+            "name": "長距離練習航海",
+            "duration": 30,
+            "requirements": {
+                "flagship_level": 2,
+                "ship_count": 4,
+                "stats": [{ "stat": "level", "min": 10 }],
+            },
+            "rewards": { "ammo": 100, "steel": 30 },
+        }))
+        .unwrap();
+
+        let ship = |level: u16, morale: u8| {
+            let mut ship = Ship::default();
+            ship.level = level;
+            ship.morale = morale;
+            ship.master.stype = 2;
+            ship
+        };
+
+        let mut fleet = Fleet::default();
+        (0..4).for_each(|_| fleet.ships.push(ship(3, 50)));
+
+        let analyzer = ExpeditionAnalyzer { fleet: &fleet };
+        let report = analyzer.analyze_expedition(&expedition, 0.0);
+
+        assert!(report.passed);
+        assert_eq!(report.great_success_rate, 0.8);
+        assert!((report.income.ammo - 140.0).abs() < 1e-9);
+        assert!((report.income_per_hour.ammo - 280.0).abs() < 1e-9);
+
+        fleet.ships.0[3] = Some(ship(1, 49));
+        let analyzer = ExpeditionAnalyzer { fleet: &fleet };
+        let report = analyzer.analyze_expedition(&expedition, 0.0);
+
+        assert!(report.passed);
+        assert_eq!(report.great_success_rate, 0.0);
+
+        fleet.ships.0[3] = None;
+        let analyzer = ExpeditionAnalyzer { fleet: &fleet };
+        let report = analyzer.analyze_expedition(&expedition, 0.0);
+
+        let failed = report
+            .requirements
+            .iter()
+            .filter(|requirement| !requirement.passed)
+            .map(|requirement| requirement.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["ship_count", "level"]);
+        assert_eq!(report.income.ammo, 0.0);
+    }
+}
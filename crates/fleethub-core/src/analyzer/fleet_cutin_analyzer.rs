@@ -3,18 +3,15 @@ use tsify::Tsify;
 
 use crate::{
     analyzer::AttackReport,
-    attack::{
-        calc_fleet_cutin_rate, get_possible_fleet_cutin_effect_vec, FleetCutinAttackParams,
-        FleetCutinEffect,
-    },
+    attack::{FleetCutinAttackParams, FleetCutinEffect},
     attack::{NightAttackParams, ShellingAttackParams},
     comp::Comp,
     member::BattleMemberRef,
     ship::Ship,
     types::{
-        AttackType, BattleDefinitions, DayCutinLike, Engagement, FleetCutin, FleetType, Formation,
-        NightAttackStyle, NightAttackType, NightConditions, NodeState, ShellingStyle, ShellingType,
-        ShipConditions, Time,
+        AttackType, BattleDefinitions, DayCutinLike, Engagement, FleetCutin, FleetCutinTag,
+        FleetType, Formation, NightAttackStyle, NightAttackType, NightConditions, NodeState,
+        ShellingStyle, ShellingType, ShipConditions, Time,
     },
 };
 
@@ -29,6 +26,8 @@ pub struct FleetCutinAttackReport<T> {
 
 #[derive(Debug, Serialize, Tsify)]
 pub struct FleetCutinReport<T> {
+    /// 組み込みの種別にない定義では`cutin`が`Other`になるため、定義の区別にはこちらを使う
+    tag: FleetCutinTag,
     cutin: FleetCutin,
     rate: Option<f64>,
     formation: Formation,
@@ -86,7 +85,8 @@ impl<'a> FleetCutinAnalyzer<'a> {
         let balloons = self.comp.balloons();
 
         let cutin = effect.cutin;
-        let rate = calc_fleet_cutin_rate(fleet, cutin);
+        let rate = self.battle_defs.calc_fleet_cutin_rate(fleet, &effect.tag);
+        let tag = effect.tag;
 
         let attacks = effect
             .attacks
@@ -124,6 +124,12 @@ impl<'a> FleetCutinAnalyzer<'a> {
                     historical_params: self
                         .battle_defs
                         .get_historical_params(node_state, &attacker, target),
+                    special_enemy_mods: self.battle_defs.get_special_enemy_mods(
+                        &attacker,
+                        target.special_enemy_type(),
+                        AttackType::Shelling(Default::default()),
+                    ),
+                    gunfit_accuracy: self.battle_defs.get_gunfit_accuracy(&attacker, false),
                     node_state,
                     balloons,
                 }
@@ -140,6 +146,7 @@ impl<'a> FleetCutinAnalyzer<'a> {
             .collect::<Vec<_>>();
 
         FleetCutinReport {
+            tag,
             cutin,
             rate,
             formation,
@@ -156,7 +163,8 @@ impl<'a> FleetCutinAnalyzer<'a> {
         let fleet_type = self.comp.night_fleet_type();
         let fleet = self.comp.night_fleet();
         let cutin = effect.cutin;
-        let rate = calc_fleet_cutin_rate(fleet, cutin);
+        let rate = self.battle_defs.calc_fleet_cutin_rate(fleet, &effect.tag);
+        let tag = effect.tag;
 
         let attacks = effect
             .attacks
@@ -198,6 +206,12 @@ impl<'a> FleetCutinAnalyzer<'a> {
                         &attacker,
                         target,
                     ),
+                    special_enemy_mods: self.battle_defs.get_special_enemy_mods(
+                        &attacker,
+                        target.special_enemy_type(),
+                        attack_type,
+                    ),
+                    gunfit_accuracy: self.battle_defs.get_gunfit_accuracy(&attacker, true),
                     night_conditions,
                 }
                 .calc_attack_params();
@@ -213,6 +227,7 @@ impl<'a> FleetCutinAnalyzer<'a> {
             .collect::<Vec<_>>();
 
         FleetCutinReport {
+            tag,
             cutin,
             rate,
             formation,
@@ -224,7 +239,7 @@ impl<'a> FleetCutinAnalyzer<'a> {
         Formation::iter()
             .flat_map(|formation| {
                 let fleet = &self.comp.main;
-                let vec = get_possible_fleet_cutin_effect_vec(
+                let vec = self.battle_defs.get_possible_fleet_cutin_effect_vec(
                     fleet,
                     formation,
                     self.engagement,
@@ -244,7 +259,7 @@ impl<'a> FleetCutinAnalyzer<'a> {
         Formation::iter()
             .flat_map(|formation| {
                 let fleet = self.comp.night_fleet();
-                let vec = get_possible_fleet_cutin_effect_vec(
+                let vec = self.battle_defs.get_possible_fleet_cutin_effect_vec(
                     fleet,
                     formation,
                     self.engagement,
@@ -263,7 +278,12 @@ impl<'a> FleetCutinAnalyzer<'a> {
         formation: Formation,
     ) -> Vec<FleetCutinReport<ShellingStyle>> {
         let fleet = &self.comp.main;
-        let vec = get_possible_fleet_cutin_effect_vec(fleet, formation, self.engagement, Time::Day);
+        let vec = self.battle_defs.get_possible_fleet_cutin_effect_vec(
+            fleet,
+            formation,
+            self.engagement,
+            Time::Day,
+        );
 
         vec.into_iter()
             .map(move |effect| self.analyze_shelling(formation, effect))
@@ -276,8 +296,12 @@ impl<'a> FleetCutinAnalyzer<'a> {
         night_conditions: &NightConditions,
     ) -> Vec<FleetCutinReport<NightAttackStyle>> {
         let fleet = self.comp.night_fleet();
-        let vec =
-            get_possible_fleet_cutin_effect_vec(fleet, formation, self.engagement, Time::Night);
+        let vec = self.battle_defs.get_possible_fleet_cutin_effect_vec(
+            fleet,
+            formation,
+            self.engagement,
+            Time::Night,
+        );
 
         vec.into_iter()
             .map(move |effect| self.analyze_night_attack(formation, effect, night_conditions))
@@ -5,8 +5,8 @@ use crate::{
     comp::Comp,
     ship::Ship,
     types::{
-        AirState, BattleDefinitions, Engagement, Formation, NightAttackStyle, NightConditions,
-        NightFleetConditions, NodeState, ShellingStyle, Side,
+        AirState, BattleDefinitions, Engagement, EngagementRates, Formation, FormationRate,
+        NightAttackStyle, NightConditions, NightFleetConditions, NodeState, ShellingStyle, Side,
     },
 };
 
@@ -136,6 +136,12 @@ pub struct NodeAttackAnalyzerConfig {
     pub node_state: NodeState,
     pub left: NodeAttackAnalyzerShipConfig,
     pub right: NodeAttackAnalyzerShipConfig,
+    /// 戦闘のシミュレーションで`engagement`の代わりに使用する発生率
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engagement_rates: Option<EngagementRates>,
+    /// 戦闘のシミュレーションで`right.formation`の代わりに使用する発生率
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub right_formation_rates: Vec<FormationRate>,
 }
 
 impl NodeAttackAnalyzerConfig {
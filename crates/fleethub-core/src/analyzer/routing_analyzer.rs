@@ -0,0 +1,285 @@
+use fasteval::{EvalNamespace, bool_to_f64};
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::{
+    comp::Comp,
+    master_data::{MasterMapRouting, MasterRoutingBranch, MasterRoutingCondition},
+    ship::Ship,
+    types::{FleetType, NodeId},
+};
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct RoutingConditionResult {
+    pub name: String,
+    /// 式を評価できなかった場合は`None`
+    pub value: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub passed: bool,
+    /// 条件を満たすために必要な`value`の変化量
+    pub gap: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct RouteChance {
+    pub node: NodeId,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct RoutingRuleResult {
+    pub routes: Vec<RouteChance>,
+    pub conditions: Vec<RoutingConditionResult>,
+    pub matched: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct RoutingBranchResult {
+    pub node: NodeId,
+    /// 進む可能性のある行き先と確率
+    pub routes: Vec<RouteChance>,
+    /// 判定した規則、最後の規則以外は条件を満たさなかった理由を示す
+    pub rules: Vec<RoutingRuleResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct RoutingAnalysis {
+    pub map: i16,
+    pub branches: Vec<RoutingBranchResult>,
+}
+
+fn count_ships<F>(comp: &Comp, args: &[f64], f: F) -> f64
+where
+    F: Fn(&Ship) -> f64,
+{
+    comp.members_by(FleetType::Main | FleetType::Escort)
+        .filter(|member| args.contains(&f(member.ship)))
+        .count() as f64
+}
+
+/// 艦隊全体に関する変数と関数
+fn comp_ns<'a>(comp: &'a Comp, phase: u8) -> impl EvalNamespace + 'a {
+    move |name: &str, args: Vec<f64>| -> Option<f64> {
+        let ships = || comp.members_by(FleetType::Main | FleetType::Escort);
+
+        let result = match name {
+            "phase" => phase.into(),
+            "hq_level" => comp.hq_level.into(),
+            "is_combined" => bool_to_f64!(comp.is_combined()),
+            "ship_count" => ships().count() as f64,
+            "transport_point" => comp.transport_point().into(),
+            "elos" => comp.elos(args.first().map_or(1, |factor| *factor as u8))?,
+            "speed" => ships().map(|member| member.ship.speed()).min()?.into(),
+
+            "flagship_ship_id" => comp.main.ships.get(0)?.ship_id.into(),
+            "flagship_ship_type" => comp.main.ships.get(0)?.stype().into(),
+
+            "count_ship_id" => count_ships(comp, &args, |ship| ship.ship_id.into()),
+            "count_ship_type" => count_ships(comp, &args, |ship| ship.stype().into()),
+            "count_ship_class" => count_ships(comp, &args, |ship| ship.ctype.into()),
+            "count_nationality" => count_ships(comp, &args, |ship| ship.master.nationality.into()),
+            "count_speed_below" => {
+                let speed = *args.first()?;
+                ships()
+                    .filter(|member| f64::from(member.ship.speed()) < speed)
+                    .count() as f64
+            }
+
+            "count_gear" => ships()
+                .map(|member| {
+                    args.iter()
+                        .map(|gear_id| member.ship.gears.count(*gear_id as u16))
+                        .sum::<usize>()
+                })
+                .sum::<usize>() as f64,
+            "count_ships_with_gear" => ships()
+                .filter(|member| {
+                    args.iter()
+                        .any(|gear_id| member.ship.gears.has(*gear_id as u16))
+                })
+                .count() as f64,
+
+            _ => return None,
+        };
+
+        Some(result)
+    }
+}
+
+/// 海域の分岐規則から、各分岐点で進む可能性のある行き先と理由を求める
+pub struct RoutingAnalyzer<'a> {
+    pub routing: &'a MasterMapRouting,
+    pub comp: &'a Comp,
+    pub phase: u8,
+}
+
+impl RoutingAnalyzer<'_> {
+    fn eval_condition(&self, condition: &MasterRoutingCondition) -> RoutingConditionResult {
+        let MasterRoutingCondition {
+            expr,
+            min,
+            max,
+            name,
+        } = condition;
+
+        let value = expr.eval(&mut comp_ns(self.comp, self.phase)).ok();
+
+        let gap = value.and_then(|value| {
+            if min.is_none() && max.is_none() {
+                return (value != 1.0).then_some(1.0 - value);
+            }
+
+            if let Some(min) = min.filter(|min| value < *min) {
+                Some(min - value)
+            } else {
+                max.filter(|max| value > *max).map(|max| max - value)
+            }
+        });
+
+        RoutingConditionResult {
+            name: if name.is_empty() {
+                expr.as_str().to_string()
+            } else {
+                name.clone()
+            },
+            value,
+            min: *min,
+            max: *max,
+            passed: value.is_some() && gap.is_none(),
+            gap,
+        }
+    }
+
+    pub fn analyze_branch(&self, branch: &MasterRoutingBranch) -> RoutingBranchResult {
+        let mut rules = vec![];
+
+        for rule in branch.rules.iter() {
+            let conditions = rule
+                .conditions
+                .iter()
+                .map(|condition| self.eval_condition(condition))
+                .collect::<Vec<_>>();
+
+            let matched = conditions.iter().all(|condition| condition.passed);
+
+            let total_rate = rule.routes.iter().map(|route| route.rate).sum::<f64>();
+            let routes = rule
+                .routes
+                .iter()
+                .filter(|route| route.rate > 0.0)
+                .map(|route| RouteChance {
+                    node: route.node,
+                    rate: route.rate / total_rate,
+                })
+                .collect();
+
+            rules.push(RoutingRuleResult {
+                routes,
+                conditions,
+                matched,
+            });
+
+            if matched {
+                break;
+            }
+        }
+
+        let routes = rules
+            .last()
+            .filter(|rule| rule.matched)
+            .map(|rule| rule.routes.clone())
+            .unwrap_or_default();
+
+        RoutingBranchResult {
+            node: branch.node,
+            routes,
+            rules,
+        }
+    }
+
+    pub fn analyze(&self) -> RoutingAnalysis {
+        let branches = self
+            .routing
+            .branches
+            .iter()
+            .map(|branch| self.analyze_branch(branch))
+            .collect();
+
+        RoutingAnalysis {
+            map: self.routing.map,
+            branches,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{fleet::Fleet, types::OrgType};
+
+    #[test]
+    fn test_routing_analyzer() {
+        let routing: MasterMapRouting = serde_json::from_value(serde_json::json!({
+            "map": 11,
+            "branches": [
+                {
+                    "node": "A",
+                    "rules": [
+                        {
+                            "conditions": [{ "expr": "ship_count", "min": 7, "name": "隻数" }],
+                            "routes": [{ "node": "B" }],
+                        },
+                        {
+                            "conditions": [{ "expr": "phase == 2" }],
+                            "routes": [{ "node": "C", "rate": 3 }, { "node": "D" }],
+                        },
+                        { "routes": [{ "node": "E" }] },
+                    ],
+                },
+            ],
+        }))
+        .unwrap();
+
+        let comp = Comp {
+            org_type: OrgType::Single,
+            hq_level: 120,
+            main: Fleet::default(),
+            escort: None,
+            route_sup: None,
+            boss_sup: None,
+        };
+
+        let analyzer = RoutingAnalyzer {
+            routing: &routing,
+            comp: &comp,
+            phase: 2,
+        };
+
+        let analysis = analyzer.analyze();
+        let branch = &analysis.branches[0];
+
+        assert_eq!(branch.rules.len(), 2);
+        assert_eq!(branch.rules[0].conditions[0].name, "隻数");
+        assert_eq!(branch.rules[0].conditions[0].value, Some(0.0));
+        assert_eq!(branch.rules[0].conditions[0].gap, Some(7.0));
+
+        let routes = branch
+            .routes
+            .iter()
+            .map(|route| (route.node.as_str(), route.rate))
+            .collect::<Vec<_>>();
+        assert_eq!(routes, vec![("C", 0.75), ("D", 0.25)]);
+
+        let analyzer = RoutingAnalyzer {
+            phase: 1,
+            ..analyzer
+        };
+        let branch = analyzer.analyze_branch(&routing.branches[0]);
+
+        assert_eq!(branch.rules.len(), 3);
+        assert_eq!(branch.rules[1].conditions[0].gap, Some(1.0));
+        assert_eq!(branch.routes[0].node.as_str(), "E");
+    }
+}
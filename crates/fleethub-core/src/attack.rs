@@ -7,10 +7,13 @@ mod damage;
 mod day_phase_attack;
 mod fleet_cutin;
 mod hit_rate;
-mod landing_craft_modifiers;
+mod hp_distribution;
+mod land_base_constants;
 mod night_phase_attack;
 mod shelling_attack;
+mod support_constants;
 mod support_shelling_attack;
+mod support_torpedo_attack;
 mod torpedo_attack;
 
 pub use airstrike::*;
@@ -21,10 +24,11 @@ pub use damage::*;
 pub use day_phase_attack::*;
 pub use fleet_cutin::*;
 pub use hit_rate::*;
-pub use landing_craft_modifiers::*;
+pub use hp_distribution::*;
 pub use night_phase_attack::*;
 pub use shelling_attack::*;
 pub use support_shelling_attack::*;
+pub use support_torpedo_attack::*;
 pub use torpedo_attack::*;
 
 #[allow(dead_code)]
@@ -3,10 +3,22 @@ use rand::prelude::*;
 use crate::{
     member::CompMemberRef,
     plane::{AirstrikeType, PlaneImpl},
-    types::{AttackPowerModifier, ContactRank, ProficiencyModifiers},
+    types::{
+        AttackPowerModifier, ContactRank, GearType, ProficiencyModifiers, SpecialEnemyModifiers,
+    },
 };
 
-use super::{AttackParams, AttackPowerParams, DefenseParams, HitRateParams};
+use super::{
+    AttackParams, AttackPowerParams, DefenseParams, HitRateParams,
+    land_base_constants::{
+        LAND_BASE_AIRSTRIKE_ACCURACY_CONSTANT, LAND_BASE_AIRSTRIKE_BASIC_BONUS,
+        LAND_BASE_AIRSTRIKE_CRITICAL_RATE_CONSTANT, LAND_BASE_AIRSTRIKE_POWER_CAP,
+        LAND_BASE_AIRSTRIKE_SLOT_COEFFICIENT, LB_ATTACKER_ANTI_SHIP_MOD, TORPEDO_BOMBER_MODS,
+    },
+    support_constants::{
+        SUPPORT_AIRSTRIKE_BASIC_BONUS, SUPPORT_AIRSTRIKE_PRECAP_MOD, SUPPORT_POWER_CAP,
+    },
+};
 
 const AIRSTRIKE_POWER_CAP: f64 = 170.0;
 
@@ -52,10 +64,10 @@ pub fn create_airstrike_params<P: PlaneImpl, R: Rng + ?Sized>(
         let evasion_term = target.evasion_term(1.0, 0.0, 1.0);
 
         evasion_term.map(|evasion_term| HitRateParams {
-            accuracy_term: 95.0,
+            accuracy_term: LAND_BASE_AIRSTRIKE_ACCURACY_CONSTANT,
             evasion_term,
             target_morale_mod: target.morale_state().hit_rate_mod(),
-            critical_rate_constant: 0.2,
+            critical_rate_constant: LAND_BASE_AIRSTRIKE_CRITICAL_RATE_CONSTANT,
             critical_percentage_bonus: proficiency_modifiers.critical_percentage_bonus,
             hit_percentage_bonus: proficiency_modifiers.hit_percentage_bonus,
         })
@@ -71,3 +83,102 @@ pub fn create_airstrike_params<P: PlaneImpl, R: Rng + ?Sized>(
         hits: 1.0,
     }
 }
+
+/// 航空支援
+///
+/// 通常の航空攻撃に定数と倍率の補正を加え、支援攻撃のキャップを使う
+pub fn create_support_airstrike_params<P: PlaneImpl, R: Rng + ?Sized>(
+    rng: &mut R,
+    plane: P,
+    remaining_ammo_mod: f64,
+    target: &CompMemberRef,
+) -> AttackParams {
+    let mut params = create_airstrike_params(
+        rng,
+        plane,
+        &ProficiencyModifiers::default(),
+        remaining_ammo_mod,
+        None,
+        target,
+    );
+
+    if let Some(attack_power_params) = params.attack_power_params.as_mut() {
+        attack_power_params.basic =
+            attack_power_params.basic.floor() + SUPPORT_AIRSTRIKE_BASIC_BONUS;
+        attack_power_params.cap = SUPPORT_POWER_CAP;
+        attack_power_params.precap_mod =
+            AttackPowerModifier::new(SUPPORT_AIRSTRIKE_PRECAP_MOD, 0.0);
+    }
+
+    params
+}
+
+/// 基地航空隊の航空攻撃
+pub fn create_land_base_airstrike_params<P: PlaneImpl, R: Rng + ?Sized>(
+    rng: &mut R,
+    plane: P,
+    recon_mod: f64,
+    contact_rank: Option<ContactRank>,
+    special_enemy_mods: SpecialEnemyModifiers,
+    target: &CompMemberRef,
+) -> AttackParams {
+    let anti_inst = target.is_installation();
+    let is_lb_attacker = matches!(
+        plane.gear_type,
+        GearType::LbAttacker | GearType::LargeLbAircraft
+    );
+
+    let attack_power_params = {
+        let slot_size = plane.slot_size().unwrap_or_default() as f64;
+
+        // 陸攻は対地攻撃時に爆装を参照する
+        let (type_mod, stat) = if is_lb_attacker {
+            if anti_inst {
+                (1.0, plane.bombing)
+            } else {
+                (LB_ATTACKER_ANTI_SHIP_MOD, plane.torpedo)
+            }
+        } else if plane.is_torpedo_bomber() {
+            let type_mod = *TORPEDO_BOMBER_MODS.choose(rng).unwrap();
+            (type_mod, plane.torpedo)
+        } else {
+            (1.0, plane.bombing)
+        };
+
+        let basic = type_mod
+            * ((stat as f64) * (LAND_BASE_AIRSTRIKE_SLOT_COEFFICIENT * slot_size).sqrt()
+                + LAND_BASE_AIRSTRIKE_BASIC_BONUS);
+
+        let contact_mod = contact_rank.map_or(1.0, |rank| rank.airstrike_power_mod());
+        let postcap_mod = AttackPowerModifier::new(contact_mod * recon_mod, 0.0);
+
+        Some(AttackPowerParams {
+            basic,
+            cap: LAND_BASE_AIRSTRIKE_POWER_CAP,
+            postcap_mod,
+            special_enemy_mods,
+            ..Default::default()
+        })
+    };
+
+    let hit_rate_params = target
+        .evasion_term(1.0, 0.0, 1.0)
+        .map(|evasion_term| HitRateParams {
+            accuracy_term: LAND_BASE_AIRSTRIKE_ACCURACY_CONSTANT,
+            evasion_term,
+            target_morale_mod: target.morale_state().hit_rate_mod(),
+            critical_rate_constant: LAND_BASE_AIRSTRIKE_CRITICAL_RATE_CONSTANT,
+            critical_percentage_bonus: 0.0,
+            hit_percentage_bonus: 0.0,
+        });
+
+    const ARMOR_PENETRATION: f64 = 0.0;
+    let defense_params = DefenseParams::from_target(target, target.side(), ARMOR_PENETRATION);
+
+    AttackParams {
+        attack_power_params,
+        hit_rate_params,
+        defense_params,
+        hits: 1.0,
+    }
+}
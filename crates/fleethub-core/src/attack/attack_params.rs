@@ -1,6 +1,6 @@
 use rand::prelude::Distribution;
 
-use crate::ship::Ship;
+use crate::{ship::Ship, utils::Histogram};
 
 use super::{AttackPower, AttackPowerParams, Damage, DefenseParams, HitRate, HitRateParams};
 
@@ -13,7 +13,11 @@ pub struct Attack {
 }
 
 impl Attack {
-    fn gen_damage_value<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> anyhow::Result<u16> {
+    fn gen_damage_value<R: rand::Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        current_hp: u16,
+    ) -> anyhow::Result<u16> {
         use anyhow::Context;
 
         let attack_power = self
@@ -32,7 +36,7 @@ impl Attack {
             hit_type,
             attack_term: attack_power.get_attack_term(hit_type),
             remaining_ammo_mod: attack_power.remaining_ammo_mod,
-            current_hp: defense_params.current_hp,
+            current_hp,
             basic_defense_power: defense_params.basic_defense_power,
             overkill_protection: defense_params.overkill_protection,
             sinkable: defense_params.sinkable,
@@ -42,13 +46,55 @@ impl Attack {
         Ok(damage.sample(rng))
     }
 
+    /// 現在の耐久が`current_hp`である目標への1回の攻撃のダメージ分布
+    pub fn damage_density(&self, current_hp: u16) -> Option<Histogram<u16, f64>> {
+        let attack_power = self.attack_power.as_ref()?;
+        let defense_params = self.defense_params.as_ref()?;
+        let hit_rate = self.hit_rate.as_ref()?;
+
+        let density = hit_rate
+            .iter()
+            .map(|(hit_type, rate)| {
+                let damage = Damage {
+                    hit_type,
+                    attack_term: attack_power.get_attack_term(hit_type),
+                    remaining_ammo_mod: attack_power.remaining_ammo_mod,
+                    current_hp,
+                    basic_defense_power: defense_params.basic_defense_power,
+                    overkill_protection: defense_params.overkill_protection,
+                    sinkable: defense_params.sinkable,
+                    is_cutin: self.is_cutin,
+                };
+
+                damage.density() * rate
+            })
+            .sum();
+
+        Some(density)
+    }
+
+    /// 攻撃を`hits`回適用する
+    ///
+    /// 命中とダメージは1回ごとに判定し、`hits`の端数は最後の1回が発生する確率として扱う
     pub fn apply<R: rand::Rng + ?Sized>(
         &self,
         rng: &mut R,
         target: &mut Ship,
     ) -> anyhow::Result<()> {
-        let value = self.gen_damage_value(rng)?;
-        target.take_damage(value);
+        let hits = self.hits.max(0.0);
+        let count = hits.ceil() as usize;
+
+        for i in 0..count {
+            let rate = (hits - i as f64).min(1.0);
+
+            if rate < 1.0 && !rng.gen_bool(rate) {
+                break;
+            }
+
+            let value = self.gen_damage_value(rng, target.current_hp)?;
+            target.take_damage(value);
+        }
+
         Ok(())
     }
 }
@@ -97,3 +143,49 @@ impl AttackParams {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    use super::*;
+
+    #[test]
+    fn test_apply_multiple_hits() {
+        let attack = Attack {
+            attack_power: Some(AttackPower {
+                normal: 100.0,
+                critical: 100.0,
+                remaining_ammo_mod: 1.0,
+                ..Default::default()
+            }),
+            hit_rate: Some(HitRate {
+                normal: 1.0,
+                critical: 0.0,
+                total: 1.0,
+            }),
+            defense_params: Some(DefenseParams {
+                max_hp: 300,
+                current_hp: 300,
+                basic_defense_power: 1.0,
+                overkill_protection: false,
+                sinkable: true,
+            }),
+            is_cutin: true,
+            hits: 2.0,
+        };
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut target = Ship::default();
+        target.current_hp = 300;
+
+        attack.apply(&mut rng, &mut target).unwrap();
+        assert_eq!(target.current_hp, 300 - 99 * 2);
+
+        let single = Attack { hits: 1.0, ..attack };
+        let mut target = Ship::default();
+        target.current_hp = 300;
+        single.apply(&mut rng, &mut target).unwrap();
+        assert_eq!(target.current_hp, 300 - 99);
+    }
+}
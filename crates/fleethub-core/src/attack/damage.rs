@@ -74,8 +74,9 @@ pub struct DefenseParams {
 
 impl DefenseParams {
     pub fn from_target(target: &Ship, side: Side, armor_penetration: f64) -> Option<Self> {
-        let overkill_protection = side.is_player() && target.morale_state() != MoraleState::Red;
-        let sinkable = side.is_enemy();
+        let overkill_protection =
+            side.is_player() && target.morale_state() != MoraleState::Red && !target.sinkable;
+        let sinkable = side.is_enemy() || target.sinkable;
 
         Some(Self {
             basic_defense_power: target.basic_defense_power(armor_penetration)?,
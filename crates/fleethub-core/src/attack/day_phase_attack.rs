@@ -2,6 +2,7 @@ use crate::{
     member::BattleMemberRef,
     types::{
         AswPhase, DayPhaseAttackStyle, Engagement, FormationParams, HistoricalParams, NodeState,
+        SpecialEnemyModifiers,
     },
 };
 
@@ -14,6 +15,8 @@ pub struct DayPhaseAttackParams<'a> {
     pub target: &'a BattleMemberRef<'a>,
     pub formation_params: FormationParams,
     pub historical_params: HistoricalParams,
+    pub special_enemy_mods: SpecialEnemyModifiers,
+    pub gunfit_accuracy: f64,
     pub node_state: NodeState,
     pub balloons: usize,
 }
@@ -40,6 +43,8 @@ impl DayPhaseAttackParams<'_> {
                 engagement,
                 formation_params,
                 historical_params,
+                special_enemy_mods: self.special_enemy_mods.clone(),
+                gunfit_accuracy: self.gunfit_accuracy,
                 node_state,
                 balloons: self.balloons,
             }
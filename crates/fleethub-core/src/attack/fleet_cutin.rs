@@ -1,11 +1,15 @@
+use std::sync::LazyLock;
+
 use arrayvec::ArrayVec;
+use fasteval::{EvalNamespace, bool_to_f64};
 
 use crate::{
     fleet::Fleet,
     ship::Ship,
     types::{
-        DamageState, Engagement, FleetCutin, Formation, GearAttr, GearType, ShipAttr, Time, ctype,
-        gear_id, matches_gear_id, matches_ship_id, ship_id,
+        CompiledEvaler, Engagement, FLEET_CUTIN_ATTACK_CAPACITY, FleetCutin, FleetCutinAttackDef,
+        FleetCutinDef, FleetCutinEquipmentModDef, FleetCutinTag, Formation, GearAttr, GearType,
+        ShipAttr, Time, ctype, gear_id, ship_id,
     },
 };
 
@@ -28,438 +32,447 @@ impl FleetCutinAttackParams {
 
 #[derive(Debug, PartialEq)]
 pub struct FleetCutinEffect {
+    pub tag: FleetCutinTag,
     pub cutin: FleetCutin,
-    pub attacks: ArrayVec<FleetCutinAttackParams, 3>,
-}
-
-pub fn get_possible_fleet_cutin_effect_vec(
-    fleet: &Fleet,
-    formation: Formation,
-    engagement: Engagement,
-    time: Time,
-) -> Vec<FleetCutinEffect> {
-    [
-        get_nelson_touch_mod(fleet, formation, engagement),
-        get_nagato_class_cutin(fleet, formation),
-        get_colorado_class_cutin(fleet, formation),
-        get_yamato_2ship_cutin(fleet, formation),
-        get_yamato_3ship_cutin(fleet, formation),
-        get_Queen_Elizabeth_class_cutin(fleet, formation),
-        get_Richelieu_class_cutin(fleet, formation),
-        time.is_night()
-            .then(|| get_kongou_class_cutin(fleet, formation, engagement))
-            .flatten(),
-    ]
-    .into_iter()
-    .flatten()
-    .collect()
+    pub attacks: ArrayVec<FleetCutinAttackParams, FLEET_CUTIN_ATTACK_CAPACITY>,
 }
 
-fn get_nelson_touch_mod(
-    fleet: &Fleet,
-    formation: Formation,
-    engagement: Engagement,
-) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::DOUBLE_LINE | Formation::CRUISING2) {
-        return None;
-    }
-
-    let s1 = fleet.ships.get(0)?;
-    let s3 = fleet.ships.get(2)?;
-    let s5 = fleet.ships.get(4)?;
-
-    if !(s1.ctype == ctype!("Nelson級") && s1.damage_state() <= DamageState::Shouha) {
-        return None;
-    }
-
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
-    }
+/// 艦隊特殊攻撃の式で使う変数と関数
+///
+/// 艦に関する関数は第1引数に1から始まる艦の位置を取る
+fn fleet_cutin_ns(fleet: &Fleet, engagement: Option<Engagement>) -> impl EvalNamespace + '_ {
+    move |name: &str, args: Vec<f64>| -> Option<f64> {
+        let engagement_is = |expected: Engagement| Some(bool_to_f64!(engagement? == expected));
+
+        let result = match name {
+            "sqrt" => args.first()?.sqrt(),
+            "surface_ship_count" => fleet
+                .ships
+                .count_by(|ship| ship.ship_type.is_surface_ship())
+                as f64,
+
+            "green_t" => engagement_is(Engagement::GreenT)?,
+            "parallel" => engagement_is(Engagement::Parallel)?,
+            "head_on" => engagement_is(Engagement::HeadOn)?,
+            "red_t" => engagement_is(Engagement::RedT)?,
+
+            _ => {
+                let index = (*args.first()? as usize).checked_sub(1)?;
+                let ship = fleet.ships.get(index)?;
+                let rest = args.get(1..).unwrap_or_default();
+
+                eval_ship_fn(ship, name, rest)?
+            }
+        };
 
-    if s3.ship_type.is_submarine()
-        || s3.ship_type.is_aircraft_carrier()
-        || s5.ship_type.is_submarine()
-        || s5.ship_type.is_aircraft_carrier()
-    {
-        return None;
+        Some(result)
     }
+}
 
-    let base = 2.0;
-
-    let engagement_mod = if engagement == Engagement::RedT {
-        1.25
-    } else {
-        1.0
-    };
-
-    let v = base * engagement_mod;
+fn eval_ship_fn(ship: &Ship, name: &str, args: &[f64]) -> Option<f64> {
+    let result = match name {
+        "ship_id" => ship.ship_id.into(),
+        "ship_class" => ship.ctype.into(),
+        "level" => ship.level.into(),
+        "luck" => ship.luck()?.into(),
+        "remodel_rank" => ship.remodel_rank().into(),
+        "damage_state" => (ship.damage_state() as u8).into(),
+
+        "ship_id_in" => bool_to_f64!(args.contains(&ship.ship_id.into())),
+        "ship_class_in" => bool_to_f64!(args.contains(&ship.ctype.into())),
+
+        "is_kai2" => bool_to_f64!(ship.has_attr(ShipAttr::Kai2)),
+        "is_battleship" => bool_to_f64!(ship.ship_type.is_battleship()),
+        "is_aircraft_carrier" => bool_to_f64!(ship.ship_type.is_aircraft_carrier()),
+        "is_submarine" => bool_to_f64!(ship.ship_type.is_submarine()),
+
+        "has" => bool_to_f64!(args.iter().any(|gear_id| ship.gears.has(*gear_id as u16))),
+        "has_gear_type" => {
+            bool_to_f64!(args.iter().any(|ty| ship.gears.has_type((*ty).into())))
+        }
+        "has_surface_radar" => bool_to_f64!(ship.gears.has_attr(GearAttr::SurfaceRadar)),
+        "has_radar_with_los" => {
+            let los = *args.first()?;
+            let result = ship
+                .gears
+                .has_by(|gear| gear.has_attr(GearAttr::Radar) && f64::from(gear.los) >= los);
+            bool_to_f64!(result)
+        }
 
-    let (s1_mod, s3_mod, s5_mod) = if s3.ctype == ctype!("Nelson級") {
-        (1.15, 1.2, 1.0)
-    } else if s5.ctype == ctype!("Nelson級") {
-        (1.15, 1.0, 1.2)
-    } else {
-        (1.0, 1.0, 1.0)
+        _ => return None,
     };
 
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::NelsonTouch,
-        attacks: [
-            FleetCutinAttackParams::new(0, v * s1_mod, 1.05),
-            FleetCutinAttackParams::new(2, v * s3_mod, 1.05),
-            FleetCutinAttackParams::new(4, v * s5_mod, 1.05),
-        ]
-        .into(),
-    })
+    Some(result)
 }
 
-fn get_nagato_class_cutin(fleet: &Fleet, formation: Formation) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::ECHELON | Formation::CRUISING2) {
-        return None;
-    }
+impl FleetCutinAttackDef {
+    fn to_params(&self, fleet: &Fleet, engagement: Engagement) -> Option<FleetCutinAttackParams> {
+        fleet.ships.get(self.index)?;
 
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
+        let mut ns = fleet_cutin_ns(fleet, Some(engagement));
 
-    if !(s1.ctype == ctype!("長門型")
-        && s1.has_attr(ShipAttr::Kai2)
-        && s1.damage_state() <= DamageState::Shouha)
-    {
-        return None;
-    }
+        let power_mod = self.power_mods.iter().try_fold(1.0, |acc, expr| {
+            expr.eval(&mut ns).ok().map(|value| acc * value)
+        })?;
 
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
-    }
+        let equipment_mod = self
+            .equipment_mods
+            .iter()
+            .filter(|def| def.requires.matches(&mut ns))
+            .fold(1.0, |acc, def| acc * def.multiplier);
 
-    if !(s2.ship_type.is_battleship() && s2.damage_state() <= DamageState::Chuuha) {
-        return None;
+        Some(FleetCutinAttackParams::new(
+            self.index,
+            power_mod * equipment_mod,
+            self.accuracy_mod,
+        ))
     }
+}
 
-    let base = (1.4, 1.2);
-
-    let partner_mod = match s2.ctype {
-        ctype!("長門型") => {
-            if s2.has_attr(ShipAttr::Kai2) {
-                (1.2, 1.4)
-            } else {
-                (1.15, 1.32)
-            }
-        }
-        ctype!("Nelson級") => {
-            if s1.ship_id == ship_id!("長門改二") {
-                (1.1, 1.25)
-            } else {
-                (1.0, 1.0)
-            }
+impl FleetCutinDef {
+    pub fn get_effect(
+        &self,
+        fleet: &Fleet,
+        formation: Formation,
+        engagement: Engagement,
+        time: Time,
+    ) -> Option<FleetCutinEffect> {
+        if !self.formations.contains(&formation)
+            || (self.night_only && !time.is_night())
+            || self.attacks.len() > FLEET_CUTIN_ATTACK_CAPACITY
+        {
+            return None;
         }
-        _ => (1.0, 1.0),
-    };
 
-    fn get_equipment_mod(ship: &Ship) -> f64 {
-        let mut v = 1.0;
-        if ship.gears.has_type(GearType::ApShell) {
-            v *= 1.35
-        }
-        if ship.gears.has_attr(GearAttr::SurfaceRadar) {
-            v *= 1.15
+        if !self
+            .requires
+            .matches(&mut fleet_cutin_ns(fleet, Some(engagement)))
+        {
+            return None;
         }
-        v
-    }
-
-    let s1_equipment_mod = get_equipment_mod(s1);
-    let s2_equipment_mod = get_equipment_mod(s2);
-
-    let s1_mod = base.0 * partner_mod.0 * s1_equipment_mod;
-    let s2_mod = base.1 * partner_mod.1 * s2_equipment_mod;
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::NagatoClassCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(1, s2_mod, 1.0),
-        ]
-        .into(),
-    })
-}
-
-fn get_colorado_class_cutin(fleet: &Fleet, formation: Formation) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::ECHELON | Formation::CRUISING2) {
-        return None;
-    }
-
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-    let s3 = fleet.ships.get(2)?;
-
-    if !(s1.ctype == ctype!("Colorado級") && s1.damage_state() <= DamageState::Shouha) {
-        return None;
-    }
-
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
-    }
-
-    if !(s2.damage_state() <= DamageState::Chuuha
-        && s2.ship_type.is_battleship()
-        && s3.damage_state() <= DamageState::Chuuha
-        && s3.ship_type.is_battleship())
-    {
-        return None;
-    }
 
-    let base = (1.5, 1.3, 1.3);
+        let attacks = self
+            .attacks
+            .iter()
+            .map(|attack| attack.to_params(fleet, engagement))
+            .collect::<Option<ArrayVec<_, FLEET_CUTIN_ATTACK_CAPACITY>>>()?;
 
-    fn is_big7(ship: &Ship) -> bool {
-        // なぜか未改造はビッグセブン補正が適応されない
-        matches!(
-            ship.ctype,
-            ctype!("長門型") | ctype!("Nelson級") | ctype!("Colorado級")
-        ) && ship.remodel_rank() >= 2
+        Some(FleetCutinEffect {
+            tag: self.tag.clone(),
+            cutin: self.tag.cutin(),
+            attacks,
+        })
     }
 
-    let big7_mod = (
-        1.0,
-        if is_big7(s2) { 1.15 } else { 1.0 },
-        if is_big7(s3) { 1.17 } else { 1.0 },
-    );
-
-    fn get_equipment_mod(ship: &Ship) -> f64 {
-        let mut v = 1.0;
-        if ship.gears.has_type(GearType::ApShell) {
-            v *= 1.35
-        }
-        if ship.gears.has_attr(GearAttr::SurfaceRadar) {
-            v *= 1.15
+    pub fn calc_rate(&self, fleet: &Fleet) -> Option<f64> {
+        if self.rate.is_empty() {
+            return None;
         }
-        if ship.gears.has(gear_id!("SG レーダー(後期型)")) {
-            v *= 1.15;
-        }
-        v
-    }
 
-    let equipment_mod = (
-        get_equipment_mod(s1),
-        get_equipment_mod(s2),
-        get_equipment_mod(s3),
-    );
-
-    let s1_mod = base.0 * big7_mod.0 * equipment_mod.0;
-    let s2_mod = base.1 * big7_mod.1 * equipment_mod.1;
-    let s3_mod = base.2 * big7_mod.2 * equipment_mod.2;
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::ColoradoClassCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(1, s2_mod, 1.0),
-            FleetCutinAttackParams::new(2, s3_mod, 1.0),
-        ]
-        .into(),
-    })
+        let percent = self.rate.eval(&mut fleet_cutin_ns(fleet, None)).ok()?;
+        Some((percent / 100.0).min(1.0))
+    }
 }
 
-fn get_kongou_class_cutin(
+pub fn get_possible_fleet_cutin_effect_vec(
     fleet: &Fleet,
     formation: Formation,
     engagement: Engagement,
-) -> Option<FleetCutinEffect> {
-    if !matches!(
-        formation,
-        Formation::LINE_AHEAD | Formation::ECHELON | Formation::CRUISING2 | Formation::CRUISING4
-    ) {
-        return None;
-    }
+    time: Time,
+) -> Vec<FleetCutinEffect> {
+    reference_fleet_cutin_defs()
+        .iter()
+        .filter_map(|def| def.get_effect(fleet, formation, engagement, time))
+        .collect()
+}
 
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
+pub fn calc_fleet_cutin_rate(fleet: &Fleet, cutin: FleetCutin) -> Option<f64> {
+    reference_fleet_cutin_defs()
+        .iter()
+        .find(|def| def.tag == FleetCutinTag::Known(cutin))?
+        .calc_rate(fleet)
+}
 
-    let is_kongou_fleet = match s1.ship_id {
-        ship_id!("金剛改二丙") => {
-            matches_ship_id!(
-                s2.ship_id,
-                "比叡改二丙" | "榛名改二" | "榛名改二乙" | "榛名改二丙" | "霧島改二丙"
-            ) || s2.ctype == ctype!("Queen Elizabeth級")
-        }
-        ship_id!("比叡改二丙") => {
-            matches_ship_id!(
-                s2.ship_id,
-                "金剛改二丙" | "榛名改二乙" | "榛名改二丙" | "霧島改二" | "霧島改二丙"
-            )
-        }
-        ship_id!("榛名改二乙") | ship_id!("榛名改二丙") => {
-            matches_ship_id!(s2.ship_id, "金剛改二丙" | "比叡改二丙" | "霧島改二丙")
-        }
-        ship_id!("霧島改二丙") => {
-            matches_ship_id!(
-                s2.ship_id,
-                "金剛改二丙" | "比叡改二丙" | "榛名改二乙" | "榛名改二丙" | "South Dakota改"
-            )
-        }
-        _ => false,
-    };
+/// 組み込みの艦隊特殊攻撃の定義
+pub fn reference_fleet_cutin_defs() -> &'static [FleetCutinDef] {
+    static DEFS: LazyLock<Vec<FleetCutinDef>> = LazyLock::new(create_reference_fleet_cutin_defs);
+    &DEFS
+}
 
-    if !is_kongou_fleet {
-        return None;
-    }
+fn expr(expr: impl Into<String>) -> CompiledEvaler {
+    CompiledEvaler::new(expr.into()).expect("invalid fleet cutin expression")
+}
 
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 5
-    {
-        return None;
+fn attack(
+    index: usize,
+    power_mods: &[&str],
+    equipment_mods: Vec<FleetCutinEquipmentModDef>,
+) -> FleetCutinAttackDef {
+    FleetCutinAttackDef {
+        index,
+        power_mods: power_mods.iter().map(|v| expr(*v)).collect(),
+        accuracy_mod: 1.0,
+        equipment_mods,
     }
+}
 
-    if !(s1.damage_state() <= DamageState::Shouha && s2.damage_state() <= DamageState::Shouha) {
-        return None;
+fn equipment_mod(requires: String, multiplier: f64) -> FleetCutinEquipmentModDef {
+    FleetCutinEquipmentModDef {
+        requires: expr(requires),
+        multiplier,
     }
+}
 
-    // 23/5/1に上方修正
-    let base = 2.4;
-
-    let engagement_mod = match engagement {
-        Engagement::GreenT => 1.25,
-        Engagement::RedT => 0.75,
-        _ => 1.0,
-    };
-
-    let v = base * engagement_mod;
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::KongouClassCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, v, 1.4),
-            FleetCutinAttackParams::new(1, v, 1.4),
-        ]
-        .into_iter()
-        .collect(),
-    })
+/// 徹甲弾と水上電探の補正
+fn basic_equipment_mods(n: usize) -> Vec<FleetCutinEquipmentModDef> {
+    vec![
+        equipment_mod(
+            format!("has_gear_type({n}, {})", GearType::ApShell as u8),
+            1.35,
+        ),
+        equipment_mod(format!("has_surface_radar({n})"), 1.15),
+    ]
 }
 
-fn get_yamato_2ship_cutin(fleet: &Fleet, formation: Formation) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::ECHELON | Formation::CRUISING4) {
-        return None;
-    }
+fn rangefinder_equipment_mod(n: usize) -> FleetCutinEquipmentModDef {
+    equipment_mod(
+        format!(
+            "has({n}, {}, {})",
+            gear_id!("15m二重測距儀+21号電探改二"),
+            gear_id!("15m二重測距儀改+21号電探改二+熟練射撃指揮所"),
+        ),
+        1.1,
+    )
+}
 
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
-    }
+fn create_reference_fleet_cutin_defs() -> Vec<FleetCutinDef> {
+    vec![
+        nelson_touch(),
+        nagato_class_cutin(),
+        colorado_class_cutin(),
+        yamato_2ship_cutin(),
+        yamato_3ship_cutin(),
+        queen_elizabeth_class_cutin(),
+        richelieu_class_cutin(),
+        kongou_class_cutin(),
+    ]
+}
 
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
+/// Int(1.1√ﾈﾙｿﾝLv+√3番艦Lv+√5番艦Lv+1.4√ﾈﾙｿﾝ運+25)
+/// https://x.com/Divinity_123/status/1820114418904002935
+fn nelson_touch() -> FleetCutinDef {
+    let nelson = ctype!("Nelson級");
+    let engagement_mod = "(red_t && 1.25) || 1";
 
-    if !(s1.damage_state() <= DamageState::Shouha && s2.damage_state() <= DamageState::Shouha) {
-        return None;
-    }
+    let s1_mod = format!("((ship_class(3) == {nelson} || ship_class(5) == {nelson}) && 1.15) || 1");
+    let s3_mod = format!("(ship_class(3) == {nelson} && 1.2) || 1");
+    let s5_mod = format!("(ship_class(3) != {nelson} && ship_class(5) == {nelson} && 1.2) || 1");
 
-    let includes_yamato_kai2 = is_yamato_kai2(s1) || is_yamato_kai2(s2);
+    let shot = |index: usize, mod_expr: &str| FleetCutinAttackDef {
+        accuracy_mod: 1.05,
+        ..attack(index, &["2", engagement_mod, mod_expr], vec![])
+    };
 
-    if !includes_yamato_kai2 {
-        return None;
+    FleetCutinDef {
+        tag: FleetCutin::NelsonTouch.into(),
+        formations: vec![Formation::DOUBLE_LINE, Formation::CRUISING2],
+        night_only: false,
+        requires: expr(format!(
+            "ship_class(1) == {nelson} && damage_state(1) <= 1 && surface_ship_count >= 6 \
+             && !is_submarine(3) && !is_aircraft_carrier(3) \
+             && !is_submarine(5) && !is_aircraft_carrier(5)"
+        )),
+        rate: expr(
+            "floor(1.1 * sqrt(level(1)) + sqrt(level(3)) + sqrt(level(5)) + 1.4 * sqrt(luck(1)) + 25)",
+        ),
+        attacks: vec![shot(0, &s1_mod), shot(2, &s3_mod), shot(4, &s5_mod)],
     }
+}
 
-    let s1_is_musashi_kai2 = is_musashi_kai2(s1);
-    let includes_musashi_kai2 = s1_is_musashi_kai2 || is_musashi_kai2(s2);
+/// (√一番艦Lv +√二番艦Lv) + 1.5*(√一番艦運 +√二番艦運)+25
+/// https://x.com/Divinity_123/status/1820114420569162214
+fn nagato_class_cutin() -> FleetCutinDef {
+    let nagato = ctype!("長門型");
+    let nelson = ctype!("Nelson級");
+    let nagato_kai2 = ship_id!("長門改二");
+
+    let s1_mod = format!(
+        "(ship_class(2) == {nagato} && ((is_kai2(2) && 1.2) || 1.15)) \
+         || (ship_class(2) == {nelson} && ship_id(1) == {nagato_kai2} && 1.1) || 1"
+    );
+    let s2_mod = format!(
+        "(ship_class(2) == {nagato} && ((is_kai2(2) && 1.4) || 1.32)) \
+         || (ship_class(2) == {nelson} && ship_id(1) == {nagato_kai2} && 1.25) || 1"
+    );
 
-    if !includes_musashi_kai2
-        && !matches_ship_id!(s2.ship_id, "Bismarck drei" | "Iowa改" | "Richelieu改")
-    {
-        return None;
+    FleetCutinDef {
+        tag: FleetCutin::NagatoClassCutin.into(),
+        formations: vec![Formation::ECHELON, Formation::CRUISING2],
+        night_only: false,
+        requires: expr(format!(
+            "ship_class(1) == {nagato} && is_kai2(1) && damage_state(1) <= 1 \
+             && surface_ship_count >= 6 && is_battleship(2) && damage_state(2) <= 2"
+        )),
+        rate: expr("sqrt(level(1)) + sqrt(level(2)) + 1.5 * (sqrt(luck(1)) + sqrt(luck(2))) + 25"),
+        attacks: vec![
+            attack(0, &["1.4", &s1_mod], basic_equipment_mods(1)),
+            attack(0, &["1.4", &s1_mod], basic_equipment_mods(1)),
+            attack(1, &["1.2", &s2_mod], basic_equipment_mods(2)),
+        ],
     }
+}
 
-    let mut base = (1.4, 1.55);
+fn colorado_class_cutin() -> FleetCutinDef {
+    // なぜか未改造はビッグセブン補正が適応されない
+    let is_big7 = |n: usize| {
+        format!(
+            "ship_class_in({n}, {}, {}, {}) && remodel_rank({n}) >= 2",
+            ctype!("長門型"),
+            ctype!("Nelson級"),
+            ctype!("Colorado級"),
+        )
+    };
 
-    if includes_musashi_kai2 {
-        base.0 *= 1.1;
+    let equipment_mods = |n: usize| {
+        let mut mods = basic_equipment_mods(n);
+        mods.push(equipment_mod(
+            format!("has({n}, {})", gear_id!("SG レーダー(後期型)")),
+            1.15,
+        ));
+        mods
+    };
 
-        let s2_is_yamato_kai2_juu = s2.ship_id == ship_id!("大和改二重");
-        base.1 *= if s2_is_yamato_kai2_juu { 1.25 } else { 1.2 };
+    FleetCutinDef {
+        tag: FleetCutin::ColoradoClassCutin.into(),
+        formations: vec![Formation::ECHELON, Formation::CRUISING2],
+        night_only: false,
+        requires: expr(format!(
+            "ship_class(1) == {} && damage_state(1) <= 1 && surface_ship_count >= 6 \
+             && damage_state(2) <= 2 && is_battleship(2) \
+             && damage_state(3) <= 2 && is_battleship(3)",
+            ctype!("Colorado級"),
+        )),
+        rate: CompiledEvaler::default(),
+        attacks: vec![
+            attack(0, &["1.5"], equipment_mods(1)),
+            attack(
+                1,
+                &["1.3", &format!("({} && 1.15) || 1", is_big7(2))],
+                equipment_mods(2),
+            ),
+            attack(
+                2,
+                &["1.3", &format!("({} && 1.17) || 1", is_big7(3))],
+                equipment_mods(3),
+            ),
+        ],
     }
+}
 
-    fn get_equipment_mod(ship: &Ship) -> f64 {
-        let mut v = 1.0;
-        if ship.gears.has_type(GearType::ApShell) {
-            v *= 1.35;
-        }
-        if ship.gears.has_attr(GearAttr::SurfaceRadar) {
-            v *= 1.15;
-        }
-        if ship.gears.has_by(|gear| {
-            matches_gear_id!(
-                gear.gear_id,
-                "15m二重測距儀+21号電探改二" | "15m二重測距儀改+21号電探改二+熟練射撃指揮所"
-            )
-        }) {
-            v *= 1.1;
-        }
+fn kongou_class_cutin() -> FleetCutinDef {
+    let kongou = ship_id!("金剛改二丙");
+    let hiei = ship_id!("比叡改二丙");
+    let haruna = ship_id!("榛名改二");
+    let haruna_b = ship_id!("榛名改二乙");
+    let haruna_c = ship_id!("榛名改二丙");
+    let kirishima = ship_id!("霧島改二");
+    let kirishima_c = ship_id!("霧島改二丙");
+    let south_dakota = ship_id!("South Dakota改");
+    let queen_elizabeth = ctype!("Queen Elizabeth級");
+
+    let pairs = format!(
+        "(ship_id(1) == {kongou} && (ship_id_in(2, {hiei}, {haruna}, {haruna_b}, {haruna_c}, {kirishima_c}) \
+         || ship_class(2) == {queen_elizabeth})) \
+         || (ship_id(1) == {hiei} && ship_id_in(2, {kongou}, {haruna_b}, {haruna_c}, {kirishima}, {kirishima_c})) \
+         || (ship_id_in(1, {haruna_b}, {haruna_c}) && ship_id_in(2, {kongou}, {hiei}, {kirishima_c})) \
+         || (ship_id(1) == {kirishima_c} && ship_id_in(2, {kongou}, {hiei}, {haruna_b}, {haruna_c}, {south_dakota}))"
+    );
 
-        v
-    }
+    let radar = "has_radar_with_los(1, 8)";
+    let searchlight = format!("has_gear_type(1, {})", GearType::LargeSearchlight as u8);
+    let equipment_mod = format!(
+        "(ship_id(1) == {kongou} && (30 * {radar} + 10 * {searchlight})) \
+         + (ship_id(1) == {hiei} && (30 * {searchlight} + 10 * {radar})) \
+         + (ship_id(1) == {haruna_b} && 20 * {radar}) \
+         + (ship_id(1) == {haruna_c} && 15 * {radar}) \
+         + (ship_id(1) == {kirishima_c} && (20 * {searchlight} + 20 * {radar}))"
+    );
 
-    let s1_equipment_mod = get_equipment_mod(s1);
-    let s2_equipment_mod = get_equipment_mod(s2);
-    let s1_mod = base.0 * s1_equipment_mod;
-    let s2_mod = base.1 * s2_equipment_mod;
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::Yamato2ShipCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(1, s2_mod, 1.0),
-        ]
-        .into(),
-    })
-}
+    // 23/5/1に上方修正
+    let power_mods = ["2.4", "(green_t && 1.25) || (red_t && 0.75) || 1"];
 
-fn get_yamato_3ship_cutin(fleet: &Fleet, formation: Formation) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::ECHELON | Formation::CRUISING4) {
-        return None;
-    }
+    let shot = |index: usize| FleetCutinAttackDef {
+        accuracy_mod: 1.4,
+        ..attack(index, &power_mods, vec![])
+    };
 
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
+    FleetCutinDef {
+        tag: FleetCutin::KongouClassCutin.into(),
+        formations: vec![
+            Formation::LINE_AHEAD,
+            Formation::ECHELON,
+            Formation::CRUISING2,
+            Formation::CRUISING4,
+        ],
+        night_only: true,
+        requires: expr(format!(
+            "({pairs}) && surface_ship_count >= 5 && damage_state(1) <= 1 && damage_state(2) <= 1"
+        )),
+        rate: expr(format!(
+            "floor(3.5 * sqrt(level(1)) + 3.5 * sqrt(level(2)) \
+             + 1.1 * sqrt(luck(1)) + 1.1 * sqrt(luck(2)) + ({equipment_mod}) - 33)"
+        )),
+        attacks: vec![shot(0), shot(1)],
     }
+}
 
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-    let s3 = fleet.ships.get(2)?;
+/// √一番艦Lv +√二番艦Lv+ 1.25√一番艦運 +1.25√二番艦運+33+水上電探装備艦数補正10+大和旗艦補正3+大和or武蔵2番艦補正4
+/// https://x.com/Divinity_123/status/1820114422343376976
+fn yamato_2ship_cutin() -> FleetCutinDef {
+    let yamato_kai2 = format!("{}, {}", ship_id!("大和改二"), ship_id!("大和改二重"));
+    let musashi_kai2 = ship_id!("武蔵改二");
+    let includes_musashi_kai2 =
+        format!("(ship_id(1) == {musashi_kai2} || ship_id(2) == {musashi_kai2})");
+
+    let s1_mod = format!("({includes_musashi_kai2} && 1.1) || 1");
+    let s2_mod = format!(
+        "({includes_musashi_kai2} && ((ship_id(2) == {} && 1.25) || 1.2)) || 1",
+        ship_id!("大和改二重"),
+    );
 
-    if !is_yamato_kai2(s1) {
-        return None;
-    }
+    let equipment_mods = |n: usize| {
+        let mut mods = basic_equipment_mods(n);
+        mods.push(rangefinder_equipment_mod(n));
+        mods
+    };
 
-    if !(s1.damage_state() <= DamageState::Shouha
-        && s2.damage_state() <= DamageState::Shouha
-        && s3.damage_state() <= DamageState::Shouha)
-    {
-        return None;
+    FleetCutinDef {
+        tag: FleetCutin::Yamato2ShipCutin.into(),
+        formations: vec![Formation::ECHELON, Formation::CRUISING4],
+        night_only: false,
+        requires: expr(format!(
+            "surface_ship_count >= 6 && damage_state(1) <= 1 && damage_state(2) <= 1 \
+             && (ship_id_in(1, {yamato_kai2}) || ship_id_in(2, {yamato_kai2})) \
+             && ({includes_musashi_kai2} || ship_id_in(2, {}, {}, {}))",
+            ship_id!("Bismarck drei"),
+            ship_id!("Iowa改"),
+            ship_id!("Richelieu改"),
+        )),
+        rate: expr(format!(
+            "sqrt(level(1)) + sqrt(level(2)) + 1.25 * sqrt(luck(1)) + 1.25 * sqrt(luck(2)) + 33 \
+             + (ship_id_in(1, {yamato_kai2}) && 3) + (ship_class(2) == {} && 4) \
+             + 10 * has_surface_radar(1) + 10 * has_surface_radar(2)",
+            ctype!("大和型"),
+        )),
+        attacks: vec![
+            attack(0, &["1.4", &s1_mod], equipment_mods(1)),
+            attack(0, &["1.4", &s1_mod], equipment_mods(1)),
+            attack(1, &["1.55", &s2_mod], equipment_mods(2)),
+        ],
     }
+}
 
-    let pair = [s2.ship_id, s3.ship_id];
-
+fn yamato_3ship_cutin() -> FleetCutinDef {
     const HELPER_PAIRS: [(u16, u16); 15] = [
         (ship_id!("長門改二"), ship_id!("陸奥改二")),
         (ship_id!("伊勢改二"), ship_id!("日向改二")),
@@ -478,386 +491,244 @@ fn get_yamato_3ship_cutin(fleet: &Fleet, formation: Formation) -> Option<FleetCu
         (ship_id!("Richelieu改"), ship_id!("Jean Bart改")),
     ];
 
-    if !(HELPER_PAIRS
-        .iter()
-        .any(|(p1, p2)| pair.contains(p1) && pair.contains(p2))
-        || pair[0] == ship_id!("武蔵改二") && matches_ship_id!(pair[1], "長門改二" | "陸奥改二"))
-    {
-        return None;
-    }
+    let musashi_kai2 = ship_id!("武蔵改二");
+    let nagato_kai2 = format!("{}, {}", ship_id!("長門改二"), ship_id!("陸奥改二"));
+    let ise_kai2 = format!("{}, {}", ship_id!("伊勢改二"), ship_id!("日向改二"));
 
-    if !(HELPER_PAIRS
+    let helper_pairs = HELPER_PAIRS
         .iter()
-        .any(|(p1, p2)| pair.contains(p1) && pair.contains(p2))
-        || pair[0] == ship_id!("武蔵改二") && matches_ship_id!(pair[1], "長門改二" | "陸奥改二"))
-    {
-        return None;
-    }
-
-    let base = if is_musashi_kai2(s2) {
-        (1.65, 1.8, 1.65)
-    } else if matches_ship_id!(s2.ship_id, "長門改二" | "陸奥改二") {
-        (1.65, 1.65, 1.65)
-    } else if matches_ship_id!(s2.ship_id, "伊勢改二" | "日向改二") {
-        (1.65, 1.575, 1.65)
-    } else {
-        (1.5, 1.5, 1.65)
-    };
-
-    fn get_equipment_mod(ship: &Ship, shots: usize) -> f64 {
-        let mut v = 1.0;
-
-        if ship.gears.has_type(GearType::ApShell) {
-            v *= 1.35;
-        }
-        if ship.gears.has_attr(GearAttr::SurfaceRadar) {
-            v *= 1.15;
-        }
-        if shots <= 2
-            && ship.gears.has_by(|gear| {
-                matches_gear_id!(
-                    gear.gear_id,
-                    "15m二重測距儀+21号電探改二" | "15m二重測距儀改+21号電探改二+熟練射撃指揮所"
-                )
-            })
-        {
-            v *= 1.1;
-        }
-
-        v
-    }
-
-    let s1_mod = base.0 * get_equipment_mod(s1, 1);
-    let s2_mod = base.1 * get_equipment_mod(s2, 2);
-    let s3_mod = base.2 * get_equipment_mod(s3, 3);
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::Yamato3ShipCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(1, s2_mod, 1.0),
-            FleetCutinAttackParams::new(2, s3_mod, 1.0),
-        ]
-        .into(),
-    })
-}
-
-fn get_Queen_Elizabeth_class_cutin(
-    fleet: &Fleet,
-    formation: Formation,
-) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::ECHELON | Formation::CRUISING2) {
-        return None;
-    }
-
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    if !(matches_ship_id!(s1.ship_id, "Warspite改" | "Valiant改")
-        && s1.damage_state() <= DamageState::Shouha)
-    {
-        return None;
-    }
-
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
-    }
-
-    if !(matches_ship_id!(s2.ship_id, "Warspite改" | "Valiant改")
-        && s2.damage_state() <= DamageState::Chuuha)
-    {
-        return None;
-    }
-
-    let base = (1.0, 1.0);
-
-    let partner_mod = if s2.ship_id == ship_id!("Warspite改") {
-        (1.2, 1.24)
-    } else {
-        (1.24, 1.24)
-    };
-
-    fn get_equipment_mod(ship: &Ship) -> f64 {
-        let mut v = 1.0;
-        if ship.gears.has_type(GearType::ApShell) {
-            v *= 1.35
-        }
-        if ship.gears.has_attr(GearAttr::SurfaceRadar) {
-            v *= 1.15
-        }
-        v
-    }
-
-    let s1_equipment_mod = get_equipment_mod(s1);
-    let s2_equipment_mod = get_equipment_mod(s2);
-
-    let s1_mod = base.0 * partner_mod.0 * s1_equipment_mod;
-    let s2_mod = base.1 * partner_mod.1 * s2_equipment_mod;
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::QueenElizabethClassCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(1, s2_mod, 1.0),
-        ]
-        .into(),
-    })
-}
-
-fn get_Richelieu_class_cutin(fleet: &Fleet, formation: Formation) -> Option<FleetCutinEffect> {
-    if !matches!(formation, Formation::DOUBLE_LINE | Formation::CRUISING2) {
-        return None;
-    }
-
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    if !(matches_ship_id!(s1.ship_id, "Richelieu改" | "Richelieu Deux" | "Jean Bart改")
-        && s1.damage_state() <= DamageState::Shouha)
-    {
-        return None;
-    }
-
-    if fleet
-        .ships
-        .count_by(|ship| ship.ship_type.is_surface_ship())
-        < 6
-    {
-        return None;
-    }
-
-    if !(matches_ship_id!(s2.ship_id, "Richelieu改" | "Richelieu Deux" | "Jean Bart改")
-        && s2.damage_state() <= DamageState::Chuuha)
-    {
-        return None;
-    }
-
-    let base = (1.0, 1.0);
-
-    let partner_mod = if s2.ship_id == ship_id!("Jean Bart改") {
-        (1.3, 1.24)
-    } else {
-        (1.24, 1.24)
-    };
+        .map(|(p1, p2)| {
+            format!(
+                "(ship_id(2) == {p1} && ship_id(3) == {p2}) || (ship_id(2) == {p2} && ship_id(3) == {p1})"
+            )
+        })
+        .chain([format!(
+            "(ship_id(2) == {musashi_kai2} && ship_id_in(3, {nagato_kai2}))"
+        )])
+        .collect::<Vec<_>>()
+        .join(" || ");
+
+    let s1_mod =
+        format!("(ship_id_in(2, {musashi_kai2}, {nagato_kai2}, {ise_kai2}) && 1.65) || 1.5");
+    let s2_mod = format!(
+        "(ship_id(2) == {musashi_kai2} && 1.8) || (ship_id_in(2, {nagato_kai2}) && 1.65) \
+         || (ship_id_in(2, {ise_kai2}) && 1.575) || 1.5"
+    );
 
-    fn get_equipment_mod(ship: &Ship) -> f64 {
-        let mut v = 1.0;
-        if ship.gears.has_type(GearType::ApShell) {
-            v *= 1.35
+    let equipment_mods = |n: usize| {
+        let mut mods = basic_equipment_mods(n);
+        if n <= 2 {
+            mods.push(rangefinder_equipment_mod(n));
         }
-        if ship.gears.has_attr(GearAttr::SurfaceRadar) {
-            v *= 1.15
-        }
-        v
-    }
-
-    let s1_equipment_mod = get_equipment_mod(s1);
-    let s2_equipment_mod = get_equipment_mod(s2);
-
-    let s1_mod = base.0 * partner_mod.0 * s1_equipment_mod;
-    let s2_mod = base.1 * partner_mod.1 * s2_equipment_mod;
-
-    Some(FleetCutinEffect {
-        cutin: FleetCutin::RichelieuClassCutin,
-        attacks: [
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(0, s1_mod, 1.0),
-            FleetCutinAttackParams::new(1, s2_mod, 1.0),
-        ]
-        .into(),
-    })
-}
-
-fn is_yamato_kai2(ship: &Ship) -> bool {
-    matches_ship_id!(ship.ship_id, "大和改二" | "大和改二重")
-}
-
-fn is_musashi_kai2(ship: &Ship) -> bool {
-    ship.ship_id == ship_id!("武蔵改二")
-}
-
-/// Int(1.1√ﾈﾙｿﾝLv+√3番艦Lv+√5番艦Lv+1.4√ﾈﾙｿﾝ運+25)
-/// https://x.com/Divinity_123/status/1820114418904002935
-fn calc_nelson_touch_rate(fleet: &Fleet) -> Option<f64> {
-    let s1 = fleet.ships.get(0)?;
-    let s3 = fleet.ships.get(2)?;
-    let s5 = fleet.ships.get(4)?;
-
-    let s1_level = s1.level as f64;
-    let s3_level = s3.level as f64;
-    let s5_level = s5.level as f64;
-    let s1_luck = s1.luck()? as f64;
-
-    let result =
-        1.1 * s1_level.sqrt() + s3_level.sqrt() + s5_level.sqrt() + 1.4 * s1_luck.sqrt() + 25.0;
-
-    Some((result.floor() / 100.0).min(1.0))
-}
-
-/// (√一番艦Lv +√二番艦Lv) + 1.5*(√一番艦運 +√二番艦運)+25
-/// https://x.com/Divinity_123/status/1820114420569162214
-fn calc_nagato_cutin_rate(fleet: &Fleet) -> Option<f64> {
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    let s1_level = s1.level as f64;
-    let s2_level = s2.level as f64;
-    let s1_luck = s1.luck()? as f64;
-    let s2_luck = s2.luck()? as f64;
-
-    let result = s1_level.sqrt() + s2_level.sqrt() + 1.5 * (s1_luck.sqrt() + s2_luck.sqrt()) + 25.0;
-    Some((result / 100.0).min(1.0))
-}
-
-/// √一番艦Lv +√二番艦Lv+ 1.25√一番艦運 +1.25√二番艦運+33+水上電探装備艦数補正10+大和旗艦補正3+大和or武蔵2番艦補正4
-/// https://x.com/Divinity_123/status/1820114422343376976
-fn calc_yamato2_ship_cutin_rate(fleet: &Fleet) -> Option<f64> {
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    let s1_level = s1.level as f64;
-    let s2_level = s2.level as f64;
-    let s1_luck = s1.luck()? as f64;
-    let s2_luck = s2.luck()? as f64;
-
-    let yamato_flagship_mod = if is_yamato_kai2(s1) { 3.0 } else { 0.0 };
-
-    let yamato_class_mod = if s2.ctype == ctype!("大和型") {
-        4.0
-    } else {
-        0.0
+        mods
     };
 
-    let mut surface_radar_ships_mod = 0.0;
-    if s1.gears.has_attr(GearAttr::SurfaceRadar) {
-        surface_radar_ships_mod += 10.0
-    }
-    if s2.gears.has_attr(GearAttr::SurfaceRadar) {
-        surface_radar_ships_mod += 10.0
+    FleetCutinDef {
+        tag: FleetCutin::Yamato3ShipCutin.into(),
+        formations: vec![Formation::ECHELON, Formation::CRUISING4],
+        night_only: false,
+        requires: expr(format!(
+            "surface_ship_count >= 6 && ship_id_in(1, {}, {}) \
+             && damage_state(1) <= 1 && damage_state(2) <= 1 && damage_state(3) <= 1 \
+             && ({helper_pairs})",
+            ship_id!("大和改二"),
+            ship_id!("大和改二重"),
+        )),
+        rate: CompiledEvaler::default(),
+        attacks: vec![
+            attack(0, &[&s1_mod], equipment_mods(1)),
+            attack(1, &[&s2_mod], equipment_mods(2)),
+            attack(2, &["1.65"], equipment_mods(3)),
+        ],
     }
-
-    let result = s1_level.sqrt()
-        + s2_level.sqrt()
-        + 1.25 * s1_luck.sqrt()
-        + 1.25 * s2_luck.sqrt()
-        + 33.0
-        + yamato_flagship_mod
-        + yamato_class_mod
-        + surface_radar_ships_mod;
-
-    Some((result / 100.0).min(1.0))
-}
-
-fn calc_kongou_class_cutin_rate(fleet: &Fleet) -> Option<f64> {
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    let s1_level = s1.level as f64;
-    let s2_level = s2.level as f64;
-    let s1_luck = s1.luck()? as f64;
-    let s2_luck = s2.luck()? as f64;
-
-    let equipment_mod = {
-        let has_radar = s1
-            .gears
-            .has_by(|g| g.has_attr(GearAttr::Radar) && g.los >= 8);
-        let has_large_searchlight = s1.gears.has_type(GearType::LargeSearchlight);
-
-        match s1.ship_id {
-            ship_id!("金剛改二丙") => {
-                (if has_radar { 30.0 } else { 0.0 })
-                    + if has_large_searchlight { 10.0 } else { 0.0 }
-            }
-            ship_id!("比叡改二丙") => {
-                (if has_large_searchlight { 30.0 } else { 0.0 })
-                    + if has_radar { 10.0 } else { 0.0 }
-            }
-            ship_id!("榛名改二乙") => {
-                if has_radar {
-                    20.0
-                } else {
-                    0.0
-                }
-            }
-            ship_id!("榛名改二丙") => {
-                if has_radar {
-                    15.0
-                } else {
-                    0.0
-                }
-            }
-            ship_id!("霧島改二丙") => {
-                (if has_large_searchlight { 20.0 } else { 0.0 })
-                    + if has_radar { 20.0 } else { 0.0 }
-            }
-            _ => 0.0,
-        }
-    };
-
-    let percent = (3.5 * s1_level.sqrt()
-        + 3.5 * s2_level.sqrt()
-        + 1.1 * s1_luck.sqrt()
-        + 1.1 * s2_luck.sqrt()
-        + equipment_mod
-        - 33.0)
-        .floor();
-
-    Some((percent / 100.0).min(1.0))
 }
 
 /// (√一番艦Lv +√二番艦Lv) + 1.2*(√一番艦運 +√二番艦運)+30
-fn calc_queen_elizabeth_class_cutin_rate(fleet: &Fleet) -> Option<f64> {
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    let s1_level = s1.level as f64;
-    let s2_level = s2.level as f64;
-    let s1_luck = s1.luck()? as f64;
-    let s2_luck = s2.luck()? as f64;
-
-    let result = s1_level.sqrt() + s2_level.sqrt() + 1.2 * (s1_luck.sqrt() + s2_luck.sqrt()) + 30.0;
-    Some((result / 100.0).min(1.0))
+fn queen_elizabeth_class_cutin() -> FleetCutinDef {
+    let warspite = ship_id!("Warspite改");
+    let valiant = ship_id!("Valiant改");
+    let s1_mod = format!("(ship_id(2) == {warspite} && 1.2) || 1.24");
+
+    FleetCutinDef {
+        tag: FleetCutin::QueenElizabethClassCutin.into(),
+        formations: vec![Formation::ECHELON, Formation::CRUISING2],
+        night_only: false,
+        requires: expr(format!(
+            "ship_id_in(1, {warspite}, {valiant}) && damage_state(1) <= 1 \
+             && surface_ship_count >= 6 \
+             && ship_id_in(2, {warspite}, {valiant}) && damage_state(2) <= 2"
+        )),
+        rate: expr("sqrt(level(1)) + sqrt(level(2)) + 1.2 * (sqrt(luck(1)) + sqrt(luck(2))) + 30"),
+        attacks: vec![
+            attack(0, &[&s1_mod], basic_equipment_mods(1)),
+            attack(0, &[&s1_mod], basic_equipment_mods(1)),
+            attack(1, &["1.24"], basic_equipment_mods(2)),
+        ],
+    }
 }
 
 /// (√一番艦Lv +√二番艦Lv) + 1.2*(√一番艦運 +√二番艦運)+Deux装備艦数補正5+30
-fn calc_Richelieu_class_cutin_rate(fleet: &Fleet) -> Option<f64> {
-    let s1 = fleet.ships.get(0)?;
-    let s2 = fleet.ships.get(1)?;
-
-    let s1_level = s1.level as f64;
-    let s2_level = s2.level as f64;
-    let s1_luck = s1.luck()? as f64;
-    let s2_luck = s2.luck()? as f64;
-
-    let mut deux_ships_mod = 0.0;
-    if s1.gears.has(gear_id!("38cm四連装砲改 deux")) {
-        deux_ships_mod += 5.0
-    }
-    if s2.gears.has(gear_id!("38cm四連装砲改 deux")) {
-        deux_ships_mod += 5.0
+fn richelieu_class_cutin() -> FleetCutinDef {
+    let richelieu = format!(
+        "{}, {}, {}",
+        ship_id!("Richelieu改"),
+        ship_id!("Richelieu Deux"),
+        ship_id!("Jean Bart改"),
+    );
+    let s1_mod = format!("(ship_id(2) == {} && 1.3) || 1.24", ship_id!("Jean Bart改"));
+    let deux = gear_id!("38cm四連装砲改 deux");
+
+    FleetCutinDef {
+        tag: FleetCutin::RichelieuClassCutin.into(),
+        formations: vec![Formation::DOUBLE_LINE, Formation::CRUISING2],
+        night_only: false,
+        requires: expr(format!(
+            "ship_id_in(1, {richelieu}) && damage_state(1) <= 1 && surface_ship_count >= 6 \
+             && ship_id_in(2, {richelieu}) && damage_state(2) <= 2"
+        )),
+        rate: expr(format!(
+            "sqrt(level(1)) + sqrt(level(2)) + 1.2 * (sqrt(luck(1)) + sqrt(luck(2))) \
+             + 5 * has(1, {deux}) + 5 * has(2, {deux}) + 30"
+        )),
+        attacks: vec![
+            attack(0, &[&s1_mod], basic_equipment_mods(1)),
+            attack(0, &[&s1_mod], basic_equipment_mods(1)),
+            attack(1, &["1.24"], basic_equipment_mods(2)),
+        ],
     }
-
-    let result = s1_level.sqrt()
-        + s2_level.sqrt()
-        + 1.2 * (s1_luck.sqrt() + s2_luck.sqrt())
-        + deux_ships_mod
-        + 30.0;
-    Some((result / 100.0).min(1.0))
 }
 
-pub fn calc_fleet_cutin_rate(fleet: &Fleet, cutin: FleetCutin) -> Option<f64> {
-    match cutin {
-        FleetCutin::NelsonTouch => calc_nelson_touch_rate(fleet),
-        FleetCutin::NagatoClassCutin => calc_nagato_cutin_rate(fleet),
-        FleetCutin::Yamato2ShipCutin => calc_yamato2_ship_cutin_rate(fleet),
-        FleetCutin::KongouClassCutin => calc_kongou_class_cutin_rate(fleet),
-        FleetCutin::QueenElizabethClassCutin => calc_queen_elizabeth_class_cutin_rate(fleet),
-        FleetCutin::RichelieuClassCutin => calc_Richelieu_class_cutin_rate(fleet),
-        _ => None,
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fleet_cutin_def() {
+        let def: FleetCutinDef = serde_json::from_value(serde_json::json!({
+            "tag": "NelsonTouch",
+            "formations": ["DoubleLine"],
+            "requires": "ship_id(1) == 1 && surface_ship_count >= 2",
+            "rate": "level(1) + level(2)",
+            "attacks": [
+                { "index": 0, "power_mods": ["2", "(red_t && 1.25) || 1"], "accuracy_mod": 1.05 },
+                {
+                    "index": 1,
+                    "power_mods": ["1.5"],
+                    "equipment_mods": [{ "requires": "has(2, 10)", "multiplier": 1.2 }]
+                }
+            ]
+        }))
+        .unwrap();
+
+        let ship = |ship_id: u16, gear_id: u16| {
+            let mut ship = Ship::default();
+            ship.ship_id = ship_id;
+            ship.level = 30;
+            ship.gears.push(crate::gear::Gear {
+                gear_id,
+                ..Default::default()
+            });
+            ship
+        };
+
+        let mut fleet = Fleet::default();
+        fleet.ships.push(ship(1, 0));
+        fleet.ships.push(ship(2, 10));
+
+        let effect = def
+            .get_effect(&fleet, Formation::DOUBLE_LINE, Engagement::RedT, Time::Day)
+            .unwrap();
+
+        assert_eq!(
+            effect.attacks.as_slice(),
+            [
+                FleetCutinAttackParams::new(0, 2.5, 1.05),
+                FleetCutinAttackParams::new(1, 1.5 * 1.2, 1.0),
+            ]
+        );
+        assert_eq!(def.calc_rate(&fleet), Some(0.6));
+
+        assert!(
+            def.get_effect(&fleet, Formation::ECHELON, Engagement::RedT, Time::Day)
+                .is_none()
+        );
+
+        fleet.ships.0[0] = Some(ship(3, 0));
+        assert!(
+            def.get_effect(&fleet, Formation::DOUBLE_LINE, Engagement::RedT, Time::Day)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_fleet_cutin_defs_merge_by_tag() {
+        let def = |tag: &str, rate: &str| -> FleetCutinDef {
+            serde_json::from_value(serde_json::json!({
+                "tag": tag,
+                "formations": ["Echelon"],
+                "requires": "1",
+                "rate": rate,
+                "attacks": []
+            }))
+            .unwrap()
+        };
+
+        let battle_defs = crate::types::BattleDefinitions {
+            fleet_cutin: vec![def("NelsonTouch", "10"), def("NewCutin", "20")],
+            ..Default::default()
+        };
+        let defs = battle_defs.fleet_cutin_defs();
+        let fleet = Fleet::default();
+
+        assert_eq!(defs.len(), reference_fleet_cutin_defs().len() + 1);
+        assert_eq!(
+            battle_defs.calc_fleet_cutin_rate(&fleet, &FleetCutin::NelsonTouch.into()),
+            Some(0.1)
+        );
+        assert_eq!(
+            battle_defs.calc_fleet_cutin_rate(&fleet, &FleetCutinTag::Other("NewCutin".into())),
+            Some(0.2)
+        );
+        assert!(
+            defs.iter()
+                .any(|def| def.tag == FleetCutin::Yamato3ShipCutin.into())
+        );
+
+        let effect = defs
+            .last()
+            .unwrap()
+            .get_effect(&fleet, Formation::ECHELON, Engagement::RedT, Time::Day)
+            .unwrap();
+        assert_eq!(effect.cutin, FleetCutin::Other);
+    }
+
+    #[test]
+    fn test_fleet_cutin_def_attacks() {
+        let def = |attacks: Vec<usize>| {
+            let attacks = attacks
+                .into_iter()
+                .map(|index| serde_json::json!({ "index": index, "power_mods": ["1"] }))
+                .collect::<Vec<_>>();
+
+            serde_json::from_value::<FleetCutinDef>(serde_json::json!({
+                "tag": "NewCutin",
+                "formations": ["Echelon"],
+                "requires": "1",
+                "attacks": attacks
+            }))
+        };
+
+        assert!(def(vec![0, 1, 2, 0]).is_err());
+
+        let mut fleet = Fleet::default();
+        fleet.ships.push(Ship::default());
+        fleet.ships.push(Ship::default());
+
+        let get_effect = |def: FleetCutinDef| {
+            def.get_effect(&fleet, Formation::ECHELON, Engagement::Parallel, Time::Day)
+        };
+
+        assert!(get_effect(def(vec![0, 1]).unwrap()).is_some());
+        assert!(get_effect(def(vec![0, 2]).unwrap()).is_none());
     }
 }
@@ -0,0 +1,194 @@
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::{types::DamageState, utils::Histogram};
+
+use super::{Attack, DefenseParams};
+
+/// 同じ目標への複数の攻撃を畳み込んだ残り耐久の厳密な分布
+///
+/// 各攻撃の命中種別とダメージの分布を、攻撃直前の耐久ごとに計算して合成する
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct HpDistribution {
+    pub max_hp: u16,
+    pub hp_density: Histogram<u16, f64>,
+}
+
+impl HpDistribution {
+    pub fn new(max_hp: u16, current_hp: u16) -> Self {
+        Self {
+            max_hp,
+            hp_density: Histogram::from_iter([(current_hp, 1.0)]),
+        }
+    }
+
+    pub fn from_defense_params(defense_params: &DefenseParams) -> Self {
+        Self::new(defense_params.max_hp, defense_params.current_hp)
+    }
+
+    /// 1回分の攻撃を`rate`の確率で適用する
+    fn apply_once(&mut self, attack: &Attack, rate: f64) -> Option<()> {
+        let mut next = Histogram::new();
+
+        for (&hp, &hp_rate) in self.hp_density.iter() {
+            if hp == 0 {
+                next += (hp, hp_rate);
+                continue;
+            }
+
+            if rate < 1.0 {
+                next += (hp, hp_rate * (1.0 - rate));
+            }
+
+            for (damage, damage_rate) in attack.damage_density(hp)? {
+                next += (hp.saturating_sub(damage), hp_rate * rate * damage_rate);
+            }
+        }
+
+        self.hp_density = next;
+        Some(())
+    }
+
+    /// 攻撃を適用する
+    ///
+    /// 防御側の耐久以外のパラメータは攻撃のものを用いる
+    /// `hits`の端数は最後の1回が発生する確率として扱う
+    pub fn apply(&mut self, attack: &Attack) -> Option<()> {
+        let hits = attack.hits.max(0.0);
+        let count = hits.ceil() as usize;
+
+        (0..count).try_for_each(|i| {
+            let rate = (hits - i as f64).min(1.0);
+            self.apply_once(attack, rate)
+        })
+    }
+
+    /// 攻撃を順に適用する
+    pub fn apply_all<'a>(&mut self, attacks: impl IntoIterator<Item = &'a Attack>) -> Option<()> {
+        attacks
+            .into_iter()
+            .try_for_each(|attack| self.apply(attack))
+    }
+
+    pub fn damage_state_density(&self) -> Histogram<DamageState, f64> {
+        self.hp_density
+            .iter()
+            .map(|(&hp, &rate)| (DamageState::new(self.max_hp, hp), rate))
+            .collect()
+    }
+
+    /// 残り耐久の期待値
+    pub fn expected_hp(&self) -> f64 {
+        self.hp_density
+            .iter()
+            .map(|(&hp, &rate)| hp as f64 * rate)
+            .sum()
+    }
+
+    /// 撃沈率
+    pub fn sunk_rate(&self) -> f64 {
+        self.hp_density.get(&0).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        attack::{AttackPower, HitRate},
+        histogram,
+    };
+
+    use super::*;
+
+    fn attack(hits: f64) -> Attack {
+        Attack {
+            attack_power: Some(AttackPower {
+                normal: 17.0,
+                critical: 25.0,
+                remaining_ammo_mod: 1.0,
+                ..Default::default()
+            }),
+            hit_rate: Some(HitRate {
+                normal: 0.6,
+                critical: 0.2,
+                total: 0.8,
+            }),
+            defense_params: Some(DefenseParams {
+                max_hp: 31,
+                current_hp: 31,
+                basic_defense_power: 13.0,
+                overkill_protection: false,
+                sinkable: true,
+            }),
+            is_cutin: false,
+            hits,
+        }
+    }
+
+    fn assert_total(dist: &HpDistribution) {
+        let total: f64 = dist.hp_density.values().sum();
+        assert!((total - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_single_attack() {
+        let attack = attack(1.0);
+        let mut dist = HpDistribution::new(31, 31);
+        dist.apply(&attack).unwrap();
+        assert_total(&dist);
+
+        let expected = attack
+            .damage_density(31)
+            .unwrap()
+            .into_iter()
+            .map(|(damage, rate)| (31 - damage, rate))
+            .collect::<Histogram<u16, f64>>();
+
+        assert_eq!(dist.hp_density, expected);
+    }
+
+    #[test]
+    fn test_multiple_hits() {
+        let mut double = HpDistribution::new(31, 31);
+        double.apply(&attack(2.0)).unwrap();
+        assert_total(&double);
+
+        let mut sequential = HpDistribution::new(31, 31);
+        sequential.apply_all(&[attack(1.0), attack(1.0)]).unwrap();
+
+        for (hp, rate) in double.hp_density.iter() {
+            assert!((rate - sequential.hp_density[hp]).abs() < 1e-12);
+        }
+
+        let mut partial = HpDistribution::new(31, 31);
+        partial.apply(&attack(1.5)).unwrap();
+        assert_total(&partial);
+        assert!(partial.expected_hp() > double.expected_hp());
+    }
+
+    #[test]
+    fn test_sunk_target() {
+        let mut once = HpDistribution::new(31, 1);
+        once.apply(&attack(1.0)).unwrap();
+        let survival_rate = 1.0 - once.sunk_rate();
+
+        // 撃沈済みの目標は以降の攻撃で変化しない
+        let mut dist = HpDistribution::new(31, 1);
+        dist.apply_all(&[attack(1.0), attack(1.0), attack(1.0)])
+            .unwrap();
+        assert_total(&dist);
+        assert!((dist.sunk_rate() - (1.0 - survival_rate.powi(3))).abs() < 1e-12);
+
+        let state_density = dist.damage_state_density();
+        assert_eq!(state_density.len(), 2);
+        assert_eq!(state_density[&DamageState::Sunk], dist.sunk_rate());
+
+        let mut dist = HpDistribution::new(31, 31);
+        dist.apply(&Attack {
+            hit_rate: Some(HitRate::default()),
+            ..attack(1.0)
+        })
+        .unwrap();
+        assert_eq!(dist.hp_density, histogram! { 31 => 1.0 });
+    }
+}
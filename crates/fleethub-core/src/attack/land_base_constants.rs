@@ -0,0 +1,24 @@
+//! 基地航空隊の航空攻撃で使う定数
+//!
+//! 出典: 艦これ攻略wiki「基地航空隊」 https://wikiwiki.jp/kancolle/基地航空隊
+
+/// 基地航空隊の航空攻撃の攻撃力キャップ
+pub(super) const LAND_BASE_AIRSTRIKE_POWER_CAP: f64 = 220.0;
+
+/// 基本攻撃力に加える定数
+pub(super) const LAND_BASE_AIRSTRIKE_BASIC_BONUS: f64 = 25.0;
+
+/// 搭載数に掛ける係数、基本攻撃力は`雷装(爆装) * sqrt(係数 * 搭載数)`
+pub(super) const LAND_BASE_AIRSTRIKE_SLOT_COEFFICIENT: f64 = 1.8;
+
+/// 陸攻の艦船への攻撃の種別倍率
+pub(super) const LB_ATTACKER_ANTI_SHIP_MOD: f64 = 0.8;
+
+/// 艦攻の種別倍率、どちらかが等確率で選ばれる
+pub(super) const TORPEDO_BOMBER_MODS: [f64; 2] = [0.8, 1.2];
+
+/// 命中項の定数
+pub(super) const LAND_BASE_AIRSTRIKE_ACCURACY_CONSTANT: f64 = 95.0;
+
+/// クリティカル率定数
+pub(super) const LAND_BASE_AIRSTRIKE_CRITICAL_RATE_CONSTANT: f64 = 0.2;
@@ -3,7 +3,7 @@ use crate::{
     types::{
         AswPhase, AttackPowerModifier, Engagement, FormationParams, GearType, HistoricalParams,
         NightAttackStyle, NightAttackType, NightConditions, NightFleetConditions,
-        NightPhaseAttackStyle, ProficiencyModifiers, ShipType,
+        NightPhaseAttackStyle, ProficiencyModifiers, ShipType, SpecialEnemyModifiers,
     },
 };
 
@@ -22,6 +22,8 @@ pub struct NightPhaseAttackParams<'a> {
     pub target: &'a BattleMemberRef<'a>,
     pub formation_params: FormationParams,
     pub historical_params: HistoricalParams,
+    pub special_enemy_mods: SpecialEnemyModifiers,
+    pub gunfit_accuracy: f64,
     pub night_conditions: &'a NightConditions,
 }
 
@@ -41,6 +43,8 @@ impl NightPhaseAttackParams<'_> {
                 target,
                 formation_params,
                 historical_params,
+                special_enemy_mods: self.special_enemy_mods.clone(),
+                gunfit_accuracy: self.gunfit_accuracy,
                 night_conditions,
             }
             .calc_attack_params(),
@@ -70,6 +74,8 @@ pub struct NightAttackParams<'a> {
     pub target: &'a BattleMemberRef<'a>,
     pub formation_params: FormationParams,
     pub historical_params: HistoricalParams,
+    pub special_enemy_mods: SpecialEnemyModifiers,
+    pub gunfit_accuracy: f64,
     pub night_conditions: &'a NightConditions,
 }
 
@@ -153,8 +159,7 @@ impl NightAttackParams<'_> {
             .as_ref()
             .map_or(1.0, |mods| mods.critical_power_mod);
 
-        let special_enemy_mods =
-            attacker.special_enemy_mods(target.special_enemy_type(), self.style.attack_type.into());
+        let special_enemy_mods = self.special_enemy_mods.clone();
         let historical_mod = self.historical_params.power_mod;
         let historical_armor_penetration = self.historical_params.armor_penetration;
         let custom_mods = attacker.custom_power_mods();
@@ -228,8 +233,13 @@ impl NightAttackParams<'_> {
         let formation_mod = self.formation_params.accuracy_mod;
         let morale_mod = attacker.morale_state().common_accuracy_mod();
         let cutin_mod = style.accuracy_mod;
-        let gunfit_accuracy = attacker.gunfit_accuracy(true);
-        let pt_mods = AntiPtImpAccuracyModifiers::new(attacker, target, style.attack_type);
+        let gunfit_accuracy = self.gunfit_accuracy;
+        let pt_mods = AntiPtImpAccuracyModifiers::new(
+            attacker,
+            target,
+            style.attack_type,
+            &self.special_enemy_mods,
+        );
         let historical_mod = self.historical_params.accuracy_mod;
 
         // 乗算前に切り捨て
@@ -2,7 +2,7 @@ use crate::{
     member::BattleMemberRef,
     types::{
         AttackPowerModifier, Engagement, FleetFactors, FormationParams, HistoricalParams,
-        NodeState, ShellingStyle, ShellingType,
+        NodeState, ShellingStyle, ShellingType, SpecialEnemyModifiers,
     },
 };
 
@@ -11,7 +11,6 @@ use super::{
     DefenseParams, HitRateParams,
 };
 
-const IS_DAY: bool = true;
 const SHELLING_POWER_CAP: f64 = 220.0;
 const SHELLING_CRITICAL_RATE_CONSTANT: f64 = 1.3;
 
@@ -22,6 +21,8 @@ pub struct ShellingAttackParams<'a> {
     pub engagement: Engagement,
     pub formation_params: FormationParams,
     pub historical_params: HistoricalParams,
+    pub special_enemy_mods: SpecialEnemyModifiers,
+    pub gunfit_accuracy: f64,
     pub node_state: NodeState,
     pub balloons: usize,
 }
@@ -44,8 +45,7 @@ impl ShellingAttackParams<'_> {
             .is_heavily_armored_ship()
             .then(|| attacker.get_ap_shell_modifiers());
 
-        let special_enemy_mods =
-            attacker.special_enemy_mods(target.special_enemy_type(), style.attack_type.into());
+        let special_enemy_mods = self.special_enemy_mods.clone();
 
         let mut aerial_power = None;
         let mut aerial_power_ebonus = 0.0;
@@ -131,12 +131,17 @@ impl ShellingAttackParams<'_> {
             let ibonus = attacker
                 .gears
                 .sum_by(|gear| gear.ibonuses.shelling_accuracy);
-            let gunfit_accuracy = attacker.gunfit_accuracy(!IS_DAY);
+            let gunfit_accuracy = self.gunfit_accuracy;
             let morale_mod = attacker.morale_state().common_accuracy_mod();
             let formation_mod = formation_params.accuracy_mod;
             let ap_shell_mod = ap_shell_mods.map(|mods| mods.1).unwrap_or(1.0);
             let cutin_mod = style.accuracy_mod;
-            let pt_mods = AntiPtImpAccuracyModifiers::new(attacker, target, style.attack_type);
+            let pt_mods = AntiPtImpAccuracyModifiers::new(
+                attacker,
+                target,
+                style.attack_type,
+                &self.special_enemy_mods,
+            );
             let historical_mod = self.historical_params.accuracy_mod;
 
             // 警戒陣回避補正
@@ -0,0 +1,18 @@
+//! 支援艦隊の攻撃で使う定数
+//!
+//! 出典: 艦これ攻略wiki「支援艦隊」 https://wikiwiki.jp/kancolle/支援艦隊
+
+/// 支援攻撃の攻撃力キャップ、砲撃・雷撃・航空支援で共通
+pub(super) const SUPPORT_POWER_CAP: f64 = 170.0;
+
+/// 支援砲撃・雷撃の命中定数
+pub(super) const SUPPORT_ACCURACY_CONSTANT: f64 = 64.0;
+
+/// 支援砲撃・雷撃のクリティカル率定数
+pub(super) const SUPPORT_CRITICAL_RATE_CONSTANT: f64 = 1.0;
+
+/// 航空支援の基本攻撃力に加える定数
+pub(super) const SUPPORT_AIRSTRIKE_BASIC_BONUS: f64 = 3.0;
+
+/// 航空支援のキャップ前補正
+pub(super) const SUPPORT_AIRSTRIKE_PRECAP_MOD: f64 = 1.35;
@@ -4,15 +4,16 @@ use crate::{
     member::BattleMemberRef,
     types::{
         AttackPowerModifier, Engagement, FormationParams, NodeState, ShellingType,
-        SupportShellingType,
+        SpecialEnemyModifiers, SupportShellingType,
     },
 };
 
-use super::{Attack, AttackParams, AttackPowerParams, DefenseParams, HitRateParams};
-
-const SUPPORT_SHELLING_POWER_CAP: f64 = 170.0;
-const SUPPORT_SHELLING_CRITICAL_RATE_CONSTANT: f64 = 1.0;
-const SUPPORT_SHELLING_ACCURACY_CONSTANT: f64 = 64.0;
+use super::{
+    Attack, AttackParams, AttackPowerParams, DefenseParams, HitRateParams,
+    support_constants::{
+        SUPPORT_ACCURACY_CONSTANT, SUPPORT_CRITICAL_RATE_CONSTANT, SUPPORT_POWER_CAP,
+    },
+};
 
 pub struct SupportShellingAttackParams<'a> {
     pub attack_type: SupportShellingType,
@@ -20,6 +21,7 @@ pub struct SupportShellingAttackParams<'a> {
     pub target: &'a BattleMemberRef<'a>,
     pub engagement: Engagement,
     pub formation_params: FormationParams,
+    pub special_enemy_mods: SpecialEnemyModifiers,
     pub node_state: NodeState,
 }
 
@@ -69,13 +71,12 @@ impl SupportShellingAttackParams<'_> {
         let precap_mod = AttackPowerModifier::new(a14, b14);
         let postcap_mod = Default::default();
 
-        let special_enemy_mods =
-            attacker.special_enemy_mods(self.target.special_enemy_type(), self.attack_type.into());
+        let special_enemy_mods = self.special_enemy_mods.clone();
 
         let params = AttackPowerParams {
             is_cutin: false,
             basic,
-            cap: SUPPORT_SHELLING_POWER_CAP,
+            cap: SUPPORT_POWER_CAP,
             precap_mod,
             postcap_mod,
             ap_shell_mod: None,
@@ -100,48 +101,13 @@ impl SupportShellingAttackParams<'_> {
         let morale_mod = attacker.morale_state().common_accuracy_mod();
         let formation_mod = self.formation_params.accuracy_mod;
 
-        // 警戒陣回避補正
-        let vanguard_mod = if target.formation.is_vanguard() {
-            if target.ship_type.is_destroyer() {
-                let is_event = self.node_state.is_event();
-
-                if is_event {
-                    match target.position.index {
-                        0 | 1 => 0.95,
-                        2 | 3 => 0.66,
-                        4 => 0.52,
-                        5 => 0.48,
-                        6 => 0.4,
-                        _ => 1.0,
-                    }
-                } else {
-                    match target.position.index {
-                        0 | 1 => 0.95,
-                        2 | 3 => 0.8,
-                        4 => 0.69,
-                        5 => 0.64,
-                        6 => 0.64,
-                        _ => 1.0,
-                    }
-                }
-            } else {
-                match target.position.index {
-                    0..=3 => 0.95,
-                    4 => 0.86,
-                    5 => 0.8,
-                    6 => 0.7,
-                    _ => 1.0,
-                }
-            }
-        } else {
-            1.0
-        };
+        let vanguard_mod = support_vanguard_mod(target, self.node_state);
 
         // 乗算前に切り捨て
-        let multiplicand =
-            ((SUPPORT_SHELLING_ACCURACY_CONSTANT + basic_accuracy_term + gears_accuracy).floor()
-                * vanguard_mod)
-                .floor();
+        let multiplicand = ((SUPPORT_ACCURACY_CONSTANT + basic_accuracy_term + gears_accuracy)
+            .floor()
+            * vanguard_mod)
+            .floor();
 
         let accuracy_term = (multiplicand * formation_mod * morale_mod).floor();
 
@@ -158,9 +124,48 @@ impl SupportShellingAttackParams<'_> {
             accuracy_term,
             evasion_term,
             target_morale_mod: target.morale_state().hit_rate_mod(),
-            critical_rate_constant: SUPPORT_SHELLING_CRITICAL_RATE_CONSTANT,
+            critical_rate_constant: SUPPORT_CRITICAL_RATE_CONSTANT,
             critical_percentage_bonus: 0.0,
             hit_percentage_bonus: 0.0,
         })
     }
 }
+
+/// 支援攻撃の警戒陣回避補正
+pub(super) fn support_vanguard_mod(target: &BattleMemberRef, node_state: NodeState) -> f64 {
+    if target.formation.is_vanguard() {
+        if target.ship_type.is_destroyer() {
+            let is_event = node_state.is_event();
+
+            if is_event {
+                match target.position.index {
+                    0 | 1 => 0.95,
+                    2 | 3 => 0.66,
+                    4 => 0.52,
+                    5 => 0.48,
+                    6 => 0.4,
+                    _ => 1.0,
+                }
+            } else {
+                match target.position.index {
+                    0 | 1 => 0.95,
+                    2 | 3 => 0.8,
+                    4 => 0.69,
+                    5 => 0.64,
+                    6 => 0.64,
+                    _ => 1.0,
+                }
+            }
+        } else {
+            match target.position.index {
+                0..=3 => 0.95,
+                4 => 0.86,
+                5 => 0.8,
+                6 => 0.7,
+                _ => 1.0,
+            }
+        }
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    member::BattleMemberRef,
+    types::{AttackPowerModifier, Engagement, FormationParams, NodeState, SpecialEnemyModifiers},
+};
+
+use super::{
+    Attack, AttackParams, AttackPowerParams, DefenseParams, HitRateParams,
+    support_constants::{
+        SUPPORT_ACCURACY_CONSTANT, SUPPORT_CRITICAL_RATE_CONSTANT, SUPPORT_POWER_CAP,
+    },
+    support_shelling_attack::support_vanguard_mod,
+};
+
+/// 長距離雷撃支援
+pub struct SupportTorpedoAttackParams<'a> {
+    pub attacker: &'a BattleMemberRef<'a>,
+    pub target: &'a BattleMemberRef<'a>,
+    pub engagement: Engagement,
+    pub formation_params: FormationParams,
+    pub special_enemy_mods: SpecialEnemyModifiers,
+    pub node_state: NodeState,
+}
+
+impl SupportTorpedoAttackParams<'_> {
+    pub fn to_attack(&self) -> Attack {
+        self.calc_attack_params().into_attack()
+    }
+
+    pub fn calc_attack_params(&self) -> AttackParams {
+        let defense_params = DefenseParams::from_target(self.target, self.target.side(), 0.0);
+
+        AttackParams {
+            attack_power_params: self.calc_attack_power_params(),
+            hit_rate_params: self.calc_hit_rate_params(),
+            defense_params,
+            hits: 1.0,
+        }
+    }
+
+    fn calc_attack_power_params(&self) -> Option<AttackPowerParams> {
+        let attacker = self.attacker;
+
+        let torpedo = attacker.torpedo()? as f64;
+        let damage_mod = attacker.damage_state().torpedo_power_mod();
+
+        let formation_mod = self.formation_params.power_mod;
+        let engagement_mod = self.engagement.modifier();
+
+        let basic = 8.0 + torpedo;
+
+        let a14 = formation_mod * engagement_mod * damage_mod;
+        let precap_mod = AttackPowerModifier::new(a14, 0.0);
+
+        let special_enemy_mods = self.special_enemy_mods.clone();
+
+        Some(AttackPowerParams {
+            basic,
+            cap: SUPPORT_POWER_CAP,
+            precap_mod,
+            remaining_ammo_mod: attacker.remaining_ammo_mod(),
+            special_enemy_mods,
+            custom_mods: attacker.custom_power_mods(),
+            ..Default::default()
+        })
+    }
+
+    fn calc_accuracy_term(&self) -> Option<f64> {
+        let attacker = self.attacker;
+        let basic_accuracy_term = attacker.basic_accuracy_term()?;
+        let gears_accuracy = attacker.gears.sum_by(|gear| gear.accuracy) as f64;
+        let morale_mod = attacker.morale_state().torpedo_accuracy_mod();
+        let formation_mod = self.formation_params.accuracy_mod;
+
+        let vanguard_mod = support_vanguard_mod(self.target, self.node_state);
+
+        // 乗算前に切り捨て
+        let multiplicand = ((SUPPORT_ACCURACY_CONSTANT + basic_accuracy_term + gears_accuracy)
+            .floor()
+            * vanguard_mod)
+            .floor();
+
+        let accuracy_term = (multiplicand * formation_mod * morale_mod).floor();
+
+        Some(accuracy_term)
+    }
+
+    fn calc_hit_rate_params(&self) -> Option<HitRateParams> {
+        let target = self.target;
+        let formation_mod = self.formation_params.target_evasion_mod;
+        let evasion_term = target.evasion_term(formation_mod, 0.0, 1.0)?;
+        let accuracy_term = self.calc_accuracy_term()?;
+
+        Some(HitRateParams {
+            accuracy_term,
+            evasion_term,
+            target_morale_mod: target.morale_state().hit_rate_mod(),
+            critical_rate_constant: SUPPORT_CRITICAL_RATE_CONSTANT,
+            critical_percentage_bonus: 0.0,
+            hit_percentage_bonus: 0.0,
+        })
+    }
+}
@@ -20,6 +20,8 @@ pub struct TorpedoAttackParams<'a> {
     pub engagement: Engagement,
     pub formation_params: FormationParams,
     pub historical_params: HistoricalParams,
+    /// 命中補正のみに使う
+    pub special_enemy_mods: SpecialEnemyModifiers,
     pub node_state: NodeState,
 }
 
@@ -111,7 +113,12 @@ impl TorpedoAttackParams<'_> {
 
         let formation_mod = self.formation_params.accuracy_mod;
         let morale_mod = attacker.morale_state().torpedo_accuracy_mod();
-        let pt_mods = AntiPtImpAccuracyModifiers::new(attacker, target, TorpedoAttackType);
+        let pt_mods = AntiPtImpAccuracyModifiers::new(
+            attacker,
+            target,
+            TorpedoAttackType,
+            &self.special_enemy_mods,
+        );
         let historical_mod = self.historical_params.accuracy_mod;
 
         // 警戒陣回避補正
@@ -3,22 +3,27 @@
 mod battle_comp;
 mod day_phase;
 mod night_phase;
+mod opening_asw_phase;
 pub mod support_shelling_phase;
+pub mod support_torpedo_phase;
 mod target_picker;
 mod torpedo_phase;
 
 use rand::prelude::*;
 
 use crate::{
-    comp::Comp,
+    plane::PlaneImpl,
+    simulator::AerialCombat,
     types::{
-        AirState, BattleDefinitions, DayCombatRound, EnemyCompType, Engagement, NodeState, Phase,
-        PlayerCompType,
+        AirState, BattleDefinitions, BattleType, DayCombatRound, EnemyCompType, Engagement,
+        NodeState, Participant, Phase, PlayerCompType,
     },
 };
 
 pub use battle_comp::*;
 use day_phase::DayPhase;
+use night_phase::NightPhase;
+use opening_asw_phase::OpeningAswPhase;
 use torpedo_phase::TorpedoPhase;
 
 pub struct Battle<'a, R>
@@ -26,11 +31,13 @@ where
     R: Rng + ?Sized,
 {
     pub rng: &'a mut R,
-    pub battle_defs: BattleDefinitions,
+    pub battle_defs: &'a BattleDefinitions,
+    pub battle_type: BattleType,
     pub engagement: Engagement,
     pub node_state: NodeState,
-    pub player_comp: BattleComp,
-    pub enemy_comp: BattleComp,
+    pub air_state: AirState,
+    pub player_comp: &'a mut BattleComp,
+    pub enemy_comp: &'a mut BattleComp,
 }
 
 impl<'a, R> Battle<'a, R>
@@ -39,78 +46,182 @@ where
 {
     pub fn new(
         rng: &'a mut R,
-        battle_defs: BattleDefinitions,
+        battle_defs: &'a BattleDefinitions,
+        battle_type: BattleType,
         engagement: Engagement,
         node_state: NodeState,
-        player_comp: Comp,
-        enemy_comp: Comp,
+        player_comp: &'a mut BattleComp,
+        enemy_comp: &'a mut BattleComp,
     ) -> Self {
         Self {
             rng,
             battle_defs,
+            battle_type,
             engagement,
             node_state,
-            player_comp: BattleComp {
-                comp: player_comp,
-                formation: Default::default(),
-            },
-            enemy_comp: BattleComp {
-                comp: enemy_comp,
-                formation: Default::default(),
-            },
+            air_state: AirState::air_parity(),
+            player_comp,
+            enemy_comp,
         }
     }
 
-    /// unimplemented
+    fn aerial_combat(&mut self) -> AerialCombat<'_, R> {
+        let player_formation = self.player_comp.formation;
+        let enemy_formation = self.enemy_comp.formation;
+
+        AerialCombat {
+            rng: self.rng,
+            battle_defs: self.battle_defs,
+            player_comp: &mut self.player_comp.comp,
+            enemy_comp: &mut self.enemy_comp.comp,
+            escort_participates: true,
+            player_formation,
+            enemy_formation,
+        }
+    }
+
+    fn has_jet_planes(&self) -> bool {
+        [&self.player_comp, &self.enemy_comp]
+            .into_iter()
+            .any(|comp| {
+                comp.planes(true)
+                    .any(|plane| plane.remains() && plane.is_jet_plane())
+            })
+    }
+
     fn try_jet_assault(&mut self) -> anyhow::Result<()> {
-        Ok(())
+        self.aerial_combat().try_jet_assault_phase()
     }
 
-    /// unimplemented
     fn try_aerial_combat(&mut self) -> anyhow::Result<()> {
+        self.air_state = self.aerial_combat().try_aerial_combat()?;
         Ok(())
     }
 
-    /// unimplemented
-    fn try_closing_torpedo_combat(&mut self) -> anyhow::Result<()> {
+    fn has_opening_asw_ships(&self) -> bool {
+        self.player_comp
+            .members(Participant::Both)
+            .any(|ship| ship.remains() && ship.can_do_opening_asw())
+    }
+
+    fn has_opening_torpedo_ships(&self) -> bool {
+        [&self.player_comp, &self.enemy_comp]
+            .into_iter()
+            .any(|comp| {
+                comp.members(Participant::Escort)
+                    .any(|ship| ship.can_do_opening_torpedo())
+            })
+    }
+
+    fn try_opening_asw(&mut self) -> anyhow::Result<()> {
+        OpeningAswPhase {
+            rng: self.rng,
+            battle_defs: self.battle_defs,
+            node_state: self.node_state,
+            engagement: self.engagement,
+            player_comp: self.player_comp,
+            enemy_comp: self.enemy_comp,
+        }
+        .try_combat()
+    }
+
+    fn try_torpedo_combat(&mut self, is_opening: bool) -> anyhow::Result<()> {
         TorpedoPhase {
             rng: self.rng,
-            battle_defs: &self.battle_defs,
+            battle_defs: self.battle_defs,
             node_state: self.node_state,
             engagement: self.engagement,
-            player_comp: &mut self.player_comp,
-            enemy_comp: &mut self.enemy_comp,
+            is_opening,
+            player_comp: self.player_comp,
+            enemy_comp: self.enemy_comp,
         }
         .try_combat()
     }
 
-    fn try_day_phase(&mut self, air_state: AirState, round: DayCombatRound) -> anyhow::Result<()> {
+    fn try_day_phase(&mut self, round: DayCombatRound) -> anyhow::Result<()> {
         DayPhase {
             rng: self.rng,
-            battle_defs: &self.battle_defs,
+            battle_defs: self.battle_defs,
             engagement: self.engagement,
             node_state: self.node_state,
-            air_state,
+            air_state: self.air_state,
             round,
-            player_comp: &mut self.player_comp,
-            enemy_comp: &mut self.enemy_comp,
+            player_comp: self.player_comp,
+            enemy_comp: self.enemy_comp,
         }
         .try_combat()
     }
 
-    pub fn try_battle(&mut self) -> anyhow::Result<()> {
+    fn try_night_phase(&mut self) -> anyhow::Result<()> {
+        NightPhase {
+            rng: self.rng,
+            battle_defs: self.battle_defs,
+            node_state: self.node_state,
+            engagement: self.engagement,
+            air_state: self.air_state,
+            player_comp: self.player_comp,
+            enemy_comp: self.enemy_comp,
+        }
+        .try_combat()
+    }
+
+    pub fn phases(&self) -> anyhow::Result<Vec<Phase>> {
         let player_comp_type = self.player_comp.org_type().try_into()?;
         let enemy_comp_type = self.enemy_comp.org_type().try_into()?;
-        let air_state = Default::default();
 
-        let phases = order_phases(player_comp_type, enemy_comp_type);
+        let mut phases = vec![];
+
+        if self.battle_type.has_day_battle() {
+            if self.has_jet_planes() {
+                phases.push(Phase::JetAssault);
+            }
 
-        phases.into_iter().try_for_each(|phase| match phase {
+            phases.push(Phase::AerialCombat);
+
+            if self.has_opening_asw_ships() {
+                phases.push(Phase::OpeningAsw);
+            }
+
+            if self.has_opening_torpedo_ships() {
+                phases.push(Phase::OpeningTorpedo);
+            }
+
+            phases.extend(order_phases(player_comp_type, enemy_comp_type));
+        }
+
+        if self.battle_type.has_night_battle() {
+            phases.push(Phase::NightCombat);
+        }
+
+        Ok(phases)
+    }
+
+    pub fn try_phase(&mut self, phase: Phase) -> anyhow::Result<()> {
+        match phase {
             Phase::JetAssault => self.try_jet_assault(),
             Phase::AerialCombat => self.try_aerial_combat(),
-            Phase::DayCombat(round) => self.try_day_phase(air_state, round),
-            Phase::ClosingTorpedo => self.try_closing_torpedo_combat(),
-            _ => unimplemented!(),
+            Phase::OpeningAsw => self.try_opening_asw(),
+            Phase::OpeningTorpedo => self.try_torpedo_combat(true),
+            Phase::DayCombat(round) => self.try_day_phase(round),
+            Phase::ClosingTorpedo => self.try_torpedo_combat(false),
+            Phase::NightCombat => self.try_night_phase(),
+        }
+    }
+
+    fn enemy_remains(&self) -> bool {
+        self.enemy_comp
+            .members(Participant::Both)
+            .any(|ship| ship.remains())
+    }
+
+    /// 昼戦で敵艦隊が全滅した場合は夜戦を行わない
+    pub fn try_battle(&mut self) -> anyhow::Result<()> {
+        self.phases()?.into_iter().try_for_each(|phase| {
+            if phase == Phase::NightCombat && !self.enemy_remains() {
+                return Ok(());
+            }
+
+            self.try_phase(phase)
         })
     }
 }
@@ -161,3 +272,321 @@ fn order_phases(player: PlayerCompType, enemy: EnemyCompType) -> Vec<Phase> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        comp::Comp,
+        fleet::Fleet,
+        gear::Gear,
+        master_data::StatInterval,
+        ship::Ship,
+        test::{battle_defs, comp, rng, ship},
+        types::{Formation, GearAttr, GearType, OrgType, ShipType, ctype, ship_id},
+    };
+
+    fn combined(org_type: OrgType, main: Vec<Ship>, escort: Vec<Ship>) -> Comp {
+        let mut comp = comp(org_type, main);
+        let mut fleet = Fleet::default();
+        escort.into_iter().for_each(|ship| fleet.ships.push(ship));
+        comp.escort = Some(fleet);
+        comp
+    }
+
+    fn phases(player: Comp, enemy: Comp) -> Vec<Phase> {
+        let battle_defs = battle_defs();
+        let mut rng = rng(0);
+        let mut player = BattleComp::new(player, Formation::LINE_AHEAD);
+        let mut enemy = BattleComp::new(enemy, Formation::LINE_AHEAD);
+
+        Battle::new(
+            &mut rng,
+            &battle_defs,
+            BattleType::Day,
+            Engagement::Parallel,
+            NodeState::default(),
+            &mut player,
+            &mut enemy,
+        )
+        .phases()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_skip_night_combat_after_annihilation() {
+        let mut battle_defs = battle_defs();
+        battle_defs.fleet_cutin = vec![
+            serde_json::from_value(serde_json::json!({
+                "tag": "NelsonTouch",
+                "formations": ["LineAhead"],
+                "night_only": true,
+                "requires": "1",
+                "rate": "100",
+                "attacks": []
+            }))
+            .unwrap(),
+        ];
+
+        // 夜戦が行われた場合のみ艦隊特殊攻撃を使用する
+        let fleet_cutin_used = |enemy_hp: u16| {
+            let mut rng = rng(0);
+            let mut enemy_ship = ship("e1", ShipType::DD, 300);
+            enemy_ship.master.firepower.1 = Some(0);
+            enemy_ship.current_hp = enemy_hp;
+
+            let mut player = BattleComp::new(
+                comp(OrgType::Single, vec![ship("p1", ShipType::DD, 30)]),
+                Formation::LINE_AHEAD,
+            );
+            let mut enemy = BattleComp::new(enemy(vec![enemy_ship]), Formation::LINE_AHEAD);
+
+            Battle::new(
+                &mut rng,
+                &battle_defs,
+                BattleType::DayAndNight,
+                Engagement::Parallel,
+                NodeState::default(),
+                &mut player,
+                &mut enemy,
+            )
+            .try_battle()
+            .unwrap();
+
+            player.main.fleet_cutin_used
+        };
+
+        assert!(fleet_cutin_used(300));
+        assert!(!fleet_cutin_used(0));
+    }
+
+    /// 先制対潜を行える艦
+    fn isuzu() -> Ship {
+        let mut ship = ship("isuzu", ShipType::CL, 50);
+        ship.ship_id = ship_id!("五十鈴改二");
+        ship.master.asw = StatInterval(Some(60), Some(60));
+        ship
+    }
+
+    fn submarine(id: &str, level: u16) -> Ship {
+        let mut ship = ship(id, ShipType::SS, 40);
+        ship.level = level;
+        ship
+    }
+
+    fn abyssal_submarine(id: &str, ctype: u16, yomi: &str) -> Ship {
+        let mut ship = ship(id, ShipType::SS, 40);
+        ship.ship_id = 1501;
+        ship.master.ship_id = 1501;
+        ship.ctype = ctype;
+        ship.master.yomi = yomi.into();
+        ship
+    }
+
+    fn enemy(ships: Vec<Ship>) -> Comp {
+        comp(OrgType::EnemySingle, ships)
+    }
+
+    fn carrier(gear_type: GearType, attr: GearAttr, slot_size: u8) -> Ship {
+        let mut gear = Gear::default();
+        gear.gear_type = gear_type;
+        gear.attrs = attr.into();
+
+        let mut ship = ship("cv", ShipType::CV, 60);
+        ship.gears.push(gear);
+        ship.slots = [Some(slot_size)].into_iter().collect();
+        ship
+    }
+
+    #[test]
+    fn test_jet_assault_participants() {
+        let dd = || ship("dd", ShipType::DD, 30);
+        let jet = |slot_size| carrier(GearType::JetFighterBomber, GearAttr::JetAircraft, slot_size);
+
+        let has_jet_assault = |player: Vec<Ship>, enemy: Vec<Ship>| {
+            phases(comp(OrgType::Single, player), self::enemy(enemy)).contains(&Phase::JetAssault)
+        };
+
+        assert!(has_jet_assault(vec![jet(10)], vec![dd()]));
+        assert!(has_jet_assault(vec![dd()], vec![jet(10)]));
+        assert!(!has_jet_assault(vec![jet(0)], vec![dd()]));
+        assert!(!has_jet_assault(
+            vec![carrier(GearType::CbDiveBomber, GearAttr::CbAircraft, 10)],
+            vec![dd()],
+        ));
+    }
+
+    #[test]
+    fn test_opening_asw_participants() {
+        let dd = || ship("dd", ShipType::DD, 30);
+
+        let has_opening_asw =
+            |player: Comp| phases(player, enemy(vec![dd()])).contains(&Phase::OpeningAsw);
+
+        assert!(has_opening_asw(comp(OrgType::Single, vec![isuzu()])));
+        assert!(!has_opening_asw(comp(OrgType::Single, vec![dd()])));
+
+        // 連合艦隊では主力艦隊と随伴艦隊の両方が参加する
+        assert!(has_opening_asw(combined(
+            OrgType::CarrierTaskForce,
+            vec![isuzu()],
+            vec![dd()],
+        )));
+        assert!(has_opening_asw(combined(
+            OrgType::CarrierTaskForce,
+            vec![dd()],
+            vec![isuzu()],
+        )));
+
+        // 敵艦は先制対潜を行わない
+        let mut enemy_isuzu = isuzu();
+        enemy_isuzu.master.ship_id = 1501;
+        assert!(
+            !phases(comp(OrgType::Single, vec![dd()]), enemy(vec![enemy_isuzu]))
+                .contains(&Phase::OpeningAsw)
+        );
+
+        // 沈んだ艦は参加しない
+        let mut sunk = isuzu();
+        sunk.current_hp = 0;
+        assert!(!has_opening_asw(comp(OrgType::Single, vec![sunk])));
+    }
+
+    #[test]
+    fn test_opening_torpedo_participants() {
+        let dd = || ship("dd", ShipType::DD, 30);
+
+        let has_opening_torpedo =
+            |player: Comp, enemy: Comp| phases(player, enemy).contains(&Phase::OpeningTorpedo);
+        let player_has_opening_torpedo =
+            |player: Comp| has_opening_torpedo(player, self::enemy(vec![dd()]));
+
+        assert!(player_has_opening_torpedo(comp(
+            OrgType::Single,
+            vec![submarine("ss", 10)]
+        )));
+        assert!(!player_has_opening_torpedo(comp(
+            OrgType::Single,
+            vec![submarine("ss", 9)]
+        )));
+        assert!(!player_has_opening_torpedo(comp(
+            OrgType::Single,
+            vec![dd()]
+        )));
+
+        // 大破した艦は参加しない
+        let mut taiha = submarine("ss", 99);
+        taiha.current_hp = 10;
+        assert!(!player_has_opening_torpedo(comp(
+            OrgType::Single,
+            vec![taiha]
+        )));
+
+        // 連合艦隊では随伴艦隊のみが参加する
+        assert!(!player_has_opening_torpedo(combined(
+            OrgType::CarrierTaskForce,
+            vec![submarine("ss", 99)],
+            vec![dd()],
+        )));
+        assert!(player_has_opening_torpedo(combined(
+            OrgType::CarrierTaskForce,
+            vec![dd()],
+            vec![submarine("ss", 99)],
+        )));
+
+        let enemy_has_opening_torpedo = |ctype: u16, yomi: &str| {
+            has_opening_torpedo(
+                comp(OrgType::Single, vec![dd()]),
+                enemy(vec![abyssal_submarine("es", ctype, yomi)]),
+            )
+        };
+
+        assert!(!enemy_has_opening_torpedo(ctype!("潜水カ級"), ""));
+        assert!(enemy_has_opening_torpedo(ctype!("潜水カ級"), "elite"));
+        assert!(enemy_has_opening_torpedo(ctype!("潜水カ級"), "flagship"));
+        assert!(enemy_has_opening_torpedo(ctype!("潜水ヨ級"), ""));
+        assert!(enemy_has_opening_torpedo(ctype!("潜水ソ級"), ""));
+    }
+
+    fn run_phase(phase: Phase, seed: u64, player: Comp, enemy: Comp) -> (BattleComp, BattleComp) {
+        let battle_defs = battle_defs();
+        let mut rng = rng(seed);
+        let mut player = BattleComp::new(player, Formation::LINE_AHEAD);
+        let mut enemy = BattleComp::new(enemy, Formation::LINE_AHEAD);
+
+        Battle::new(
+            &mut rng,
+            &battle_defs,
+            BattleType::Day,
+            Engagement::Parallel,
+            NodeState::default(),
+            &mut player,
+            &mut enemy,
+        )
+        .try_phase(phase)
+        .unwrap();
+
+        (player, enemy)
+    }
+
+    fn hp_by_id(comp: &Comp, id: &str) -> u16 {
+        comp.all_members()
+            .find(|ship| ship.id == id)
+            .map(|ship| ship.current_hp)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_opening_asw_phase() {
+        let mut damaged = 0;
+
+        for seed in 0..50 {
+            let player = comp(OrgType::Single, vec![isuzu()]);
+            let enemy = enemy(vec![
+                ship("e_dd", ShipType::DD, 40),
+                ship("e_ss", ShipType::SS, 40),
+            ]);
+
+            let (player, enemy) = run_phase(Phase::OpeningAsw, seed, player, enemy);
+
+            // 対潜攻撃は潜水艦のみを狙い、反撃は受けない
+            assert_eq!(hp_by_id(&enemy, "e_dd"), 40);
+            assert_eq!(hp_by_id(&player, "isuzu"), 50);
+
+            if hp_by_id(&enemy, "e_ss") < 40 {
+                damaged += 1;
+            }
+        }
+
+        assert!(damaged > 0);
+    }
+
+    #[test]
+    fn test_opening_torpedo_phase() {
+        let mut damaged = 0;
+
+        for seed in 0..50 {
+            let player = comp(
+                OrgType::Single,
+                vec![submarine("ss", 99), ship("dd", ShipType::DD, 30)],
+            );
+            let enemy = enemy(vec![
+                ship("e_dd", ShipType::DD, 40),
+                abyssal_submarine("e_ss", ctype!("潜水カ級"), ""),
+            ]);
+
+            let (player, enemy) = run_phase(Phase::OpeningTorpedo, seed, player, enemy);
+
+            // 潜水艦は雷撃の対象にならず、潜水カ級(無印)は開幕雷撃を行わない
+            assert_eq!(hp_by_id(&enemy, "e_ss"), 40);
+            assert_eq!(hp_by_id(&player, "ss"), 40);
+            assert_eq!(hp_by_id(&player, "dd"), 30);
+
+            if hp_by_id(&enemy, "e_dd") < 40 {
+                damaged += 1;
+            }
+        }
+
+        assert!(damaged > 0);
+    }
+}
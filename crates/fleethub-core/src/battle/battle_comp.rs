@@ -7,11 +7,29 @@ use enumset::EnumSet;
 use rand::prelude::*;
 
 use crate::{
+    attack::FleetCutinEffect,
     comp::Comp,
     member::{BattleMemberMut, BattleMemberRef},
-    types::{FleetType, Formation, OrgType, Participant, ShipPosition},
+    types::{
+        BattleDefinitions, Engagement, FleetType, Formation, OrgType, Participant, ShipPosition,
+        Time,
+    },
 };
 
+/// 艦隊特殊攻撃に参加する自艦隊の艦か
+pub(super) fn is_fleet_cutin_participant(
+    effect: &FleetCutinEffect,
+    fleet_type: FleetType,
+    position: ShipPosition,
+) -> bool {
+    position.side().is_player()
+        && position.fleet_type == fleet_type
+        && effect
+            .attacks
+            .iter()
+            .any(|params| params.index == position.index)
+}
+
 pub struct BattleComp {
     pub comp: Comp,
     pub formation: Formation,
@@ -87,6 +105,43 @@ impl BattleComp {
             })
     }
 
+    /// 艦隊特殊攻撃の発動判定
+    ///
+    /// 発動した艦隊は出撃中に再度発動しない
+    /// 発動率が不明な場合は判定せず、`fleet_cutin_skipped`に記録する
+    pub fn try_fleet_cutin<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        battle_defs: &BattleDefinitions,
+        fleet_type: FleetType,
+        engagement: Engagement,
+        time: Time,
+    ) -> Option<FleetCutinEffect> {
+        let formation = self.formation;
+        let fleet = self.get_fleet_mut(fleet_type)?;
+
+        if fleet.fleet_cutin_used {
+            return None;
+        }
+
+        let effect = battle_defs
+            .get_possible_fleet_cutin_effect_vec(fleet, formation, engagement, time)
+            .into_iter()
+            .next()?;
+
+        let Some(rate) = battle_defs.calc_fleet_cutin_rate(fleet, &effect.tag) else {
+            fleet.fleet_cutin_skipped = true;
+            return None;
+        };
+
+        if !rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            return None;
+        }
+
+        fleet.fleet_cutin_used = true;
+        Some(effect)
+    }
+
     pub fn get_ship(&self, position: ShipPosition) -> Option<BattleMemberRef<'_>> {
         self.comp.get_battle_member(self.formation, position)
     }
@@ -113,7 +168,7 @@ impl BattleComp {
     ) -> impl Iterator<Item = ShipPosition> + 'a {
         let mut group = self
             .members(participant)
-            .filter(|ship| ship.participates_in_day_combat(anti_inst))
+            .filter(|ship| ship.remains() && ship.participates_in_day_combat(anti_inst))
             .fold(BTreeMap::<u8, Vec<ShipPosition>>::new(), |mut acc, ship| {
                 let position = ship.position;
                 let range = ship.range().unwrap_or_default();
@@ -126,3 +181,90 @@ impl BattleComp {
         group.into_values().rev().flatten()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        fleet::Fleet,
+        ship::Ship,
+        test::rng,
+        types::{FleetCutin, ShipType, ctype},
+    };
+
+    #[test]
+    fn test_try_fleet_cutin() {
+        let ship = |ctype: u16| {
+            let mut ship = Ship::default();
+            ship.ship_type = ShipType::BB;
+            ship.ctype = ctype;
+            ship.level = 99;
+            ship.current_hp = 90;
+            ship.master.max_hp.0 = Some(90);
+            ship.master.luck.0 = Some(40);
+            ship
+        };
+
+        let mut fleet = Fleet::default();
+        fleet.ships.push(ship(ctype!("Nelson級")));
+        (0..5).for_each(|_| fleet.ships.push(ship(0)));
+
+        let comp = Comp {
+            org_type: OrgType::Single,
+            hq_level: 120,
+            main: fleet,
+            escort: None,
+            route_sup: None,
+            boss_sup: None,
+        };
+        let mut comp = BattleComp::new(comp, Formation::DOUBLE_LINE);
+        let mut rng = rng(0);
+        let battle_defs = BattleDefinitions::default();
+
+        let mut try_fleet_cutin = |comp: &mut BattleComp| {
+            comp.try_fleet_cutin(
+                &mut rng,
+                &battle_defs,
+                FleetType::Main,
+                Engagement::Parallel,
+                Time::Day,
+            )
+        };
+
+        let effect = (0..100).find_map(|_| try_fleet_cutin(&mut comp)).unwrap();
+        assert_eq!(effect.cutin, FleetCutin::NelsonTouch);
+        assert!(comp.main.fleet_cutin_used);
+        assert!((0..100).all(|_| try_fleet_cutin(&mut comp).is_none()));
+
+        comp.reset_battle_state();
+        assert!((0..100).any(|_| try_fleet_cutin(&mut comp).is_some()));
+
+        // 発動率が不明な場合は判定しない
+        comp.reset_battle_state();
+        let battle_defs = BattleDefinitions {
+            fleet_cutin: vec![
+                serde_json::from_value(serde_json::json!({
+                    "tag": "NelsonTouch",
+                    "formations": ["DoubleLine"],
+                    "requires": "1",
+                    "attacks": []
+                }))
+                .unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        assert!((0..100).all(|_| {
+            comp.try_fleet_cutin(
+                &mut rng,
+                &battle_defs,
+                FleetType::Main,
+                Engagement::Parallel,
+                Time::Day,
+            )
+            .is_none()
+        }));
+        assert!(comp.main.fleet_cutin_skipped);
+        assert!(!comp.main.fleet_cutin_used);
+    }
+}
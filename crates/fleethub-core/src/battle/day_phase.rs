@@ -2,16 +2,17 @@ use itertools::Itertools;
 use rand::prelude::*;
 
 use crate::{
-    attack::DayPhaseAttackParams,
+    attack::{DayPhaseAttackParams, FleetCutinAttackParams, FleetCutinEffect},
     battle::target_picker::TargetPicker,
     error::SHIP_NOT_FOUND,
     types::{
-        AirState, BattleDefinitions, DayCombatRound, DayPhaseAttackStyle, DayPhaseAttackType,
-        Engagement, NodeState, Participant, ShellingStyle, ShipPosition,
+        AirState, BattleDefinitions, DayCombatRound, DayCutinLike, DayPhaseAttackStyle,
+        DayPhaseAttackType, Engagement, FleetCutin, FleetType, NodeState, Participant,
+        ShellingStyle, ShellingType, ShipPosition, Time,
     },
 };
 
-use super::battle_comp::BattleComp;
+use super::battle_comp::{BattleComp, is_fleet_cutin_participant};
 
 impl DayCombatRound {
     fn attack_participant(self) -> Participant {
@@ -56,16 +57,67 @@ where
         let player_comp_has_inst = self.player_comp.has_installation(target_participant);
         let enemy_comp_has_inst = self.enemy_comp.has_installation(target_participant);
 
-        let player_order =
-            self.player_comp
-                .order_by_range(self.rng, attack_participant, enemy_comp_has_inst);
+        let player_order = self
+            .player_comp
+            .order_by_range(self.rng, attack_participant, enemy_comp_has_inst)
+            .collect::<Vec<_>>();
         let enemy_order =
             self.enemy_comp
                 .order_by_range(self.rng, attack_participant, player_comp_has_inst);
 
+        // 艦隊特殊攻撃は第一巡の開始時に判定する
+        // 旗艦に手番がない場合は判定しない
+        let flagship_acts = player_order
+            .iter()
+            .any(|position| position.fleet_type == FleetType::Main && position.is_flagship());
+
+        let fleet_cutin = if self.round == DayCombatRound::Main1 && flagship_acts {
+            self.player_comp.try_fleet_cutin(
+                self.rng,
+                self.battle_defs,
+                FleetType::Main,
+                self.engagement,
+                Time::Day,
+            )
+        } else {
+            None
+        };
+
         player_order
+            .into_iter()
             .interleave(enemy_order)
-            .try_for_each(|attacker_position| self.try_attack(attacker_position))
+            .try_for_each(|attacker_position| match &fleet_cutin {
+                Some(effect)
+                    if is_fleet_cutin_participant(effect, FleetType::Main, attacker_position) =>
+                {
+                    // 参加艦の手番は旗艦の手番での特殊攻撃に置き換わる
+                    if attacker_position.is_flagship() {
+                        self.try_fleet_cutin_attacks(effect)
+                    } else {
+                        Ok(())
+                    }
+                }
+                _ => self.try_attack(attacker_position, None),
+            })
+    }
+
+    fn try_fleet_cutin_attacks(&mut self, effect: &FleetCutinEffect) -> anyhow::Result<()> {
+        effect.attacks.iter().try_for_each(|params| {
+            let position = self
+                .player_comp
+                .get_ship_position(FleetType::Main, params.index);
+
+            let remains = self
+                .player_comp
+                .get_ship(position)
+                .is_some_and(|ship| ship.remains());
+
+            if remains {
+                self.try_attack(position, Some((effect.cutin, params)))
+            } else {
+                Ok(())
+            }
+        })
     }
 
     fn fleet_los_mod(&self, attacker_position: ShipPosition) -> f64 {
@@ -76,7 +128,11 @@ where
         }
     }
 
-    fn try_attack(&mut self, attacker_position: ShipPosition) -> anyhow::Result<()> {
+    fn try_attack(
+        &mut self,
+        attacker_position: ShipPosition,
+        fleet_cutin: Option<(FleetCutin, &FleetCutinAttackParams)>,
+    ) -> anyhow::Result<()> {
         let target_participant = self.round.target_participant();
 
         let attacker_side = attacker_position.side();
@@ -95,6 +151,11 @@ where
             .get_ship(attacker_position)
             .expect(SHIP_NOT_FOUND);
 
+        // 同じ巡の先の手番で撃沈された
+        if !attacker.remains() {
+            return Ok(());
+        }
+
         let mut picker = TargetPicker::<DayPhaseAttackType>::new(
             self.battle_defs,
             &attacker,
@@ -123,8 +184,17 @@ where
             self.battle_defs
                 .get_historical_params(self.node_state, &attacker, &target.as_ref());
 
-        let style = match attack_type {
-            DayPhaseAttackType::Shelling(attack_type) => {
+        let style = match (attack_type, fleet_cutin) {
+            (DayPhaseAttackType::Shelling(_), Some((cutin, params))) => {
+                DayPhaseAttackStyle::Shelling(ShellingStyle {
+                    attack_type: ShellingType::Normal,
+                    cutin: Some(DayCutinLike::FleetCutin(cutin)),
+                    power_mod: params.power_mod,
+                    accuracy_mod: params.accuracy_mod,
+                    hits: 1.0,
+                })
+            }
+            (DayPhaseAttackType::Shelling(attack_type), None) => {
                 let is_main_flagship = attacker.position.is_main_flagship();
 
                 let observation_term = attacker
@@ -141,9 +211,15 @@ where
                 let style = ShellingStyle::new(attack_type, cutin_def);
                 DayPhaseAttackStyle::Shelling(style)
             }
-            DayPhaseAttackType::Asw(ty) => ty.into(),
+            (DayPhaseAttackType::Asw(ty), _) => ty.into(),
         };
 
+        let special_enemy_mods = self.battle_defs.get_special_enemy_mods(
+            &attacker,
+            target.special_enemy_type(),
+            style.to_attack_type(),
+        );
+
         DayPhaseAttackParams {
             style,
             engagement,
@@ -151,6 +227,8 @@ where
             target: &target.as_ref(),
             formation_params,
             historical_params,
+            special_enemy_mods,
+            gunfit_accuracy: self.battle_defs.get_gunfit_accuracy(&attacker, false),
             node_state,
             // todo
             balloons: 0,
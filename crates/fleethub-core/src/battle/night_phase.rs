@@ -1,17 +1,89 @@
+use itertools::Itertools;
 use rand::prelude::*;
 
 use crate::{
-    attack::NightPhaseAttackParams,
+    attack::{FleetCutinAttackParams, FleetCutinEffect, NightPhaseAttackParams},
     error::SHIP_NOT_FOUND,
+    fleet::Fleet,
+    plane::PlaneImpl,
     ship::NightCutinTermParams,
     types::{
-        AirState, BattleDefinitions, Engagement, NightAttackStyle, NightConditions,
-        NightPhaseAttackStyle, NightPhaseAttackType, NodeState, Participant, ShipPosition,
+        AirState, BattleDefinitions, ContactRank, DamageState, Engagement, FleetCutin, GearType,
+        NightAttackStyle, NightAttackType, NightConditions, NightFleetConditions,
+        NightPhaseAttackStyle, NightPhaseAttackType, NodeState, Participant, ShipPosition, Side,
+        Time,
     },
     utils::some_or_return,
 };
 
-use super::{battle_comp::BattleComp, target_picker::TargetPicker};
+use super::{
+    battle_comp::{BattleComp, is_fleet_cutin_participant},
+    target_picker::{SearchlightState, TargetPicker},
+};
+
+/// 照明弾の発動率
+///
+/// 出典: 艦これ攻略wiki「夜戦」 https://wikiwiki.jp/kancolle/夜戦
+const STARSHELL_ACTIVATION_RATE: f64 = 0.7;
+
+/// 探照灯を照射した艦へ攻撃を引き付けるための`SearchlightState::rerolls`
+///
+/// 探照灯艦以外を選んだ場合は`rerolls + 1`回まで攻撃対象を選び直す
+///
+/// 出典: 艦これ攻略wiki「夜戦」 https://wikiwiki.jp/kancolle/夜戦
+const SEARCHLIGHT_REROLLS: usize = 1;
+
+/// 大型探照灯を照射した場合の`SearchlightState::rerolls`
+const LARGE_SEARCHLIGHT_REROLLS: usize = 2;
+
+fn try_night_contact<R: Rng + ?Sized>(rng: &mut R, fleet: &Fleet) -> Option<ContactRank> {
+    fleet
+        .ships
+        .values()
+        .filter(|ship| ship.current_hp > 0)
+        .flat_map(|ship| {
+            ship.planes()
+                .filter(|plane| plane.is_night_recon() && plane.slot_size.unwrap_or_default() > 0)
+                .map(move |plane| (plane.contact_rank(), plane.night_contact_rate(ship.level)))
+        })
+        .find_map(|(rank, rate)| rng.gen_bool(rate).then_some(rank))
+}
+
+fn try_starshell<R: Rng + ?Sized>(rng: &mut R, fleet: &Fleet) -> Option<usize> {
+    let (index, _) = fleet.ships.iter().find(|(_, ship)| {
+        ship.damage_state() < DamageState::Taiha && ship.gears.has_type(GearType::Starshell)
+    })?;
+
+    rng.gen_bool(STARSHELL_ACTIVATION_RATE).then_some(index)
+}
+
+fn find_searchlight(fleet: &Fleet) -> Option<(usize, bool)> {
+    fleet.ships.iter().find_map(|(index, ship)| {
+        if ship.damage_state() >= DamageState::Taiha {
+            return None;
+        }
+
+        if ship.gears.has_type(GearType::LargeSearchlight) {
+            Some((index, true))
+        } else if ship.gears.has_type(GearType::Searchlight) {
+            Some((index, false))
+        } else {
+            None
+        }
+    })
+}
+
+/// 探照灯を照射した艦隊への攻撃で攻撃対象を選び直す条件
+fn searchlight_state(conditions: &NightFleetConditions) -> Option<SearchlightState> {
+    conditions.searchlight_index.map(|index| SearchlightState {
+        index,
+        rerolls: if conditions.activates_large_searchlight {
+            LARGE_SEARCHLIGHT_REROLLS
+        } else {
+            SEARCHLIGHT_REROLLS
+        },
+    })
+}
 
 pub struct NightPhase<'a, R>
 where
@@ -30,14 +102,122 @@ impl<R> NightPhase<'_, R>
 where
     R: Rng + ?Sized,
 {
-    pub fn try_combat(&self) -> anyhow::Result<()> {
-        todo!()
+    pub fn try_combat(&mut self) -> anyhow::Result<()> {
+        let night_conditions = NightConditions {
+            player: self.resolve_night_fleet_conditions(Side::Player),
+            enemy: self.resolve_night_fleet_conditions(Side::Enemy),
+        };
+
+        let player_order = self
+            .player_comp
+            .members(Participant::Escort)
+            .map(|ship| ship.position)
+            .collect::<Vec<_>>();
+        let enemy_order = self
+            .enemy_comp
+            .members(Participant::Escort)
+            .map(|ship| ship.position)
+            .collect::<Vec<_>>();
+
+        // 旗艦が攻撃できない場合は判定しない
+        let fleet_type = self.player_comp.night_fleet_type();
+        let flagship_acts = self
+            .player_comp
+            .members(Participant::Escort)
+            .any(|ship| ship.position.is_flagship() && ship.damage_state() < DamageState::Taiha);
+
+        let fleet_cutin = if flagship_acts {
+            self.player_comp.try_fleet_cutin(
+                self.rng,
+                self.battle_defs,
+                fleet_type,
+                self.engagement,
+                Time::Night,
+            )
+        } else {
+            None
+        };
+
+        player_order
+            .into_iter()
+            .interleave(enemy_order)
+            .try_for_each(|attacker_position| match &fleet_cutin {
+                Some(effect)
+                    if is_fleet_cutin_participant(effect, fleet_type, attacker_position) =>
+                {
+                    // 参加艦の手番は旗艦の手番での特殊攻撃に置き換わる
+                    if attacker_position.is_flagship() {
+                        self.try_fleet_cutin_attacks(effect, &night_conditions)
+                    } else {
+                        Ok(())
+                    }
+                }
+                _ => self.try_attack(attacker_position, &night_conditions, None),
+            })
+    }
+
+    fn try_fleet_cutin_attacks(
+        &mut self,
+        effect: &FleetCutinEffect,
+        night_conditions: &NightConditions,
+    ) -> anyhow::Result<()> {
+        let fleet_type = self.player_comp.night_fleet_type();
+
+        effect.attacks.iter().try_for_each(|params| {
+            let position = self.player_comp.get_ship_position(fleet_type, params.index);
+
+            let remains = self
+                .player_comp
+                .get_ship(position)
+                .is_some_and(|ship| ship.remains());
+
+            if remains {
+                self.try_attack(position, night_conditions, Some((effect.cutin, params)))
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// 制空権喪失時は夜間触接が発生しない
+    fn can_do_night_contact(&self, side: Side) -> bool {
+        if side.is_player() {
+            self.air_state != AirState::AirIncapability
+        } else {
+            self.air_state != AirState::AirSupremacy
+        }
+    }
+
+    fn resolve_night_fleet_conditions(&mut self, side: Side) -> NightFleetConditions {
+        let can_do_night_contact = self.can_do_night_contact(side);
+
+        let fleet = if side.is_player() {
+            self.player_comp.night_fleet()
+        } else {
+            self.enemy_comp.night_fleet()
+        };
+
+        let night_contact_rank = if can_do_night_contact {
+            try_night_contact(self.rng, fleet)
+        } else {
+            None
+        };
+        let starshell_index = try_starshell(self.rng, fleet);
+        let searchlight = find_searchlight(fleet);
+
+        NightFleetConditions {
+            night_contact_rank,
+            starshell_index,
+            searchlight_index: searchlight.map(|(index, _)| index),
+            activates_large_searchlight: searchlight.is_some_and(|(_, large)| large),
+        }
     }
 
     fn try_attack(
         &mut self,
         attacker_position: ShipPosition,
         night_conditions: &NightConditions,
+        fleet_cutin: Option<(FleetCutin, &FleetCutinAttackParams)>,
     ) -> anyhow::Result<()> {
         let attacker_side = attacker_position.side();
         let engagement = self.engagement;
@@ -52,12 +232,18 @@ where
             .get_ship(attacker_position)
             .expect(SHIP_NOT_FOUND);
 
+        if attacker.damage_state() >= DamageState::Taiha {
+            return Ok(());
+        }
+
+        let target_night_conditions = night_conditions.night_fleet_conditions(!attacker_side);
+
         let mut picker = TargetPicker::<NightPhaseAttackType>::new(
             self.battle_defs,
             &attacker,
             target_comp,
-            Participant::Main,
-            None,
+            Participant::Escort,
+            searchlight_state(target_night_conditions),
         );
 
         let picked = some_or_return!(picker.choose(self.rng), Ok(()));
@@ -75,8 +261,17 @@ where
             self.battle_defs
                 .get_historical_params(self.node_state, &attacker, &target.as_ref());
 
-        let style = match attack_type {
-            NightPhaseAttackType::Night(attack_type) => {
+        let style = match (attack_type, fleet_cutin) {
+            (NightPhaseAttackType::Night(_), Some((cutin, params))) => {
+                NightPhaseAttackStyle::Night(NightAttackStyle {
+                    attack_type: NightAttackType::Normal,
+                    cutin: Some(cutin.into()),
+                    power_mod: params.power_mod,
+                    accuracy_mod: params.accuracy_mod,
+                    ..Default::default()
+                })
+            }
+            (NightPhaseAttackType::Night(attack_type), None) => {
                 let anti_inst = target.is_installation();
                 let cutin_set = attacker.get_possible_night_cutin_set(anti_inst);
 
@@ -98,9 +293,15 @@ where
 
                 NightPhaseAttackStyle::Night(NightAttackStyle::new(attack_type, cutin_def))
             }
-            NightPhaseAttackType::Asw(ty) => ty.into(),
+            (NightPhaseAttackType::Asw(ty), _) => ty.into(),
         };
 
+        let special_enemy_mods = self.battle_defs.get_special_enemy_mods(
+            &attacker,
+            target.special_enemy_type(),
+            style.to_attack_type(),
+        );
+
         let attack = NightPhaseAttackParams {
             style,
             engagement,
@@ -108,6 +309,8 @@ where
             target: &target.as_ref(),
             formation_params,
             historical_params,
+            special_enemy_mods,
+            gunfit_accuracy: self.battle_defs.get_gunfit_accuracy(&attacker, true),
             night_conditions,
         }
         .to_attack();
@@ -115,3 +318,134 @@ where
         attack.apply(self.rng, &mut target)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gear::Gear,
+        ship::Ship,
+        test::{battle_defs, comp, damaged_ship, rng, ship},
+        types::{Formation, OrgType, ShipType},
+    };
+
+    fn equip(mut ship: Ship, gear_type: GearType) -> Ship {
+        let mut gear = Gear::default();
+        gear.gear_type = gear_type;
+        ship.gears.push(gear);
+        ship
+    }
+
+    fn single_fleet(ships: Vec<Ship>) -> Fleet {
+        comp(OrgType::Single, ships).main
+    }
+
+    #[test]
+    fn test_try_starshell() {
+        let mut rng = rng(0);
+        let trials = 10000;
+
+        let fleet = single_fleet(vec![
+            ship("p1", ShipType::DD, 30),
+            equip(ship("p2", ShipType::DD, 30), GearType::Starshell),
+        ]);
+        let activated = (0..trials)
+            .filter_map(|_| try_starshell(&mut rng, &fleet))
+            .inspect(|&index| assert_eq!(index, 1))
+            .count();
+        let rate = activated as f64 / trials as f64;
+        assert!((rate - STARSHELL_ACTIVATION_RATE).abs() < 0.02, "{rate}");
+
+        // 大破した艦の照明弾は発動しない
+        let mut taiha = equip(ship("p1", ShipType::DD, 30), GearType::Starshell);
+        taiha.current_hp = 7;
+        let fleet = single_fleet(vec![taiha]);
+        assert!((0..100).all(|_| try_starshell(&mut rng, &fleet).is_none()));
+    }
+
+    #[test]
+    fn test_find_searchlight() {
+        let fleet = single_fleet(vec![
+            ship("p1", ShipType::DD, 30),
+            equip(ship("p2", ShipType::DD, 30), GearType::Searchlight),
+        ]);
+        assert_eq!(find_searchlight(&fleet), Some((1, false)));
+
+        let fleet = single_fleet(vec![
+            equip(ship("p1", ShipType::BB, 80), GearType::LargeSearchlight),
+            equip(ship("p2", ShipType::DD, 30), GearType::Searchlight),
+        ]);
+        assert_eq!(find_searchlight(&fleet), Some((0, true)));
+
+        let fleet = single_fleet(vec![ship("p1", ShipType::DD, 30)]);
+        assert_eq!(find_searchlight(&fleet), None);
+    }
+
+    #[test]
+    fn test_searchlight_rerolls() {
+        let battle_defs = battle_defs();
+        let mut rng = rng(0);
+
+        let player = BattleComp::new(
+            comp(OrgType::Single, vec![ship("p1", ShipType::DD, 30)]),
+            Formation::LINE_AHEAD,
+        );
+        let attacker = player
+            .get_ship(player.members(Participant::Escort).next().unwrap().position)
+            .unwrap();
+
+        let hit_rate = |rng: &mut dyn RngCore, gear_type: GearType| {
+            // 小破させて旗艦をかばわないようにする
+            let enemy = BattleComp::new(
+                comp(
+                    OrgType::EnemySingle,
+                    vec![
+                        ship("e1", ShipType::BB, 300),
+                        equip(damaged_ship("e2", ShipType::BB, 300, 200), gear_type),
+                    ],
+                ),
+                Formation::LINE_AHEAD,
+            );
+            let searchlight = find_searchlight(&enemy.main);
+            let conditions = NightFleetConditions {
+                searchlight_index: searchlight.map(|(index, _)| index),
+                activates_large_searchlight: searchlight.is_some_and(|(_, large)| large),
+                ..Default::default()
+            };
+
+            let trials = 10000;
+            let hits = (0..trials)
+                .filter(|_| {
+                    let mut picker = TargetPicker::<NightPhaseAttackType>::new(
+                        &battle_defs,
+                        &attacker,
+                        &enemy,
+                        Participant::Escort,
+                        searchlight_state(&conditions),
+                    );
+                    picker.choose(rng).unwrap().position.index == 1
+                })
+                .count();
+
+            hits as f64 / trials as f64
+        };
+
+        // 2隻から選んだ艦が探照灯艦でなければ`rerolls + 1`回まで選び直す
+        let expected = |rerolls: usize| 1.0 - 0.5_f64.powi(rerolls as i32 + 2);
+
+        let rate = hit_rate(&mut rng, GearType::Sonar);
+        assert!((rate - 0.5).abs() < 0.02, "{rate}");
+
+        let rate = hit_rate(&mut rng, GearType::Searchlight);
+        assert!(
+            (rate - expected(SEARCHLIGHT_REROLLS)).abs() < 0.02,
+            "{rate}"
+        );
+
+        let rate = hit_rate(&mut rng, GearType::LargeSearchlight);
+        assert!(
+            (rate - expected(LARGE_SEARCHLIGHT_REROLLS)).abs() < 0.02,
+            "{rate}"
+        );
+    }
+}
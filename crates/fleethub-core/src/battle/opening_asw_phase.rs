@@ -0,0 +1,107 @@
+use rand::prelude::*;
+
+use crate::{
+    attack::DayPhaseAttackParams,
+    battle::target_picker::TargetPicker,
+    error::SHIP_NOT_FOUND,
+    types::{AswAttackType, BattleDefinitions, Engagement, NodeState, Participant, ShipPosition},
+};
+
+use super::battle_comp::BattleComp;
+
+/// 先制対潜攻撃
+///
+/// 味方のみが攻撃し、連合艦隊では主力艦隊と随伴艦隊の両方が参加する
+pub(super) struct OpeningAswPhase<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub rng: &'a mut R,
+    pub battle_defs: &'a BattleDefinitions,
+    pub node_state: NodeState,
+    pub engagement: Engagement,
+    pub player_comp: &'a mut BattleComp,
+    pub enemy_comp: &'a mut BattleComp,
+}
+
+impl<'a, R> OpeningAswPhase<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub fn try_combat(&mut self) -> anyhow::Result<()> {
+        self.player_comp
+            .members(Participant::Both)
+            .filter(|ship| ship.remains() && ship.can_do_opening_asw())
+            .map(|ship| ship.position)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .try_for_each(|attacker_position| self.try_attack(attacker_position))
+    }
+
+    fn target_participant(&self) -> Participant {
+        if self.enemy_comp.comp.is_combined() {
+            Participant::Both
+        } else {
+            Participant::Escort
+        }
+    }
+
+    fn try_attack(&mut self, attacker_position: ShipPosition) -> anyhow::Result<()> {
+        let target_participant = self.target_participant();
+        let node_state = self.node_state;
+        let engagement = self.engagement;
+
+        let attacker = self
+            .player_comp
+            .get_ship(attacker_position)
+            .expect(SHIP_NOT_FOUND);
+
+        let mut picker = TargetPicker::<AswAttackType>::new(
+            self.battle_defs,
+            &attacker,
+            self.enemy_comp,
+            target_participant,
+        );
+
+        let picked = if let Some(picked) = picker.choose(self.rng) {
+            picked
+        } else {
+            return Ok(());
+        };
+
+        let mut target = self
+            .enemy_comp
+            .get_ship_mut(picked.position)
+            .expect(SHIP_NOT_FOUND);
+
+        let formation_params = self.battle_defs.get_formation_params(
+            picked.attack_type,
+            attacker.conditions(),
+            target.conditions(),
+        );
+        let historical_params =
+            self.battle_defs
+                .get_historical_params(self.node_state, &attacker, &target.as_ref());
+
+        let special_enemy_mods = self.battle_defs.get_special_enemy_mods(
+            &attacker,
+            target.special_enemy_type(),
+            picked.attack_type,
+        );
+
+        DayPhaseAttackParams {
+            style: picked.attack_type.into(),
+            engagement,
+            attacker: &attacker,
+            target: &target.as_ref(),
+            formation_params,
+            historical_params,
+            special_enemy_mods,
+            gunfit_accuracy: self.battle_defs.get_gunfit_accuracy(&attacker, false),
+            node_state,
+            balloons: 0,
+        }
+        .to_attack()
+        .apply(self.rng, &mut target)
+    }
+}
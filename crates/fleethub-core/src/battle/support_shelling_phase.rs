@@ -19,6 +19,8 @@ where
     pub battle_defs: &'a BattleDefinitions,
     pub engagement: Engagement,
     pub node_state: NodeState,
+    /// 攻撃する支援艦隊 (`RouteSup`か`BossSup`)
+    pub support_fleet: FleetType,
     pub player_comp: &'a mut BattleComp,
     pub enemy_comp: &'a mut BattleComp,
 }
@@ -31,7 +33,7 @@ where
         #[allow(clippy::needless_collect)]
         let order = self
             .player_comp
-            .members_by(FleetType::RouteSup)
+            .members_by(self.support_fleet)
             .map(|ship| ship.position)
             .collect::<Vec<_>>();
 
@@ -74,12 +76,19 @@ where
             target.conditions(),
         );
 
+        let special_enemy_mods = self.battle_defs.get_special_enemy_mods(
+            &attacker,
+            target.special_enemy_type(),
+            attack_type,
+        );
+
         SupportShellingAttackParams {
             attack_type,
             engagement,
             attacker: &attacker,
             target: &target.as_ref(),
             formation_params,
+            special_enemy_mods,
             node_state: self.node_state,
         }
         .to_attack()
@@ -0,0 +1,94 @@
+use rand::prelude::*;
+
+use crate::{
+    attack::SupportTorpedoAttackParams,
+    battle::target_picker::TargetPicker,
+    error::SHIP_NOT_FOUND,
+    types::{
+        BattleDefinitions, Engagement, FleetType, NodeState, Participant, ShipPosition,
+        TorpedoAttackType,
+    },
+};
+
+use super::battle_comp::BattleComp;
+
+pub struct SupportTorpedoPhase<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub rng: &'a mut R,
+    pub battle_defs: &'a BattleDefinitions,
+    pub engagement: Engagement,
+    pub node_state: NodeState,
+    /// 攻撃する支援艦隊 (`RouteSup`か`BossSup`)
+    pub support_fleet: FleetType,
+    pub player_comp: &'a mut BattleComp,
+    pub enemy_comp: &'a mut BattleComp,
+}
+
+impl<'a, R> SupportTorpedoPhase<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub fn try_combat(&mut self) -> anyhow::Result<()> {
+        let order = self
+            .player_comp
+            .members_by(self.support_fleet)
+            .filter(|ship| ship.can_do_closing_torpedo())
+            .map(|ship| ship.position)
+            .collect::<Vec<_>>();
+
+        order
+            .into_iter()
+            .try_for_each(|attacker_position| self.try_attack(attacker_position))
+    }
+
+    fn try_attack(&mut self, attacker_position: ShipPosition) -> anyhow::Result<()> {
+        let engagement = self.engagement;
+
+        let attacker = self
+            .player_comp
+            .get_ship(attacker_position)
+            .expect(SHIP_NOT_FOUND);
+
+        let mut picker = TargetPicker::<TorpedoAttackType>::new(
+            self.battle_defs,
+            self.enemy_comp,
+            Participant::Both,
+        );
+
+        let picked = if let Some(picked) = picker.choose(self.rng) {
+            picked
+        } else {
+            return Ok(());
+        };
+
+        let mut target = self
+            .enemy_comp
+            .get_ship_mut(picked.position)
+            .expect(SHIP_NOT_FOUND);
+
+        let formation_params = self.battle_defs.get_formation_params(
+            picked.attack_type,
+            attacker.conditions(),
+            target.conditions(),
+        );
+
+        let special_enemy_mods = self.battle_defs.get_special_enemy_mods(
+            &attacker,
+            target.special_enemy_type(),
+            picked.attack_type,
+        );
+
+        SupportTorpedoAttackParams {
+            engagement,
+            attacker: &attacker,
+            target: &target.as_ref(),
+            formation_params,
+            special_enemy_mods,
+            node_state: self.node_state,
+        }
+        .to_attack()
+        .apply(self.rng, &mut target)
+    }
+}
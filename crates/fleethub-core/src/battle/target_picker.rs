@@ -3,8 +3,8 @@ use rand::prelude::*;
 use crate::{
     member::BattleMemberRef,
     types::{
-        BattleDefinitions, DayPhaseAttackType, Formation, NightAttackType, NightPhaseAttackType,
-        Participant, ShipPosition, SupportShellingType, TorpedoAttackType,
+        AswAttackType, AswPhase, BattleDefinitions, DayPhaseAttackType, Formation, NightAttackType,
+        NightPhaseAttackType, Participant, ShipPosition, SupportShellingType, TorpedoAttackType,
     },
 };
 
@@ -98,6 +98,7 @@ impl TargetPicker<DayPhaseAttackType> {
         let mut has_asw = false;
         let mut candidates = target_comp
             .members(target_participant)
+            .filter(|ship| ship.remains())
             .filter_map(|ship| {
                 let attack_type = attacker.select_day_phase_attack_type(&ship)?;
 
@@ -139,6 +140,7 @@ impl TargetPicker<TorpedoAttackType> {
 
         let candidates = target_comp
             .members(target_participant)
+            .filter(|ship| ship.remains())
             .filter_map(|ship| {
                 (!ship.is_installation() && !ship.is_submarine()).then(|| Candidate {
                     attack_type: TorpedoAttackType,
@@ -157,6 +159,41 @@ impl TargetPicker<TorpedoAttackType> {
     }
 }
 
+impl TargetPicker<AswAttackType> {
+    /// 開幕対潜では潜水艦のみを対象とする
+    pub fn new(
+        battle_defs: &BattleDefinitions,
+        attacker: &BattleMemberRef,
+        target_comp: &BattleComp,
+        target_participant: Participant,
+    ) -> Self {
+        let target_formation = target_comp.formation;
+        let flagship_protection_rate = battle_defs.get_flagship_protection_rate(target_formation);
+
+        let candidates = attacker
+            .select_asw_attack_type(AswPhase::Opening)
+            .map(|attack_type| {
+                target_comp
+                    .members(target_participant)
+                    .filter(|ship| ship.remains() && ship.is_submarine())
+                    .map(|ship| Candidate {
+                        attack_type,
+                        position: ship.position,
+                        is_protector: ship.is_protector(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            candidates,
+            flagship_protection_rate,
+            formation: target_formation,
+            searchlight_state: None,
+        }
+    }
+}
+
 impl TargetPicker<NightPhaseAttackType> {
     pub fn new(
         battle_defs: &BattleDefinitions,
@@ -173,6 +210,7 @@ impl TargetPicker<NightPhaseAttackType> {
         let mut has_asw = false;
         let mut candidates = target_comp
             .members(target_participant)
+            .filter(|ship| ship.remains())
             .filter_map(|ship| {
                 let attack_type = attacker.select_night_phase_attack_type(&ship)?;
 
@@ -221,6 +259,7 @@ impl TargetPicker<SupportShellingType> {
 
         let candidates = target_comp
             .members(Participant::Both)
+            .filter(|ship| ship.remains())
             .filter_map(|ship| {
                 let attack_type = attacker.select_day_phase_attack_type(&ship)?;
 
@@ -12,6 +12,9 @@ use crate::{
 
 use super::battle_comp::BattleComp;
 
+/// 雷撃戦
+///
+/// 連合艦隊では随伴艦隊のみが攻撃する
 pub struct TorpedoPhase<'a, R>
 where
     R: Rng + ?Sized,
@@ -20,6 +23,8 @@ where
     pub battle_defs: &'a BattleDefinitions,
     pub node_state: NodeState,
     pub engagement: Engagement,
+    /// 開幕雷撃であるか
+    pub is_opening: bool,
     pub player_comp: &'a mut BattleComp,
     pub enemy_comp: &'a mut BattleComp,
 }
@@ -32,22 +37,23 @@ where
         let player = self.player_comp.members(Participant::Escort);
         let enemy = self.enemy_comp.members(Participant::Escort);
 
+        let is_opening = self.is_opening;
+
         player
             .chain(enemy)
+            .filter(|ship| {
+                if is_opening {
+                    ship.can_do_opening_torpedo()
+                } else {
+                    ship.can_do_closing_torpedo()
+                }
+            })
             .map(|ship| ship.position)
             .collect::<Vec<_>>()
             .into_iter()
             .try_for_each(|attacker_position| self.try_attack(attacker_position))
     }
 
-    fn target_participant(&self) -> Participant {
-        if self.enemy_comp.comp.is_combined() {
-            Participant::Both
-        } else {
-            Participant::Escort
-        }
-    }
-
     fn try_attack(&mut self, attacker_position: ShipPosition) -> anyhow::Result<()> {
         let attacker_side = attacker_position.side();
         let node_state = self.node_state;
@@ -63,6 +69,11 @@ where
             .get_ship(attacker_position)
             .expect(SHIP_NOT_FOUND);
 
+        // 先の攻撃で撃沈された
+        if !attacker.remains() {
+            return Ok(());
+        }
+
         let mut picker = TargetPicker::<TorpedoAttackType>::new(
             self.battle_defs,
             target_comp,
@@ -85,12 +96,19 @@ where
             self.battle_defs
                 .get_historical_params(self.node_state, &attacker, &target.as_ref());
 
+        let special_enemy_mods = self.battle_defs.get_special_enemy_mods(
+            &attacker,
+            target.special_enemy_type(),
+            AttackType::Torpedo,
+        );
+
         let attack = TorpedoAttackParams {
             attacker: &attacker,
             target: &target.as_ref(),
             engagement,
             formation_params,
             historical_params,
+            special_enemy_mods,
             node_state,
         }
         .to_attack();
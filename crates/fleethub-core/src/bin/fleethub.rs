@@ -0,0 +1,247 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use serde::{Serialize, de::DeserializeOwned};
+
+use fleethub_core::{
+    FhCore,
+    analyzer::{CompAnalyzerConfig, NodeAttackAnalyzerConfig},
+    comp::Comp,
+    master_data::{MasterDataDiff, MasterDataReport},
+    native::{ParallelConfig, simulate_battle_parallel},
+    simulator::{SortieInput, SortieNode, SupportConfig, seeded_rng},
+    types::{BattleType, FleetType, OrgState, SupportType},
+};
+
+const USAGE: &str = "\
+Usage: fleethub <COMMAND> [OPTIONS]
+
+Commands:
+  analyze-comp      --master <FILE> --org <FILE> --config <FILE>
+  simulate-battle   --master <FILE> --player <FILE> --enemy <FILE>
+                    [--config <FILE>] [--battle-type <Day|DayAndNight|Night>]
+                    [--times <N>] [--threads <N>] [--seed <N>]
+  simulate-support  --master <FILE> --player <FILE> --enemy <FILE>
+                    [--config <FILE>] [--support-fleet <RouteSup|BossSup>]
+                    [--support-type <Shelling|Aerial|Torpedo>]
+                    [--times <N>] [--seed <N>]
+  simulate-sortie   --master <FILE> --player <FILE> --sortie <FILE>
+                    [--times <N>] [--seed <N>]
+  validate-master   --master <FILE>
+  diff-master       --old <FILE> --new <FILE>
+
+--master はマスターデータの JSON、--org, --player, --enemy は OrgState の JSON、
+--config は各解析の設定、--sortie は SortieInput の JSON を指定する。結果は JSON で標準出力に書き出す。";
+
+const DEFAULT_TIMES: usize = 10000;
+
+struct Args {
+    command: String,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut iter = std::env::args().skip(1);
+
+        let Some(command) = iter.next() else {
+            bail!("{USAGE}");
+        };
+
+        let mut options = HashMap::new();
+
+        while let Some(key) = iter.next() {
+            let Some(key) = key.strip_prefix("--") else {
+                bail!("不正な引数です: {key}\n\n{USAGE}");
+            };
+
+            let value = iter
+                .next()
+                .with_context(|| format!("--{key} の値がありません"))?;
+
+            options.insert(key.to_string(), value);
+        }
+
+        Ok(Self { command, options })
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(String::as_str)
+    }
+
+    fn required(&self, key: &str) -> Result<&str> {
+        self.get(key)
+            .with_context(|| format!("--{key} を指定してください\n\n{USAGE}"))
+    }
+
+    fn parse_opt<T: std::str::FromStr>(&self, key: &str) -> Result<Option<T>> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .ok()
+                    .with_context(|| format!("--{key} の値が不正です: {value}"))
+            })
+            .transpose()
+    }
+
+    fn parse_or<T: std::str::FromStr>(&self, key: &str, default: T) -> Result<T> {
+        Ok(self.parse_opt(key)?.unwrap_or(default))
+    }
+
+    /// JSON ファイルを読み込む
+    fn json<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        read_json(self.required(key)?)
+    }
+
+    /// JSON 文字列として列挙型を読み込む
+    fn variant<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.get(key)
+            .map(|value| {
+                serde_json::from_value(serde_json::Value::String(value.to_string()))
+                    .with_context(|| format!("--{key} の値が不正です: {value}"))
+            })
+            .transpose()
+    }
+
+    fn node_config(&self) -> Result<NodeAttackAnalyzerConfig> {
+        if self.get("config").is_some() {
+            self.json("config")
+        } else {
+            Ok(Default::default())
+        }
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T> {
+    let path = path.as_ref();
+    let text =
+        fs::read_to_string(path).with_context(|| format!("{} を読み込めません", path.display()))?;
+
+    serde_json::from_str(&text).with_context(|| format!("{} の形式が不正です", path.display()))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn load_core(args: &Args) -> Result<FhCore> {
+    let path = args.required("master")?;
+    let text = fs::read_to_string(path).with_context(|| format!("{path} を読み込めません"))?;
+
+    FhCore::from_json(&text)
+}
+
+fn load_comp(core: &FhCore, args: &Args, key: &str) -> Result<Comp> {
+    let state: OrgState = args.json(key)?;
+    Ok(core.try_create_org(state)?.create_comp())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse()?;
+
+    match args.command.as_str() {
+        "analyze-comp" => {
+            let core = load_core(&args)?;
+            let comp = load_comp(&core, &args, "org")?;
+            let config: CompAnalyzerConfig = args.json("config")?;
+
+            print_json(&core.create_analyzer().analyze_comp(&comp, config))
+        }
+        "simulate-battle" => {
+            let core = load_core(&args)?;
+            let player = load_comp(&core, &args, "player")?;
+            let enemy = load_comp(&core, &args, "enemy")?;
+            let config = args.node_config()?;
+            let battle_type = args
+                .variant::<BattleType>("battle-type")?
+                .unwrap_or_default();
+            let parallel = ParallelConfig {
+                times: args.parse_or("times", DEFAULT_TIMES)?,
+                threads: args.parse_or("threads", 1)?,
+                seed: args.parse_opt("seed")?,
+            };
+
+            let result = simulate_battle_parallel(
+                &core.create_analyzer(),
+                &player,
+                &enemy,
+                &config,
+                battle_type,
+                parallel,
+            )?;
+
+            print_json(&result)
+        }
+        "simulate-support" => {
+            let core = load_core(&args)?;
+            let player = load_comp(&core, &args, "player")?;
+            let enemy = load_comp(&core, &args, "enemy")?;
+            let config = args.node_config()?;
+            let support_config = SupportConfig {
+                support_fleet: args
+                    .variant::<FleetType>("support-fleet")?
+                    .unwrap_or(FleetType::RouteSup),
+                support_type: args.variant::<SupportType>("support-type")?,
+            };
+            let times = args.parse_or("times", DEFAULT_TIMES)?;
+            let (mut rng, seed) = seeded_rng(args.parse_opt("seed")?);
+
+            let mut result = core.create_analyzer().try_simulate_support(
+                &mut rng,
+                &player,
+                &enemy,
+                &config,
+                support_config,
+                times,
+            )?;
+            result.seed = Some(seed);
+
+            print_json(&result)
+        }
+        "simulate-sortie" => {
+            let core = load_core(&args)?;
+            let player = load_comp(&core, &args, "player")?;
+            let input: SortieInput = args.json("sortie")?;
+            let times = args.parse_or("times", DEFAULT_TIMES)?;
+            let (mut rng, seed) = seeded_rng(args.parse_opt("seed")?);
+
+            let nodes = input
+                .nodes
+                .into_iter()
+                .map(|node| {
+                    Ok(SortieNode {
+                        enemy: core.try_create_org(node.enemy)?.create_comp(),
+                        config: node.config,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut result = core.create_analyzer().try_simulate_sortie(
+                &mut rng,
+                &player,
+                &nodes,
+                &input.config,
+                times,
+            )?;
+            result.seed = Some(seed);
+
+            print_json(&result)
+        }
+        "validate-master" => {
+            let value = read_json(args.required("master")?)?;
+            print_json(&MasterDataReport::new(&value))
+        }
+        "diff-master" => {
+            let old = read_json(args.required("old")?)?;
+            let new = read_json(args.required("new")?)?;
+            print_json(&MasterDataDiff::new(&old, &new))
+        }
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        command => bail!("不明なコマンドです: {command}\n\n{USAGE}"),
+    }
+}
@@ -303,9 +303,10 @@ impl Comp {
 
     pub fn reset_battle_state(&mut self) {
         self.main.reset_battle_state();
-        if let Some(f) = self.escort.as_mut() {
-            f.reset_battle_state()
-        }
+        [&mut self.escort, &mut self.route_sup, &mut self.boss_sup]
+            .into_iter()
+            .flatten()
+            .for_each(|f| f.reset_battle_state());
     }
 }
 
@@ -464,6 +465,12 @@ impl Comp {
             .filter(|ship| ship.gears.has_by(|g| g.is_balloon()))
             .count()
     }
+
+    /// 交戦形態に影響する彩雲の有無
+    pub fn has_saiun(&self) -> bool {
+        self.members_by(FleetType::Main | FleetType::Escort)
+            .any(|ship| ship.gears.has_by(|g| g.is_saiun()))
+    }
 }
 
 #[allow(dead_code)]
@@ -74,7 +74,7 @@ impl Factory {
         let equippability = self.master_data.create_ship_equippability(master_ship);
 
         let ebonuses: EBonuses = if gears.has_by(|gear| !gear.is_abyssal()) {
-            EBonuses::new(master_ship, &gears)
+            self.master_data.create_ebonuses(master_ship, &gears)
         } else {
             Default::default()
         };
@@ -113,6 +113,8 @@ impl Factory {
             len,
             hash,
             ships,
+            fleet_cutin_used: false,
+            fleet_cutin_skipped: false,
         }
     }
 
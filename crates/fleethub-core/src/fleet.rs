@@ -3,7 +3,10 @@ use wasm_bindgen::prelude::*;
 
 use crate::{
     ship::Ship,
-    types::{gear_id, ship_id, AirWaveType, DamageState, FleetMeta, GearType, ShipKey},
+    types::{
+        gear_id, ship_id, AirWaveType, DamageState, FleetMeta, GearType, ShipKey, ShipType,
+        SupportType,
+    },
     utils::OptionalArray,
 };
 
@@ -26,6 +29,12 @@ pub struct Fleet {
     pub len: usize,
     #[wasm_bindgen(skip)]
     pub ships: ShipArray,
+    /// 出撃中に艦隊特殊攻撃を発動した
+    #[wasm_bindgen(skip)]
+    pub fleet_cutin_used: bool,
+    /// 発動率が不明なため艦隊特殊攻撃の判定を省いた
+    #[wasm_bindgen(skip)]
+    pub fleet_cutin_skipped: bool,
 }
 
 #[wasm_bindgen]
@@ -230,16 +239,109 @@ impl Fleet {
     }
 
     pub fn reset_battle_state(&mut self) {
-        self.ships.values_mut().for_each(|ship| {
-            ship.current_hp = ship.max_hp().unwrap_or_default();
-        });
+        self.fleet_cutin_used = false;
+        self.fleet_cutin_skipped = false;
+        self.ships
+            .values_mut()
+            .for_each(|ship| ship.reset_battle_state());
     }
 }
 
 impl Fleet {
+    /// 支援艦隊の種別
+    ///
+    /// 駆逐艦が2隻未満の場合は支援が発動しない。
+    /// 戦艦級が2隻以上、または戦艦級1隻と重巡級3隻以上なら砲撃支援、
+    /// 空母級が2隻以上、または空母級1隻で戦艦級と重巡級がいなければ航空支援、
+    /// 戦艦級と重巡級が合わせて2隻以上なら砲撃支援、それ以外は雷撃支援となる
+    ///
+    /// 出典: 艦これ攻略wiki「支援艦隊」 https://wikiwiki.jp/kancolle/支援艦隊
+    pub fn support_type(&self) -> Option<SupportType> {
+        let count =
+            |f: fn(ShipType) -> bool| self.ships.values().filter(|ship| f(ship.ship_type)).count();
+
+        let dd = count(|t| t == ShipType::DD);
+        let bb = count(|t| matches!(t, ShipType::FBB | ShipType::BB | ShipType::BBV));
+        let ca = count(|t| matches!(t, ShipType::CA | ShipType::CAV));
+        let carriers = count(|t| {
+            matches!(
+                t,
+                ShipType::CV | ShipType::CVL | ShipType::CVB | ShipType::AV | ShipType::LHA
+            )
+        });
+
+        if dd < 2 {
+            None
+        } else if bb >= 2 || (bb >= 1 && ca >= 3) {
+            Some(SupportType::Shelling)
+        } else if carriers >= 2 || (carriers >= 1 && bb + ca == 0) {
+            Some(SupportType::Aerial)
+        } else if bb + ca >= 2 {
+            Some(SupportType::Shelling)
+        } else {
+            Some(SupportType::Torpedo)
+        }
+    }
+
     pub(crate) fn amagiri_index(&self) -> Option<usize> {
         self.ships
             .iter()
             .find_map(|(index, ship)| ship.is_amagiri().then_some(index))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        test::{comp, ship},
+        types::OrgType,
+    };
+
+    fn support_type(ship_types: &[ShipType]) -> Option<SupportType> {
+        let ships = ship_types
+            .iter()
+            .enumerate()
+            .map(|(index, &ship_type)| ship(&index.to_string(), ship_type, 50))
+            .collect();
+
+        comp(OrgType::Single, ships).main.support_type()
+    }
+
+    #[test]
+    fn test_support_type() {
+        use ShipType::*;
+
+        assert_eq!(support_type(&[CL, DD, CL, CL, CL, CL]), None);
+        assert_eq!(support_type(&[BB, BB, CV, CV, DD]), None);
+
+        assert_eq!(
+            support_type(&[BB, FBB, CV, CV, DD, DD]),
+            Some(SupportType::Shelling)
+        );
+        assert_eq!(
+            support_type(&[BBV, CA, CAV, CA, DD, DD]),
+            Some(SupportType::Shelling)
+        );
+        assert_eq!(
+            support_type(&[CV, CVL, BB, CA, DD, DD]),
+            Some(SupportType::Aerial)
+        );
+        assert_eq!(
+            support_type(&[CVL, CL, DD, DD, DD, DD]),
+            Some(SupportType::Aerial)
+        );
+        assert_eq!(
+            support_type(&[CV, BB, CA, DD, DD, DD]),
+            Some(SupportType::Shelling)
+        );
+        assert_eq!(
+            support_type(&[AV, CA, DD, DD, DD, DD]),
+            Some(SupportType::Torpedo)
+        );
+        assert_eq!(
+            support_type(&[CL, DD, DD, DD, DD, DD]),
+            Some(SupportType::Torpedo)
+        );
+    }
+}
@@ -241,6 +241,10 @@ impl Gear {
         )
     }
 
+    pub fn is_saiun(&self) -> bool {
+        matches_gear_id!(self.gear_id, "彩雲" | "彩雲(東カロリン空)" | "彩雲(偵四)")
+    }
+
     fn get_proficiency_type(&self) -> ProficiencyType {
         if self.is_hayabusa_20th_squadron() {
             ProficiencyType::AntiSubPatrolAircraft
@@ -489,6 +493,8 @@ impl Gear {
                 "radius" => self.radius.into(),
                 "cost" => self.cost.into(),
                 "improvable" => bool_to_f64!(self.improvable),
+                "stars" => self.stars.into(),
+                "ace" => self.ace().into(),
 
                 "types" => {
                     let index = args.first()?.floor() as usize;
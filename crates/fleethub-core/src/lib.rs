@@ -15,6 +15,9 @@ pub mod gear;
 pub mod gear_array;
 pub mod master_data;
 pub mod member;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
+pub mod optimizer;
 pub mod org;
 pub mod plane;
 mod result;
@@ -27,14 +30,21 @@ use gloo_utils::format::JsValueSerdeExt;
 use wasm_bindgen::{prelude::*, JsCast};
 
 use air_squadron::AirSquadron;
-use analyzer::Analyzer;
+use analyzer::{
+    Analyzer, ExpeditionAnalysis, ExpeditionAnalyzer, RoutingAnalysis, RoutingAnalyzer,
+};
 use comp::Comp;
 use factory::Factory;
 use fleet::Fleet;
 use gear::Gear;
 use master_data::MasterData;
+use optimizer::{
+    FleetLoadoutOptimizer, FleetLoadoutOptimizerConfig, FleetLoadoutOptimizerResult,
+    LoadoutOptimizer, LoadoutOptimizerConfig, LoadoutOptimizerResult,
+};
 use org::Org;
 use ship::Ship;
+use simulator::{SortieInput, SortieNode, SortieSimulatorResult, seeded_rng};
 use types::{AirSquadronState, FleetState, GearState, OrgState, ShipState};
 
 #[wasm_bindgen]
@@ -125,13 +135,193 @@ impl FhCore {
     pub fn create_analyzer(&self) -> Analyzer {
         Analyzer::new(self.factory.master_data.battle_definitions())
     }
+
+    pub fn simulate_sortie(
+        &self,
+        player_comp: &Comp,
+        input: SortieInput,
+        times: usize,
+        seed: Option<u64>,
+    ) -> Result<SortieSimulatorResult, JsValue> {
+        let nodes = input
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let enemy = self
+                    .factory
+                    .create_org(Some(node.enemy))
+                    .ok_or_else(|| JsValue::from("敵編成の作成に失敗しました"))?
+                    .create_comp();
+
+                Ok(SortieNode {
+                    enemy,
+                    config: node.config,
+                })
+            })
+            .collect::<Result<Vec<_>, JsValue>>()?;
+
+        let (mut rng, seed) = seeded_rng(seed);
+
+        self.create_analyzer()
+            .try_simulate_sortie(&mut rng, player_comp, &nodes, &input.config, times)
+            .map(|mut result| {
+                result.seed = Some(seed);
+                result
+            })
+            .map_err(|err| JsValue::from(&err.to_string()))
+    }
+
+    pub fn optimize_loadout(
+        &self,
+        config: LoadoutOptimizerConfig,
+        ship: &Ship,
+        target: &Ship,
+    ) -> LoadoutOptimizerResult {
+        let battle_defs = self.factory.master_data.battle_definitions();
+
+        LoadoutOptimizer {
+            factory: &self.factory,
+            battle_defs: &battle_defs,
+            config: &config,
+            ship,
+            target,
+        }
+        .optimize()
+    }
+
+    pub fn optimize_fleet_loadout(
+        &self,
+        config: FleetLoadoutOptimizerConfig,
+        comp: &Comp,
+        target: &Ship,
+    ) -> FleetLoadoutOptimizerResult {
+        let battle_defs = self.factory.master_data.battle_definitions();
+
+        FleetLoadoutOptimizer {
+            factory: &self.factory,
+            battle_defs: &battle_defs,
+            config: &config,
+            comp,
+            target,
+        }
+        .optimize()
+    }
+
+    /// 海域の分岐規則が未定義の場合は空の結果を返す
+    pub fn analyze_routing(&self, org: &Org, map: i16, phase: u8) -> RoutingAnalysis {
+        let Some(routing) = self.factory.master_data.get_map_routing(map) else {
+            return RoutingAnalysis {
+                map,
+                branches: vec![],
+            };
+        };
+
+        RoutingAnalyzer {
+            routing,
+            comp: &org.create_comp(),
+            phase,
+        }
+        .analyze()
+    }
+
+    /// マスターデータに定義された全ての遠征について判定する
+    pub fn analyze_expeditions(&self, fleet: &Fleet) -> ExpeditionAnalysis {
+        ExpeditionAnalyzer { fleet }.analyze(&self.factory.master_data.expeditions)
+    }
 }
 
 #[cfg(test)]
 pub mod test {
     use rand::prelude::*;
 
+    use crate::{
+        comp::Comp,
+        fleet::Fleet,
+        master_data::{MasterShip, StatInterval},
+        ship::Ship,
+        types::{BattleDefinitions, Formation, OrgType, ShipState, ShipType},
+    };
+
     pub fn rng(seed: u64) -> impl Rng {
         SmallRng::seed_from_u64(seed)
     }
+
+    /// 戦闘の計算に必要なステータスを持つ艦
+    pub fn ship(id: &str, ship_type: ShipType, max_hp: u16) -> Ship {
+        damaged_ship(id, ship_type, max_hp, max_hp)
+    }
+
+    /// 出撃ごとに`current_hp`から始まる艦
+    pub fn damaged_ship(id: &str, ship_type: ShipType, max_hp: u16, current_hp: u16) -> Ship {
+        let master = MasterShip {
+            max_hp: StatInterval(Some(max_hp), Some(max_hp)),
+            firepower: StatInterval(Some(40), Some(40)),
+            torpedo: StatInterval(Some(0), Some(0)),
+            armor: StatInterval(Some(30), Some(30)),
+            anti_air: StatInterval(Some(0), Some(0)),
+            evasion: StatInterval(Some(40), Some(40)),
+            asw: StatInterval(Some(0), Some(0)),
+            los: StatInterval(Some(10), Some(10)),
+            luck: StatInterval(Some(10), Some(10)),
+            range: Some(1),
+            fuel: 10,
+            ammo: 10,
+            ..Default::default()
+        };
+        let state = ShipState {
+            id: Some(id.into()),
+            level: Some(99),
+            current_hp: Some(current_hp),
+            ..Default::default()
+        };
+
+        let mut ship = Ship::new(
+            0,
+            state,
+            &master,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        ship.ship_type = ship_type;
+        ship
+    }
+
+    pub fn comp(org_type: OrgType, ships: Vec<Ship>) -> Comp {
+        let mut main = Fleet::default();
+        ships.into_iter().for_each(|ship| main.ships.push(ship));
+
+        Comp {
+            org_type,
+            hq_level: 120,
+            main,
+            escort: None,
+            route_sup: None,
+            boss_sup: None,
+        }
+    }
+
+    /// 全ての陣形で補正のない定義
+    pub fn battle_defs() -> BattleDefinitions {
+        let mut battle_defs = BattleDefinitions::default();
+
+        [
+            Formation::LINE_AHEAD,
+            Formation::DOUBLE_LINE,
+            Formation::DIAMOND,
+            Formation::ECHELON,
+            Formation::LINE_ABREAST,
+            Formation::VANGUARD,
+            Formation::CRUISING1,
+            Formation::CRUISING2,
+            Formation::CRUISING3,
+            Formation::CRUISING4,
+        ]
+        .into_iter()
+        .for_each(|formation| {
+            battle_defs.formation.insert(formation, Default::default());
+        });
+
+        battle_defs
+    }
 }
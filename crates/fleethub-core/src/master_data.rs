@@ -1,7 +1,12 @@
 mod master_battle_definitions;
+mod master_data_diff;
+mod master_data_report;
+mod master_ebonus;
 mod master_equippability;
+mod master_expedition;
 mod master_gear;
 mod master_ibonus;
+mod master_routing;
 mod master_ship;
 
 use serde::{de::DeserializeOwned, Deserialize};
@@ -9,12 +14,20 @@ use serde_with::{serde_as, DefaultOnError};
 use tsify::Tsify;
 
 pub use master_battle_definitions::*;
+pub use master_data_diff::*;
+pub use master_data_report::*;
+pub use master_ebonus::*;
 pub use master_equippability::*;
+pub use master_expedition::*;
 pub use master_gear::*;
 pub use master_ibonus::*;
+pub use master_routing::*;
 pub use master_ship::*;
 
-use crate::types::{BattleDefinitions, CompiledEvaler, GearAttr, ShipAttr};
+use crate::{
+    gear_array::GearArray,
+    types::{BattleDefinitions, CompiledEvaler, EBonuses, GearAttr, ShipAttr},
+};
 
 #[serde_as]
 #[derive(Debug, Default, Clone, Deserialize, Tsify)]
@@ -41,8 +54,14 @@ struct MasterDataShadow {
     ships: Vec<MasterShip>,
     ship_attrs: Vec<MasterAttrRule<ShipAttr>>,
     ibonuses: MasterIBonuses,
+    #[serde(default)]
+    ebonuses: Option<MasterEBonuses>,
     #[serde(alias = "equippable")]
     equippability: MasterEquippability,
+    #[serde(default)]
+    routing: Vec<MasterMapRouting>,
+    #[serde(default)]
+    expeditions: Vec<MasterExpedition>,
     #[serde(flatten)]
     battle_definitions: MasterBattleDefinitions,
 }
@@ -57,7 +76,13 @@ pub struct MasterData {
     pub ships: Vec<MasterShip>,
     pub ship_attrs: Vec<MasterAttrRule<ShipAttr>>,
     pub ibonuses: MasterIBonuses,
+    /// 未定義の場合は equipment-bonus モジュールで計算する
+    ///
+    /// モジュールとの一致は`tests/ebonuses.rs`で確認する
+    pub ebonuses: Option<MasterEBonuses>,
     pub equippability: MasterEquippability,
+    pub routing: Vec<MasterMapRouting>,
+    pub expeditions: Vec<MasterExpedition>,
     #[serde(flatten)]
     pub battle_definitions: MasterBattleDefinitions,
 }
@@ -71,7 +96,10 @@ impl From<MasterDataShadow> for MasterData {
             mut ships,
             ship_attrs,
             ibonuses,
+            ebonuses,
             equippability,
+            routing,
+            expeditions,
             battle_definitions,
         } = def;
 
@@ -98,7 +126,10 @@ impl From<MasterDataShadow> for MasterData {
             ships,
             ship_attrs,
             ibonuses,
+            ebonuses,
             equippability,
+            routing,
+            expeditions,
             battle_definitions,
         }
     }
@@ -109,10 +140,28 @@ impl MasterData {
         self.ibonuses.eval(gear, stars)
     }
 
+    /// 装備ボーナスの定義がない場合は equipment-bonus モジュールで計算する
+    pub fn create_ebonuses(&self, ship: &MasterShip, gears: &GearArray) -> EBonuses {
+        match &self.ebonuses {
+            Some(master) => EBonuses::from_master(ship, gears, master),
+            None => EBonuses::new(ship, gears),
+        }
+    }
+
     pub fn create_ship_equippability(&self, ship: &MasterShip) -> ShipEquippability {
         self.equippability.create_ship_equippability(ship)
     }
 
+    pub fn get_map_routing(&self, map: i16) -> Option<&MasterMapRouting> {
+        self.routing.iter().find(|routing| routing.map == map)
+    }
+
+    pub fn get_expedition(&self, id: u16) -> Option<&MasterExpedition> {
+        self.expeditions
+            .iter()
+            .find(|expedition| expedition.id == id)
+    }
+
     pub fn battle_definitions(&self) -> BattleDefinitions {
         self.battle_definitions.battle_definitions()
     }
@@ -2,8 +2,8 @@ use serde::Deserialize;
 use tsify::Tsify;
 
 use crate::types::{
-    AntiAirCutinDef, BattleDefinitions, DayCutinDef, HistoricalBonusDef, NestedFormationDef,
-    NightCutinDef,
+    AntiAirCutinDef, BattleDefinitions, DayCutinDef, FleetCutinDef, GunfitAccuracyDef,
+    HistoricalBonusDef, NestedFormationDef, NightCutinDef, SpecialEnemyModDef,
 };
 
 #[derive(Debug, Default, Clone, Deserialize, Tsify)]
@@ -14,6 +14,12 @@ pub struct MasterBattleDefinitions {
     pub night_cutin: Vec<NightCutinDef>,
     #[serde(default)]
     pub historical_bonuses: Vec<HistoricalBonusDef>,
+    #[serde(default)]
+    pub fleet_cutin: Vec<FleetCutinDef>,
+    #[serde(default)]
+    pub special_enemy: Vec<SpecialEnemyModDef>,
+    #[serde(default)]
+    pub gunfit_accuracy: Vec<GunfitAccuracyDef>,
 }
 
 impl MasterBattleDefinitions {
@@ -48,6 +54,10 @@ impl MasterBattleDefinitions {
             day_cutin,
             night_cutin,
             historical_bonuses: self.historical_bonuses.clone(),
+            fleet_cutin: self.fleet_cutin.clone(),
+            special_enemy: self.special_enemy.clone(),
+            historical_bonus_rule_sets: Vec::new(),
+            gunfit_accuracy: self.gunfit_accuracy.clone(),
         }
     }
 }
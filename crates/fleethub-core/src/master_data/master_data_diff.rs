@@ -0,0 +1,303 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+const BATTLE_DEFINITION_KEYS: [&str; 8] = [
+    "formation",
+    "anti_air_cutin",
+    "day_cutin",
+    "night_cutin",
+    "historical_bonuses",
+    "fleet_cutin",
+    "special_enemy",
+    "gunfit_accuracy",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MasterDataEntry {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MasterDataEntryChange {
+    pub id: u64,
+    pub name: String,
+    /// 変更された項目名
+    pub fields: Vec<String>,
+}
+
+/// IDを持つ要素の差分
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MasterDataEntriesDiff {
+    pub added: Vec<MasterDataEntry>,
+    pub removed: Vec<MasterDataEntry>,
+    pub changed: Vec<MasterDataEntryChange>,
+}
+
+impl MasterDataEntriesDiff {
+    fn new(old: &Value, new: &Value, key: &str, id_key: &str) -> Self {
+        let index = |value: &Value| -> Vec<(u64, Map<String, Value>)> {
+            value
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|item| {
+                    let id = item.get(id_key)?.as_u64()?;
+                    Some((id, item.as_object()?.clone()))
+                })
+                .collect()
+        };
+
+        let old_entries = index(old);
+        let new_entries = index(new);
+
+        let to_entry = |id: u64, entry: &Map<String, Value>| MasterDataEntry {
+            id,
+            name: entry_name(entry),
+        };
+
+        let mut diff = Self::default();
+
+        new_entries
+            .iter()
+            .for_each(|(id, new_entry)| match find_entry(&old_entries, *id) {
+                None => diff.added.push(to_entry(*id, new_entry)),
+                Some(old_entry) => {
+                    let fields = changed_fields(old_entry, new_entry);
+
+                    if !fields.is_empty() {
+                        diff.changed.push(MasterDataEntryChange {
+                            id: *id,
+                            name: entry_name(new_entry),
+                            fields,
+                        });
+                    }
+                }
+            });
+
+        old_entries
+            .iter()
+            .filter(|(id, _)| find_entry(&new_entries, *id).is_none())
+            .for_each(|(id, old_entry)| diff.removed.push(to_entry(*id, old_entry)));
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// IDを持たない定義の一覧の差分
+#[derive(Debug, Clone, Serialize)]
+pub struct MasterDataListDiff {
+    pub key: String,
+    pub added: Vec<Value>,
+    pub removed: Vec<Value>,
+}
+
+impl MasterDataListDiff {
+    fn new(key: &str, old: Option<&Value>, new: Option<&Value>) -> Option<Self> {
+        let to_vec = |value: Option<&Value>| match value {
+            Some(Value::Array(array)) => array.clone(),
+            Some(Value::Null) | None => vec![],
+            Some(value) => vec![value.clone()],
+        };
+
+        let old = to_vec(old);
+        let new = to_vec(new);
+
+        let difference = |a: &[Value], b: &[Value]| {
+            let mut rest = b.to_vec();
+
+            a.iter()
+                .filter(|item| match rest.iter().position(|other| other == *item) {
+                    Some(index) => {
+                        rest.remove(index);
+                        false
+                    }
+                    None => true,
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        let added = difference(&new, &old);
+        let removed = difference(&old, &new);
+
+        if added.is_empty() && removed.is_empty() {
+            None
+        } else {
+            Some(Self {
+                key: key.to_string(),
+                added,
+                removed,
+            })
+        }
+    }
+}
+
+/// マスターデータの更新前後の差分
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MasterDataDiff {
+    pub ships: MasterDataEntriesDiff,
+    pub gears: MasterDataEntriesDiff,
+    pub ibonuses: Vec<MasterDataListDiff>,
+    pub ebonuses: Option<MasterDataListDiff>,
+    pub routing: Option<MasterDataListDiff>,
+    pub expeditions: MasterDataEntriesDiff,
+    pub battle_definitions: Vec<MasterDataListDiff>,
+}
+
+impl MasterDataDiff {
+    pub fn new(old: &Value, new: &Value) -> Self {
+        let mut ibonus_keys = [old, new]
+            .into_iter()
+            .filter_map(|value| value.get("ibonuses")?.as_object())
+            .flat_map(|map| map.keys().cloned())
+            .collect::<Vec<_>>();
+
+        ibonus_keys.sort();
+        ibonus_keys.dedup();
+
+        let ibonuses = ibonus_keys
+            .iter()
+            .filter_map(|key| {
+                MasterDataListDiff::new(
+                    key,
+                    old.get("ibonuses").and_then(|v| v.get(key)),
+                    new.get("ibonuses").and_then(|v| v.get(key)),
+                )
+            })
+            .collect();
+
+        let battle_definitions = BATTLE_DEFINITION_KEYS
+            .into_iter()
+            .filter_map(|key| MasterDataListDiff::new(key, old.get(key), new.get(key)))
+            .collect();
+
+        Self {
+            ships: MasterDataEntriesDiff::new(old, new, "ships", "ship_id"),
+            gears: MasterDataEntriesDiff::new(old, new, "gears", "gear_id"),
+            ibonuses,
+            ebonuses: MasterDataListDiff::new("ebonuses", old.get("ebonuses"), new.get("ebonuses")),
+            routing: MasterDataListDiff::new("routing", old.get("routing"), new.get("routing")),
+            expeditions: MasterDataEntriesDiff::new(old, new, "expeditions", "id"),
+            battle_definitions,
+        }
+    }
+
+    pub fn from_json(old: &str, new: &str) -> anyhow::Result<Self> {
+        let old: Value = serde_json::from_str(old)?;
+        let new: Value = serde_json::from_str(new)?;
+        Ok(Self::new(&old, &new))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ships.is_empty()
+            && self.gears.is_empty()
+            && self.ibonuses.is_empty()
+            && self.ebonuses.is_none()
+            && self.routing.is_none()
+            && self.expeditions.is_empty()
+            && self.battle_definitions.is_empty()
+    }
+}
+
+fn find_entry(entries: &[(u64, Map<String, Value>)], id: u64) -> Option<&Map<String, Value>> {
+    entries
+        .iter()
+        .find(|(other, _)| *other == id)
+        .map(|(_, entry)| entry)
+}
+
+fn entry_name(entry: &Map<String, Value>) -> String {
+    entry
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn changed_fields(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<String> {
+    let mut fields = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(value))
+        .map(|(key, _)| key.clone())
+        .chain(old.keys().filter(|key| !new.contains_key(*key)).cloned())
+        .collect::<Vec<_>>();
+
+    fields.sort();
+    fields
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_master_data_diff() {
+        let old = json!({
+            "ships": [
+                { "ship_id": 1, "name": "a", "max_hp": [15, 16] },
+                { "ship_id": 2, "name": "b" },
+            ],
+            "gears": [{ "gear_id": 1, "name": "gun" }],
+            "ibonuses": {
+                "shelling_power": [{ "expr": "gear_id == 1", "formula": "x" }],
+            },
+            "day_cutin": [{ "tag": "MainMain" }],
+        });
+
+        let new = json!({
+            "ships": [
+                { "ship_id": 1, "name": "a", "max_hp": [16, 16], "speed": 10 },
+                { "ship_id": 3, "name": "c" },
+            ],
+            "gears": [{ "gear_id": 1, "name": "gun" }],
+            "ibonuses": {
+                "shelling_power": [{ "expr": "gear_id == 1", "formula": "2 * x" }],
+                "torpedo_power": [],
+            },
+            "day_cutin": [{ "tag": "MainMain" }],
+            "night_cutin": [{ "tag": "DoubleAttack" }],
+            "fleet_cutin": [{ "tag": "NelsonTouch" }],
+            "special_enemy": [{ "targets": ["PtImp"], "field": "pt_mod" }],
+            "gunfit_accuracy": [{ "name": "長門型", "rules": [] }],
+        });
+
+        let diff = MasterDataDiff::new(&old, &new);
+
+        assert!(!diff.is_empty());
+        assert_eq!(diff.ships.added.len(), 1);
+        assert_eq!(diff.ships.added[0].id, 3);
+        assert_eq!(diff.ships.removed[0].name, "b");
+        assert_eq!(diff.ships.changed[0].fields, vec!["max_hp", "speed"]);
+        assert!(diff.gears.is_empty());
+
+        assert_eq!(diff.ibonuses.len(), 1);
+        assert_eq!(diff.ibonuses[0].key, "shelling_power");
+        assert_eq!(diff.ibonuses[0].added[0]["formula"], "2 * x");
+        assert_eq!(diff.ibonuses[0].removed[0]["formula"], "x");
+
+        let keys = diff
+            .battle_definitions
+            .iter()
+            .map(|diff| diff.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                "night_cutin",
+                "fleet_cutin",
+                "special_enemy",
+                "gunfit_accuracy"
+            ]
+        );
+
+        assert!(MasterDataDiff::new(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,587 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::types::{
+    CompiledEvaler, FleetCutinDef, GearAttr, GunfitAccuracyDef, ShipAttr, SpecialEnemyModDef,
+};
+
+use super::{MasterData, MasterGear, MasterShip, MstEquipShipValue};
+
+const IBONUS_EXPR_KEYS: [&str; 2] = ["expr", "formula"];
+const EQUIPPABILITY_EXPR_KEYS: [&str; 3] = ["ship", "include", "exclude"];
+const EBONUS_EXPR_KEYS: [&str; 2] = ["ship", "gear"];
+const EBONUS_REQUIREMENT_KEYS: [&str; 2] = ["requires", "requires_any"];
+const HISTORICAL_BONUS_EXPR_KEYS: [&str; 3] = ["node", "ship", "enemy"];
+const FLEET_CUTIN_EXPR_KEYS: [&str; 2] = ["requires", "rate"];
+const SPECIAL_ENEMY_EXPR_KEYS: [&str; 2] = ["requires", "count"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MasterDataIssueKind {
+    /// 構文解析できない式
+    InvalidExpr,
+    /// 読み込めない装備ボーナスの条件
+    InvalidEBonusRequirement,
+    /// 読み込めない戦闘の定義、マスターデータ全体が読み込めなくなる
+    InvalidBattleDefinition,
+    /// 読み込めない艦娘
+    InvalidShip,
+    /// 読み込めない装備
+    InvalidGear,
+    /// 不明な属性のタグ
+    UnknownAttrTag,
+    /// どの艦娘、装備にも一致しない属性
+    UnmatchedAttrRule,
+    /// 装備可能設定の不正な値
+    InvalidEquippability,
+    /// 存在しない装備ID
+    UnknownGearId,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MasterDataIssue {
+    pub kind: MasterDataIssueKind,
+    /// JSON Pointer 形式の位置
+    pub path: String,
+    pub message: String,
+}
+
+/// マスターデータの検証結果
+///
+/// 読み込み時には無視されたり既定値で置き換えられる問題を列挙する
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MasterDataReport {
+    pub issues: Vec<MasterDataIssue>,
+}
+
+fn items<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = (usize, &'a Value)> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+}
+
+fn entries<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = (&'a String, &'a Value)> {
+    value
+        .get(key)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+}
+
+impl MasterDataReport {
+    pub fn new(value: &Value) -> Self {
+        let mut report = Self::default();
+
+        let mut ships =
+            report.check_entries::<MasterShip>(value, "ships", MasterDataIssueKind::InvalidShip);
+        let mut gears =
+            report.check_entries::<MasterGear>(value, "gears", MasterDataIssueKind::InvalidGear);
+
+        // 読み込み時と同様に、先に定義された属性を付与しながら判定する
+        report.check_attr_rules(value, "ship_attrs", |expr, tag: ShipAttr| {
+            ships.iter_mut().fold(false, |matched, ship| {
+                if !expr.matches(&mut ship.ns()) {
+                    return matched;
+                }
+                ship.attrs.insert(tag);
+                true
+            })
+        });
+        report.check_attr_rules(value, "gear_attrs", |expr, tag: GearAttr| {
+            gears.iter_mut().fold(false, |matched, gear| {
+                if !expr.matches(&mut gear.ns()) {
+                    return matched;
+                }
+                gear.attrs.insert(tag);
+                true
+            })
+        });
+
+        entries(value, "ibonuses").for_each(|(key, rules)| {
+            rules
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .for_each(|(i, rule)| {
+                    report.check_exprs(&format!("/ibonuses/{key}/{i}"), rule, &IBONUS_EXPR_KEYS);
+                });
+        });
+
+        items(value, "ebonuses").for_each(|(i, rule)| {
+            let path = format!("/ebonuses/{i}");
+            report.check_exprs(&path, rule, &EBONUS_EXPR_KEYS);
+
+            EBONUS_REQUIREMENT_KEYS.iter().for_each(|key| {
+                items(rule, key).for_each(|(j, requirement)| {
+                    report.check_ebonus_requirement(format!("{path}/{key}/{j}"), requirement);
+                });
+            });
+        });
+
+        items(value, "historical_bonuses").for_each(|(i, def)| {
+            report.check_exprs(
+                &format!("/historical_bonuses/{i}"),
+                def,
+                &HISTORICAL_BONUS_EXPR_KEYS,
+            );
+        });
+
+        report.check_battle_definitions::<FleetCutinDef>(
+            value,
+            "fleet_cutin",
+            |report, path, def| {
+                report.check_exprs(path, def, &FLEET_CUTIN_EXPR_KEYS);
+
+                items(def, "attacks").for_each(|(i, attack)| {
+                    let path = format!("{path}/attacks/{i}");
+
+                    items(attack, "power_mods").for_each(|(j, expr)| {
+                        report.check_expr(format!("{path}/power_mods/{j}"), expr);
+                    });
+                    items(attack, "equipment_mods").for_each(|(j, equipment_mod)| {
+                        report.check_exprs(
+                            &format!("{path}/equipment_mods/{j}"),
+                            equipment_mod,
+                            &["requires"],
+                        );
+                    });
+                });
+            },
+        );
+
+        report.check_battle_definitions::<SpecialEnemyModDef>(
+            value,
+            "special_enemy",
+            |report, path, def| report.check_exprs(path, def, &SPECIAL_ENEMY_EXPR_KEYS),
+        );
+
+        // フィット砲補正の定義は式を持たない
+        report.check_battle_definitions::<GunfitAccuracyDef>(
+            value,
+            "gunfit_accuracy",
+            |_, _, _| (),
+        );
+
+        items(value, "routing").for_each(|(i, routing)| {
+            items(routing, "branches").for_each(|(j, branch)| {
+                items(branch, "rules").for_each(|(k, rule)| {
+                    items(rule, "conditions").for_each(|(l, condition)| {
+                        if let Some(expr) = condition.get("expr") {
+                            report.check_expr(
+                                format!("/routing/{i}/branches/{j}/rules/{k}/conditions/{l}/expr"),
+                                expr,
+                            );
+                        }
+                    });
+                });
+            });
+        });
+
+        let equippability_key = if value.get("equippability").is_some() {
+            "equippability"
+        } else {
+            "equippable"
+        };
+
+        if let Some(equippability) = value.get(equippability_key) {
+            let gear_ids = gears.iter().map(|gear| gear.gear_id).collect::<Vec<_>>();
+            report.check_equippability(equippability_key, equippability, &gear_ids);
+        }
+
+        report
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn push(&mut self, kind: MasterDataIssueKind, path: String, message: impl Into<String>) {
+        self.issues.push(MasterDataIssue {
+            kind,
+            path,
+            message: message.into(),
+        });
+    }
+
+    /// 読み込めた要素のみを返す
+    fn check_entries<T: DeserializeOwned>(
+        &mut self,
+        value: &Value,
+        key: &str,
+        kind: MasterDataIssueKind,
+    ) -> Vec<T> {
+        items(value, key)
+            .filter_map(|(i, item)| match T::deserialize(item) {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    self.push(kind, format!("/{key}/{i}"), err.to_string());
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn check_expr(&mut self, path: String, value: &Value) -> Option<CompiledEvaler> {
+        let Some(expr) = value.as_str() else {
+            if !value.is_null() {
+                self.push(
+                    MasterDataIssueKind::InvalidExpr,
+                    path,
+                    "文字列ではありません",
+                );
+            }
+            return None;
+        };
+
+        match CompiledEvaler::new(expr.to_string()) {
+            Ok(evaler) => Some(evaler),
+            Err(err) => {
+                self.push(
+                    MasterDataIssueKind::InvalidExpr,
+                    path,
+                    format!("{expr}: {err:?}"),
+                );
+                None
+            }
+        }
+    }
+
+    /// 式の文字列か`{ gear, min_count }`の形式を受け付ける
+    fn check_ebonus_requirement(&mut self, path: String, value: &Value) {
+        if value.is_string() {
+            self.check_expr(path, value);
+            return;
+        }
+
+        let Some(gear) = value.get("gear") else {
+            self.push(
+                MasterDataIssueKind::InvalidEBonusRequirement,
+                path,
+                value.to_string(),
+            );
+            return;
+        };
+
+        self.check_expr(format!("{path}/gear"), gear);
+
+        if let Some(min_count) = value.get("min_count")
+            && min_count.as_u64().is_none()
+        {
+            self.push(
+                MasterDataIssueKind::InvalidEBonusRequirement,
+                format!("{path}/min_count"),
+                min_count.to_string(),
+            );
+        }
+    }
+
+    fn check_exprs(&mut self, path: &str, value: &Value, keys: &[&str]) {
+        keys.iter().for_each(|key| {
+            if let Some(expr) = value.get(key) {
+                self.check_expr(format!("{path}/{key}"), expr);
+            }
+        });
+    }
+
+    /// `check`で式を検証し、式に問題がない場合は定義として読み込めるかを検証する
+    fn check_battle_definitions<T: DeserializeOwned>(
+        &mut self,
+        value: &Value,
+        key: &str,
+        check: impl Fn(&mut Self, &str, &Value),
+    ) {
+        items(value, key).for_each(|(i, def)| {
+            let path = format!("/{key}/{i}");
+            let issue_count = self.issues.len();
+
+            check(self, &path, def);
+
+            if self.issues.len() != issue_count {
+                return;
+            }
+
+            if let Err(err) = T::deserialize(def) {
+                self.push(
+                    MasterDataIssueKind::InvalidBattleDefinition,
+                    path,
+                    err.to_string(),
+                );
+            }
+        });
+    }
+
+    /// `apply`は一致する要素に属性を付与し、一致する要素があるかを返す
+    ///
+    /// 不明なタグは読み込み時と同様に既定値として扱う
+    fn check_attr_rules<T: DeserializeOwned + Default>(
+        &mut self,
+        value: &Value,
+        key: &str,
+        mut apply: impl FnMut(&CompiledEvaler, T) -> bool,
+    ) {
+        items(value, key).for_each(|(i, rule)| {
+            let path = format!("/{key}/{i}");
+
+            let tag = rule
+                .get("tag")
+                .map(|tag| {
+                    T::deserialize(tag).unwrap_or_else(|_| {
+                        self.push(
+                            MasterDataIssueKind::UnknownAttrTag,
+                            format!("{path}/tag"),
+                            tag.to_string(),
+                        );
+                        T::default()
+                    })
+                })
+                .unwrap_or_default();
+
+            let Some(expr) = rule.get("expr") else {
+                return;
+            };
+
+            let matched = self
+                .check_expr(format!("{path}/expr"), expr)
+                .map(|evaler| apply(&evaler, tag));
+
+            if matched == Some(false) {
+                self.push(
+                    MasterDataIssueKind::UnmatchedAttrRule,
+                    format!("{path}/expr"),
+                    expr.to_string(),
+                );
+            }
+        });
+    }
+
+    fn check_equippability(&mut self, key: &str, value: &Value, gear_ids: &[u16]) {
+        items(value, "rules").for_each(|(i, rule)| {
+            self.check_exprs(&format!("/{key}/rules/{i}"), rule, &EQUIPPABILITY_EXPR_KEYS);
+        });
+
+        entries(value, "equip_ship").for_each(|(ship_id, equip_ship)| {
+            entries(equip_ship, "api_equip_type").for_each(|(gear_type, item)| {
+                let path = format!("/{key}/equip_ship/{ship_id}/api_equip_type/{gear_type}");
+
+                match MstEquipShipValue::deserialize(item) {
+                    Ok(MstEquipShipValue::GearIds(ids)) => ids
+                        .into_iter()
+                        .filter(|gear_id| !gear_ids.contains(gear_id))
+                        .for_each(|gear_id| {
+                            self.push(
+                                MasterDataIssueKind::UnknownGearId,
+                                path.clone(),
+                                gear_id.to_string(),
+                            )
+                        }),
+                    Ok(MstEquipShipValue::Null) => (),
+                    Err(err) => self.push(
+                        MasterDataIssueKind::InvalidEquippability,
+                        path,
+                        err.to_string(),
+                    ),
+                }
+            });
+        });
+
+        entries(value, "equip_exslot_ship").for_each(|(gear_id, _)| {
+            let path = format!("/{key}/equip_exslot_ship/{gear_id}");
+
+            match gear_id.parse::<u16>() {
+                Ok(id) if gear_ids.contains(&id) => (),
+                Ok(_) => self.push(MasterDataIssueKind::UnknownGearId, path, gear_id.clone()),
+                Err(_) => self.push(
+                    MasterDataIssueKind::InvalidEquippability,
+                    path,
+                    format!("{gear_id} は装備IDではありません"),
+                ),
+            }
+        });
+    }
+}
+
+impl MasterData {
+    /// 検証結果とともに読み込む
+    pub fn load(json: &str) -> anyhow::Result<(Self, MasterDataReport)> {
+        use anyhow::Context;
+
+        let value: Value =
+            serde_json::from_str(json).context("マスターデータの JSON が不正です")?;
+        let report = MasterDataReport::new(&value);
+        let master_data =
+            Self::deserialize(value).context("マスターデータの読み込みに失敗しました")?;
+
+        Ok((master_data, report))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ship(ship_id: u16) -> Value {
+        let stat = serde_json::json!([null, null]);
+
+        serde_json::json!({
+            "ship_id": ship_id,
+            "name": "",
+            "yomi": "",
+            "stype": 2,
+            "ctype": 1,
+            "max_hp": stat,
+            "firepower": stat,
+            "armor": stat,
+            "torpedo": stat,
+            "evasion": stat,
+            "anti_air": stat,
+            "asw": stat,
+            "los": stat,
+            "luck": stat,
+            "speed": 10,
+            "slotnum": 0,
+            "slots": [],
+            "stock": [],
+        })
+    }
+
+    #[test]
+    fn test_master_data_report() {
+        let value = serde_json::json!({
+            "ships": [ship(1), ship(2), { "ship_id": "invalid" }],
+            "gears": [
+                { "gear_id": 1, "name": "gun", "types": [1, 1, 1, 1, 1] },
+            ],
+            "ship_attrs": [
+                { "tag": "NotExists", "name": "", "expr": "ship_id == 1" },
+                { "tag": "Kai2", "name": "", "expr": "ship_id == 2" },
+                { "tag": "RoyalNavy", "name": "", "expr": "Kai2" },
+                { "tag": "Installation", "name": "", "expr": "ship_id == 3" },
+            ],
+            "gear_attrs": [
+                { "tag": "HighAngleMount", "name": "", "expr": "gear_id == (" },
+            ],
+            "ibonuses": {
+                "shelling_power": [{ "expr": "gear_id == 1", "formula": "sqrt(x" }],
+            },
+            "ebonuses": [
+                {
+                    "gear": "gear_id == 1",
+                    "requires": [
+                        "gear_id == 2",
+                        { "gear": "gear_type == 5", "min_count": 2 },
+                        { "gear": "gear_type ==", "min_count": -1 },
+                        { "min_count": 2 },
+                    ],
+                    "requires_any": ["gear_id == 3", "gear_id =="],
+                    "bonus": { "firepower": 1 },
+                },
+            ],
+            "fleet_cutin": [
+                {
+                    "tag": "NelsonTouch",
+                    "formations": [],
+                    "requires": "ship_id(1) == 1",
+                    "attacks": [
+                        { "index": 0, "power_mods": ["2", "(red_t && 1.25"] },
+                    ],
+                },
+                {
+                    "tag": "NewCutin",
+                    "formations": [],
+                    "requires": "1",
+                    "attacks": [
+                        { "index": 0, "power_mods": [] },
+                        { "index": 1, "power_mods": [] },
+                        { "index": 2, "power_mods": [] },
+                        { "index": 3, "power_mods": [] },
+                    ],
+                },
+            ],
+            "special_enemy": [
+                { "targets": ["PtImp"], "count": "count_gear_type(1", "field": "pt_mod" },
+                { "targets": ["PtImp"], "field": "not_exists" },
+            ],
+            "gunfit_accuracy": [
+                { "name": "長門型", "rules": [{ "gear_ids": [1], "value": 1 }] },
+                { "name": "陸奥", "rules": [{ "gear_ids": [1], "scale": "log" }] },
+            ],
+            "equippability": {
+                "equip_ship": {
+                    "1": { "api_equip_type": { "1": [1, 2], "2": 1 } },
+                },
+                "equip_exslot_ship": { "3": {} },
+                "rules": [],
+            },
+        });
+
+        let report = MasterDataReport::new(&value);
+
+        let issues = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.path.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            issues,
+            vec![
+                (MasterDataIssueKind::InvalidShip, "/ships/2"),
+                (MasterDataIssueKind::UnknownAttrTag, "/ship_attrs/0/tag"),
+                (MasterDataIssueKind::UnmatchedAttrRule, "/ship_attrs/3/expr"),
+                (MasterDataIssueKind::InvalidExpr, "/gear_attrs/0/expr"),
+                (
+                    MasterDataIssueKind::InvalidExpr,
+                    "/ibonuses/shelling_power/0/formula"
+                ),
+                (
+                    MasterDataIssueKind::InvalidExpr,
+                    "/ebonuses/0/requires/2/gear"
+                ),
+                (
+                    MasterDataIssueKind::InvalidEBonusRequirement,
+                    "/ebonuses/0/requires/2/min_count"
+                ),
+                (
+                    MasterDataIssueKind::InvalidEBonusRequirement,
+                    "/ebonuses/0/requires/3"
+                ),
+                (
+                    MasterDataIssueKind::InvalidExpr,
+                    "/ebonuses/0/requires_any/1"
+                ),
+                (
+                    MasterDataIssueKind::InvalidExpr,
+                    "/fleet_cutin/0/attacks/0/power_mods/1"
+                ),
+                (
+                    MasterDataIssueKind::InvalidBattleDefinition,
+                    "/fleet_cutin/1"
+                ),
+                (MasterDataIssueKind::InvalidExpr, "/special_enemy/0/count"),
+                (
+                    MasterDataIssueKind::InvalidBattleDefinition,
+                    "/special_enemy/1"
+                ),
+                (
+                    MasterDataIssueKind::InvalidBattleDefinition,
+                    "/gunfit_accuracy/1"
+                ),
+                (
+                    MasterDataIssueKind::UnknownGearId,
+                    "/equippability/equip_ship/1/api_equip_type/1"
+                ),
+                (
+                    MasterDataIssueKind::InvalidEquippability,
+                    "/equippability/equip_ship/1/api_equip_type/2"
+                ),
+                (
+                    MasterDataIssueKind::UnknownGearId,
+                    "/equippability/equip_exslot_ship/3"
+                ),
+            ]
+        );
+    }
+}
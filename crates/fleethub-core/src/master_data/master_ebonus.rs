@@ -0,0 +1,293 @@
+use serde::Deserialize;
+use tsify::Tsify;
+
+use crate::{
+    gear::Gear,
+    types::{CompiledEvaler, EBonuses},
+};
+
+use super::MasterShip;
+
+fn default_min_count() -> usize {
+    1
+}
+
+macro_rules! impl_ebonus_values {
+    ($($key: ident: $ty: ty,)*) => {
+        #[derive(Debug, Default, Clone, Deserialize, Tsify)]
+        #[serde(default)]
+        pub struct MasterEBonusValues {
+            $(pub $key: $ty,)*
+        }
+
+        impl MasterEBonusValues {
+            fn apply(&self, ebonuses: &mut EBonuses, times: usize) {
+                $(ebonuses.$key += self.$key * times as $ty;)*
+            }
+        }
+    };
+}
+
+impl_ebonus_values!(
+    firepower: i16,
+    torpedo: i16,
+    anti_air: i16,
+    armor: i16,
+    evasion: i16,
+    asw: i16,
+    los: i16,
+    bombing: i16,
+    accuracy: i16,
+    range: i8,
+);
+
+/// 同時に搭載が必要な装備の条件
+#[derive(Debug, Clone, Deserialize, Tsify)]
+#[serde(untagged)]
+pub enum MasterEBonusRequirement {
+    /// 条件を満たす装備が1つ以上
+    Gear(CompiledEvaler),
+    /// 条件を満たす装備が`min_count`個以上
+    Count {
+        gear: CompiledEvaler,
+        #[serde(default = "default_min_count")]
+        min_count: usize,
+    },
+}
+
+impl MasterEBonusRequirement {
+    fn is_satisfied(&self, gears: &[&Gear]) -> bool {
+        let (gear, min_count) = match self {
+            Self::Gear(gear) => (gear, 1),
+            Self::Count { gear, min_count } => (gear, *min_count),
+        };
+
+        let count = gears.iter().filter(|g| gear.matches(&mut g.ns())).count();
+
+        count >= min_count.max(1)
+    }
+}
+
+/// 装備ボーナスの定義
+///
+/// `ship`を満たす艦が`gear`を満たす装備を`min_count`個以上搭載している場合に`bonus`を加算する
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterEBonusRule {
+    /// 対象艦の条件 (空の場合は全ての艦)
+    #[serde(default)]
+    pub ship: CompiledEvaler,
+    /// 対象装備の条件
+    pub gear: CompiledEvaler,
+    #[serde(default = "default_min_count")]
+    pub min_count: usize,
+    /// 対象装備1つごとに加算する場合の上限数 (未指定の場合は1回のみ加算)
+    #[serde(default)]
+    pub max_stack: Option<usize>,
+    /// 同時に搭載が必要な装備の条件 (全てを満たす必要がある)
+    #[serde(default)]
+    pub requires: Vec<MasterEBonusRequirement>,
+    /// 同時に搭載が必要な装備の条件 (いずれかを満たせばよい)
+    #[serde(default)]
+    pub requires_any: Vec<MasterEBonusRequirement>,
+    pub bonus: MasterEBonusValues,
+}
+
+impl MasterEBonusRule {
+    fn times(&self, ship: &MasterShip, gears: &[&Gear]) -> usize {
+        if !self.ship.is_empty() && !self.ship.matches(&mut ship.ns()) {
+            return 0;
+        }
+
+        let count = gears
+            .iter()
+            .filter(|gear| self.gear.matches(&mut gear.ns()))
+            .count();
+
+        if count == 0 || count < self.min_count {
+            return 0;
+        }
+
+        let satisfies_requires = self.requires.iter().all(|req| req.is_satisfied(gears))
+            && (self.requires_any.is_empty()
+                || self.requires_any.iter().any(|req| req.is_satisfied(gears)));
+
+        if !satisfies_requires {
+            return 0;
+        }
+
+        self.max_stack.map_or(1, |max_stack| count.min(max_stack))
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Tsify)]
+#[serde(transparent)]
+pub struct MasterEBonuses {
+    pub rules: Vec<MasterEBonusRule>,
+}
+
+impl MasterEBonuses {
+    /// 速力と航空戦火力を除く装備ボーナスを計算する
+    pub fn eval(&self, ship: &MasterShip, gears: &[&Gear]) -> EBonuses {
+        let mut ebonuses = EBonuses::default();
+
+        self.rules.iter().for_each(|rule| {
+            let times = rule.times(ship, gears);
+
+            if times > 0 {
+                rule.bonus.apply(&mut ebonuses, times);
+            }
+        });
+
+        ebonuses
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_master_ebonuses() {
+        let ebonuses: MasterEBonuses = serde_json::from_value(serde_json::json!([
+            {
+                "ship": "ship_class == 38",
+                "gear": "gear_id == 1",
+                "max_stack": 2,
+                "bonus": { "firepower": 2, "evasion": 1 }
+            },
+            {
+                "gear": "gear_id == 1 && stars >= 4",
+                "bonus": { "accuracy": 1 }
+            },
+            {
+                "gear": "gear_id == 1",
+                "requires": ["gear_id == 2"],
+                "bonus": { "range": 1 }
+            },
+            {
+                "gear": "gear_id == 2",
+                "min_count": 2,
+                "bonus": { "anti_air": 3 }
+            },
+        ]))
+        .unwrap();
+
+        let ship = MasterShip {
+            ctype: 38,
+            ..Default::default()
+        };
+
+        let gun = Gear {
+            gear_id: 1,
+            ..Default::default()
+        };
+        let improved_gun = Gear {
+            stars: 4,
+            ..gun.clone()
+        };
+        let radar = Gear {
+            gear_id: 2,
+            ..Default::default()
+        };
+
+        let result = ebonuses.eval(&ship, &[&gun, &gun, &improved_gun]);
+        assert_eq!(result.firepower, 4);
+        assert_eq!(result.evasion, 2);
+        assert_eq!(result.accuracy, 1);
+        assert_eq!(result.range, 0);
+
+        let result = ebonuses.eval(&ship, &[&gun, &radar]);
+        assert_eq!(result.firepower, 2);
+        assert_eq!(result.range, 1);
+        assert_eq!(result.anti_air, 0);
+
+        let other_ship = MasterShip::default();
+        let result = ebonuses.eval(&other_ship, &[&improved_gun, &radar, &radar]);
+        assert_eq!(result.firepower, 0);
+        assert_eq!(result.accuracy, 1);
+        assert_eq!(result.anti_air, 3);
+    }
+
+    #[test]
+    fn test_master_ebonus_requirements() {
+        // 夕雲型と12.7cm連装砲D型改二
+        let ebonuses: MasterEBonuses = serde_json::from_value(serde_json::json!([
+            {
+                "ship": "ship_class == 38",
+                "gear": "gear_id == 267",
+                "max_stack": 4,
+                "bonus": { "firepower": 2, "evasion": 1 }
+            },
+            {
+                "ship": "ship_class == 38",
+                "gear": "gear_id == 267",
+                "requires": ["gear_type_in(12, 13) && los >= 5"],
+                "bonus": { "firepower": 2, "torpedo": 3, "evasion": 2 }
+            },
+            {
+                "ship": "ship_class == 38",
+                "gear": "gear_id == 267",
+                "requires": [
+                    "gear_type_in(12, 13) && los >= 5",
+                    { "gear": "gear_type == 5", "min_count": 2 }
+                ],
+                "bonus": { "torpedo": 1 }
+            },
+            {
+                "ship": "ship_class == 38",
+                "gear": "gear_id == 267",
+                "requires_any": ["gear_type == 5", "gear_type == 15"],
+                "bonus": { "los": 1 }
+            },
+        ]))
+        .unwrap();
+
+        let ship = MasterShip {
+            ctype: 38,
+            ..Default::default()
+        };
+
+        let gun = Gear {
+            gear_id: 267,
+            ..Default::default()
+        };
+        let radar = Gear {
+            gear_id: 28,
+            los: 5,
+            types: [2, 8, 12, 11, 0].into(),
+            ..Default::default()
+        };
+        let torpedo = Gear {
+            gear_id: 15,
+            types: [2, 4, 5, 5, 0].into(),
+            ..Default::default()
+        };
+
+        let result = ebonuses.eval(&ship, &[&gun, &gun]);
+        assert_eq!(
+            (result.firepower, result.torpedo, result.evasion),
+            (4, 0, 2)
+        );
+        assert_eq!(result.los, 0);
+
+        let result = ebonuses.eval(&ship, &[&gun, &gun, &radar]);
+        assert_eq!(
+            (result.firepower, result.torpedo, result.evasion),
+            (6, 3, 4)
+        );
+
+        let result = ebonuses.eval(&ship, &[&gun, &radar, &torpedo]);
+        assert_eq!(
+            (result.firepower, result.torpedo, result.evasion),
+            (4, 3, 3)
+        );
+        assert_eq!(result.los, 1);
+
+        let result = ebonuses.eval(&ship, &[&gun, &radar, &torpedo, &torpedo]);
+        assert_eq!(
+            (result.firepower, result.torpedo, result.evasion),
+            (4, 4, 3)
+        );
+        assert_eq!(result.los, 1);
+    }
+}
@@ -16,20 +16,24 @@ pub struct MstStype {
     pub api_equip_type: HashMap<u8, u8>,
 }
 
-impl From<serde_json::Value> for MstEquipShipValue {
-    fn from(value: serde_json::Value) -> Self {
+impl TryFrom<serde_json::Value> for MstEquipShipValue {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
         use serde_json::Value;
 
         match value {
-            Value::Null => Self::Null,
-            Value::Array(_) => Self::GearIds(serde_json::from_value(value).unwrap()),
-            _ => unimplemented!(),
+            Value::Null => Ok(Self::Null),
+            Value::Array(_) => serde_json::from_value(value)
+                .map(Self::GearIds)
+                .map_err(|err| err.to_string()),
+            _ => Err(format!("null または装備IDの配列が必要です: {value}")),
         }
     }
 }
 
 #[derive(Debug, Clone, Deserialize, Tsify)]
-#[serde(from = "serde_json::Value", untagged)]
+#[serde(try_from = "serde_json::Value", untagged)]
 pub enum MstEquipShipValue {
     #[tsify(type = "null")]
     Null,
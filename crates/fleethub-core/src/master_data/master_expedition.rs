@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// 合計値を条件とする能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpeditionStat {
+    Level,
+    Firepower,
+    AntiAir,
+    Asw,
+    Los,
+}
+
+impl ExpeditionStat {
+    /// `Fleet::sum_ship_stat_by`のキー
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Level => "level",
+            Self::Firepower => "firepower",
+            Self::AntiAir => "anti_air",
+            Self::Asw => "asw",
+            Self::Los => "los",
+        }
+    }
+}
+
+/// `ship_types`のいずれかに該当する艦が`count`隻以上
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterExpeditionShipTypeRequirement {
+    pub ship_types: Vec<u8>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterExpeditionStatRequirement {
+    pub stat: ExpeditionStat,
+    pub min: f64,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Tsify)]
+#[serde(default)]
+pub struct MasterExpeditionRequirements {
+    pub flagship_level: Option<u16>,
+    /// 空の場合は旗艦の艦種を問わない
+    pub flagship_ship_types: Vec<u8>,
+    pub ship_count: Option<usize>,
+    pub ship_types: Vec<MasterExpeditionShipTypeRequirement>,
+    pub stats: Vec<MasterExpeditionStatRequirement>,
+    /// ドラム缶(輸送用)を装備した艦の数
+    pub drum_carriers: Option<usize>,
+    /// ドラム缶(輸送用)の合計数
+    pub drum_count: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct ExpeditionResources {
+    pub fuel: f64,
+    pub ammo: f64,
+    pub steel: f64,
+    pub bauxite: f64,
+}
+
+impl ExpeditionResources {
+    pub fn scale(self, rate: f64) -> Self {
+        Self {
+            fuel: self.fuel * rate,
+            ammo: self.ammo * rate,
+            steel: self.steel * rate,
+            bauxite: self.bauxite * rate,
+        }
+    }
+}
+
+/// 大成功の判定方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+pub enum ExpeditionGreatSuccessType {
+    /// 全艦キラキラの場合のみ大成功し得る
+    #[default]
+    Regular,
+    /// ドラム缶の追加数を満たすかで大成功率が変化する
+    Drum,
+    /// 旗艦のレベルで大成功率が変化する
+    FlagshipLevel,
+}
+
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterExpedition {
+    pub id: u16,
+    #[serde(default)]
+    pub name: String,
+    /// 所要時間 (分)
+    pub duration: u32,
+    #[serde(default)]
+    pub requirements: MasterExpeditionRequirements,
+    #[serde(default)]
+    pub rewards: ExpeditionResources,
+    #[serde(default)]
+    pub great_success_type: ExpeditionGreatSuccessType,
+    /// `Drum`の場合の大成功に必要なドラム缶の合計数
+    #[serde(default)]
+    pub great_success_drum_count: Option<usize>,
+}
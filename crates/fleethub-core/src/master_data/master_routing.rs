@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::types::{CompiledEvaler, NodeId};
+
+fn default_rate() -> f64 {
+    1.0
+}
+
+/// 分岐の条件
+///
+/// `min`と`max`が未指定の場合は`expr`を真偽値として扱い、
+/// いずれかが指定されている場合は`expr`の値がその範囲にあるかを判定する
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterRoutingCondition {
+    pub expr: CompiledEvaler,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// 表示名 (空の場合は式を表示する)
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+pub struct MasterRoute {
+    pub node: NodeId,
+    /// 同じ規則の他の行き先との相対的な確率
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+}
+
+/// 全ての条件を満たす場合に`routes`のいずれかへ進む
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterRoutingRule {
+    #[serde(default)]
+    pub conditions: Vec<MasterRoutingCondition>,
+    pub routes: Vec<MasterRoute>,
+}
+
+/// 分岐点ごとの規則
+///
+/// 先に定義された規則から判定し、最初に条件を満たした規則の行き先へ進む
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterRoutingBranch {
+    /// 分岐点
+    pub node: NodeId,
+    pub rules: Vec<MasterRoutingRule>,
+}
+
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct MasterMapRouting {
+    /// `NodeState::map`と同じ表記
+    pub map: i16,
+    pub branches: Vec<MasterRoutingBranch>,
+}
+
+impl MasterMapRouting {
+    pub fn get_branch(&self, node: &str) -> Option<&MasterRoutingBranch> {
+        self.branches
+            .iter()
+            .find(|branch| branch.node.as_str() == node)
+    }
+}
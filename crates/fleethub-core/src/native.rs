@@ -0,0 +1,176 @@
+//! wasm を経由しないネイティブ環境向けのAPI
+
+use std::{fs, path::Path, thread};
+
+use anyhow::{Context, Result};
+use rand::prelude::*;
+
+use crate::{
+    FhCore,
+    analyzer::{Analyzer, NodeAttackAnalyzerConfig},
+    comp::Comp,
+    master_data::{MasterData, MasterDataReport},
+    org::Org,
+    simulator::{BattleSimulatorResult, seeded_rng},
+    types::{BattleType, OrgState},
+};
+
+impl FhCore {
+    /// `ebonuses`がない場合、装備ボーナスの計算には node と equipment-bonus パッケージが必要
+    pub fn from_json(json: &str) -> Result<Self> {
+        let master_data: MasterData =
+            serde_json::from_str(json).context("マスターデータの読み込みに失敗しました")?;
+
+        Ok(Self::from_master_data(master_data))
+    }
+
+    pub fn try_create_org(&self, state: OrgState) -> Result<Org> {
+        self.create_org(Some(state))
+            .context("編成の作成に失敗しました")
+    }
+}
+
+/// マスターデータのファイルを検証結果とともに読み込む
+pub fn load_master_data(path: impl AsRef<Path>) -> Result<(MasterData, MasterDataReport)> {
+    let path = path.as_ref();
+    let json =
+        fs::read_to_string(path).with_context(|| format!("{} を読み込めません", path.display()))?;
+
+    MasterData::load(&json)
+}
+
+/// 試行回数をスレッド数で分割する
+pub fn split_times(times: usize, threads: usize) -> Vec<usize> {
+    let threads = threads.clamp(1, times.max(1));
+    let base = times / threads;
+    let rem = times % threads;
+
+    (0..threads)
+        .map(|i| if i < rem { base + 1 } else { base })
+        .collect()
+}
+
+/// 並列実行の設定
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelConfig {
+    pub times: usize,
+    pub threads: usize,
+    /// 同じシード値とスレッド数であれば同じ結果を返す
+    pub seed: Option<u64>,
+}
+
+/// 各スレッドに独立した乱数生成器を割り当てて戦闘を試行する
+pub fn simulate_battle_parallel(
+    analyzer: &Analyzer,
+    player_comp: &Comp,
+    enemy_comp: &Comp,
+    config: &NodeAttackAnalyzerConfig,
+    battle_type: BattleType,
+    parallel: ParallelConfig,
+) -> Result<BattleSimulatorResult> {
+    let (mut rng, seed) = seeded_rng(parallel.seed);
+
+    let jobs = split_times(parallel.times, parallel.threads)
+        .into_iter()
+        .map(|times| Ok((SmallRng::from_rng(&mut rng)?, times)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let results = thread::scope(|scope| {
+        let handles = jobs
+            .into_iter()
+            .map(|(mut rng, times)| {
+                scope.spawn(move || {
+                    analyzer
+                        .try_simulate_battle(
+                            &mut rng,
+                            player_comp,
+                            enemy_comp,
+                            config,
+                            battle_type,
+                            times,
+                        )
+                        .map(|result| (result, times))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect::<Result<Vec<_>>>()
+    })?;
+
+    let mut result = BattleSimulatorResult::merge(results);
+    result.seed = Some(seed);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        test::{battle_defs, comp, ship},
+        types::{OrgType, ShipType},
+    };
+
+    #[test]
+    fn test_split_times() {
+        assert_eq!(split_times(10, 3), vec![4, 3, 3]);
+        assert_eq!(split_times(2, 4), vec![1, 1]);
+        assert_eq!(split_times(0, 4), vec![0]);
+        assert_eq!(split_times(5, 0), vec![5]);
+    }
+
+    #[test]
+    fn test_simulate_battle_parallel() {
+        let analyzer = Analyzer::new(battle_defs());
+        let player = comp(
+            OrgType::Single,
+            vec![ship("p1", ShipType::BB, 80), ship("p2", ShipType::DD, 30)],
+        );
+        let enemy = comp(
+            OrgType::EnemySingle,
+            vec![ship("e1", ShipType::BB, 80), ship("e2", ShipType::DD, 30)],
+        );
+        let config = NodeAttackAnalyzerConfig::default();
+
+        let simulate = |seed: Option<u64>, threads: usize| {
+            let parallel = ParallelConfig {
+                times: 200,
+                threads,
+                seed,
+            };
+            simulate_battle_parallel(
+                &analyzer,
+                &player,
+                &enemy,
+                &config,
+                BattleType::DayAndNight,
+                parallel,
+            )
+            .unwrap()
+        };
+
+        // シード値を除いた結果
+        let value = |mut result: BattleSimulatorResult| {
+            result.seed = None;
+            serde_json::to_value(result).unwrap()
+        };
+
+        let result = simulate(Some(42), 4);
+        assert_eq!(result.seed, Some(42));
+
+        let expected = value(result);
+        assert_eq!(value(simulate(Some(42), 4)), expected);
+        assert_ne!(value(simulate(Some(43), 4)), expected);
+
+        // シード値を省略した場合も返されたシード値で再現できる
+        let result = simulate(None, 3);
+        let seed = result.seed.unwrap();
+        assert_eq!(value(simulate(Some(seed), 3)), value(result));
+    }
+}
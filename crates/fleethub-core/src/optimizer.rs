@@ -0,0 +1,5 @@
+mod fleet_loadout_optimizer;
+mod loadout_optimizer;
+
+pub use fleet_loadout_optimizer::*;
+pub use loadout_optimizer::*;
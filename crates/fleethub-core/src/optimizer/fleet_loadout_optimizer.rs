@@ -0,0 +1,537 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{
+    analyzer::{AttackAnalyzerConfig, ShipAnalyzerConfig},
+    comp::Comp,
+    factory::Factory,
+    gear_array::GearArray,
+    ship::Ship,
+    types::{AirWaveType, BattleDefinitions, FleetType, GearState, GearVecState},
+};
+
+use super::{
+    InventoryItem, LoadoutObjective, LoadoutOptimizer, LoadoutOptimizerConfig, create_gears,
+    group_inventory,
+};
+
+/// 制約の不足分がこの値以下であれば満たしているとみなす
+const VIOLATION_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct FleetLoadoutShipConfig {
+    pub fleet_type: FleetType,
+    pub index: usize,
+    pub objective: LoadoutObjective,
+    /// 目的の値に掛ける重み
+    pub weight: f64,
+    /// `left`が対象の艦、`right`が目標
+    pub ship_analyzer: ShipAnalyzerConfig,
+}
+
+impl Default for FleetLoadoutShipConfig {
+    fn default() -> Self {
+        Self {
+            fleet_type: Default::default(),
+            index: 0,
+            objective: Default::default(),
+            weight: 1.0,
+            ship_analyzer: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct FleetLoadoutConstraints {
+    /// マップ索敵の下限
+    pub min_elos: Option<f64>,
+    /// 分岐点係数
+    pub node_divaricated_factor: u8,
+    /// 制空値の下限
+    pub min_fighter_power: Option<i32>,
+    pub air_type: AirWaveType,
+    pub escort_participates: bool,
+    /// 輸送物資量(TP)の下限
+    pub min_transport_point: Option<i32>,
+}
+
+impl Default for FleetLoadoutConstraints {
+    fn default() -> Self {
+        Self {
+            min_elos: None,
+            node_divaricated_factor: 1,
+            min_fighter_power: None,
+            air_type: AirWaveType::Carrier,
+            escort_participates: false,
+            min_transport_point: None,
+        }
+    }
+}
+
+impl FleetLoadoutConstraints {
+    /// 各制約の下限に対する不足の割合の合計
+    fn violation(&self, comp: &Comp) -> f64 {
+        fn shortfall(min: Option<f64>, value: Option<f64>) -> f64 {
+            let Some(min) = min else {
+                return 0.0;
+            };
+
+            match value {
+                Some(value) => ((min - value) / min.abs().max(1.0)).max(0.0),
+                None => 1.0,
+            }
+        }
+
+        shortfall(self.min_elos, comp.elos(self.node_divaricated_factor))
+            + shortfall(
+                self.min_fighter_power.map(f64::from),
+                comp.fighter_power(self.escort_participates, self.air_type)
+                    .map(f64::from),
+            )
+            + shortfall(
+                self.min_transport_point.map(f64::from),
+                Some(comp.transport_point() as f64),
+            )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+#[tsify(from_wasm_abi)]
+pub struct FleetLoadoutOptimizerConfig {
+    /// 最適化する艦の設定、重みが0の艦は制約を満たすためにのみ装備を割り当てる
+    ///
+    /// 含まれない艦の装備は変更せず、所持装備からも除く
+    pub ships: Vec<FleetLoadoutShipConfig>,
+    pub constraints: FleetLoadoutConstraints,
+    /// 所持している装備、各装備は1隻にのみ割り当てる
+    pub inventory: Vec<GearState>,
+    /// 補強増設を使用するか
+    pub use_exslot: bool,
+    /// 各艦の初期解の探索で残す候補の数
+    pub beam_width: usize,
+    /// 局所探索の最大反復回数
+    pub max_iterations: usize,
+}
+
+impl Default for FleetLoadoutOptimizerConfig {
+    fn default() -> Self {
+        Self {
+            ships: Default::default(),
+            constraints: Default::default(),
+            inventory: Default::default(),
+            use_exslot: true,
+            beam_width: 10,
+            max_iterations: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct FleetLoadoutShip {
+    pub fleet_type: FleetType,
+    pub index: usize,
+    pub gears: GearVecState,
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct FleetLoadoutOptimizerResult {
+    pub ships: Vec<FleetLoadoutShip>,
+    /// 重み付きの目的の値の合計
+    pub score: f64,
+    pub elos: Option<f64>,
+    pub fighter_power: Option<i32>,
+    pub transport_point: i32,
+    /// 全ての制約を満たすか
+    pub feasible: bool,
+    /// 評価した装備の組み合わせの数
+    pub evaluated: usize,
+}
+
+struct Member {
+    fleet_type: FleetType,
+    index: usize,
+    /// 装備を外す前の艦
+    base: Ship,
+    /// 目的を持たない艦は`None`
+    weight: Option<f64>,
+    loadout_config: LoadoutOptimizerConfig,
+    analyzer_config: AttackAnalyzerConfig,
+    slots: [Option<usize>; GearArray::CAPACITY],
+    score: Option<f64>,
+}
+
+impl Member {
+    fn weighted_score(&self, score: Option<f64>) -> f64 {
+        self.weight.zip(score).map_or(0.0, |(w, s)| w * s)
+    }
+}
+
+/// 制約の不足の割合と目的の値
+#[derive(Debug, Clone, Copy)]
+struct Fitness {
+    violation: f64,
+    score: f64,
+}
+
+impl Fitness {
+    fn is_better_than(&self, other: &Self) -> bool {
+        if (self.violation - other.violation).abs() > VIOLATION_EPSILON {
+            self.violation < other.violation
+        } else {
+            self.score > other.score
+        }
+    }
+}
+
+/// 所持装備から装備中の装備を1つ除く
+///
+/// `id`が一致する装備を優先し、なければ改修値と熟練度が同じ装備を除く
+fn exclude_equipped(inventory: &mut Vec<GearState>, equipped: &GearState) {
+    let same_gear = |state: &GearState| {
+        state.gear_id == equipped.gear_id
+            && state.stars.unwrap_or_default() == equipped.stars.unwrap_or_default()
+            && state.exp.unwrap_or_default() == equipped.exp.unwrap_or_default()
+    };
+
+    let position = inventory
+        .iter()
+        .position(|state| equipped.id.is_some() && state.id == equipped.id)
+        .or_else(|| inventory.iter().position(same_gear));
+
+    if let Some(position) = position {
+        inventory.remove(position);
+    }
+}
+
+struct Move {
+    member: usize,
+    slot_index: usize,
+    item: Option<usize>,
+    ship: Ship,
+    score: Option<f64>,
+    fitness: Fitness,
+}
+
+/// 所持装備を艦隊全体に割り当てる
+///
+/// 目的を持つ艦から重みの大きい順に`LoadoutOptimizer`で初期解を求めた後、
+/// 1スロットずつ装備を入れ替える局所探索で制約の不足と目的の値を改善する
+pub struct FleetLoadoutOptimizer<'a> {
+    pub factory: &'a Factory,
+    pub battle_defs: &'a BattleDefinitions,
+    pub config: &'a FleetLoadoutOptimizerConfig,
+    pub comp: &'a Comp,
+    pub target: &'a Ship,
+}
+
+impl FleetLoadoutOptimizer<'_> {
+    fn members(&self) -> Vec<Member> {
+        self.comp
+            .members_by(FleetType::Main | FleetType::Escort)
+            .filter_map(|member| {
+                let fleet_type = member.position.fleet_type;
+                let index = member.position.index;
+
+                let ship_config = self
+                    .config
+                    .ships
+                    .iter()
+                    .find(|config| config.fleet_type == fleet_type && config.index == index)?;
+
+                let loadout_config = LoadoutOptimizerConfig {
+                    objective: ship_config.objective,
+                    ship_analyzer: ship_config.ship_analyzer.clone(),
+                    inventory: vec![],
+                    use_exslot: self.config.use_exslot,
+                    top_k: 1,
+                    beam_width: self.config.beam_width,
+                };
+
+                let analyzer_config = loadout_config
+                    .ship_analyzer
+                    .clone()
+                    .into_attack_analyzer_config(true);
+
+                Some(Member {
+                    fleet_type,
+                    index,
+                    base: member.ship.clone(),
+                    weight: Some(ship_config.weight).filter(|weight| *weight > 0.0),
+                    loadout_config,
+                    analyzer_config,
+                    slots: [None; GearArray::CAPACITY],
+                    score: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 最適化しない艦が装備している装備を除いた所持装備
+    fn available_inventory(&self, members: &[Member]) -> Vec<GearState> {
+        let mut inventory = self.config.inventory.clone();
+
+        self.comp
+            .all_members()
+            .filter(|member| {
+                !members.iter().any(|m| {
+                    m.fleet_type == member.position.fleet_type && m.index == member.position.index
+                })
+            })
+            .flat_map(|member| member.ship.state().gears.into_iter().flatten())
+            .for_each(|equipped| exclude_equipped(&mut inventory, &equipped));
+
+        inventory
+    }
+
+    fn loadout_optimizer<'b>(&'b self, member: &'b Member) -> LoadoutOptimizer<'b> {
+        LoadoutOptimizer {
+            factory: self.factory,
+            battle_defs: self.battle_defs,
+            config: &member.loadout_config,
+            ship: &member.base,
+            target: self.target,
+        }
+    }
+
+    /// 目的を持たない艦は解析せずに艦のみを作成する
+    fn evaluate(
+        &self,
+        inventory: &[InventoryItem],
+        member: &Member,
+        slots: &[Option<usize>; GearArray::CAPACITY],
+    ) -> Option<(Ship, Option<f64>)> {
+        let gears = create_gears(inventory, slots);
+
+        if member.weight.is_none() {
+            let mut state = member.base.state();
+            state.gears = gears;
+            return Some((self.factory.create_ship(Some(state))?, None));
+        }
+
+        let (ship, score, _) = self
+            .loadout_optimizer(member)
+            .evaluate_gears(&member.analyzer_config, gears)?;
+
+        Some((ship, score))
+    }
+
+    fn reserved(
+        inventory: &[InventoryItem],
+        members: &[Member],
+        exclude: Option<usize>,
+    ) -> Vec<usize> {
+        let mut reserved = vec![0; inventory.len()];
+
+        members
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != exclude)
+            .flat_map(|(_, member)| member.slots.iter().flatten())
+            .for_each(|&item_index| reserved[item_index] += 1);
+
+        reserved
+    }
+
+    fn set_ship(comp: &mut Comp, member: &Member, ship: Ship) -> Option<Ship> {
+        let slot = comp
+            .get_fleet_mut(member.fleet_type)?
+            .ships
+            .get_mut(member.index)?;
+
+        Some(std::mem::replace(slot, ship))
+    }
+
+    pub fn optimize(&self) -> FleetLoadoutOptimizerResult {
+        let mut members = self.members();
+        let inventory = group_inventory(self.factory, &self.available_inventory(&members));
+        let mut comp = self.comp.clone();
+        let mut evaluated = 0;
+
+        // 最適化する艦の装備を外した状態から始める
+        for member in members.iter_mut() {
+            if let Some((ship, score)) = self.evaluate(&inventory, member, &member.slots) {
+                member.score = score;
+                Self::set_ship(&mut comp, member, ship);
+            }
+            evaluated += 1;
+        }
+
+        let mut order = (0..members.len())
+            .filter(|&i| members[i].weight.is_some())
+            .collect::<Vec<_>>();
+
+        order.sort_by(|&a, &b| {
+            members[b]
+                .weight
+                .partial_cmp(&members[a].weight)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        for i in order {
+            let reserved = Self::reserved(&inventory, &members, Some(i));
+            let (beam, count) = self
+                .loadout_optimizer(&members[i])
+                .search(&inventory, &reserved);
+            evaluated += count;
+
+            let Some(best) = beam.into_iter().find(|partial| partial.score.is_some()) else {
+                continue;
+            };
+
+            members[i].slots = best.slots;
+
+            if let Some((ship, score)) = self.evaluate(&inventory, &members[i], &best.slots) {
+                members[i].score = score;
+                Self::set_ship(&mut comp, &members[i], ship);
+            }
+        }
+
+        let total_score = |members: &[Member]| -> f64 {
+            members
+                .iter()
+                .map(|member| member.weighted_score(member.score))
+                .sum()
+        };
+
+        let mut fitness = Fitness {
+            violation: self.config.constraints.violation(&comp),
+            score: total_score(&members),
+        };
+
+        for _ in 0..self.config.max_iterations {
+            let mut best: Option<Move> = None;
+            let reserved = Self::reserved(&inventory, &members, None);
+
+            for (member_index, member) in members.iter().enumerate() {
+                let optimizer = self.loadout_optimizer(member);
+                let base_score = fitness.score - member.weighted_score(member.score);
+
+                for slot_index in optimizer.slot_indexes() {
+                    let current = member.slots[slot_index];
+
+                    let candidates = std::iter::once(None).chain(
+                        inventory
+                            .iter()
+                            .enumerate()
+                            .filter(|(item_index, item)| {
+                                reserved[*item_index] < item.count
+                                    && optimizer.can_equip(item, slot_index)
+                            })
+                            .map(|(item_index, _)| Some(item_index)),
+                    );
+
+                    for item in candidates.filter(|item| *item != current) {
+                        let mut slots = member.slots;
+                        slots[slot_index] = item;
+
+                        let Some((ship, score)) = self.evaluate(&inventory, member, &slots) else {
+                            continue;
+                        };
+                        evaluated += 1;
+
+                        let Some(prev) = Self::set_ship(&mut comp, member, ship) else {
+                            continue;
+                        };
+
+                        let candidate_fitness = Fitness {
+                            violation: self.config.constraints.violation(&comp),
+                            score: base_score + member.weighted_score(score),
+                        };
+
+                        let ship = Self::set_ship(&mut comp, member, prev).unwrap_or_default();
+
+                        let improves = best.as_ref().map_or(fitness, |m| m.fitness);
+
+                        if candidate_fitness.is_better_than(&improves) {
+                            best = Some(Move {
+                                member: member_index,
+                                slot_index,
+                                item,
+                                ship,
+                                score,
+                                fitness: candidate_fitness,
+                            });
+                        }
+                    }
+                }
+            }
+
+            let Some(best) = best else {
+                break;
+            };
+
+            let member = &mut members[best.member];
+            member.slots[best.slot_index] = best.item;
+            member.score = best.score;
+            Self::set_ship(&mut comp, member, best.ship);
+            fitness = best.fitness;
+        }
+
+        let ships = members
+            .iter()
+            .map(|member| FleetLoadoutShip {
+                fleet_type: member.fleet_type,
+                index: member.index,
+                gears: create_gears(&inventory, &member.slots),
+                score: member.score,
+            })
+            .collect();
+
+        let constraints = &self.config.constraints;
+
+        FleetLoadoutOptimizerResult {
+            ships,
+            score: fitness.score,
+            elos: comp.elos(constraints.node_divaricated_factor),
+            fighter_power: comp
+                .fighter_power(constraints.escort_participates, constraints.air_type),
+            transport_point: comp.transport_point(),
+            feasible: fitness.violation <= VIOLATION_EPSILON,
+            evaluated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fitness() {
+        let fitness = |violation, score| Fitness { violation, score };
+
+        assert!(fitness(0.0, 10.0).is_better_than(&fitness(0.1, 100.0)));
+        assert!(fitness(0.1, 100.0).is_better_than(&fitness(0.1, 10.0)));
+        assert!(!fitness(0.1, 10.0).is_better_than(&fitness(0.1, 10.0)));
+    }
+
+    #[test]
+    fn test_exclude_equipped() {
+        let gear = |id: Option<&str>, gear_id: u16, stars: Option<u8>| GearState {
+            id: id.map(String::from),
+            gear_id,
+            stars,
+            ..Default::default()
+        };
+
+        let mut inventory = vec![
+            gear(Some("a"), 1, Some(10)),
+            gear(Some("b"), 1, None),
+            gear(Some("c"), 2, None),
+        ];
+
+        exclude_equipped(&mut inventory, &gear(None, 1, Some(0)));
+        assert_eq!(inventory.len(), 2);
+        assert_eq!(inventory[0].id.as_deref(), Some("a"));
+
+        exclude_equipped(&mut inventory, &gear(Some("c"), 2, None));
+        exclude_equipped(&mut inventory, &gear(None, 3, None));
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(inventory[0].id.as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,582 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{
+    analyzer::{
+        AttackAnalysis, AttackAnalyzer, AttackAnalyzerConfig, ShipAnalyzerConfig,
+        calc_ship_anti_air_cutin_chance,
+    },
+    factory::Factory,
+    gear::Gear,
+    gear_array::{GearArray, into_gear_key},
+    ship::Ship,
+    types::{BattleDefinitions, GearAttr, GearState, GearVecState},
+};
+
+/// 装備の最適化の目的
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+pub enum LoadoutObjective {
+    /// 昼戦の期待ダメージ
+    #[default]
+    DayDamage,
+    /// 夜戦の期待ダメージ
+    NightDamage,
+    /// 夜戦で目標を撃沈する確率
+    NightSinkRate,
+    /// 先制対潜が可能な装備のうち先制対潜の攻撃力
+    OpeningAsw,
+    /// 対空カットインの発動率
+    AntiAirCutinRate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+#[tsify(from_wasm_abi)]
+pub struct LoadoutOptimizerConfig {
+    pub objective: LoadoutObjective,
+    /// `left`が最適化する艦、`right`が目標
+    pub ship_analyzer: ShipAnalyzerConfig,
+    /// 所持している装備
+    pub inventory: Vec<GearState>,
+    /// 補強増設を使用するか
+    pub use_exslot: bool,
+    /// 返す候補の数
+    pub top_k: usize,
+    /// 探索で各スロットごとに残す候補の数
+    pub beam_width: usize,
+}
+
+impl Default for LoadoutOptimizerConfig {
+    fn default() -> Self {
+        Self {
+            objective: Default::default(),
+            ship_analyzer: Default::default(),
+            inventory: Default::default(),
+            use_exslot: true,
+            top_k: 5,
+            beam_width: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct LoadoutCandidate {
+    pub gears: GearVecState,
+    pub score: f64,
+    pub analysis: AttackAnalysis,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct LoadoutOptimizerResult {
+    pub candidates: Vec<LoadoutCandidate>,
+    /// 評価した装備の組み合わせの数
+    pub evaluated: usize,
+}
+
+/// 同じ装備、改修値、熟練度をまとめた所持装備
+pub(crate) struct InventoryItem {
+    pub state: GearState,
+    pub gear: Gear,
+    pub count: usize,
+}
+
+/// `slots`は`InventoryItem`の添字
+pub(crate) fn create_gears(
+    inventory: &[InventoryItem],
+    slots: &[Option<usize>; GearArray::CAPACITY],
+) -> GearVecState {
+    slots
+        .map(|slot| slot.map(|index| inventory[index].state.clone()))
+        .into()
+}
+
+pub(crate) fn group_inventory(factory: &Factory, states: &[GearState]) -> Vec<InventoryItem> {
+    let mut items: Vec<InventoryItem> = vec![];
+
+    states.iter().for_each(|state| {
+        let same = items.iter_mut().find(|item| {
+            item.state.gear_id == state.gear_id
+                && item.state.stars == state.stars
+                && item.state.exp == state.exp
+        });
+
+        if let Some(item) = same {
+            item.count += 1;
+        } else if let Some(gear) = factory.create_gear(Some(state.clone())) {
+            items.push(InventoryItem {
+                state: GearState {
+                    id: None,
+                    ..state.clone()
+                },
+                gear,
+                count: 1,
+            });
+        }
+    });
+
+    items
+}
+
+#[derive(Clone)]
+pub(crate) struct PartialLoadout {
+    /// `InventoryItem`の添字
+    pub slots: [Option<usize>; GearArray::CAPACITY],
+    pub score: Option<f64>,
+    /// 目的の値が同じか求まらない組み合わせの順位付けに使う値
+    pub progress: f64,
+    pub analysis: Option<AttackAnalysis>,
+}
+
+impl PartialLoadout {
+    fn cmp_desc(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .score
+            .partial_cmp(&self.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| other.progress.total_cmp(&self.progress))
+    }
+
+    /// スロットの順番だけが異なる組み合わせを同一視するための値
+    ///
+    /// 航空機は搭載数によって性能が変わるため、スロットの位置を区別する
+    fn key(&self, inventory: &[InventoryItem]) -> PartialLoadoutKey {
+        let mut unordered = vec![];
+        let mut ordered = vec![];
+
+        self.slots[..GearArray::EXSLOT_INDEX]
+            .iter()
+            .enumerate()
+            .for_each(|(slot_index, slot)| {
+                let Some(item_index) = *slot else {
+                    return;
+                };
+
+                let gear = &inventory[item_index].gear;
+
+                if gear.has_proficiency() || gear.has_attr(GearAttr::AntiSubAircraft) {
+                    ordered.push((slot_index, item_index));
+                } else {
+                    unordered.push(item_index);
+                }
+            });
+
+        unordered.sort_unstable();
+
+        (unordered, ordered, self.slots[GearArray::EXSLOT_INDEX])
+    }
+}
+
+type PartialLoadoutKey = (Vec<usize>, Vec<(usize, usize)>, Option<usize>);
+
+/// 所持装備から目的の値が大きくなる装備の組み合わせを探索する
+///
+/// スロットごとに上位`beam_width`件の組み合わせのみを残すビームサーチのため、最適解とは限らない
+pub struct LoadoutOptimizer<'a> {
+    pub factory: &'a Factory,
+    pub battle_defs: &'a BattleDefinitions,
+    pub config: &'a LoadoutOptimizerConfig,
+    pub ship: &'a Ship,
+    pub target: &'a Ship,
+}
+
+impl LoadoutOptimizer<'_> {
+    pub(crate) fn slot_indexes(&self) -> Vec<usize> {
+        let mut indexes =
+            (0..self.ship.master.slotnum.min(GearArray::EXSLOT_INDEX)).collect::<Vec<_>>();
+
+        if self.config.use_exslot {
+            indexes.push(GearArray::EXSLOT_INDEX);
+        }
+
+        indexes
+    }
+
+    pub(crate) fn attack_analyzer_config(&self) -> AttackAnalyzerConfig {
+        self.config
+            .ship_analyzer
+            .clone()
+            .into_attack_analyzer_config(true)
+    }
+
+    fn evaluate(
+        &self,
+        inventory: &[InventoryItem],
+        analyzer_config: &AttackAnalyzerConfig,
+        slots: [Option<usize>; GearArray::CAPACITY],
+    ) -> PartialLoadout {
+        match self.evaluate_gears(analyzer_config, create_gears(inventory, &slots)) {
+            Some((ship, score, analysis)) => PartialLoadout {
+                slots,
+                score,
+                progress: self.progress(&ship),
+                analysis: Some(analysis),
+            },
+            None => PartialLoadout {
+                slots,
+                score: None,
+                progress: 0.0,
+                analysis: None,
+            },
+        }
+    }
+
+    /// 装備を載せ替えた艦と目的の値を返す
+    pub(crate) fn evaluate_gears(
+        &self,
+        analyzer_config: &AttackAnalyzerConfig,
+        gears: GearVecState,
+    ) -> Option<(Ship, Option<f64>, AttackAnalysis)> {
+        let mut state = self.ship.state();
+        state.gears = gears;
+
+        let ship = self.factory.create_ship(Some(state))?;
+
+        let analysis = AttackAnalyzer {
+            battle_defs: self.battle_defs,
+            config: analyzer_config.clone(),
+            attacker: &ship,
+            target: self.target,
+        }
+        .analyze();
+
+        let score = self.score(&ship, &analysis);
+
+        Some((ship, score, analysis))
+    }
+
+    fn score(&self, ship: &Ship, analysis: &AttackAnalysis) -> Option<f64> {
+        match self.config.objective {
+            LoadoutObjective::DayDamage => analysis.day.expected_damage(),
+            LoadoutObjective::NightDamage => analysis.night.expected_damage(),
+            LoadoutObjective::NightSinkRate => analysis.night.sunk_rate(),
+            LoadoutObjective::OpeningAsw => {
+                if !ship.can_do_opening_asw() {
+                    return None;
+                }
+
+                analysis
+                    .opening_asw
+                    .data
+                    .values()
+                    .filter_map(|report| Some(report.attack_power.as_ref()?.normal))
+                    .reduce(f64::max)
+            }
+            LoadoutObjective::AntiAirCutinRate => Some(
+                calc_ship_anti_air_cutin_chance(self.battle_defs, ship)
+                    .into_iter()
+                    .map(|(_, rate)| rate)
+                    .sum(),
+            ),
+        }
+    }
+
+    /// 途中の組み合わせで目的の値が求まらない場合に、目的に近いものを残すための値
+    ///
+    /// 先制対潜は条件を満たすまで評価できないため、対潜値の大きいものを優先する
+    fn progress(&self, ship: &Ship) -> f64 {
+        match self.config.objective {
+            LoadoutObjective::OpeningAsw => ship.asw().unwrap_or_default() as f64,
+            _ => 0.0,
+        }
+    }
+
+    pub(crate) fn can_equip(&self, item: &InventoryItem, index: usize) -> bool {
+        into_gear_key(index).is_some_and(|key| self.ship.can_equip(&item.gear, key))
+    }
+
+    /// 評価の高い順に並べた組み合わせと評価した数を返す
+    ///
+    /// `reserved`は他で使用している`InventoryItem`ごとの数
+    pub(crate) fn search(
+        &self,
+        inventory: &[InventoryItem],
+        reserved: &[usize],
+    ) -> (Vec<PartialLoadout>, usize) {
+        let analyzer_config = self.attack_analyzer_config();
+        let beam_width = self.config.beam_width.max(1);
+
+        let mut evaluated = 1;
+        let mut beam =
+            vec![self.evaluate(inventory, &analyzer_config, [None; GearArray::CAPACITY])];
+
+        for slot_index in self.slot_indexes() {
+            let mut next = beam.clone();
+
+            for partial in beam.iter() {
+                for (item_index, item) in inventory.iter().enumerate() {
+                    let used = partial
+                        .slots
+                        .iter()
+                        .filter(|slot| **slot == Some(item_index))
+                        .count();
+
+                    if used + reserved[item_index] >= item.count
+                        || !self.can_equip(item, slot_index)
+                    {
+                        continue;
+                    }
+
+                    let mut slots = partial.slots;
+                    slots[slot_index] = Some(item_index);
+
+                    next.push(self.evaluate(inventory, &analyzer_config, slots));
+                    evaluated += 1;
+                }
+            }
+
+            next.sort_by(PartialLoadout::cmp_desc);
+
+            let mut seen = HashSet::new();
+            next.retain(|partial| seen.insert(partial.key(inventory)));
+            next.truncate(beam_width);
+            beam = next;
+        }
+
+        (beam, evaluated)
+    }
+
+    pub fn optimize(&self) -> LoadoutOptimizerResult {
+        let inventory = group_inventory(self.factory, &self.config.inventory);
+        let (beam, evaluated) = self.search(&inventory, &vec![0; inventory.len()]);
+
+        let candidates = beam
+            .into_iter()
+            .filter_map(|partial| {
+                Some(LoadoutCandidate {
+                    gears: create_gears(&inventory, &partial.slots),
+                    score: partial.score?,
+                    analysis: partial.analysis?,
+                })
+            })
+            .take(self.config.top_k)
+            .collect();
+
+        LoadoutOptimizerResult {
+            candidates,
+            evaluated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        analyzer::AttackAnalyzerShipConfig,
+        master_data::{MasterData, MasterEBonuses, MasterEquippability, MstStype},
+        types::Formation,
+    };
+
+    fn master_data() -> MasterData {
+        let ship = |ship_id: u16| {
+            serde_json::from_value(json!({
+                "ship_id": ship_id,
+                "name": "",
+                "yomi": "",
+                "stype": 2,
+                "ctype": 0,
+                "max_hp": [30, 30],
+                "firepower": [10, 30],
+                "armor": [10, 10],
+                "torpedo": [0, 0],
+                "evasion": [40, 80],
+                "anti_air": [0, 0],
+                "asw": [0, 0],
+                "los": [0, 0],
+                "luck": [10, 50],
+                "speed": 10,
+                "range": 1,
+                "slotnum": 2,
+                "slots": [0, 0],
+                "stock": [],
+            }))
+            .unwrap()
+        };
+
+        // 小口径主砲と大口径主砲
+        let gear = |gear_id: u16, gear_type: u8, firepower: i16| {
+            serde_json::from_value(json!({
+                "gear_id": gear_id,
+                "name": "",
+                "types": [1, 1, gear_type, 1, 0],
+                "firepower": firepower,
+            }))
+            .unwrap()
+        };
+
+        MasterData {
+            gears: vec![gear(1, 1, 5), gear(2, 1, 2), gear(3, 3, 20)],
+            ships: vec![ship(1), ship(1501)],
+            ebonuses: Some(MasterEBonuses::default()),
+            equippability: MasterEquippability {
+                equip_stype: vec![MstStype {
+                    api_id: 2,
+                    api_equip_type: [(1, 1), (3, 0)].into_iter().collect(),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_partial_loadout_order() {
+        let partial = |score: Option<f64>, progress: f64| PartialLoadout {
+            slots: [None; GearArray::CAPACITY],
+            score,
+            progress,
+            analysis: None,
+        };
+
+        let mut partials = [
+            partial(None, 10.0),
+            partial(Some(1.0), 0.0),
+            partial(None, 30.0),
+            partial(Some(2.0), 0.0),
+        ];
+        partials.sort_by(PartialLoadout::cmp_desc);
+
+        // 目的の値が求まらない組み合わせは対潜値などの進捗が大きい順に残す
+        assert_eq!(
+            partials
+                .iter()
+                .map(|partial| (partial.score, partial.progress))
+                .collect::<Vec<_>>(),
+            [
+                (Some(2.0), 0.0),
+                (Some(1.0), 0.0),
+                (None, 30.0),
+                (None, 10.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loadout_optimizer() {
+        let factory = Factory::new(master_data());
+        let mut battle_defs = BattleDefinitions::default();
+        battle_defs
+            .formation
+            .insert(Formation::LINE_AHEAD, Default::default());
+
+        let ship = factory.create_ship_by_id(1).unwrap();
+        let target = factory.create_ship_by_id(1501).unwrap();
+
+        let gear = |gear_id: u16| GearState {
+            gear_id,
+            ..Default::default()
+        };
+        let config = LoadoutOptimizerConfig {
+            ship_analyzer: ShipAnalyzerConfig {
+                right: AttackAnalyzerShipConfig::dummy_enemy(),
+                ..Default::default()
+            },
+            inventory: vec![gear(1), gear(2), gear(3), gear(3)],
+            use_exslot: false,
+            top_k: 3,
+            ..Default::default()
+        };
+
+        let result = LoadoutOptimizer {
+            factory: &factory,
+            battle_defs: &battle_defs,
+            config: &config,
+            ship: &ship,
+            target: &target,
+        }
+        .optimize();
+
+        let candidates = &result.candidates;
+        assert_eq!(candidates.len(), 3);
+        assert!(
+            candidates
+                .windows(2)
+                .all(|pair| pair[0].score >= pair[1].score)
+        );
+
+        // 装備できない大口径主砲は選ばれない
+        assert!(candidates.iter().all(|candidate| {
+            candidate
+                .gears
+                .iter()
+                .flatten()
+                .all(|state| state.gear_id != 3)
+        }));
+
+        let gear_ids = |candidate: &LoadoutCandidate| {
+            let mut ids = candidate
+                .gears
+                .iter()
+                .flatten()
+                .map(|state| state.gear_id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+        assert_eq!(gear_ids(&candidates[0]), [1, 2]);
+        assert!(candidates[0].score > candidates[2].score);
+    }
+
+    #[test]
+    fn test_loadout_optimizer_dedupe() {
+        let factory = Factory::new(master_data());
+        let mut battle_defs = BattleDefinitions::default();
+        battle_defs
+            .formation
+            .insert(Formation::LINE_AHEAD, Default::default());
+
+        let ship = factory.create_ship_by_id(1).unwrap();
+        let target = factory.create_ship_by_id(1501).unwrap();
+
+        let gear = |gear_id: u16| GearState {
+            gear_id,
+            ..Default::default()
+        };
+        let config = LoadoutOptimizerConfig {
+            ship_analyzer: ShipAnalyzerConfig {
+                right: AttackAnalyzerShipConfig::dummy_enemy(),
+                ..Default::default()
+            },
+            inventory: vec![gear(1), gear(1), gear(2), gear(2)],
+            use_exslot: false,
+            top_k: 10,
+            ..Default::default()
+        };
+
+        let result = LoadoutOptimizer {
+            factory: &factory,
+            battle_defs: &battle_defs,
+            config: &config,
+            ship: &ship,
+            target: &target,
+        }
+        .optimize();
+
+        let mut gear_sets = result
+            .candidates
+            .iter()
+            .map(|candidate| {
+                let mut ids = candidate
+                    .gears
+                    .iter()
+                    .flatten()
+                    .map(|state| state.gear_id)
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids
+            })
+            .collect::<Vec<_>>();
+
+        // スロットの順番だけが異なる組み合わせは1つにまとめる
+        // [1, 1], [1, 2], [2, 2], [1], [2], []
+        assert_eq!(gear_sets.len(), 6);
+        gear_sets.sort();
+        gear_sets.dedup();
+        assert_eq!(gear_sets.len(), 6);
+    }
+}
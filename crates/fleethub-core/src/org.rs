@@ -8,7 +8,7 @@ use crate::{
     comp::Comp,
     fleet::Fleet,
     ship::Ship,
-    types::{FleetKey, FleetType, GearAttr, OrgType, ShipKey, Side},
+    types::{FleetKey, FleetType, OrgType, ShipKey, Side},
 };
 
 #[wasm_bindgen]
@@ -233,21 +233,7 @@ impl Org {
             .filter(|air_squadron| air_squadron.mode.is_air_defense())
             .collect::<Vec<_>>();
 
-        let count = as_vec
-            .iter()
-            .map(|air_squadron| {
-                air_squadron
-                    .gears
-                    .count_attr(GearAttr::HighAltitudeInterceptor)
-            })
-            .sum::<usize>();
-
-        let modifier = match count {
-            0 => 0.5,
-            1 => 0.8,
-            2 => 1.1,
-            _ => 1.2,
-        };
+        let modifier = AirSquadron::high_altitude_interception_mod(as_vec.iter().copied());
 
         let interception_power = as_vec
             .iter()
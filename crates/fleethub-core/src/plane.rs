@@ -234,6 +234,15 @@ impl<T: PlaneImpl> FromIterator<T> for PlaneVec<T> {
     }
 }
 
+impl<T: PlaneImpl> IntoIterator for PlaneVec<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+
 impl<T: PlaneImpl> PlaneVec<T> {
     pub fn iter(&self) -> std::slice::Iter<'_, T> {
         self.vec.iter()
@@ -16,15 +16,19 @@ use crate::{
     master_data::{MasterShip, ShipEquippability},
     plane::{Plane, PlaneImpl, PlaneMut},
     types::{
-        AirStateRank, AirWaveType, AswAttackType, AswPhase, CustomPowerModifiers, DamageState,
-        DayPhaseAttackType, DefensePower, EBonuses, GearAttr, GearType, MoraleState,
+        AirStateRank, AirWaveType, AswAttackType, AswPhase, CustomPowerModifiers, DamageControl,
+        DamageState, DayPhaseAttackType, DefensePower, EBonuses, GearAttr, GearType, MoraleState,
         NightAttackType, NightPhaseAttackType, ShellingType, ShipAttr, ShipCategory, ShipMeta,
         ShipState, ShipType, Side, SlotSizeVec, SpecialEnemyType, ctype, gear_id, matches_gear_id,
         matches_ship_id, ship_id,
     },
 };
 
+pub use gunfit_accuracy::{gunfit_accuracy, gunfit_accuracy_info, reference_gunfit_accuracy_defs};
 pub use night_cutin::NightCutinTermParams;
+pub use special_enemy_modifiers::{
+    land_base_special_enemy_modifiers, reference_special_enemy_mod_defs, special_enemy_modifiers,
+};
 
 #[wasm_bindgen]
 #[derive(Debug, Default, Clone)]
@@ -63,6 +67,13 @@ pub struct Ship {
     pub master: MasterShip,
     #[wasm_bindgen(skip)]
     pub equippability: ShipEquippability,
+
+    /// 大破進撃した随伴艦など、轟沈を防ぐ仕組みが働かない
+    #[wasm_bindgen(skip)]
+    pub sinkable: bool,
+    /// 出撃中に使用した応急修理の数
+    #[wasm_bindgen(readonly)]
+    pub used_damage_controls: u8,
 }
 
 fn get_marriage_bonus(left: u16) -> u16 {
@@ -183,6 +194,27 @@ fn nisshin_max_slot_size(master: &MasterShip, gears: &GearArray, index: usize) -
     }
 }
 
+fn init_slots(state: &ShipState, master: &MasterShip, gears: &GearArray) -> SlotSizeVec {
+    let is_nisshin = master.ctype == ctype!("日進型");
+
+    state
+        .slots
+        .clone()
+        .into_iter()
+        .enumerate()
+        .map(|(index, slot_size)| {
+            slot_size.or_else(|| {
+                if is_nisshin {
+                    nisshin_max_slot_size(master, gears, index)
+                } else {
+                    master.get_max_slot_size(index)
+                }
+            })
+        })
+        .collect::<SlotSizeVec>()
+        .with_slotnum(master.slotnum)
+}
+
 impl PartialEq for Ship {
     fn eq(&self, other: &Self) -> bool {
         self.state.id.is_some() && self.state.id == other.state.id
@@ -207,26 +239,7 @@ impl Ship {
         ebonuses: EBonuses,
     ) -> Self {
         let ctype = master.ctype;
-        let is_nisshin = ctype == ctype!("日進型");
-
-        let slotnum = master.slotnum;
-
-        let slots = state
-            .slots
-            .clone()
-            .into_iter()
-            .enumerate()
-            .map(|(index, slot_size)| {
-                slot_size.or_else(|| {
-                    if is_nisshin {
-                        nisshin_max_slot_size(master, &gears, index)
-                    } else {
-                        master.get_max_slot_size(index)
-                    }
-                })
-            })
-            .collect::<SlotSizeVec>()
-            .with_slotnum(slotnum);
+        let slots = init_slots(&state, master, &gears);
 
         let mut ship = Ship {
             id: state.id.clone().unwrap_or_default(),
@@ -248,6 +261,9 @@ impl Ship {
             equippability,
             master: master.clone(),
             state,
+
+            sinkable: false,
+            used_damage_controls: 0,
         };
 
         if ship.current_hp == 0 {
@@ -257,6 +273,21 @@ impl Ship {
         ship
     }
 
+    /// 耐久と搭載数を初期状態に戻す
+    pub fn reset_battle_state(&mut self) {
+        self.current_hp = self
+            .state
+            .current_hp
+            .filter(|&hp| hp > 0)
+            .or_else(|| self.max_hp())
+            .unwrap_or_default();
+        self.ammo = self.state.ammo.unwrap_or(self.master.ammo);
+        self.fuel = self.state.fuel.unwrap_or(self.master.fuel);
+        self.slots = init_slots(&self.state, &self.master, &self.gears);
+        self.sinkable = false;
+        self.used_damage_controls = 0;
+    }
+
     pub fn planes(&self) -> impl Iterator<Item = Plane<'_>> {
         self.gears
             .0
@@ -698,6 +729,37 @@ impl Ship {
         threshold <= asw
     }
 
+    /// 開幕雷撃
+    ///
+    /// 深海棲艦の潜水艦は潜水カ級(無印)を除いて開幕雷撃を行う
+    ///
+    /// 出典: 艦これ攻略wiki「戦闘について」 https://wikiwiki.jp/kancolle/戦闘について
+    pub fn can_do_opening_torpedo(&self) -> bool {
+        if self.damage_state() >= DamageState::Taiha {
+            return false;
+        }
+
+        if self.gears.has_type(GearType::MidgetSubmarine) {
+            return true;
+        }
+
+        if !self.is_submarine() {
+            return false;
+        }
+
+        if self.is_abyssal() {
+            self.ctype != ctype!("潜水カ級")
+                || matches!(self.master.yomi.as_str(), "elite" | "flagship")
+        } else {
+            self.level >= 10 || self.gears.has_type(GearType::SubmarineTorpedo)
+        }
+    }
+
+    /// 中破以上または素の雷装が0の艦は雷撃戦に参加しない
+    pub fn can_do_closing_torpedo(&self) -> bool {
+        self.damage_state() < DamageState::Chuuha && self.naked_torpedo().unwrap_or_default() > 0
+    }
+
     pub fn asw_synergy_mod(&self) -> f64 {
         let gears = &self.gears;
 
@@ -1348,8 +1410,46 @@ impl Ship {
 }
 
 impl Ship {
+    /// 消耗品枠、補強増設、通常スロットの順に使用する応急修理
+    pub fn damage_controls(&self) -> impl Iterator<Item = DamageControl> + '_ {
+        let gears = self
+            .gears
+            .get(GearArray::EXSLOT_INDEX)
+            .into_iter()
+            .chain(self.gears.without_ex().map(|(_, gear)| gear))
+            .filter_map(|gear| DamageControl::from_gear_id(gear.gear_id));
+
+        self.state.damage_control.into_iter().chain(gears)
+    }
+
+    /// 未使用の応急修理
+    pub fn available_damage_control(&self) -> Option<DamageControl> {
+        self.damage_controls().nth(self.used_damage_controls.into())
+    }
+
     pub fn take_damage(&mut self, value: u16) {
         self.current_hp = self.current_hp.saturating_sub(value);
+
+        if self.current_hp == 0 {
+            self.use_damage_control();
+        }
+    }
+
+    fn use_damage_control(&mut self) {
+        let Some(damage_control) = self.available_damage_control() else {
+            return;
+        };
+        let Some(max_hp) = self.max_hp() else {
+            return;
+        };
+
+        self.used_damage_controls += 1;
+        self.current_hp = damage_control.revived_hp(max_hp);
+
+        if damage_control.restores_supplies() {
+            self.fuel = self.max_fuel();
+            self.ammo = self.max_ammo();
+        }
     }
 
     pub fn set_damage_state(&mut self, damage_state: DamageState) {
@@ -1397,6 +1497,31 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_damage_control() {
+        let mut ship = Ship {
+            level: 99,
+            current_hp: 10,
+            state: ShipState {
+                damage_control: Some(DamageControl::Personnel),
+                ..Default::default()
+            },
+            master: MasterShip {
+                max_hp: StatInterval(Some(40), None),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        ship.take_damage(10);
+        assert_eq!(ship.current_hp, 8);
+        assert_eq!(ship.used_damage_controls, 1);
+        assert_eq!(ship.available_damage_control(), None);
+
+        ship.take_damage(10);
+        assert_eq!(ship.current_hp, 0);
+    }
+
     #[test]
     fn test_stats() {
         macro_rules! def_stats_test {
@@ -1,6 +1,14 @@
+use std::{borrow::Borrow, sync::LazyLock};
+
 use wasm_bindgen::prelude::*;
 
-use crate::types::{GearType, ShipType, ctype, gear_id, matches_gear_id, ship_id};
+use crate::{
+    gear::Gear,
+    types::{
+        GearType, GunfitAccuracyDef, GunfitAccuracyInfo, GunfitGearContribution, GunfitRuleDef,
+        GunfitScale, ShipType, ctype, gear_id, ship_id,
+    },
+};
 
 use super::Ship;
 
@@ -16,536 +24,886 @@ impl Ship {
         self.state.night_gunfit_accuracy.map(|v| v.into())
     }
 
-    /// フィット砲補正
-    pub fn gunfit_accuracy(&self, is_night: bool) -> f64 {
-        let state = if is_night {
+    fn state_gunfit_accuracy(&self, is_night: bool) -> Option<f64> {
+        if is_night {
             self.state_night_gunfit_accuracy()
         } else {
             self.state_day_gunfit_accuracy()
-        };
-
-        state.unwrap_or_else(|| match self.ship_type {
-            ShipType::DD => destroyer_bonus(self),
-            ShipType::CL | ShipType::CLT | ShipType::CT => light_cruiser_bonus(self),
-            ShipType::CA | ShipType::CAV => heavy_cruiser_bonus(self, is_night),
-            ShipType::FBB | ShipType::BB | ShipType::BBV => battleship_bonus(self, is_night),
-            ShipType::AV => seaplane_tender_bonus(self),
-            _ => 0.0,
-        })
+        }
     }
 }
 
-/// 駆逐フィット補正
-fn destroyer_bonus(ship: &Ship) -> f64 {
-    let mut r = 0.0;
-    let gears = &ship.gears;
-    let ctype = ship.ctype;
-    let ship_id = ship.ship_id;
-
-    let count_130mm_twin_group = gears.count(gear_id!("130mm B-13連装砲")) as f64;
-    let count_5inch_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "5inch単装砲 Mk.30" | "5inch単装砲 Mk.30改" | "5inch単装砲 Mk.30改+GFCS Mk.37"
-        )
-    }) as f64;
-    let count_qf_group = gears.count(gear_id!("QF 4.7inch砲 Mk.XII改")) as f64;
-    let count_asdic_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "Type124 ASDIC" | "Type144/147 ASDIC" | "HF/DF + Type144/147 ASDIC"
-        )
-    }) as f64;
-    let count_12cm_single_group = gears.count(gear_id!("12cm単装砲改二")) as f64;
-
-    match ctype {
-        ctype!("Ташкент級") => {
-            r += 5.0 * count_130mm_twin_group.sqrt();
-        }
-        ctype!("Fletcher級") => {
-            r += 4.0 * count_5inch_group.sqrt();
-        }
-        ctype!("John C.Butler級") => {
-            r += 4.0 * count_5inch_group.sqrt();
-        }
-        ctype!("J級") => {
-            r += 3.0 * count_qf_group.sqrt();
-            r += 3.0 * count_asdic_group.sqrt();
-        }
-        ctype!("睦月型") => {
-            r += 5.0 * count_12cm_single_group.sqrt();
-        }
-        _ => {}
-    };
-
-    if ship_id == ship_id!("Верный") {
-        r += 5.0 * count_130mm_twin_group.sqrt();
+impl GunfitAccuracyDef {
+    fn is_target(&self, ship: &Ship) -> bool {
+        (self.ship_types.is_empty() || self.ship_types.contains(&ship.ship_type))
+            && (self.ctypes.is_empty() || self.ctypes.contains(&ship.ctype))
+            && (self.ship_ids.is_empty() || self.ship_ids.contains(&ship.ship_id))
+            && !self.exclude_ctypes.contains(&ship.ctype)
+            && !self.exclude_ship_ids.contains(&ship.ship_id)
+            && self
+                .abyssal
+                .is_none_or(|abyssal| abyssal == ship.is_abyssal())
     }
-    r
 }
 
-/// 軽巡フィット補正
-fn light_cruiser_bonus(ship: &Ship) -> f64 {
-    if ship.is_abyssal() {
-        return -3.0 * ship.gears.count_type(GearType::MediumMainGun) as f64;
+impl GunfitRuleDef {
+    fn matches_gear(&self, gear: &Gear) -> bool {
+        self.gear_ids.contains(&gear.gear_id) || self.gear_types.contains(&gear.gear_type)
     }
 
-    let mut r = 0.0;
-    let gears = &ship.gears;
-    let ctype = ship.ctype;
-
-    let is_married = ship.level > 99;
-    let m1 = if is_married { 2.0 } else { 0.0 };
-    let m2 = if is_married { 0.6 } else { 1.0 };
-    let m3 = if is_married { 0.8 } else { 1.0 };
-
-    let count_14cm_group = gears.count_by(|gear| {
-        matches_gear_id!(gear.gear_id, "14cm単装砲" | "14cm連装砲" | "14cm連装砲改")
-    }) as f64;
-
-    let count_15_2cm_twin_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "15.2cm連装砲"
-                | "15.2cm連装砲改"
-                | "15.2cm連装砲改二"
-                | "Bofors 15.2cm連装砲 Model 1930"
-                | "Bofors 15cm連装速射砲 Mk.9 Model 1938"
-                | "Bofors 15cm連装速射砲 Mk.9改+単装速射砲 Mk.10改 Model 1938"
-                | "6inch 連装速射砲 Mk.XXI"
-        )
-    }) as f64;
-
-    let count_15_2cm_triple_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "6inch三連装速射砲 Mk.16"
-                | "6inch三連装速射砲 Mk.16 mod.2"
-                | "6inch Mk.XXIII三連装砲"
-                | "152mm/55 三連装速射砲"
-                | "152mm/55 三連装速射砲改"
-        )
-    }) as f64;
-
-    let count_15_5cm_group = gears
-        .count_by(|gear| matches_gear_id!(gear.gear_id, "15.5cm三連装砲" | "15.5cm三連装砲改"))
-        as f64;
-
-    let count_5inch_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "5inch連装両用砲(集中配備)" | "5inch連装砲(副砲配置) 集中配備"
-        )
-    }) as f64;
-
-    let count_20_3cm_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "20.3cm連装砲"
-                | "20.3cm(3号)連装砲"
-                | "20.3cm(2号)連装砲"
-                | "試製20.3cm(4号)連装砲"
-                | "203mm/53 連装砲"
-                | "SKC34 20.3cm連装砲"
-                | "18cm/57 三連装主砲"
-        )
-    }) as f64;
-
-    let count_8inch_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "8inch三連装砲 Mk.9" | "8inch三連装砲 Mk.9 mod.2"
-        )
-    }) as f64;
-
-    match ctype {
-        ctype!("阿賀野型") => {
-            if count_14cm_group >= 1.0 {
-                r += m1
-            };
-            r += 3.0 * count_15_2cm_twin_group.sqrt();
-            if count_15_2cm_twin_group >= 1.0 {
-                r += m1
-            };
-            r += -2.0 * count_15_5cm_group.sqrt();
-            if count_15_5cm_group >= 1.0 {
-                r += m1
-            };
-            r += m2 * -3.0 * count_15_2cm_triple_group.sqrt();
-            r += m2 * -3.0 * count_5inch_group.sqrt();
-            r += m2 * -4.0 * count_20_3cm_group.sqrt();
-            r += m3 * -11.0 * count_8inch_group.sqrt();
+    /// 規則が適用されない場合は`None`
+    fn calc(&self, ship: &Ship, is_night: bool) -> Option<f64> {
+        if self.night.is_some_and(|night| night != is_night) {
+            return None;
         }
 
-        ctype!("大淀型") => {
-            if count_14cm_group >= 1.0 {
-                r += m1
-            };
-            if count_15_2cm_twin_group >= 1.0 {
-                r += m1
-            };
-            r += m2 * -1.0 * count_15_5cm_group.sqrt();
-            r += m2 * -3.0 * count_15_2cm_triple_group.sqrt();
-            r += m2 * -3.0 * count_5inch_group.sqrt();
-            r += m2 * -5.0 * count_20_3cm_group.sqrt();
-            r += m3 * -11.0 * count_8inch_group.sqrt();
-        }
+        let gears = &ship.gears;
 
-        ctype!("Atlanta級") => {
-            if count_14cm_group >= 1.0 {
-                r += m1
-            };
-            if count_15_2cm_twin_group >= 1.0 {
-                r += m1
-            };
-            r += -2.0 * count_15_5cm_group.sqrt();
-            if count_15_5cm_group >= 1.0 {
-                r += m1
-            };
-            r += m2 * -3.0 * count_15_2cm_triple_group.sqrt();
-            if count_5inch_group >= 1.0 {
-                r += m1 * 1.5
-            };
-            r += m2 * -4.0 * count_20_3cm_group.sqrt();
-            r += m3 * -11.0 * count_8inch_group.sqrt();
+        if gears.has_by(|gear| self.unless_gear_ids.contains(&gear.gear_id)) {
+            return None;
         }
-        _ => {
-            if count_14cm_group >= 1.0 {
-                r += m1
-            };
-            if count_15_2cm_twin_group >= 1.0 {
-                r += m1
-            };
-            r += -2.0 * count_15_5cm_group.sqrt();
-            if count_15_5cm_group >= 1.0 {
-                r += m1
-            };
-            r += m2 * -3.0 * count_15_2cm_triple_group.sqrt();
-            r += m2 * -3.0 * count_5inch_group.sqrt();
-            r += m2 * -3.0 * count_20_3cm_group.sqrt();
-            r += m3 * -11.0 * count_8inch_group.sqrt();
+
+        let count = gears.count_by(|gear| self.matches_gear(gear));
+
+        if count == 0 {
+            return None;
         }
+
+        let is_married = ship.level > 99;
+        let value = if is_married {
+            self.married_value.unwrap_or(self.value)
+        } else {
+            self.value
+        };
+
+        let result = match self.scale {
+            GunfitScale::Sqrt => value * (count as f64).sqrt(),
+            GunfitScale::Linear => value * count as f64,
+            GunfitScale::Any => value,
+        };
+
+        Some(result)
     }
+}
 
-    r
+fn matched_rules<'a>(
+    defs: &'a [impl Borrow<GunfitAccuracyDef>],
+    ship: &'a Ship,
+) -> impl Iterator<Item = &'a GunfitRuleDef> {
+    defs.iter()
+        .map(Borrow::borrow)
+        .filter(|def| def.is_target(ship))
+        .flat_map(|def| def.rules.iter())
 }
 
-/// 重巡フィット補正
-fn heavy_cruiser_bonus(ship: &Ship, is_night: bool) -> f64 {
-    let mut result = 0.0;
-    let gears = &ship.gears;
-    let ctype = ship.ctype;
-
-    if is_night {
-        if gears.has(gear_id!("20.3cm連装砲")) {
-            result += 10.0
-        } else if gears.has(gear_id!("20.3cm(2号)連装砲"))
-            || gears.has(gear_id!("20.3cm(3号)連装砲"))
-            || gears.has(gear_id!("試製20.3cm(4号)連装砲"))
-        {
-            result += 15.0
-        }
+fn default_gunfit_accuracy(
+    defs: &[impl Borrow<GunfitAccuracyDef>],
+    ship: &Ship,
+    is_night: bool,
+) -> f64 {
+    matched_rules(defs, ship)
+        .filter_map(|rule| rule.calc(ship, is_night))
+        .fold(0.0, |acc, value| acc + value)
+}
+
+/// フィット砲補正
+///
+/// `ShipState`で指定されている場合はその値を使う
+pub fn gunfit_accuracy(
+    defs: &[impl Borrow<GunfitAccuracyDef>],
+    ship: &Ship,
+    is_night: bool,
+) -> f64 {
+    ship.state_gunfit_accuracy(is_night)
+        .unwrap_or_else(|| default_gunfit_accuracy(defs, ship, is_night))
+}
+
+/// 規則の補正値は、対象の装備に等分して割り当てる
+pub fn gunfit_accuracy_info(
+    defs: &[impl Borrow<GunfitAccuracyDef>],
+    ship: &Ship,
+    is_night: bool,
+) -> GunfitAccuracyInfo {
+    let mut gears: Vec<GunfitGearContribution> = Vec::new();
+
+    matched_rules(defs, ship).for_each(|rule| {
+        let Some(value) = rule.calc(ship, is_night) else {
+            return;
+        };
+
+        let targets = ship
+            .gears
+            .iter()
+            .filter(|(_, gear)| rule.matches_gear(gear))
+            .collect::<Vec<_>>();
+        let share = value / targets.len() as f64;
+
+        targets.into_iter().for_each(|(index, gear)| {
+            if let Some(item) = gears.iter_mut().find(|item| item.index == index) {
+                item.value += share;
+            } else {
+                gears.push(GunfitGearContribution {
+                    index,
+                    gear_id: gear.gear_id,
+                    value: share,
+                });
+            }
+        });
+    });
+
+    gears.sort_by_key(|item| item.index);
+
+    let state = ship.state_gunfit_accuracy(is_night);
+
+    GunfitAccuracyInfo {
+        value: state.unwrap_or_else(|| default_gunfit_accuracy(defs, ship, is_night)),
+        overridden: state.is_some(),
+        gears,
     }
+}
 
-    if ctype == ctype!("最上型") {
-        result += 2.0 * (gears.count(gear_id!("15.5cm三連装砲")) as f64);
-        result += 5.0 * (gears.count(gear_id!("15.5cm三連装砲改")) as f64);
-    } else if ctype == ctype!("Zara級") {
-        let count = gears.count(gear_id!("203mm/53 連装砲")) as f64;
-        result += count.sqrt();
+pub fn reference_gunfit_accuracy_defs() -> &'static [GunfitAccuracyDef] {
+    static DEFS: LazyLock<Vec<GunfitAccuracyDef>> =
+        LazyLock::new(create_reference_gunfit_accuracy_defs);
+    &DEFS
+}
+
+fn rule(gear_ids: &[u16], scale: GunfitScale, value: f64) -> GunfitRuleDef {
+    GunfitRuleDef {
+        gear_ids: gear_ids.to_vec(),
+        scale,
+        value,
+        ..Default::default()
     }
+}
 
-    result
+/// 補正値 × √個数
+fn sqrt(gear_ids: &[u16], value: f64) -> GunfitRuleDef {
+    rule(gear_ids, GunfitScale::Sqrt, value)
 }
 
-/// 水母フィット補正
-fn seaplane_tender_bonus(ship: &Ship) -> f64 {
-    let gears = &ship.gears;
-    let mut result = 0.0;
-
-    let count14cm15cm = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "14cm単装砲"
-                | "14cm連装砲"
-                | "14cm連装砲改"
-                | "15.2cm単装砲"
-                | "15.2cm連装砲"
-                | "15.2cm連装砲改"
-                | "15.2cm三連装砲"
-        )
-    });
+/// 補正値 × 個数
+fn linear(gear_ids: &[u16], value: f64) -> GunfitRuleDef {
+    rule(gear_ids, GunfitScale::Linear, value)
+}
 
-    let count203 = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "20.3cm連装砲"
-                | "20.3cm(3号)連装砲"
-                | "20.3cm(2号)連装砲"
-                | "試製20.3cm(4号)連装砲"
-                | "203mm/53 連装砲"
-                | "152mm/55 三連装速射砲"
-                | "152mm/55 三連装速射砲改"
-        )
-    });
+/// 1個以上で補正値
+fn any(gear_ids: &[u16], value: f64) -> GunfitRuleDef {
+    rule(gear_ids, GunfitScale::Any, value)
+}
 
-    // 20.3,15.2混載時ペナ消失
-    if count203 > 0 && count14cm15cm == 0 {
-        result += -8.0;
+fn married(rule: GunfitRuleDef, value: f64) -> GunfitRuleDef {
+    GunfitRuleDef {
+        married_value: Some(value),
+        ..rule
     }
+}
 
-    result += -6.0 * count14cm15cm as f64;
-    result += -10.0 * count203 as f64;
+/// ケッコンカッコカリ時は補正値に`m`を掛ける
+fn married_mod(rule: GunfitRuleDef, m: f64) -> GunfitRuleDef {
+    let value = rule.value * m;
+    married(rule, value)
+}
 
-    result
+fn day_only(mut def: GunfitAccuracyDef) -> GunfitAccuracyDef {
+    def.rules
+        .iter_mut()
+        .for_each(|rule| rule.night = Some(false));
+    def
 }
 
-/// 戦艦フィット補正
-fn battleship_bonus(ship: &Ship, is_night: bool) -> f64 {
-    // todo!
-    if is_night {
-        return 0.0;
+fn def(
+    name: &str,
+    ship_types: &[ShipType],
+    ctypes: &[u16],
+    rules: Vec<GunfitRuleDef>,
+) -> GunfitAccuracyDef {
+    GunfitAccuracyDef {
+        name: name.to_string(),
+        ship_types: ship_types.to_vec(),
+        ctypes: ctypes.to_vec(),
+        rules,
+        ..Default::default()
     }
+}
+
+fn create_reference_gunfit_accuracy_defs() -> Vec<GunfitAccuracyDef> {
+    [
+        destroyer_defs(),
+        light_cruiser_defs(),
+        heavy_cruiser_defs(),
+        seaplane_tender_defs(),
+        battleship_defs(),
+    ]
+    .concat()
+}
 
-    let mut r = 0.0;
-    let gears = &ship.gears;
-    let ship_id = ship.ship_id;
-    let ctype = ship.ctype;
-    let is_married = ship.level > 99;
-    let m = if is_married { 0.6 } else { 1.0 };
-
-    let count_51cm_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "51cm連装砲" | "試製51cm連装砲" | "試製51cm三連装砲"
-        )
-    }) as f64;
-
-    let count_46cm_triple_kai = gears.count(gear_id!("46cm三連装砲改")) as f64;
-    let count_46cm_triple = gears.count(gear_id!("46cm三連装砲")) as f64;
-    let count_proto_46cm = gears.count(gear_id!("試製46cm連装砲")) as f64;
-
-    let count_16inch_mk7 = gears.count(gear_id!("16inch三連装砲 Mk.7")) as f64;
-    let count_16inch_mk7_gfcs = gears.count(gear_id!("16inch三連装砲 Mk.7+GFCS")) as f64;
-    let count_16inch_mk7_group = count_16inch_mk7 + count_16inch_mk7_gfcs;
-
-    let count_41cm_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "41cm連装砲"
-                | "試製41cm三連装砲"
-                | "41cm三連装砲改"
-                | "41cm三連装砲改二"
-                | "41cm連装砲改二"
-                | "16inch Mk.I三連装砲"
-                | "16inch Mk.I三連装砲+AFCT改"
-                | "16inch Mk.I三連装砲改+FCR type284"
-                | "16inch Mk.I連装砲"
-                | "16inch Mk.V連装砲"
-                | "16inch Mk.VIII連装砲改"
-                | "16inch三連装砲 Mk.6"
-                | "16inch三連装砲 Mk.6 mod.2"
-                | "16inch三連装砲 Mk.6+GFCS"
-        )
-    }) as f64;
-
-    let count_381mm_group = gears.count_by(|gear| {
-        matches_gear_id!(gear.gear_id, "381mm/50 三連装砲" | "381mm/50 三連装砲改")
-    }) as f64;
-
-    let count_38cm_quad_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "38cm四連装砲" | "38cm四連装砲改" | "38cm四連装砲改 deux"
-        )
-    }) as f64;
-
-    let count_35_6cm_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "35.6cm連装砲"
-                | "試製35.6cm三連装砲"
-                | "35.6cm連装砲(ダズル迷彩)"
-                | "35.6cm三連装砲改(ダズル迷彩仕様)"
-                | "35.6cm連装砲改"
-                | "35.6cm連装砲改二"
-                | "38cm連装砲"
-                | "38cm連装砲改"
-                | "35.6cm連装砲改三(ダズル迷彩仕様)"
-                | "35.6cm連装砲改四"
-        )
-    }) as f64;
-
-    let count_30_5cm_group = gears.count_by(|gear| {
-        matches_gear_id!(
-            gear.gear_id,
-            "30.5cm三連装砲" | "30.5cm三連装砲改" | "305mm/46 連装砲" | "305mm/46 三連装砲"
-        )
-    }) as f64;
-
-    let count_320mm_group = gears
-        .count_by(|gear| matches_gear_id!(gear.gear_id, "320mm/44 連装砲" | "320mm/44 三連装砲"))
-        as f64;
+/// 駆逐フィット補正
+fn destroyer_defs() -> Vec<GunfitAccuracyDef> {
+    const SHIP_TYPES: &[ShipType] = &[ShipType::DD];
+
+    let twin_130mm_group = [gear_id!("130mm B-13連装砲")];
+    let inch5_group = [
+        gear_id!("5inch単装砲 Mk.30"),
+        gear_id!("5inch単装砲 Mk.30改"),
+        gear_id!("5inch単装砲 Mk.30改+GFCS Mk.37"),
+    ];
+    let qf_group = [gear_id!("QF 4.7inch砲 Mk.XII改")];
+    let asdic_group = [
+        gear_id!("Type124 ASDIC"),
+        gear_id!("Type144/147 ASDIC"),
+        gear_id!("HF/DF + Type144/147 ASDIC"),
+    ];
+    let single_12cm_group = [gear_id!("12cm単装砲改二")];
+
+    vec![
+        def(
+            "Ташкент級",
+            SHIP_TYPES,
+            &[ctype!("Ташкент級")],
+            vec![sqrt(&twin_130mm_group, 5.0)],
+        ),
+        def(
+            "Fletcher級",
+            SHIP_TYPES,
+            &[ctype!("Fletcher級")],
+            vec![sqrt(&inch5_group, 4.0)],
+        ),
+        def(
+            "John C.Butler級",
+            SHIP_TYPES,
+            &[ctype!("John C.Butler級")],
+            vec![sqrt(&inch5_group, 4.0)],
+        ),
+        def(
+            "J級",
+            SHIP_TYPES,
+            &[ctype!("J級")],
+            vec![sqrt(&qf_group, 3.0), sqrt(&asdic_group, 3.0)],
+        ),
+        def(
+            "睦月型",
+            SHIP_TYPES,
+            &[ctype!("睦月型")],
+            vec![sqrt(&single_12cm_group, 5.0)],
+        ),
+        GunfitAccuracyDef {
+            ship_ids: vec![ship_id!("Верный")],
+            ..def(
+                "Верный",
+                SHIP_TYPES,
+                &[],
+                vec![sqrt(&twin_130mm_group, 5.0)],
+            )
+        },
+    ]
+}
+
+/// 軽巡フィット補正
+fn light_cruiser_defs() -> Vec<GunfitAccuracyDef> {
+    const SHIP_TYPES: &[ShipType] = &[ShipType::CL, ShipType::CLT, ShipType::CT];
+    const M1: f64 = 2.0;
+    const M2: f64 = 0.6;
+    const M3: f64 = 0.8;
+
+    let group_14cm = [
+        gear_id!("14cm単装砲"),
+        gear_id!("14cm連装砲"),
+        gear_id!("14cm連装砲改"),
+    ];
+    let twin_15_2cm_group = [
+        gear_id!("15.2cm連装砲"),
+        gear_id!("15.2cm連装砲改"),
+        gear_id!("15.2cm連装砲改二"),
+        gear_id!("Bofors 15.2cm連装砲 Model 1930"),
+        gear_id!("Bofors 15cm連装速射砲 Mk.9 Model 1938"),
+        gear_id!("Bofors 15cm連装速射砲 Mk.9改+単装速射砲 Mk.10改 Model 1938"),
+        gear_id!("6inch 連装速射砲 Mk.XXI"),
+    ];
+    let triple_15_2cm_group = [
+        gear_id!("6inch三連装速射砲 Mk.16"),
+        gear_id!("6inch三連装速射砲 Mk.16 mod.2"),
+        gear_id!("6inch Mk.XXIII三連装砲"),
+        gear_id!("152mm/55 三連装速射砲"),
+        gear_id!("152mm/55 三連装速射砲改"),
+    ];
+    let group_15_5cm = [gear_id!("15.5cm三連装砲"), gear_id!("15.5cm三連装砲改")];
+    let inch5_group = [
+        gear_id!("5inch連装両用砲(集中配備)"),
+        gear_id!("5inch連装砲(副砲配置) 集中配備"),
+    ];
+    let group_20_3cm = [
+        gear_id!("20.3cm連装砲"),
+        gear_id!("20.3cm(3号)連装砲"),
+        gear_id!("20.3cm(2号)連装砲"),
+        gear_id!("試製20.3cm(4号)連装砲"),
+        gear_id!("203mm/53 連装砲"),
+        gear_id!("SKC34 20.3cm連装砲"),
+        gear_id!("18cm/57 三連装主砲"),
+    ];
+    let inch8_group = [
+        gear_id!("8inch三連装砲 Mk.9"),
+        gear_id!("8inch三連装砲 Mk.9 mod.2"),
+    ];
+
+    // ケッコンカッコカリ時のみ加算
+    let m1 = |gear_ids: &[u16]| married(any(gear_ids, 0.0), M1);
+    let m2 = |gear_ids: &[u16], value| married_mod(sqrt(gear_ids, value), M2);
+    let m3 = |gear_ids: &[u16], value| married_mod(sqrt(gear_ids, value), M3);
+
+    let special_ctypes = [ctype!("阿賀野型"), ctype!("大淀型"), ctype!("Atlanta級")];
+
+    vec![
+        GunfitAccuracyDef {
+            abyssal: Some(true),
+            ..def(
+                "深海軽巡",
+                SHIP_TYPES,
+                &[],
+                vec![GunfitRuleDef {
+                    gear_types: vec![GearType::MediumMainGun],
+                    ..linear(&[], -3.0)
+                }],
+            )
+        },
+        GunfitAccuracyDef {
+            abyssal: Some(false),
+            ..def(
+                "阿賀野型",
+                SHIP_TYPES,
+                &[ctype!("阿賀野型")],
+                vec![
+                    m1(&group_14cm),
+                    sqrt(&twin_15_2cm_group, 3.0),
+                    m1(&twin_15_2cm_group),
+                    sqrt(&group_15_5cm, -2.0),
+                    m1(&group_15_5cm),
+                    m2(&triple_15_2cm_group, -3.0),
+                    m2(&inch5_group, -3.0),
+                    m2(&group_20_3cm, -4.0),
+                    m3(&inch8_group, -11.0),
+                ],
+            )
+        },
+        GunfitAccuracyDef {
+            abyssal: Some(false),
+            ..def(
+                "大淀型",
+                SHIP_TYPES,
+                &[ctype!("大淀型")],
+                vec![
+                    m1(&group_14cm),
+                    m1(&twin_15_2cm_group),
+                    m2(&group_15_5cm, -1.0),
+                    m2(&triple_15_2cm_group, -3.0),
+                    m2(&inch5_group, -3.0),
+                    m2(&group_20_3cm, -5.0),
+                    m3(&inch8_group, -11.0),
+                ],
+            )
+        },
+        GunfitAccuracyDef {
+            abyssal: Some(false),
+            ..def(
+                "Atlanta級",
+                SHIP_TYPES,
+                &[ctype!("Atlanta級")],
+                vec![
+                    m1(&group_14cm),
+                    m1(&twin_15_2cm_group),
+                    sqrt(&group_15_5cm, -2.0),
+                    m1(&group_15_5cm),
+                    m2(&triple_15_2cm_group, -3.0),
+                    married(any(&inch5_group, 0.0), M1 * 1.5),
+                    m2(&group_20_3cm, -4.0),
+                    m3(&inch8_group, -11.0),
+                ],
+            )
+        },
+        GunfitAccuracyDef {
+            abyssal: Some(false),
+            exclude_ctypes: special_ctypes.to_vec(),
+            ..def(
+                "軽巡",
+                SHIP_TYPES,
+                &[],
+                vec![
+                    m1(&group_14cm),
+                    m1(&twin_15_2cm_group),
+                    sqrt(&group_15_5cm, -2.0),
+                    m1(&group_15_5cm),
+                    m2(&triple_15_2cm_group, -3.0),
+                    m2(&inch5_group, -3.0),
+                    m2(&group_20_3cm, -3.0),
+                    m3(&inch8_group, -11.0),
+                ],
+            )
+        },
+    ]
+}
+
+/// 重巡フィット補正
+fn heavy_cruiser_defs() -> Vec<GunfitAccuracyDef> {
+    const SHIP_TYPES: &[ShipType] = &[ShipType::CA, ShipType::CAV];
+
+    let twin_20_3cm = [gear_id!("20.3cm連装砲")];
+    let improved_20_3cm_group = [
+        gear_id!("20.3cm(2号)連装砲"),
+        gear_id!("20.3cm(3号)連装砲"),
+        gear_id!("試製20.3cm(4号)連装砲"),
+    ];
+
+    vec![
+        def(
+            "重巡夜戦",
+            SHIP_TYPES,
+            &[],
+            vec![
+                GunfitRuleDef {
+                    night: Some(true),
+                    ..any(&twin_20_3cm, 10.0)
+                },
+                GunfitRuleDef {
+                    night: Some(true),
+                    unless_gear_ids: twin_20_3cm.to_vec(),
+                    ..any(&improved_20_3cm_group, 15.0)
+                },
+            ],
+        ),
+        def(
+            "最上型",
+            SHIP_TYPES,
+            &[ctype!("最上型")],
+            vec![
+                linear(&[gear_id!("15.5cm三連装砲")], 2.0),
+                linear(&[gear_id!("15.5cm三連装砲改")], 5.0),
+            ],
+        ),
+        def(
+            "Zara級",
+            SHIP_TYPES,
+            &[ctype!("Zara級")],
+            vec![sqrt(&[gear_id!("203mm/53 連装砲")], 1.0)],
+        ),
+    ]
+}
+
+/// 水母フィット補正
+fn seaplane_tender_defs() -> Vec<GunfitAccuracyDef> {
+    let group_14cm_15cm = [
+        gear_id!("14cm単装砲"),
+        gear_id!("14cm連装砲"),
+        gear_id!("14cm連装砲改"),
+        gear_id!("15.2cm単装砲"),
+        gear_id!("15.2cm連装砲"),
+        gear_id!("15.2cm連装砲改"),
+        gear_id!("15.2cm三連装砲"),
+    ];
+    let group_203 = [
+        gear_id!("20.3cm連装砲"),
+        gear_id!("20.3cm(3号)連装砲"),
+        gear_id!("20.3cm(2号)連装砲"),
+        gear_id!("試製20.3cm(4号)連装砲"),
+        gear_id!("203mm/53 連装砲"),
+        gear_id!("152mm/55 三連装速射砲"),
+        gear_id!("152mm/55 三連装速射砲改"),
+    ];
+
+    vec![def(
+        "水母",
+        &[ShipType::AV],
+        &[],
+        vec![
+            // 20.3,15.2混載時ペナ消失
+            GunfitRuleDef {
+                unless_gear_ids: group_14cm_15cm.to_vec(),
+                ..any(&group_203, -8.0)
+            },
+            linear(&group_14cm_15cm, -6.0),
+            linear(&group_203, -10.0),
+        ],
+    )]
+}
+
+/// 戦艦フィット補正
+///
+/// 夜戦の補正値は出典のある検証データがないため、昼戦のみ適用する
+/// マスターデータで同じ名前の定義に夜戦の規則を含めれば、その定義のみ置き換えられる
+fn battleship_defs() -> Vec<GunfitAccuracyDef> {
+    const SHIP_TYPES: &[ShipType] = &[ShipType::FBB, ShipType::BB, ShipType::BBV];
+    const M: f64 = 0.6;
+
+    let group_51cm = [
+        gear_id!("51cm連装砲"),
+        gear_id!("試製51cm連装砲"),
+        gear_id!("試製51cm三連装砲"),
+    ];
+    let triple_46cm_kai = [gear_id!("46cm三連装砲改")];
+    let triple_46cm = [gear_id!("46cm三連装砲")];
+    let proto_46cm = [gear_id!("試製46cm連装砲")];
+    let inch16_mk7 = [gear_id!("16inch三連装砲 Mk.7")];
+    let inch16_mk7_gfcs = [gear_id!("16inch三連装砲 Mk.7+GFCS")];
+    let inch16_mk7_group = [inch16_mk7, inch16_mk7_gfcs].concat();
+    let group_41cm = [
+        gear_id!("41cm連装砲"),
+        gear_id!("試製41cm三連装砲"),
+        gear_id!("41cm三連装砲改"),
+        gear_id!("41cm三連装砲改二"),
+        gear_id!("41cm連装砲改二"),
+        gear_id!("16inch Mk.I三連装砲"),
+        gear_id!("16inch Mk.I三連装砲+AFCT改"),
+        gear_id!("16inch Mk.I三連装砲改+FCR type284"),
+        gear_id!("16inch Mk.I連装砲"),
+        gear_id!("16inch Mk.V連装砲"),
+        gear_id!("16inch Mk.VIII連装砲改"),
+        gear_id!("16inch三連装砲 Mk.6"),
+        gear_id!("16inch三連装砲 Mk.6 mod.2"),
+        gear_id!("16inch三連装砲 Mk.6+GFCS"),
+    ];
+    let group_381mm = [
+        gear_id!("381mm/50 三連装砲"),
+        gear_id!("381mm/50 三連装砲改"),
+    ];
+    let quad_38cm_group = [
+        gear_id!("38cm四連装砲"),
+        gear_id!("38cm四連装砲改"),
+        gear_id!("38cm四連装砲改 deux"),
+    ];
+    let group_35_6cm = [
+        gear_id!("35.6cm連装砲"),
+        gear_id!("試製35.6cm三連装砲"),
+        gear_id!("35.6cm連装砲(ダズル迷彩)"),
+        gear_id!("35.6cm三連装砲改(ダズル迷彩仕様)"),
+        gear_id!("35.6cm連装砲改"),
+        gear_id!("35.6cm連装砲改二"),
+        gear_id!("38cm連装砲"),
+        gear_id!("38cm連装砲改"),
+        gear_id!("35.6cm連装砲改三(ダズル迷彩仕様)"),
+        gear_id!("35.6cm連装砲改四"),
+    ];
+    let group_30_5cm = [
+        gear_id!("30.5cm三連装砲"),
+        gear_id!("30.5cm三連装砲改"),
+        gear_id!("305mm/46 連装砲"),
+        gear_id!("305mm/46 三連装砲"),
+    ];
+    let group_320mm = [gear_id!("320mm/44 連装砲"), gear_id!("320mm/44 三連装砲")];
+    let group_51cm_46cm = [&group_51cm[..], &triple_46cm_kai, &triple_46cm].concat();
 
     // データ不足
-    // let count_38_1cm = gears.count_by(|gear| {
-    //     matches_gear_id!(gear.gear_id, "38.1cm Mk.I連装砲" | "38.1cm Mk.I/N連装砲改")
-    // }) as f64;
-
-    match ctype {
-        ctype!("Гангут級") => {
-            r += -14.0 * m * count_46cm_triple_kai.sqrt();
-            r += -14.0 * m * count_46cm_triple.sqrt();
-            r += -11.0 * m * count_proto_46cm.sqrt();
-            r += -9.0 * m * count_16inch_mk7_group.sqrt();
-            r += -9.0 * m * count_41cm_group.sqrt();
-            r += -2.0 * count_381mm_group.sqrt();
-            r += 4.0 * count_35_6cm_group.sqrt();
-            r += 12.0 * count_30_5cm_group.sqrt();
-        }
-        ctype!("Conte di Cavour級") => {
-            r += -14.0 * m * count_46cm_triple_kai.sqrt();
-            r += -14.0 * m * count_46cm_triple.sqrt();
-            r += -11.0 * m * count_proto_46cm.sqrt();
-            r += -9.0 * m * count_16inch_mk7_group.sqrt();
-            r += -9.0 * m * count_41cm_group.sqrt();
-            r += -2.0 * m * count_381mm_group.sqrt();
-            r += 4.0 * count_35_6cm_group.sqrt();
-            r += 14.0 * count_320mm_group.sqrt();
-        }
-        ctype!("金剛型") => {
-            r += -10.0 * m * count_46cm_triple_kai.sqrt();
-            r += -10.0 * m * count_46cm_triple.sqrt();
-            r += -7.0 * m * count_proto_46cm.sqrt();
-            r += -5.0 * m * count_16inch_mk7_group.sqrt();
-            r += -5.0 * m * count_41cm_group.sqrt();
-            r += -2.0 * m * count_381mm_group.sqrt();
-            r += 7.0 * count_35_6cm_group.sqrt();
-        }
-        ctype!("Bismarck級") | ctype!("V.Veneto級") => {
-            r += -14.0 * m * count_46cm_triple_kai.sqrt();
-            r += -14.0 * m * count_46cm_triple.sqrt();
-            r += -11.0 * m * count_proto_46cm.sqrt();
-            r += -9.0 * m * count_16inch_mk7_group.sqrt();
-            r += -9.0 * m * count_41cm_group.sqrt();
-            r += 1.0 * m * count_381mm_group.sqrt();
-            r += 4.0 * count_35_6cm_group.sqrt();
-        }
-        ctype!("Iowa級") => {
-            r += -10.0 * m * count_46cm_triple_kai.sqrt();
-            r += -10.0 * m * count_46cm_triple.sqrt();
-            r += -7.0 * m * count_proto_46cm.sqrt();
-            r += -5.0 * m * count_41cm_group.sqrt();
-            r += -2.0 * m * count_381mm_group.sqrt();
-            r += 2.0 * m * count_16inch_mk7.sqrt();
-            r += 9.0 * m * count_16inch_mk7_gfcs.sqrt();
-            r += 4.0 * count_35_6cm_group.sqrt();
-        }
-        ctype!("Colorado級") => {
-            r += -7.0 * m * count_46cm_triple_kai.sqrt();
-            r += -7.0 * m * count_46cm_triple.sqrt();
-            r += -3.0 * m * count_proto_46cm.sqrt();
-            r += 2.0 * m * count_381mm_group.sqrt();
-            r += 2.0 * count_16inch_mk7_group.sqrt();
-            r += 2.0 * count_41cm_group.sqrt();
-            r += 2.0 * count_35_6cm_group.sqrt();
-        }
-        ctype!("Richelieu級") => {
-            r += -10.0 * m * count_46cm_triple_kai.sqrt();
-            r += -10.0 * m * count_46cm_triple.sqrt();
-            r += -7.0 * m * count_proto_46cm.sqrt();
-            r += -7.0 * m * count_16inch_mk7_group.sqrt();
-            r += -7.0 * m * count_41cm_group.sqrt();
-            r += -2.0 * m * count_381mm_group.sqrt();
-            r += 2.0 * count_38cm_quad_group.sqrt();
-            r += 4.0 * count_35_6cm_group.sqrt();
-        }
-        ctype!("Queen Elizabeth級") => {
-            r += -7.0 * m * count_46cm_triple_kai.sqrt();
-            r += -7.0 * m * count_46cm_triple.sqrt();
-            r += -3.0 * m * count_proto_46cm.sqrt();
-            r += 2.0 * m * count_381mm_group.sqrt();
-            r += 2.0 * count_16inch_mk7_group.sqrt();
-            r += 2.0 * count_41cm_group.sqrt();
-            r += 2.0 * count_35_6cm_group.sqrt();
-        }
-        ctype!("Nelson級") => {
-            if ship_id != ship_id!("Nelson改") {
-                r += -7.0 * m * count_46cm_triple_kai.sqrt();
-                r += -7.0 * m * count_46cm_triple.sqrt();
-                r += -3.0 * m * count_proto_46cm.sqrt();
-                r += 2.0 * m * count_381mm_group.sqrt();
-                r += 2.0 * count_16inch_mk7_group.sqrt();
-                r += 2.0 * count_41cm_group.sqrt();
-                r += 2.0 * count_35_6cm_group.sqrt();
-            }
-        }
-        ctype!("大和型") => match ship_id {
-            ship_id!("大和改二") => {
-                r += -10.0 * m * count_51cm_group.sqrt();
-                r += -10.0 * m * count_46cm_triple_kai.sqrt();
-                r += -10.0 * m * count_46cm_triple.sqrt();
-                r += -7.0 * m * count_proto_46cm.sqrt();
-                r += -5.0 * m * count_16inch_mk7_group.sqrt();
-                r += -5.0 * m * count_41cm_group.sqrt();
-                r += -2.0 * m * count_381mm_group.sqrt();
-                r += 7.0 * count_46cm_triple_kai.sqrt();
-                r += 3.0 * count_46cm_triple.sqrt();
-                r += 3.0 * count_proto_46cm.sqrt();
-                r += 4.0 * count_35_6cm_group.sqrt();
-            }
-            ship_id!("大和改二重") => {
-                r += -8.0
-                    * m
-                    * (count_51cm_group + count_46cm_triple_kai + count_46cm_triple).sqrt();
-                r += -5.0 * m * count_proto_46cm.sqrt();
-                r += 2.0 * m * count_381mm_group.sqrt();
-                r += 7.0 * count_46cm_triple_kai.sqrt();
-                r += 3.0 * count_46cm_triple.sqrt();
-                r += 3.0 * count_proto_46cm.sqrt();
-                r += 2.0 * count_16inch_mk7_group.sqrt();
-                r += 2.0 * count_41cm_group.sqrt();
-                r += 4.0 * count_35_6cm_group.sqrt();
-            }
-            _ => {
-                r += 7.0 * count_46cm_triple_kai.sqrt();
-                r += 3.0 * count_46cm_triple.sqrt();
-                r += 3.0 * count_proto_46cm.sqrt();
-            }
+    // 38.1cm Mk.I連装砲, 38.1cm Mk.I/N連装砲改
+
+    let m = |gear_ids: &[u16], value| married_mod(sqrt(gear_ids, value), M);
+
+    // 英米戦艦などと同等の補正
+    let standard_rules = |value_35_6cm| {
+        vec![
+            m(&triple_46cm_kai, -7.0),
+            m(&triple_46cm, -7.0),
+            m(&proto_46cm, -3.0),
+            m(&group_381mm, 2.0),
+            sqrt(&inch16_mk7_group, 2.0),
+            sqrt(&group_41cm, 2.0),
+            sqrt(&group_35_6cm, value_35_6cm),
+        ]
+    };
+
+    let yamato_kai2 = ship_id!("大和改二");
+    let yamato_kai2_juu = ship_id!("大和改二重");
+    let nagato_kai2 = ship_id!("長門改二");
+
+    let defs = vec![
+        def(
+            "Гангут級",
+            SHIP_TYPES,
+            &[ctype!("Гангут級")],
+            vec![
+                m(&triple_46cm_kai, -14.0),
+                m(&triple_46cm, -14.0),
+                m(&proto_46cm, -11.0),
+                m(&inch16_mk7_group, -9.0),
+                m(&group_41cm, -9.0),
+                sqrt(&group_381mm, -2.0),
+                sqrt(&group_35_6cm, 4.0),
+                sqrt(&group_30_5cm, 12.0),
+            ],
+        ),
+        def(
+            "Conte di Cavour級",
+            SHIP_TYPES,
+            &[ctype!("Conte di Cavour級")],
+            vec![
+                m(&triple_46cm_kai, -14.0),
+                m(&triple_46cm, -14.0),
+                m(&proto_46cm, -11.0),
+                m(&inch16_mk7_group, -9.0),
+                m(&group_41cm, -9.0),
+                m(&group_381mm, -2.0),
+                sqrt(&group_35_6cm, 4.0),
+                sqrt(&group_320mm, 14.0),
+            ],
+        ),
+        def(
+            "金剛型",
+            SHIP_TYPES,
+            &[ctype!("金剛型")],
+            vec![
+                m(&triple_46cm_kai, -10.0),
+                m(&triple_46cm, -10.0),
+                m(&proto_46cm, -7.0),
+                m(&inch16_mk7_group, -5.0),
+                m(&group_41cm, -5.0),
+                m(&group_381mm, -2.0),
+                sqrt(&group_35_6cm, 7.0),
+            ],
+        ),
+        def(
+            "Bismarck級, V.Veneto級",
+            SHIP_TYPES,
+            &[ctype!("Bismarck級"), ctype!("V.Veneto級")],
+            vec![
+                m(&triple_46cm_kai, -14.0),
+                m(&triple_46cm, -14.0),
+                m(&proto_46cm, -11.0),
+                m(&inch16_mk7_group, -9.0),
+                m(&group_41cm, -9.0),
+                m(&group_381mm, 1.0),
+                sqrt(&group_35_6cm, 4.0),
+            ],
+        ),
+        def(
+            "Iowa級",
+            SHIP_TYPES,
+            &[ctype!("Iowa級")],
+            vec![
+                m(&triple_46cm_kai, -10.0),
+                m(&triple_46cm, -10.0),
+                m(&proto_46cm, -7.0),
+                m(&group_41cm, -5.0),
+                m(&group_381mm, -2.0),
+                m(&inch16_mk7, 2.0),
+                m(&inch16_mk7_gfcs, 9.0),
+                sqrt(&group_35_6cm, 4.0),
+            ],
+        ),
+        def(
+            "Colorado級",
+            SHIP_TYPES,
+            &[ctype!("Colorado級")],
+            standard_rules(2.0),
+        ),
+        def(
+            "Richelieu級",
+            SHIP_TYPES,
+            &[ctype!("Richelieu級")],
+            vec![
+                m(&triple_46cm_kai, -10.0),
+                m(&triple_46cm, -10.0),
+                m(&proto_46cm, -7.0),
+                m(&inch16_mk7_group, -7.0),
+                m(&group_41cm, -7.0),
+                m(&group_381mm, -2.0),
+                sqrt(&quad_38cm_group, 2.0),
+                sqrt(&group_35_6cm, 4.0),
+            ],
+        ),
+        def(
+            "Queen Elizabeth級",
+            SHIP_TYPES,
+            &[ctype!("Queen Elizabeth級")],
+            standard_rules(2.0),
+        ),
+        GunfitAccuracyDef {
+            exclude_ship_ids: vec![ship_id!("Nelson改")],
+            ..def(
+                "Nelson級",
+                SHIP_TYPES,
+                &[ctype!("Nelson級")],
+                standard_rules(2.0),
+            )
         },
-        ctype!("長門型") => match ship_id {
-            ship_id!("長門改二") => {
-                r += -5.0 * m * count_51cm_group.sqrt();
-                r += -7.0 * m * count_46cm_triple_kai.sqrt();
-                r += -7.0 * m * count_46cm_triple.sqrt();
-                r += -3.0 * m * count_proto_46cm.sqrt();
-                r += 2.0 * m * count_381mm_group.sqrt();
-                r += 2.0 * count_16inch_mk7_group.sqrt();
-                r += 5.0 * count_41cm_group.sqrt();
-                r += 2.0 * count_35_6cm_group.sqrt();
-            }
-            _ => {
-                r += -5.0 * m * count_51cm_group.sqrt();
-                r += -7.0 * m * count_46cm_triple_kai.sqrt();
-                r += -7.0 * m * count_46cm_triple.sqrt();
-                r += -3.0 * m * count_proto_46cm.sqrt();
-                r += 2.0 * m * count_381mm_group.sqrt();
-                r += 2.0 * count_16inch_mk7_group.sqrt();
-                r += 4.0 * count_41cm_group.sqrt();
-                r += 2.0 * count_35_6cm_group.sqrt();
-            }
+        GunfitAccuracyDef {
+            ship_ids: vec![yamato_kai2],
+            ..def(
+                "大和改二",
+                SHIP_TYPES,
+                &[ctype!("大和型")],
+                vec![
+                    m(&group_51cm, -10.0),
+                    m(&triple_46cm_kai, -10.0),
+                    m(&triple_46cm, -10.0),
+                    m(&proto_46cm, -7.0),
+                    m(&inch16_mk7_group, -5.0),
+                    m(&group_41cm, -5.0),
+                    m(&group_381mm, -2.0),
+                    sqrt(&triple_46cm_kai, 7.0),
+                    sqrt(&triple_46cm, 3.0),
+                    sqrt(&proto_46cm, 3.0),
+                    sqrt(&group_35_6cm, 4.0),
+                ],
+            )
+        },
+        GunfitAccuracyDef {
+            ship_ids: vec![yamato_kai2_juu],
+            ..def(
+                "大和改二重",
+                SHIP_TYPES,
+                &[ctype!("大和型")],
+                vec![
+                    m(&group_51cm_46cm, -8.0),
+                    m(&proto_46cm, -5.0),
+                    m(&group_381mm, 2.0),
+                    sqrt(&triple_46cm_kai, 7.0),
+                    sqrt(&triple_46cm, 3.0),
+                    sqrt(&proto_46cm, 3.0),
+                    sqrt(&inch16_mk7_group, 2.0),
+                    sqrt(&group_41cm, 2.0),
+                    sqrt(&group_35_6cm, 4.0),
+                ],
+            )
         },
-        ctype!("伊勢型") | ctype!("扶桑型") => {
-            r += -7.0 * m * count_46cm_triple_kai.sqrt();
-            r += -7.0 * m * count_46cm_triple.sqrt();
-            r += -3.0 * m * count_proto_46cm.sqrt();
-            r += 2.0 * m * count_381mm_group.sqrt();
-            r += 2.0 * count_16inch_mk7_group.sqrt();
-            r += 2.0 * count_41cm_group.sqrt();
-            r += 4.0 * count_35_6cm_group.sqrt();
+        GunfitAccuracyDef {
+            exclude_ship_ids: vec![yamato_kai2, yamato_kai2_juu],
+            ..def(
+                "大和型",
+                SHIP_TYPES,
+                &[ctype!("大和型")],
+                vec![
+                    sqrt(&triple_46cm_kai, 7.0),
+                    sqrt(&triple_46cm, 3.0),
+                    sqrt(&proto_46cm, 3.0),
+                ],
+            )
+        },
+        GunfitAccuracyDef {
+            ship_ids: vec![nagato_kai2],
+            ..def(
+                "長門改二",
+                SHIP_TYPES,
+                &[ctype!("長門型")],
+                vec![
+                    m(&group_51cm, -5.0),
+                    m(&triple_46cm_kai, -7.0),
+                    m(&triple_46cm, -7.0),
+                    m(&proto_46cm, -3.0),
+                    m(&group_381mm, 2.0),
+                    sqrt(&inch16_mk7_group, 2.0),
+                    sqrt(&group_41cm, 5.0),
+                    sqrt(&group_35_6cm, 2.0),
+                ],
+            )
+        },
+        GunfitAccuracyDef {
+            exclude_ship_ids: vec![nagato_kai2],
+            ..def(
+                "長門型",
+                SHIP_TYPES,
+                &[ctype!("長門型")],
+                vec![
+                    m(&group_51cm, -5.0),
+                    m(&triple_46cm_kai, -7.0),
+                    m(&triple_46cm, -7.0),
+                    m(&proto_46cm, -3.0),
+                    m(&group_381mm, 2.0),
+                    sqrt(&inch16_mk7_group, 2.0),
+                    sqrt(&group_41cm, 4.0),
+                    sqrt(&group_35_6cm, 2.0),
+                ],
+            )
+        },
+        def(
+            "伊勢型, 扶桑型",
+            SHIP_TYPES,
+            &[ctype!("伊勢型"), ctype!("扶桑型")],
+            standard_rules(4.0),
+        ),
+    ];
+
+    defs.into_iter().map(day_only).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gunfit_accuracy_def() {
+        let defs: Vec<GunfitAccuracyDef> = serde_json::from_value(serde_json::json!([
+            {
+                "ship_types": ["BB"],
+                "ctypes": [ctype!("長門型")],
+                "rules": [
+                    { "gear_ids": [gear_id!("46cm三連装砲")], "value": -7, "married_value": -4.2 },
+                    { "gear_ids": [gear_id!("41cm連装砲")], "night": true, "scale": "any", "value": 4 },
+                ],
+            },
+        ]))
+        .unwrap();
+
+        let mut ship = Ship {
+            ship_type: ShipType::BB,
+            ctype: ctype!("長門型"),
+            level: 99,
+            ..Default::default()
+        };
+
+        for gear_id in [
+            gear_id!("46cm三連装砲"),
+            gear_id!("46cm三連装砲"),
+            gear_id!("41cm連装砲"),
+        ] {
+            ship.gears.push(Gear {
+                gear_id,
+                ..Default::default()
+            });
         }
-        _ => {}
-    };
 
-    r
+        assert_eq!(gunfit_accuracy(&defs, &ship, false), -7.0 * 2.0_f64.sqrt());
+        assert_eq!(
+            gunfit_accuracy(&defs, &ship, true),
+            -7.0 * 2.0_f64.sqrt() + 4.0
+        );
+
+        ship.level = 100;
+        assert_eq!(gunfit_accuracy(&defs, &ship, false), -4.2 * 2.0_f64.sqrt());
+
+        let info = gunfit_accuracy_info(&defs, &ship, true);
+        let values = info
+            .gears
+            .iter()
+            .map(|item| (item.index, item.value))
+            .collect::<Vec<_>>();
+        let share = -4.2 * 2.0_f64.sqrt() / 2.0;
+        assert_eq!(values, vec![(0, share), (1, share), (2, 4.0)]);
+        assert!(!info.overridden);
+
+        ship.state.night_gunfit_accuracy = Some(10.0.into());
+        let info = gunfit_accuracy_info(&defs, &ship, true);
+        assert_eq!(info.value, 10.0);
+        assert!(info.overridden);
+        assert_eq!(gunfit_accuracy(&defs, &ship, false), -4.2 * 2.0_f64.sqrt());
+    }
+
+    #[test]
+    fn test_battleship_gunfit_accuracy_is_day_only() {
+        let mut ship = Ship {
+            ship_type: ShipType::BB,
+            ctype: ctype!("長門型"),
+            level: 99,
+            ..Default::default()
+        };
+        ship.gears.push(Gear {
+            gear_id: gear_id!("46cm三連装砲"),
+            ..Default::default()
+        });
+
+        let defs = reference_gunfit_accuracy_defs();
+        assert_eq!(gunfit_accuracy(defs, &ship, false), -7.0);
+        assert_eq!(gunfit_accuracy(defs, &ship, true), 0.0);
+
+        ship.state.night_gunfit_accuracy = Some(5.0.into());
+        let info = gunfit_accuracy_info(defs, &ship, true);
+        assert_eq!(info.value, 5.0);
+        assert!(info.overridden);
+    }
 }
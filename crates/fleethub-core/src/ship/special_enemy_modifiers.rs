@@ -1,889 +1,1751 @@
+use std::{str::FromStr, sync::LazyLock};
+
+use fasteval::{EvalNamespace, bool_to_f64};
+
+use SpecialEnemyAttackCategory::{LandBase, Night, Shelling, SupportShelling, Torpedo};
+use SpecialEnemyModField::*;
+
 use crate::{
-    attack::LandingCraftModifiers,
+    gear::Gear,
     ship::Ship,
     types::{
-        AttackPowerModifier, AttackType, GearAttr, GearType, NightAttackType, ShellingType,
-        ShipType, SpecialEnemyModifiers, SpecialEnemyType, ctype, gear_id, matches_gear_id,
+        AttackPowerModifier, AttackType, CompiledEvaler, GearAttr, GearType, NightAttackType,
+        ShellingType, ShipType, SpecialEnemyAttackCategory, SpecialEnemyModDef,
+        SpecialEnemyModField, SpecialEnemyModifiers, SpecialEnemyType, StarsBonusTermDef, ctype,
+        gear_id,
     },
 };
 
-impl Ship {
-    /// 特効補正
-    pub fn special_enemy_mods(
-        &self,
-        special_enemy_type: SpecialEnemyType,
-        attack_type: AttackType,
-    ) -> SpecialEnemyModifiers {
-        special_enemy_modifiers(self, special_enemy_type, attack_type)
+/// 特効補正の規則で使う変数と関数
+///
+/// 装備属性の名前は、その属性を持つ装備の個数になる
+fn special_enemy_ns(ship: &Ship, is_aerial: bool) -> impl EvalNamespace + '_ {
+    move |name: &str, args: Vec<f64>| -> Option<f64> {
+        let gears = &ship.gears;
+
+        let result = match name {
+            "is_aerial" => bool_to_f64!(is_aerial),
+            "nationality" => ship.master.nationality.into(),
+            "ship_type_in" => bool_to_f64!(args.contains(&(ship.ship_type as u8).into())),
+            "ship_class_in" => bool_to_f64!(args.contains(&ship.ctype.into())),
+            "count" => args
+                .iter()
+                .map(|gear_id| gears.count(*gear_id as u16))
+                .sum::<usize>() as f64,
+            "count_gear_type" => args
+                .iter()
+                .map(|gear_type| gears.count_type((*gear_type).into()))
+                .sum::<usize>() as f64,
+            _ => {
+                let attr = GearAttr::from_str(name).ok()?;
+                gears.count_attr(attr) as f64
+            }
+        };
+
+        Some(result)
     }
 }
 
-macro_rules! apply_mod_impl {
-    ($name1:expr, a, $v:expr) => {
-        $name1.a *= $v
-    };
+/// 基地航空隊の特効補正の規則で使う変数と関数
+///
+/// 機体のみを評価するため、艦に関する変数と関数は定義せず、その規則は適用されない
+fn land_base_special_enemy_ns(plane: &Gear) -> impl EvalNamespace + '_ {
+    move |name: &str, args: Vec<f64>| -> Option<f64> {
+        let result = match name {
+            "is_aerial" => 1.0,
+            "count" => bool_to_f64!(args.contains(&plane.gear_id.into())),
+            "count_gear_type" => bool_to_f64!(args.contains(&(plane.gear_type as u8).into())),
+            "nationality" | "ship_type_in" | "ship_class_in" => return None,
+            _ => {
+                let attr = GearAttr::from_str(name).ok()?;
+                bool_to_f64!(plane.has_attr(attr))
+            }
+        };
 
-    ($name1:expr, b, $v:expr) => {
-        $name1.b += $v
-    };
+        Some(result)
+    }
 }
 
-macro_rules! apply_mod {
-    ($name1:expr, $name2:ident, $e:expr, [ $v1:expr ]) => {
-        if $e >= 1 {
-            apply_mod_impl!($name1, $name2, $v1)
-        }
-    };
+fn field_mut(
+    mods: &mut SpecialEnemyModifiers,
+    field: SpecialEnemyModField,
+) -> &mut AttackPowerModifier {
+    match field {
+        PrecapGeneralMod => &mut mods.precap_general_mod,
+        StypeMod => &mut mods.stype_mod,
+        LandingCraftSynergyMod => &mut mods.landing_craft_synergy_mod,
+        TokuDaihatsuTankMod => &mut mods.toku_daihatsu_tank_mod,
+        M4a1ddMod => &mut mods.m4a1dd_mod,
+        HoniMod => &mut mods.honi_mod,
+        TokuDaihatsuChihaMod => &mut mods.toku_daihatsu_chiha_mod,
+        TokuDaihatsuChihaKaiMod => &mut mods.toku_daihatsu_chiha_kai_mod,
+        ArmyInfantryMod => &mut mods.army_infantry_mod,
+        ArmyInfantryChihaKaiMod => &mut mods.army_infantry_chiha_kai_mod,
+        T97TankChihaMod => &mut mods.t97_tank_chiha_mod,
+        T97TankChihaKaiMod => &mut mods.t97_tank_chiha_kai_mod,
+        LandingForcesMod => &mut mods.landing_forces_mod,
+        LandingForcesGroupMod => &mut mods.landing_forces_group_mod,
+        LandingForcesT4Mod => &mut mods.landing_forces_t4_mod,
+        LandingForcesT4kaiMod => &mut mods.landing_forces_t4kai_mod,
+        T4TankGroupMod => &mut mods.t4_tank_group_mod,
+        T4TankKaiMod => &mut mods.t4_tank_kai_mod,
+        Panzer2Mod => &mut mods.panzer2_mod,
+        R35FrenchMod => &mut mods.r35_french_mod,
+        PostcapGeneralMod => &mut mods.postcap_general_mod,
+        // 対象になった時点で補正が有効になる
+        PtMod => mods.pt_mod.get_or_insert_with(Default::default),
+        PtAccuracyMod => &mut mods.pt_accuracy_mod,
+        PtShipTypeAccuracyMod => &mut mods.pt_ship_type_accuracy_mod,
+        PtEquipmentAccuracyMod => &mut mods.pt_equipment_accuracy_mod,
+        PtNightAccuracyMod => &mut mods.pt_night_accuracy_mod,
+    }
+}
 
-    ($name1:expr, $name2:ident, $e:expr, [ $v1:expr, $v2:expr ]) => {
-        match $e {
-            0 => (),
-            1 => apply_mod_impl!($name1, $name2, $v1),
-            2 => apply_mod_impl!($name1, $name2, $v2),
-            _ => apply_mod_impl!($name1, $name2, $v2),
-        }
-    };
+/// 個数に対応する値、個数が表より多い場合は最後の値
+fn pick(values: &[f64], count: usize) -> Option<f64> {
+    let index = count.checked_sub(1)?;
+    values.get(index).or(values.last()).copied()
+}
 
-    ($name1:expr, $name2:ident, $e:expr, [ $v1:expr, $v2:expr, $v3:expr ]) => {
-        match $e {
-            0 => (),
-            1 => apply_mod_impl!($name1, $name2, $v1),
-            2 => apply_mod_impl!($name1, $name2, $v2),
-            3 => apply_mod_impl!($name1, $name2, $v3),
-            _ => apply_mod_impl!($name1, $name2, $v3),
-        }
-    };
+impl StarsBonusTermDef {
+    fn matches(&self, gear: &Gear) -> bool {
+        self.gear_ids.contains(&gear.gear_id) || self.gear_types.contains(&gear.gear_type)
+    }
+}
 
-    ($name1:expr, $name2:ident, $e:expr, [ $v1:expr, $v2:expr, $v3:expr, $v4:expr ]) => {
-        match $e {
-            0 => (),
-            1 => apply_mod_impl!($name1, $name2, $v1),
-            2 => apply_mod_impl!($name1, $name2, $v2),
-            3 => apply_mod_impl!($name1, $name2, $v3),
-            4 => apply_mod_impl!($name1, $name2, $v4),
-            _ => apply_mod_impl!($name1, $name2, $v4),
-        }
-    };
+/// `stars_average`は対象の装備の平均改修値
+fn stars_bonus(
+    terms: &[StarsBonusTermDef],
+    stars_average: &impl Fn(&StarsBonusTermDef) -> f64,
+) -> f64 {
+    terms
+        .iter()
+        .fold(1.0, |v, term| v + stars_average(term) / term.divisor)
+}
 
-    ($name1:expr, $name2:ident, $e:expr, [ $v1:expr, $v2:expr, $v3:expr, $v4:expr, $v5:expr ]) => {
-        match $e {
-            0 => (),
-            1 => apply_mod_impl!($name1, $name2, $v1),
-            2 => apply_mod_impl!($name1, $name2, $v2),
-            3 => apply_mod_impl!($name1, $name2, $v3),
-            4 => apply_mod_impl!($name1, $name2, $v4),
-            5 => apply_mod_impl!($name1, $name2, $v5),
-            _ => apply_mod_impl!($name1, $name2, $v5),
-        }
-    };
+fn ship_stars_average(ship: &Ship) -> impl Fn(&StarsBonusTermDef) -> f64 + '_ {
+    |term| {
+        ship.gears
+            .mean_by(|gear| term.matches(gear).then_some(gear.stars as f64))
+            .unwrap_or_default()
+    }
+}
 
-    ($name1:expr, $name2:ident, $e:expr, $v:expr) => {
-        if $e {
-            apply_mod_impl!($name1, $name2, $v);
+fn plane_stars_average(plane: &Gear) -> impl Fn(&StarsBonusTermDef) -> f64 + '_ {
+    |term| {
+        if term.matches(plane) {
+            plane.stars as f64
+        } else {
+            0.0
         }
-    };
+    }
 }
 
-fn anti_pt_imp_modifiers(attacker: &Ship, attack_type: AttackType) -> SpecialEnemyModifiers {
-    let mut mods = SpecialEnemyModifiers::new();
-    let gears = &attacker.gears;
-
-    match attack_type {
-        AttackType::Shelling(_) | AttackType::Night(_) => {
-            let mut pt_mod = AttackPowerModifier::default();
-
-            let small_gun_count = gears.count_type(GearType::SmallMainGun);
-            let sec_gun_count = gears.count_type(GearType::SecondaryGun);
-            let aa_gun_count = gears.count_type(GearType::AntiAirGun);
-            let lookouts_count = gears.count_type(GearType::ShipPersonnel);
-
-            let ab_count = gears.count(gear_id!("装甲艇(AB艇)"));
-            let armed_count = gears.count(gear_id!("武装大発"));
-            let armored_boat_group_count = ab_count + armed_count;
-
-            let armored_boat_group_stars_average = gears
-                .mean_by(|gear| {
-                    (matches_gear_id!(gear.gear_id, "装甲艇(AB艇)" | "武装大発"))
-                        .then(|| gear.stars as f64)
-                })
-                .unwrap_or_default();
-
-            let cb_dive_bomber_count = gears.count_type(GearType::CbDiveBomber);
-            let jet_fighter_bomber_count = gears.count_type(GearType::JetFighterBomber);
-            let has_seaplane = gears.has_type(GearType::SeaplaneBomber)
-                || gears.has_type(GearType::SeaplaneFighter);
-
-            apply_mod!(pt_mod, a, small_gun_count, [1.5, 1.5 * 1.4]);
-            apply_mod!(pt_mod, a, sec_gun_count, [1.3]);
-            apply_mod!(
-                pt_mod,
-                a,
-                cb_dive_bomber_count.max(jet_fighter_bomber_count),
-                [1.4, 1.4 * 1.3]
-            );
-            apply_mod!(pt_mod, a, has_seaplane, 1.2);
-            apply_mod!(pt_mod, a, aa_gun_count, [1.2, 1.2 * 1.2]);
-            apply_mod!(pt_mod, a, lookouts_count, [1.1]);
-            apply_mod!(pt_mod, a, armored_boat_group_count, [1.2, 1.2 * 1.1]);
-
-            pt_mod.a *= 1.0 + armored_boat_group_stars_average / 50.0;
-
-            if attack_type.is_night() {
-                pt_mod.a *= 0.6;
-            }
+impl SpecialEnemyModDef {
+    fn is_target(&self, special_enemy_type: SpecialEnemyType, attack_type: AttackType) -> bool {
+        self.targets.contains(&special_enemy_type)
+            && (self.attack_types.is_empty()
+                || self
+                    .attack_types
+                    .iter()
+                    .any(|category| category.matches(attack_type)))
+    }
 
-            mods.pt_mod = Some(pt_mod);
+    fn apply<T: EvalNamespace>(
+        &self,
+        mods: &mut SpecialEnemyModifiers,
+        ns: &mut T,
+        stars_average: &impl Fn(&StarsBonusTermDef) -> f64,
+    ) {
+        if !self.requires.is_empty() && !self.requires.matches(ns) {
+            return;
         }
 
-        AttackType::Torpedo => {
-            mods.pt_mod = Some(Default::default());
+        let count = if self.count.is_empty() {
+            1
+        } else {
+            self.count.eval(ns).unwrap_or_default() as usize
+        };
+
+        let modifier = field_mut(mods, self.field);
+
+        if let Some(a) = pick(&self.a, count) {
+            modifier.a *= self
+                .stars_bonuses
+                .iter()
+                .fold(a, |a, terms| a * stars_bonus(terms, stars_average));
         }
 
-        _ => (),
+        if let Some(b) = pick(&self.b, count) {
+            modifier.b += b;
+        }
     }
-
-    mods
 }
 
-/// 戦艦夏姫
-fn anti_battleship_summer_princess_modifiers(
+/// 規則を順に適用して特効補正を求める
+pub fn special_enemy_modifiers<'a>(
+    defs: impl IntoIterator<Item = &'a SpecialEnemyModDef>,
     attacker: &Ship,
+    special_enemy_type: SpecialEnemyType,
     attack_type: AttackType,
 ) -> SpecialEnemyModifiers {
-    if !matches!(attack_type, AttackType::Shelling(_) | AttackType::Night(_)) {
-        return Default::default();
-    }
+    let is_aerial = matches!(
+        attack_type,
+        AttackType::Shelling(ShellingType::Aerial) | AttackType::Night(NightAttackType::Aerial)
+    );
 
     let mut mods = SpecialEnemyModifiers::new();
-    let gears = &attacker.gears;
-
-    let has_aa_shell = gears.has_type(GearType::ApShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.2);
-
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    apply_mod!(mods.postcap_general_mod, a, has_seaplane, 1.1);
-
-    let cb_swordfish_count = gears.count_attr(GearAttr::CbSwordfish);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        cb_swordfish_count,
-        [1.15, 1.15 * 1.05]
-    );
+    let mut ns = special_enemy_ns(attacker, is_aerial);
+    let stars_average = ship_stars_average(attacker);
 
-    if matches!(
-        attacker.ctype,
-        ctype!("Bismarck級")
-            | ctype!("Admiral Hipper級")
-            | ctype!("Ark Royal級")
-            | ctype!("Gotland級")
-            | ctype!("Nelson級")
-    ) {
-        mods.postcap_general_mod.a *= 1.1
-    }
+    defs.into_iter()
+        .filter(|def| def.is_target(special_enemy_type, attack_type))
+        .for_each(|def| def.apply(&mut mods, &mut ns, &stars_average));
 
     mods
 }
 
-/// 重巡夏姫
-fn anti_heavy_cruiser_summer_princess_modifiers(
-    attacker: &Ship,
-    attack_type: AttackType,
+/// 基地航空隊の航空攻撃の特効補正
+///
+/// 攻撃種別に`LandBase`を含む規則を、機体のみで評価する
+pub fn land_base_special_enemy_modifiers<'a>(
+    defs: impl IntoIterator<Item = &'a SpecialEnemyModDef>,
+    plane: &Gear,
+    special_enemy_type: SpecialEnemyType,
 ) -> SpecialEnemyModifiers {
-    if !matches!(attack_type, AttackType::Shelling(_) | AttackType::Night(_)) {
-        return Default::default();
-    }
-
     let mut mods = SpecialEnemyModifiers::new();
-    let gears = &attacker.gears;
-
-    let has_aa_shell = gears.has_type(GearType::ApShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.1);
-
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    apply_mod!(mods.postcap_general_mod, a, has_seaplane, 1.15);
-
-    let cb_swordfish_count = gears.count_attr(GearAttr::CbSwordfish);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        cb_swordfish_count,
-        [1.1, 1.1 * 1.1]
-    );
+    let mut ns = land_base_special_enemy_ns(plane);
+    let stars_average = plane_stars_average(plane);
 
-    if matches!(
-        attacker.ctype,
-        ctype!("Bismarck級")
-            | ctype!("Admiral Hipper級")
-            | ctype!("Ark Royal級")
-            | ctype!("Gotland級")
-            | ctype!("Nelson級")
-    ) {
-        mods.postcap_general_mod.a *= 1.1
-    }
+    defs.into_iter()
+        .filter(|def| {
+            def.targets.contains(&special_enemy_type) && def.attack_types.contains(&LandBase)
+        })
+        .for_each(|def| def.apply(&mut mods, &mut ns, &stars_average));
 
     mods
 }
 
-/// 戦艦仏棲姫
-fn anti_french_battleship_princess_modifiers(
-    attacker: &Ship,
-    attack_type: AttackType,
-) -> SpecialEnemyModifiers {
-    if !matches!(attack_type, AttackType::Shelling(_) | AttackType::Night(_)) {
-        return Default::default();
-    }
+/// 組み込みの特効補正の規則
+pub fn reference_special_enemy_mod_defs() -> &'static [SpecialEnemyModDef] {
+    static DEFS: LazyLock<Vec<SpecialEnemyModDef>> =
+        LazyLock::new(create_reference_special_enemy_mod_defs);
+    &DEFS
+}
 
-    let gears = &attacker.gears;
-    let mut mods = SpecialEnemyModifiers::new();
+const SHELLING_OR_NIGHT: &[SpecialEnemyAttackCategory] = &[Shelling, Night];
 
-    let has_aa_shell = gears.has_type(GearType::ApShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.2);
-
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    apply_mod!(mods.postcap_general_mod, a, has_seaplane, 1.1);
-
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        gears.has(gear_id!("Laté 298B")),
-        1.2
-    );
+fn expr(expr: impl Into<String>) -> CompiledEvaler {
+    CompiledEvaler::new(expr.into()).unwrap()
+}
 
-    let dive_bomber_count =
-        gears.count_type(GearType::CbDiveBomber) + gears.count_type(GearType::JetFighterBomber);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        dive_bomber_count,
-        [1.1, 1.1 * 1.15]
-    );
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        attacker.master.nationality == 34,
-        1.15
-    );
+fn count(gear_ids: &[u16]) -> String {
+    format!("count({})", join(gear_ids))
+}
 
-    mods
+fn count_type(gear_types: &[GearType]) -> String {
+    let gear_types = gear_types
+        .iter()
+        .map(|gear_type| *gear_type as u8)
+        .collect::<Vec<_>>();
+    format!("count_gear_type({})", join(&gear_types))
 }
 
-/// 空母夏鬼
-fn anti_summer_aircraft_carrier_demon_modifiers(
-    attacker: &Ship,
-    attack_type: AttackType,
-) -> SpecialEnemyModifiers {
-    if !matches!(attack_type, AttackType::Shelling(_) | AttackType::Night(_)) {
-        return Default::default();
+fn rule(
+    field: SpecialEnemyModField,
+    count: impl Into<String>,
+    a: &[f64],
+    b: &[f64],
+) -> SpecialEnemyModDef {
+    SpecialEnemyModDef {
+        id: String::new(),
+        targets: vec![],
+        attack_types: vec![],
+        requires: Default::default(),
+        count: expr(count),
+        field,
+        a: a.to_vec(),
+        b: b.to_vec(),
+        stars_bonuses: vec![],
     }
+}
 
-    let gears = &attacker.gears;
-    let mut mods = SpecialEnemyModifiers::new();
-
-    let has_aa_shell = gears.has_type(GearType::ApShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.1);
-
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    apply_mod!(mods.postcap_general_mod, a, has_seaplane, 1.1);
-
-    let dive_bomber_count =
-        gears.count_type(GearType::CbDiveBomber) + gears.count_type(GearType::JetFighterBomber);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        dive_bomber_count,
-        [1.1, 1.1 * 1.1]
-    );
+fn when(requires: impl Into<String>, def: SpecialEnemyModDef) -> SpecialEnemyModDef {
+    SpecialEnemyModDef {
+        requires: expr(requires),
+        ..def
+    }
+}
 
-    let cb_swordfish_count = gears.count_attr(GearAttr::CbSwordfish);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        cb_swordfish_count,
-        [1.1, 1.1 * 1.2]
-    );
+/// 攻撃種別は規則側で指定されていればそちらを使う
+fn scope(
+    targets: &[SpecialEnemyType],
+    attack_types: &[SpecialEnemyAttackCategory],
+    defs: Vec<SpecialEnemyModDef>,
+) -> Vec<SpecialEnemyModDef> {
+    defs.into_iter()
+        .map(|def| SpecialEnemyModDef {
+            targets: targets.to_vec(),
+            attack_types: if def.attack_types.is_empty() {
+                attack_types.to_vec()
+            } else {
+                def.attack_types
+            },
+            ..def
+        })
+        .collect()
+}
 
-    if matches!(
-        attacker.ctype,
-        ctype!("Bismarck級")
-            | ctype!("Admiral Hipper級")
-            | ctype!("Ark Royal級")
-            | ctype!("Gotland級")
-            | ctype!("Nelson級")
-    ) {
-        mods.postcap_general_mod.a *= 1.1
-    }
+fn seaplane() -> String {
+    count_type(&[GearType::SeaplaneBomber, GearType::SeaplaneFighter])
+}
 
-    mods
+fn dive_bomber() -> String {
+    count_type(&[GearType::CbDiveBomber, GearType::JetFighterBomber])
 }
 
-/// 泊地水鬼 バカンスmode
-fn anti_anchorage_water_demon_vacation_mode_modifiers(
-    attacker: &Ship,
-    attack_type: AttackType,
-) -> SpecialEnemyModifiers {
-    let mut mods = SpecialEnemyModifiers::new();
-    if !matches!(attack_type, AttackType::Shelling(_) | AttackType::Night(_)) {
-        return mods;
-    }
+fn european_classes() -> String {
+    format!(
+        "ship_class_in({})",
+        join(&[
+            ctype!("Bismarck級"),
+            ctype!("Admiral Hipper級"),
+            ctype!("Ark Royal級"),
+            ctype!("Gotland級"),
+            ctype!("Nelson級"),
+        ])
+    )
+}
 
-    let dlc_mods = LandingCraftModifiers::new(
-        attacker,
-        attack_type,
-        SpecialEnemyType::AnchorageWaterDemonVacationMode,
-    );
-    mods.postcap_general_mod.a *= dlc_mods.postcap;
+fn create_reference_special_enemy_mod_defs() -> Vec<SpecialEnemyModDef> {
+    [
+        ("anti_pt_imp", anti_pt_imp_defs()),
+        (
+            "anti_battleship_summer_princess",
+            anti_battleship_summer_princess_defs(),
+        ),
+        (
+            "anti_heavy_cruiser_summer_princess",
+            anti_heavy_cruiser_summer_princess_defs(),
+        ),
+        (
+            "anti_french_battleship_princess",
+            anti_french_battleship_princess_defs(),
+        ),
+        (
+            "anti_summer_aircraft_carrier_demon",
+            anti_summer_aircraft_carrier_demon_defs(),
+        ),
+        (
+            "anti_european_water_princess",
+            anti_european_water_princess_defs(),
+        ),
+        (
+            "anti_anchorage_water_demon_vacation_mode",
+            anti_anchorage_water_demon_vacation_mode_defs(),
+        ),
+        ("anti_dock_princess", anti_dock_princess_defs()),
+        ("anti_installation", anti_installation_defs()),
+        ("land_base", land_base_defs()),
+    ]
+    .into_iter()
+    .flat_map(|(id, defs)| {
+        defs.into_iter().map(move |def| SpecialEnemyModDef {
+            id: id.to_string(),
+            ..def
+        })
+    })
+    .collect()
+}
 
-    let gears = &attacker.gears;
+fn anti_pt_imp_defs() -> Vec<SpecialEnemyModDef> {
+    let pt = |count: String, a: &[f64]| rule(PtMod, count, a, &[]);
+    let armored_boat_group = [gear_id!("装甲艇(AB艇)"), gear_id!("武装大発")];
 
-    let dive_bomber_count =
-        gears.count_type(GearType::CbDiveBomber) + gears.count_type(GearType::JetFighterBomber);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        dive_bomber_count,
-        [1.4, 1.4 * 1.75]
+    let equipment_accuracy = |count: String, a: &[f64]| rule(PtEquipmentAccuracyMod, count, a, &[]);
+    let light_cruisers = format!(
+        "ship_type_in({})",
+        join(&[ShipType::CL as u8, ShipType::CLT as u8, ShipType::CT as u8])
     );
 
-    let wg42_count = gears.count(gear_id!("WG42 (Wurfgerät 42)"));
-    apply_mod!(mods.postcap_general_mod, a, wg42_count, [1.2, 1.2 * 1.3]);
-
-    let type4_rocket_count = gears.count(gear_id!("艦載型 四式20cm対地噴進砲"));
-    let type4_rocket_cd_count = gears.count(gear_id!("四式20cm対地噴進砲 集中配備"));
-    let type4_rocket_group_count = type4_rocket_count + type4_rocket_cd_count;
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        type4_rocket_group_count,
-        [1.15, 1.15 * 1.4]
-    );
+    let stars_mod = SpecialEnemyModDef {
+        stars_bonuses: vec![vec![StarsBonusTermDef {
+            gear_ids: armored_boat_group.to_vec(),
+            gear_types: vec![],
+            divisor: 50.0,
+        }]],
+        ..rule(PtMod, "", &[1.0], &[])
+    };
 
-    let mortar_count = gears.count(gear_id!("二式12cm迫撃砲改"));
-    let mortar_cd_count = gears.count(gear_id!("二式12cm迫撃砲改 集中配備"));
-    let mortar_group_count = mortar_count + mortar_cd_count;
-    apply_mod!(mods.postcap_general_mod, a, mortar_group_count, [1.1]);
+    [
+        scope(
+            &[SpecialEnemyType::PtImp],
+            SHELLING_OR_NIGHT,
+            vec![
+                pt(count_type(&[GearType::SmallMainGun]), &[1.5, 1.5 * 1.4]),
+                pt(count_type(&[GearType::SecondaryGun]), &[1.3]),
+                pt(
+                    format!(
+                        "max({}, {})",
+                        count_type(&[GearType::CbDiveBomber]),
+                        count_type(&[GearType::JetFighterBomber])
+                    ),
+                    &[1.4, 1.4 * 1.3],
+                ),
+                pt(seaplane(), &[1.2]),
+                pt(count_type(&[GearType::AntiAirGun]), &[1.2, 1.2 * 1.2]),
+                pt(count_type(&[GearType::ShipPersonnel]), &[1.1]),
+                pt(count(&armored_boat_group), &[1.2, 1.2 * 1.1]),
+                stars_mod,
+            ],
+        ),
+        scope(
+            &[SpecialEnemyType::PtImp],
+            &[Night],
+            vec![pt("".into(), &[0.6])],
+        ),
+        scope(
+            &[SpecialEnemyType::PtImp],
+            &[Torpedo],
+            vec![pt("".into(), &[])],
+        ),
+        // 命中補正
+        scope(
+            &[SpecialEnemyType::PtImp],
+            &[Shelling, Night, Torpedo],
+            vec![rule(PtAccuracyMod, "", &[0.42], &[24.0])],
+        ),
+        scope(
+            &[SpecialEnemyType::PtImp],
+            SHELLING_OR_NIGHT,
+            vec![
+                when(
+                    light_cruisers.clone(),
+                    rule(PtShipTypeAccuracyMod, "", &[0.82], &[]),
+                ),
+                when(
+                    format!("!{light_cruisers} && !ship_type_in({})", ShipType::DD as u8),
+                    rule(PtShipTypeAccuracyMod, "", &[0.7], &[]),
+                ),
+                equipment_accuracy(count_type(&[GearType::SmallMainGun]), &[1.3, 1.3 * 1.15]),
+                equipment_accuracy(count_type(&[GearType::AntiAirGun]), &[1.45, 1.45 * 1.35]),
+                equipment_accuracy(count_type(&[GearType::ShipPersonnel]), &[1.75]),
+                equipment_accuracy(count_type(&[GearType::SecondaryGun]), &[1.55]),
+                equipment_accuracy(count(&armored_boat_group), &[1.45, 1.45 * 1.3]),
+                equipment_accuracy(seaplane(), &[1.5]),
+                equipment_accuracy(count_type(&[GearType::CbDiveBomber]), &[1.38, 1.38 * 1.2]),
+            ],
+        ),
+        scope(
+            &[SpecialEnemyType::PtImp],
+            &[Night],
+            vec![rule(PtNightAccuracyMod, "", &[0.7], &[])],
+        ),
+    ]
+    .concat()
+}
 
-    let has_aa_shell = gears.has_type(GearType::AntiAirShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.45);
+/// 戦艦夏姫
+fn anti_battleship_summer_princess_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+
+    scope(
+        &[SpecialEnemyType::BattleshipSummerPrincess],
+        SHELLING_OR_NIGHT,
+        vec![
+            postcap(count_type(&[GearType::ApShell]), &[1.2]),
+            postcap(seaplane(), &[1.1]),
+            postcap("CbSwordfish".into(), &[1.15, 1.15 * 1.05]),
+            postcap(european_classes(), &[1.1]),
+        ],
+    )
+}
 
-    if attacker.ctype == ctype!("大和型") || attacker.ctype == ctype!("長門型") {
-        mods.postcap_general_mod.a *= 1.2;
-    }
+/// 重巡夏姫
+fn anti_heavy_cruiser_summer_princess_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+
+    scope(
+        &[SpecialEnemyType::HeavyCruiserSummerPrincess],
+        SHELLING_OR_NIGHT,
+        vec![
+            postcap(count_type(&[GearType::ApShell]), &[1.1]),
+            postcap(seaplane(), &[1.15]),
+            postcap("CbSwordfish".into(), &[1.1, 1.1 * 1.1]),
+            postcap(european_classes(), &[1.1]),
+        ],
+    )
+}
 
-    mods
+/// 戦艦仏棲姫
+fn anti_french_battleship_princess_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+
+    scope(
+        &[SpecialEnemyType::FrenchBattleshipPrincess],
+        SHELLING_OR_NIGHT,
+        vec![
+            postcap(count_type(&[GearType::ApShell]), &[1.2]),
+            postcap(seaplane(), &[1.1]),
+            postcap(count(&[gear_id!("Laté 298B")]), &[1.2]),
+            postcap(dive_bomber(), &[1.1, 1.1 * 1.15]),
+            postcap("nationality == 34".into(), &[1.15]),
+        ],
+    )
 }
 
-/// 船渠棲姫
-fn anti_dock_princess_modifiers(attacker: &Ship, attack_type: AttackType) -> SpecialEnemyModifiers {
-    if !matches!(
-        attack_type,
-        AttackType::Shelling(_) | AttackType::Night(_) | AttackType::Torpedo
-    ) {
-        return Default::default();
-    }
+/// 空母夏鬼
+fn anti_summer_aircraft_carrier_demon_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+
+    scope(
+        &[SpecialEnemyType::SummerAircraftCarrierDemon],
+        SHELLING_OR_NIGHT,
+        vec![
+            postcap(count_type(&[GearType::ApShell]), &[1.1]),
+            postcap(seaplane(), &[1.1]),
+            postcap(dive_bomber(), &[1.1, 1.1 * 1.1]),
+            postcap("CbSwordfish".into(), &[1.1, 1.1 * 1.2]),
+            postcap(european_classes(), &[1.1]),
+        ],
+    )
+}
 
-    let gears = &attacker.gears;
-    let mut mods = SpecialEnemyModifiers::new();
+/// 欧州水姫
+fn anti_european_water_princess_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+
+    scope(
+        &[SpecialEnemyType::EuropeanWaterPrincess],
+        SHELLING_OR_NIGHT,
+        vec![
+            postcap(count_type(&[GearType::ApShell]), &[1.15]),
+            postcap(seaplane(), &[1.1]),
+            postcap("CbSwordfish".into(), &[1.2, 1.2 * 1.3]),
+            postcap(european_classes(), &[1.15]),
+        ],
+    )
+}
 
-    let dlc_mods =
-        LandingCraftModifiers::new(attacker, attack_type, SpecialEnemyType::DockPrincess);
-    mods.postcap_general_mod.a *= dlc_mods.postcap;
-
-    let dive_bomber_count =
-        gears.count_type(GearType::CbDiveBomber) + gears.count_type(GearType::JetFighterBomber);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        dive_bomber_count,
-        [1.1, 1.1 * 1.1]
-    );
+/// 泊地水鬼 バカンスmode
+fn anti_anchorage_water_demon_vacation_mode_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+    let targets = &[SpecialEnemyType::AnchorageWaterDemonVacationMode];
+
+    let landing_craft_table = LandingCraftTable {
+        a: 1.4,
+        b: 1.15,
+        c: 1.2,
+        dd: 1.4,
+        e: 1.2,
+        ee: 1.4,
+        f: 1.2,
+        g: 1.2,
+        hh: 1.1,
+        i: 1.8,
+        j: 1.0,
+        t2: (2.4, 1.35),
+        lf: (1.0, 1.0, 1.0),
+        cef_ibonus: false,
+    };
 
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    apply_mod!(mods.postcap_general_mod, a, has_seaplane, 1.1);
+    [
+        landing_craft_defs(
+            targets,
+            SHELLING_OR_NIGHT,
+            PostcapGeneralMod,
+            landing_craft_table,
+        ),
+        scope(
+            targets,
+            SHELLING_OR_NIGHT,
+            vec![
+                postcap(dive_bomber(), &[1.4, 1.4 * 1.75]),
+                postcap(count(&[gear_id!("WG42 (Wurfgerät 42)")]), &[1.2, 1.2 * 1.3]),
+                postcap(type4_rocket_group(), &[1.15, 1.15 * 1.4]),
+                postcap(mortar_group(), &[1.1]),
+                postcap(count_type(&[GearType::AntiAirShell]), &[1.45]),
+                postcap(
+                    format!("ship_class_in({}, {})", ctype!("大和型"), ctype!("長門型")),
+                    &[1.2],
+                ),
+            ],
+        ),
+    ]
+    .concat()
+}
 
-    let wg42_count = gears.count(gear_id!("WG42 (Wurfgerät 42)"));
-    apply_mod!(mods.postcap_general_mod, a, wg42_count, [1.1, 1.2]);
+/// 船渠棲姫
+fn anti_dock_princess_defs() -> Vec<SpecialEnemyModDef> {
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+    let targets = &[SpecialEnemyType::DockPrincess];
+    let attack_types = &[Shelling, Night, Torpedo];
+
+    let landing_craft_table = LandingCraftTable {
+        a: 1.1,
+        b: 1.15,
+        c: 1.15,
+        dd: 1.15,
+        e: 1.15,
+        ee: 1.15,
+        f: 1.15,
+        g: 1.1,
+        hh: 1.1,
+        i: 1.1,
+        j: 1.4,
+        t2: (1.2, 1.2),
+        lf: (1.0, 1.0, 1.0),
+        cef_ibonus: false,
+    };
 
-    let has_aa_shell = gears.has_type(GearType::AntiAirShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.3);
+    [
+        landing_craft_defs(
+            targets,
+            attack_types,
+            PostcapGeneralMod,
+            landing_craft_table,
+        ),
+        scope(
+            targets,
+            attack_types,
+            vec![
+                postcap(dive_bomber(), &[1.1, 1.1 * 1.1]),
+                postcap(seaplane(), &[1.1]),
+                postcap(count(&[gear_id!("WG42 (Wurfgerät 42)")]), &[1.1, 1.2]),
+                postcap(count_type(&[GearType::AntiAirShell]), &[1.3]),
+                postcap("nationality == 31".into(), &[1.1]),
+            ],
+        ),
+    ]
+    .concat()
+}
 
-    if attacker.master.nationality == 31 {
-        mods.postcap_general_mod.a *= 1.1;
-    }
+/// 基地航空隊の陸上型への補正
+///
+/// 出典: 艦これ攻略wiki「基地航空隊」 https://wikiwiki.jp/kancolle/基地航空隊
+fn land_base_defs() -> Vec<SpecialEnemyModDef> {
+    use SpecialEnemyType::{HarbourSummerPrincess, IsolatedIsland, Pillbox};
+
+    let lb_attacker = || count_type(&[GearType::LbAttacker, GearType::LargeLbAircraft]);
+    let precap = |a: f64| rule(PrecapGeneralMod, lb_attacker(), &[a], &[]);
+
+    [
+        scope(&[Pillbox], &[LandBase], vec![precap(1.6)]),
+        scope(&[IsolatedIsland], &[LandBase], vec![precap(1.18)]),
+        scope(&[HarbourSummerPrincess], &[LandBase], vec![precap(1.1)]),
+    ]
+    .concat()
+}
 
-    mods
+fn type4_rocket_group() -> String {
+    count(&[
+        gear_id!("艦載型 四式20cm対地噴進砲"),
+        gear_id!("四式20cm対地噴進砲 集中配備"),
+    ])
 }
 
-/// 欧州水姫
-fn anti_european_water_princess_modifiers(
-    attacker: &Ship,
-    attack_type: AttackType,
-) -> SpecialEnemyModifiers {
-    if !matches!(attack_type, AttackType::Shelling(_) | AttackType::Night(_)) {
-        return Default::default();
-    }
+fn mortar_group() -> String {
+    count(&[
+        gear_id!("二式12cm迫撃砲改"),
+        gear_id!("二式12cm迫撃砲改 集中配備"),
+    ])
+}
 
-    let mut mods = SpecialEnemyModifiers::new();
-    let gears = &attacker.gears;
-
-    let has_aa_shell = gears.has_type(GearType::ApShell);
-    apply_mod!(mods.postcap_general_mod, a, has_aa_shell, 1.15);
-
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    apply_mod!(mods.postcap_general_mod, a, has_seaplane, 1.1);
-
-    let cb_swordfish_count = gears.count_attr(GearAttr::CbSwordfish);
-    apply_mod!(
-        mods.postcap_general_mod,
-        a,
-        cb_swordfish_count,
-        [1.2, 1.2 * 1.3]
-    );
+/// 陸上型
+fn anti_installation_defs() -> Vec<SpecialEnemyModDef> {
+    use SpecialEnemyType::{
+        HarbourSummerPrincess, IsolatedIsland, NewSupplyDepot, Pillbox, SoftSkinned, SupplyDepot,
+    };
 
-    if matches!(
-        attacker.ctype,
-        ctype!("Bismarck級")
-            | ctype!("Admiral Hipper級")
-            | ctype!("Ark Royal級")
-            | ctype!("Gotland級")
-            | ctype!("Nelson級")
-    ) {
-        mods.postcap_general_mod.a *= 1.15
-    }
+    let precap = |count: String, a: &[f64]| rule(PrecapGeneralMod, count, a, &[]);
+    let postcap = |count: String, a: &[f64]| rule(PostcapGeneralMod, count, a, &[]);
+    let precap_b = |count: String, b: &[f64]| rule(PrecapGeneralMod, count, &[], b);
+    let merge = |field, count: String, a: f64, b: f64| rule(field, count, &[a], &[b]);
+
+    let aa_shell = || count_type(&[GearType::AntiAirShell]);
+    let ap_shell = || count_type(&[GearType::ApShell]);
+    let wg42 = || count(&[gear_id!("WG42 (Wurfgerät 42)")]);
+    // 航空攻撃では対地可能な艦爆のみ数える
+    let dive_bomber = |a: &[f64]| {
+        [
+            when("is_aerial", precap("AntiInstDiveBomber".into(), a)),
+            when("!is_aerial", precap(dive_bomber(), a)),
+        ]
+    };
 
-    mods
+    let toku_dlc = gear_id!("特大発動艇");
+    let t89_tank = gear_id!("大発動艇(八九式中戦車&陸戦隊)");
+    let panzer2 = gear_id!("大発動艇(II号戦車/北アフリカ仕様)");
+    let m4a1dd = gear_id!("M4A1 DD");
+    let ab = gear_id!("装甲艇(AB艇)");
+    let armed = gear_id!("武装大発");
+    let shikon = gear_id!("特大発動艇+戦車第11連隊");
+    let honi1 = gear_id!("特大発動艇+一式砲戦車");
+    let toku_dlc_panzer3 = gear_id!("特大発動艇+Ⅲ号戦車(北アフリカ仕様)");
+    let toku_dlc_panzer3_j = gear_id!("特大発動艇+Ⅲ号戦車J型");
+    let chiha = gear_id!("特大発動艇+チハ");
+    let chiha_kai = gear_id!("特大発動艇+チハ改");
+    let t2_tank = gear_id!("特二式内火艇");
+    let t4_tank = gear_id!("特四式内火艇");
+    let t4_tank_kai = gear_id!("特四式内火艇改");
+    let army_infantry = gear_id!("陸軍歩兵部隊");
+    let t97_tank_chiha = gear_id!("九七式中戦車(チハ)");
+    let t97_tank_chiha_kai = gear_id!("九七式中戦車 新砲塔(チハ改)");
+    let army_infantry_chiha_kai = gear_id!("陸軍歩兵部隊+チハ改");
+    let r35_french = gear_id!("大発動艇(R35&フランス兵)");
+
+    let landing_forces = count(&[
+        army_infantry,
+        t97_tank_chiha,
+        t97_tank_chiha_kai,
+        army_infantry_chiha_kai,
+    ]);
+
+    // 上陸用舟艇シナジー
+    let synergy_a = count(&[armed]);
+    let synergy_b = count(&[ab]);
+    let synergy_c = count(&[
+        gear_id!("大発動艇"),
+        toku_dlc,
+        t89_tank,
+        panzer2,
+        honi1,
+        t4_tank,
+        t4_tank_kai,
+        r35_french,
+    ]);
+    let synergy_d = count(&[shikon, toku_dlc_panzer3, chiha, chiha_kai, t2_tank]);
+    let synergy =
+        |condition: String, a: f64, b: f64| merge(LandingCraftSynergyMod, condition, a, b);
+
+    let without_new_supply_depot = &[
+        SoftSkinned,
+        Pillbox,
+        IsolatedIsland,
+        SupplyDepot,
+        HarbourSummerPrincess,
+    ];
+
+    [
+        // 砲撃支援
+        scope(
+            &[IsolatedIsland, HarbourSummerPrincess],
+            &[SupportShelling],
+            vec![precap(aa_shell(), &[1.75])],
+        ),
+        scope(
+            &[SoftSkinned, SupplyDepot],
+            &[SupportShelling],
+            vec![precap(aa_shell(), &[2.5])],
+        ),
+        // 上陸用舟艇補正
+        landing_craft_defs(
+            &[Pillbox],
+            SHELLING_OR_NIGHT,
+            PrecapGeneralMod,
+            LandingCraftTable {
+                a: 1.8,
+                b: 1.15,
+                c: 1.5,
+                dd: 1.4,
+                e: 1.5,
+                ee: 1.4,
+                f: 1.5,
+                g: 1.3,
+                hh: 1.2,
+                i: 2.0,
+                j: 1.0,
+                t2: (2.4, 1.35),
+                lf: (1.0, 1.0, 1.0),
+                cef_ibonus: false,
+            },
+        ),
+        landing_craft_defs(
+            &[IsolatedIsland],
+            SHELLING_OR_NIGHT,
+            PrecapGeneralMod,
+            LandingCraftTable {
+                a: 1.8,
+                b: 1.15,
+                c: 1.2,
+                dd: 1.4,
+                e: 1.2,
+                ee: 1.4,
+                f: 1.2,
+                g: 1.3,
+                hh: 1.1,
+                i: 1.8,
+                j: 1.0,
+                t2: (2.4, 1.35),
+                lf: (1.0, 1.0, 1.0),
+                cef_ibonus: false,
+            },
+        ),
+        landing_craft_defs(
+            &[HarbourSummerPrincess],
+            SHELLING_OR_NIGHT,
+            PrecapGeneralMod,
+            LandingCraftTable {
+                a: 1.7,
+                b: 1.2,
+                c: 1.6,
+                dd: 1.5,
+                e: 1.6,
+                ee: 1.5,
+                f: 1.6,
+                g: 1.5,
+                hh: 1.1,
+                i: 2.0,
+                j: 1.0,
+                t2: (2.8, 1.5),
+                lf: (1.0, 1.0, 1.0),
+                cef_ibonus: false,
+            },
+        ),
+        landing_craft_defs(
+            &[SoftSkinned, SupplyDepot],
+            SHELLING_OR_NIGHT,
+            PrecapGeneralMod,
+            LandingCraftTable {
+                a: 1.4,
+                b: 1.15,
+                c: 1.5,
+                dd: 1.3,
+                e: 1.5,
+                ee: 1.3,
+                f: 1.5,
+                g: 1.1,
+                hh: 1.1,
+                i: 1.1,
+                j: 1.0,
+                t2: (1.5, 1.2),
+                lf: (1.4, 1.2, 1.1),
+                cef_ibonus: false,
+            },
+        ),
+        landing_craft_defs(
+            &[SupplyDepot, NewSupplyDepot],
+            SHELLING_OR_NIGHT,
+            PostcapGeneralMod,
+            LandingCraftTable {
+                a: 1.7,
+                b: 1.2,
+                c: 1.3,
+                dd: 1.6,
+                e: 1.3,
+                ee: 1.6,
+                f: 1.3,
+                g: 1.5,
+                hh: 1.1,
+                i: 1.2,
+                j: 1.0,
+                t2: (1.7, 1.5),
+                lf: (1.85, 1.45, 1.2),
+                cef_ibonus: true,
+            },
+        ),
+        // 集積地キャップ後補正
+        scope(
+            &[SupplyDepot, NewSupplyDepot],
+            SHELLING_OR_NIGHT,
+            vec![
+                postcap(wg42(), &[1.25, 1.25 * 1.3]),
+                postcap(type4_rocket_group(), &[1.2, 1.2 * 1.4]),
+                postcap(mortar_group(), &[1.15, 1.15 * 1.2]),
+            ],
+        ),
+        scope(
+            without_new_supply_depot,
+            SHELLING_OR_NIGHT,
+            vec![
+                precap_b(wg42(), &[75.0, 110.0, 140.0, 160.0]),
+                precap_b(
+                    count(&[gear_id!("二式12cm迫撃砲改")]),
+                    &[30.0, 55.0, 75.0, 90.0],
+                ),
+                precap_b(
+                    count(&[gear_id!("二式12cm迫撃砲改 集中配備")]),
+                    &[60.0, 110.0, 150.0, 180.0],
+                ),
+                precap_b(
+                    count(&[gear_id!("艦載型 四式20cm対地噴進砲")]),
+                    &[55.0, 115.0, 160.0, 190.0],
+                ),
+                precap_b(
+                    count(&[gear_id!("四式20cm対地噴進砲 集中配備")]),
+                    &[80.0, 170.0, 230.0, 260.0],
+                ),
+                // 艦種補正
+                rule(
+                    StypeMod,
+                    format!("ship_type_in({}, {})", ShipType::SS as u8, ShipType::SSV as u8),
+                    &[],
+                    &[30.0],
+                ),
+                // 特殊上陸艇補正
+                merge(
+                    TokuDaihatsuTankMod,
+                    count(&[shikon, honi1, toku_dlc_panzer3, toku_dlc_panzer3_j]),
+                    1.8,
+                    25.0,
+                ),
+                merge(M4a1ddMod, count(&[m4a1dd]), 1.4, 35.0),
+                merge(HoniMod, count(&[honi1]), 1.3, 42.0),
+                merge(TokuDaihatsuChihaMod, count(&[chiha]), 1.4, 28.0),
+                merge(TokuDaihatsuChihaKaiMod, count(&[chiha_kai]), 1.5, 33.0),
+                merge(
+                    ArmyInfantryMod,
+                    count(&[army_infantry, army_infantry_chiha_kai]),
+                    1.2,
+                    60.0,
+                ),
+                merge(
+                    T97TankChihaMod,
+                    count(&[t97_tank_chiha, t97_tank_chiha_kai]),
+                    1.5,
+                    70.0,
+                ),
+                merge(T97TankChihaKaiMod, count(&[t97_tank_chiha_kai]), 1.5, 50.0),
+                merge(
+                    ArmyInfantryChihaKaiMod,
+                    count(&[army_infantry_chiha_kai]),
+                    1.6,
+                    70.0,
+                ),
+                merge(
+                    LandingForcesMod,
+                    format!("{landing_forces} >= 2"),
+                    2.0,
+                    100.0,
+                ),
+                merge(
+                    LandingForcesGroupMod,
+                    format!(
+                        "{landing_forces} >= 2 && ({} >= 1 || {} >= 3)",
+                        count(&[army_infantry_chiha_kai]),
+                        count(&[
+                            army_infantry,
+                            t97_tank_chiha,
+                            t97_tank_chiha_kai,
+                            t2_tank,
+                            t4_tank,
+                            t4_tank_kai,
+                        ])
+                    ),
+                    3.0,
+                    150.0,
+                ),
+                merge(
+                    LandingForcesT4Mod,
+                    format!("{landing_forces} >= 2 && {} >= 1", count(&[t4_tank])),
+                    1.0,
+                    100.0,
+                ),
+                merge(
+                    LandingForcesT4kaiMod,
+                    format!("{landing_forces} >= 2 && {} >= 1", count(&[t4_tank_kai])),
+                    1.0,
+                    172.0,
+                ),
+                merge(T4TankGroupMod, count(&[t4_tank, t4_tank_kai]), 1.2, 42.0),
+                merge(T4TankKaiMod, count(&[t4_tank_kai]), 1.1, 28.0),
+                merge(Panzer2Mod, count(&[panzer2]), 1.15, 15.0),
+                merge(R35FrenchMod, count(&[r35_french]), 1.2, 20.0),
+                // 上陸用舟艇シナジー
+                synergy(
+                    format!(
+                        "{synergy_a} + {synergy_b} == 1 && {synergy_c} + {synergy_d} >= 1"
+                    ),
+                    1.2,
+                    10.0,
+                ),
+                synergy(
+                    format!(
+                        "{synergy_a} == 1 && {synergy_b} == 1 && {synergy_c} == 1 && {synergy_d} == 0"
+                    ),
+                    1.3,
+                    15.0,
+                ),
+                synergy(
+                    format!(
+                        "{synergy_a} == 1 && {synergy_b} == 1 && {synergy_c} == 0 && {synergy_d} == 1"
+                    ),
+                    1.4,
+                    20.0,
+                ),
+                synergy(
+                    format!(
+                        "{synergy_a} == 1 && {synergy_b} == 1 && {synergy_c} + {synergy_d} >= 2"
+                    ),
+                    1.5,
+                    25.0,
+                ),
+            ],
+        ),
+        scope(
+            &[Pillbox],
+            SHELLING_OR_NIGHT,
+            [
+                vec![
+                    precap(ap_shell(), &[1.85]),
+                    precap(wg42(), &[1.6, 1.6 * 1.7]),
+                    precap(type4_rocket_group(), &[1.5, 1.5 * 1.8]),
+                    precap(mortar_group(), &[1.3, 1.3 * 1.5]),
+                    precap(seaplane(), &[1.5]),
+                ],
+                dive_bomber(&[1.5, 1.5 * 2.0]).into(),
+                vec![precap(
+                    format!("ship_type_in({}, {})", ShipType::DD as u8, ShipType::CL as u8),
+                    &[1.4],
+                )],
+            ]
+            .concat(),
+        ),
+        scope(
+            &[IsolatedIsland],
+            SHELLING_OR_NIGHT,
+            [
+                vec![
+                    precap(aa_shell(), &[1.75]),
+                    precap(wg42(), &[1.4, 1.4 * 1.5]),
+                    precap(type4_rocket_group(), &[1.3, 1.3 * 1.65]),
+                    precap(mortar_group(), &[1.2, 1.2 * 1.4]),
+                ],
+                dive_bomber(&[1.4, 1.4 * 1.75]).into(),
+            ]
+            .concat(),
+        ),
+        scope(
+            &[HarbourSummerPrincess],
+            SHELLING_OR_NIGHT,
+            [
+                vec![
+                    precap(aa_shell(), &[1.75]),
+                    precap(ap_shell(), &[1.3]),
+                    precap(wg42(), &[1.4, 1.4 * 1.2]),
+                    precap(type4_rocket_group(), &[1.25, 1.25 * 1.4]),
+                    precap(mortar_group(), &[1.1, 1.1 * 1.15]),
+                    precap(seaplane(), &[1.3]),
+                ],
+                dive_bomber(&[1.3, 1.3 * 1.2]).into(),
+            ]
+            .concat(),
+        ),
+        scope(
+            &[SoftSkinned, SupplyDepot],
+            SHELLING_OR_NIGHT,
+            vec![
+                precap(aa_shell(), &[2.5]),
+                precap(wg42(), &[1.3, 1.3 * 1.4]),
+                precap(type4_rocket_group(), &[1.25, 1.25 * 1.5]),
+                precap(mortar_group(), &[1.2, 1.2 * 1.3]),
+                precap(seaplane(), &[1.2]),
+            ],
+        ),
+    ]
+    .concat()
 }
 
-/// 特効補正
-fn special_enemy_modifiers(
-    attacker: &Ship,
-    special_enemy_type: SpecialEnemyType,
-    attack_type: AttackType,
-) -> SpecialEnemyModifiers {
-    match special_enemy_type {
-        SpecialEnemyType::PtImp => {
-            return anti_pt_imp_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::BattleshipSummerPrincess => {
-            return anti_battleship_summer_princess_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::HeavyCruiserSummerPrincess => {
-            return anti_heavy_cruiser_summer_princess_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::AnchorageWaterDemonVacationMode => {
-            return anti_anchorage_water_demon_vacation_mode_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::FrenchBattleshipPrincess => {
-            return anti_french_battleship_princess_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::DockPrincess => {
-            return anti_dock_princess_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::SummerAircraftCarrierDemon => {
-            return anti_summer_aircraft_carrier_demon_modifiers(attacker, attack_type);
-        }
-        SpecialEnemyType::EuropeanWaterPrincess => {
-            return anti_european_water_princess_modifiers(attacker, attack_type);
+/// 上陸用舟艇補正の係数
+struct LandingCraftTable {
+    a: f64,
+    b: f64,
+    c: f64,
+    dd: f64,
+    e: f64,
+    ee: f64,
+    f: f64,
+    g: f64,
+    hh: f64,
+    i: f64,
+    j: f64,
+    t2: (f64, f64),
+    lf: (f64, f64, f64),
+    /// c, e, f に改修補正が掛かる
+    cef_ibonus: bool,
+}
+
+/// 上陸用舟艇補正の規則
+///
+/// キャップ前補正のg, hhは昼戦のみ
+fn landing_craft_defs(
+    targets: &[SpecialEnemyType],
+    attack_types: &[SpecialEnemyAttackCategory],
+    field: SpecialEnemyModField,
+    table: LandingCraftTable,
+) -> Vec<SpecialEnemyModDef> {
+    let is_postcap = field == PostcapGeneralMod;
+
+    let ab = gear_id!("装甲艇(AB艇)");
+    let armed = gear_id!("武装大発");
+    let honi1 = gear_id!("特大発動艇+一式砲戦車");
+    let toku_dlc_panzer3 = gear_id!("特大発動艇+Ⅲ号戦車(北アフリカ仕様)");
+    let toku_dlc_panzer3_j = gear_id!("特大発動艇+Ⅲ号戦車J型");
+    let chiha = gear_id!("特大発動艇+チハ");
+    let chiha_kai = gear_id!("特大発動艇+チハ改");
+    let t2_tank = gear_id!("特二式内火艇");
+    let t4_tank = gear_id!("特四式内火艇");
+    let t4_tank_kai = gear_id!("特四式内火艇改");
+    let army_infantry = gear_id!("陸軍歩兵部隊");
+    let t97_tank_chiha = gear_id!("九七式中戦車(チハ)");
+    let t97_tank_chiha_kai = gear_id!("九七式中戦車 新砲塔(チハ改)");
+    let army_infantry_chiha_kai = gear_id!("陸軍歩兵部隊+チハ改");
+
+    let t4_tank_group = count(&[t4_tank, t4_tank_kai]);
+    let landing_forces = count(&[
+        army_infantry,
+        t97_tank_chiha,
+        t97_tank_chiha_kai,
+        army_infantry_chiha_kai,
+    ]);
+    let c_count = count(&[
+        gear_id!("大発動艇(八九式中戦車&陸戦隊)"),
+        honi1,
+        toku_dlc_panzer3,
+        toku_dlc_panzer3_j,
+        army_infantry,
+        army_infantry_chiha_kai,
+    ]);
+    let armored_boat_group = count(&[ab, armed]);
+
+    let ibonus1 = vec![
+        StarsBonusTermDef {
+            gear_ids: vec![],
+            gear_types: vec![GearType::LandingCraft],
+            divisor: 50.0,
+        },
+        StarsBonusTermDef {
+            gear_ids: vec![t4_tank, t4_tank_kai],
+            gear_types: vec![],
+            divisor: 50.0,
+        },
+    ];
+    let ibonus2 = vec![StarsBonusTermDef {
+        gear_ids: vec![t2_tank],
+        gear_types: vec![],
+        divisor: 30.0,
+    }];
+
+    let factor = |count: String, a: f64| rule(field, count, &[a], &[]);
+    let with_stars =
+        |def: SpecialEnemyModDef, stars_bonuses: Vec<Vec<StarsBonusTermDef>>| SpecialEnemyModDef {
+            stars_bonuses,
+            ..def
+        };
+    let day_only = |def: SpecialEnemyModDef| {
+        if is_postcap {
+            def
+        } else {
+            SpecialEnemyModDef {
+                attack_types: vec![Shelling],
+                ..def
+            }
         }
-        SpecialEnemyType::None => {
-            return Default::default();
+    };
+    let cef_stars = |def: SpecialEnemyModDef| {
+        if table.cef_ibonus {
+            with_stars(def, vec![ibonus1.clone()])
+        } else {
+            def
         }
-        _ => (),
-    }
+    };
 
-    match attack_type {
-        AttackType::SupportShelling(_) => {
-            return anti_inst_support_shelling_modifiers(attacker, special_enemy_type);
-        }
+    let (t2_1, t2_2) = table.t2;
+    let (lf1, lf2, lf3) = table.lf;
+
+    let defs = vec![
+        with_stars(
+            factor(
+                format!(
+                    "{} + {t4_tank_group} + {landing_forces}",
+                    count_type(&[GearType::LandingCraft])
+                ),
+                table.a,
+            ),
+            vec![ibonus1.clone(), ibonus2],
+        ),
+        factor(
+            count(&[
+                gear_id!("特大発動艇"),
+                toku_dlc_panzer3,
+                toku_dlc_panzer3_j,
+                army_infantry,
+                army_infantry_chiha_kai,
+            ]),
+            table.b,
+        ),
+        cef_stars(factor(c_count.clone(), table.c)),
+        factor(
+            format!(
+                "{c_count} + {} >= 2",
+                count(&[chiha, chiha_kai, t97_tank_chiha, t97_tank_chiha_kai])
+            ),
+            table.dd,
+        ),
+        cef_stars(factor(
+            count(&[gear_id!("大発動艇(II号戦車/北アフリカ仕様)")]),
+            table.e,
+        )),
+        factor(
+            format!(
+                "{} >= 2",
+                count(&[gear_id!("大発動艇(II号戦車/北アフリカ仕様)")])
+            ),
+            table.ee,
+        ),
+        cef_stars(factor(
+            count(&[gear_id!("大発動艇(R35&フランス兵)")]),
+            table.f,
+        )),
+        day_only(factor(armored_boat_group.clone(), table.g)),
+        day_only(factor(
+            format!("{armored_boat_group} >= 2 || {t4_tank_group} >= 2"),
+            table.hh,
+        )),
+        factor(
+            count(&[
+                gear_id!("M4A1 DD"),
+                chiha_kai,
+                toku_dlc_panzer3_j,
+                t97_tank_chiha_kai,
+                army_infantry_chiha_kai,
+            ]),
+            table.i,
+        ),
+        factor(
+            count(&[
+                gear_id!("特大発動艇+戦車第11連隊"),
+                honi1,
+                toku_dlc_panzer3,
+                toku_dlc_panzer3_j,
+            ]),
+            table.j,
+        ),
+        // 1: 1つ目の条件のみ, 2: 2つ目の条件のみ, 3: 両方
+        rule(
+            field,
+            format!(
+                "({t2} >= 1) + 2 * ({t2} >= 2 || {} >= 1)",
+                count(&[t4_tank_kai]),
+                t2 = count(&[t2_tank]),
+            ),
+            &[t2_1, t2_2, t2_1 * t2_2],
+            &[],
+        ),
+        rule(
+            field,
+            landing_forces,
+            &[lf1, lf1 * lf2, lf1 * lf2 * lf3],
+            &[],
+        ),
+    ];
+
+    // 1.0 の補正は省く
+    let defs = defs
+        .into_iter()
+        .filter(|def| def.a.iter().any(|a| *a != 1.0) || !def.stars_bonuses.is_empty())
+        .collect();
+
+    scope(targets, attack_types, defs)
+}
 
-        AttackType::Torpedo | AttackType::Asw(_) => {
-            return Default::default();
-        }
+#[cfg(test)]
+mod baseline;
 
-        AttackType::Shelling(_) | AttackType::Night(_) => (),
-    }
+#[cfg(test)]
+mod test {
+    use rand::prelude::*;
 
-    let mut mods = SpecialEnemyModifiers::new();
-    let dlc_mods = LandingCraftModifiers::new(attacker, attack_type, special_enemy_type);
-    mods.precap_general_mod.a *= dlc_mods.precap;
-    mods.postcap_general_mod.a *= dlc_mods.postcap;
-
-    let gears = &attacker.gears;
-
-    let has_seaplane = gears.has_by(|gear| {
-        matches!(
-            gear.gear_type,
-            GearType::SeaplaneBomber | GearType::SeaplaneFighter
-        )
-    });
-    let has_aa_shell = gears.has_type(GearType::AntiAirShell);
-    let has_ap_shell = gears.has_type(GearType::ApShell);
-
-    let dive_bomber_count = if matches!(
-        attack_type,
-        AttackType::Shelling(ShellingType::Aerial) | AttackType::Night(NightAttackType::Aerial)
-    ) {
-        gears.count_attr(GearAttr::AntiInstDiveBomber)
-    } else {
-        gears.count_type(GearType::CbDiveBomber) + gears.count_type(GearType::JetFighterBomber)
+    use super::*;
+    use crate::{
+        gear::Gear,
+        test::rng,
+        types::{AswAttackType, SupportShellingType},
     };
 
-    let wg42_count = gears.count(gear_id!("WG42 (Wurfgerät 42)"));
-    let type4_rocket_count = gears.count(gear_id!("艦載型 四式20cm対地噴進砲"));
-    let type4_rocket_cd_count = gears.count(gear_id!("四式20cm対地噴進砲 集中配備"));
-    let type4_rocket_group_count = type4_rocket_count + type4_rocket_cd_count;
-    let mortar_count = gears.count(gear_id!("二式12cm迫撃砲改"));
-    let mortar_cd_count = gears.count(gear_id!("二式12cm迫撃砲改 集中配備"));
-    let mortar_group_count = mortar_count + mortar_cd_count;
-
-    let toku_dlc_count = gears.count(gear_id!("特大発動艇"));
-    let t89_tank_count = gears.count(gear_id!("大発動艇(八九式中戦車&陸戦隊)"));
-    let panzer2_count = gears.count(gear_id!("大発動艇(II号戦車/北アフリカ仕様)"));
-    let m4a1dd_count = gears.count(gear_id!("M4A1 DD"));
-    let ab_count = gears.count(gear_id!("装甲艇(AB艇)"));
-    let armed_count = gears.count(gear_id!("武装大発"));
-    let shikon_count = gears.count(gear_id!("特大発動艇+戦車第11連隊"));
-    let honi1_count = gears.count(gear_id!("特大発動艇+一式砲戦車"));
-    let toku_dlc_panzer3_count = gears.count(gear_id!("特大発動艇+Ⅲ号戦車(北アフリカ仕様)"));
-    let toku_dlc_panzer3_j_count = gears.count(gear_id!("特大発動艇+Ⅲ号戦車J型"));
-    let chiha_count = gears.count(gear_id!("特大発動艇+チハ"));
-    let chiha_kai_count = gears.count(gear_id!("特大発動艇+チハ改"));
-    let t2_tank_count = gears.count(gear_id!("特二式内火艇"));
-    let t4_tank_count = gears.count(gear_id!("特四式内火艇"));
-    let t4_tank_kai_count = gears.count(gear_id!("特四式内火艇改"));
-    let army_infantry_count = gears.count(gear_id!("陸軍歩兵部隊"));
-    let t97_tank_chiha_count = gears.count(gear_id!("九七式中戦車(チハ)"));
-    let t97_tank_chiha_kai_count = gears.count(gear_id!("九七式中戦車 新砲塔(チハ改)"));
-    let army_infantry_chiha_kai_count = gears.count(gear_id!("陸軍歩兵部隊+チハ改"));
-    let r35_french_count = gears.count(gear_id!("大発動艇(R35&フランス兵)"));
-
-    let dlc_sp1_count =
-        shikon_count + honi1_count + toku_dlc_panzer3_count + toku_dlc_panzer3_j_count;
-    let dlc_sp2_count = m4a1dd_count;
-    let dlc_sp3_count = honi1_count;
-    let dlc_sp4_count = chiha_count;
-    let dlc_sp5_count = chiha_kai_count;
-    let landing_forces_count = army_infantry_count
-        + t97_tank_chiha_count
-        + t97_tank_chiha_kai_count
-        + army_infantry_chiha_kai_count;
-
-    // 集積地キャップ後補正
-    if matches!(
-        special_enemy_type,
-        SpecialEnemyType::SupplyDepot | SpecialEnemyType::NewSupplyDepot
-    ) {
-        apply_mod!(mods.postcap_general_mod, a, wg42_count, [1.25, 1.25 * 1.3]);
-        apply_mod!(
-            mods.postcap_general_mod,
-            a,
-            type4_rocket_group_count,
-            [1.2, 1.2 * 1.4]
-        );
-        apply_mod!(
-            mods.postcap_general_mod,
-            a,
-            mortar_group_count,
-            [1.15, 1.15 * 1.2]
-        );
-    }
+    #[test]
+    fn test_special_enemy_mod_def() {
+        let defs: Vec<SpecialEnemyModDef> = serde_json::from_value(serde_json::json!([
+            {
+                "targets": ["DockPrincess"],
+                "attack_types": ["Shelling"],
+                "count": format!("count({})", gear_id!("WG42 (Wurfgerät 42)")),
+                "field": "precap_general_mod",
+                "a": [1.2, 1.5],
+                "b": [10, 20],
+            },
+            {
+                "targets": ["DockPrincess"],
+                "requires": "CbSwordfish >= 1",
+                "field": "postcap_general_mod",
+                "a": [1.1],
+                "stars_bonuses": [[{ "gear_types": ["LandingCraft"], "divisor": 50 }]],
+            },
+            {
+                "targets": ["PtImp"],
+                "field": "pt_mod",
+            },
+        ]))
+        .unwrap();
+
+        let mut ship = Ship::default();
+
+        for _ in 0..3 {
+            ship.gears.push(Gear {
+                gear_id: gear_id!("WG42 (Wurfgerät 42)"),
+                ..Default::default()
+            });
+        }
 
-    if special_enemy_type == SpecialEnemyType::NewSupplyDepot {
-        return mods;
-    }
+        let shelling = AttackType::Shelling(Default::default());
+        let night = AttackType::Night(Default::default());
 
-    apply_mod!(
-        mods.precap_general_mod,
-        b,
-        wg42_count,
-        [75.0, 110.0, 140.0, 160.0]
-    );
-    apply_mod!(
-        mods.precap_general_mod,
-        b,
-        mortar_count,
-        [30.0, 55.0, 75.0, 90.0]
-    );
-    apply_mod!(
-        mods.precap_general_mod,
-        b,
-        mortar_cd_count,
-        [60.0, 110.0, 150.0, 180.0]
-    );
-    apply_mod!(
-        mods.precap_general_mod,
-        b,
-        type4_rocket_count,
-        [55.0, 115.0, 160.0, 190.0]
-    );
-    apply_mod!(
-        mods.precap_general_mod,
-        b,
-        type4_rocket_cd_count,
-        [80.0, 170.0, 230.0, 260.0]
-    );
+        let mods = special_enemy_modifiers(&defs, &ship, SpecialEnemyType::DockPrincess, shelling);
+        assert_eq!(mods.precap_general_mod, (1.5, 20.0).into());
+        assert_eq!(mods.postcap_general_mod, Default::default());
+        assert_eq!(mods.pt_mod, None);
 
-    // 艦種補正
-    apply_mod!(
-        mods.stype_mod,
-        b,
-        matches!(attacker.ship_type, ShipType::SS | ShipType::SSV),
-        30.0
-    );
+        let mods = special_enemy_modifiers(&defs, &ship, SpecialEnemyType::DockPrincess, night);
+        assert_eq!(mods, Default::default());
 
-    // 特殊上陸艇補正
-    {
-        if dlc_sp1_count >= 1 {
-            mods.toku_daihatsu_tank_mod.merge(1.8, 25.0);
-        }
+        ship.gears.push(Gear {
+            gear_type: GearType::LandingCraft,
+            stars: 5,
+            attrs: GearAttr::CbSwordfish.into(),
+            ..Default::default()
+        });
 
-        if dlc_sp2_count >= 1 {
-            mods.m4a1dd_mod.merge(1.4, 35.0);
-        }
+        let mods = special_enemy_modifiers(&defs, &ship, SpecialEnemyType::DockPrincess, night);
+        assert_eq!(mods.postcap_general_mod, (1.1 * 1.1, 0.0).into());
 
-        if dlc_sp3_count >= 1 {
-            mods.honi_mod.merge(1.3, 42.0);
-        }
+        let mods = special_enemy_modifiers(&defs, &ship, SpecialEnemyType::PtImp, night);
+        assert_eq!(mods.pt_mod, Some(Default::default()));
+    }
 
-        if dlc_sp4_count >= 1 {
-            mods.toku_daihatsu_chiha_mod.merge(1.4, 28.0);
-        }
+    #[test]
+    fn test_anti_pt_imp_accuracy_modifiers() {
+        let defs = reference_special_enemy_mod_defs();
+        let shelling = AttackType::Shelling(Default::default());
+        let night = AttackType::Night(Default::default());
 
-        if dlc_sp5_count >= 1 {
-            mods.toku_daihatsu_chiha_kai_mod.merge(1.5, 33.0);
-        }
+        let mut ship = Ship {
+            ship_type: ShipType::CL,
+            ..Default::default()
+        };
 
-        if army_infantry_count + army_infantry_chiha_kai_count >= 1 {
-            mods.army_infantry_mod.merge(1.2, 60.0);
+        for gear_type in [
+            GearType::SmallMainGun,
+            GearType::SmallMainGun,
+            GearType::AntiAirGun,
+        ] {
+            ship.gears.push(Gear {
+                gear_type,
+                ..Default::default()
+            });
         }
 
-        if t97_tank_chiha_count + t97_tank_chiha_kai_count >= 1 {
-            mods.t97_tank_chiha_mod.merge(1.5, 70.0);
-        }
+        let mods = special_enemy_modifiers(defs, &ship, SpecialEnemyType::PtImp, shelling);
+        assert_eq!(mods.pt_accuracy_mod, (0.42, 24.0).into());
+        assert_eq!(mods.pt_ship_type_accuracy_mod.a, 0.82);
+        assert_eq!(mods.pt_equipment_accuracy_mod.a, 1.3 * 1.15 * 1.45);
+        assert_eq!(mods.pt_night_accuracy_mod.a, 1.0);
 
-        if t97_tank_chiha_kai_count >= 1 {
-            mods.t97_tank_chiha_kai_mod.merge(1.5, 50.0);
-        }
+        let mods = special_enemy_modifiers(defs, &ship, SpecialEnemyType::PtImp, night);
+        assert_eq!(mods.pt_night_accuracy_mod.a, 0.7);
 
-        if army_infantry_chiha_kai_count >= 1 {
-            mods.army_infantry_chiha_kai_mod.merge(1.6, 70.0);
-        }
+        let mods =
+            special_enemy_modifiers(defs, &ship, SpecialEnemyType::PtImp, AttackType::Torpedo);
+        assert_eq!(mods.pt_accuracy_mod, (0.42, 24.0).into());
+        assert_eq!(mods.pt_ship_type_accuracy_mod.a, 1.0);
+        assert_eq!(mods.pt_equipment_accuracy_mod.a, 1.0);
 
-        if landing_forces_count >= 2 {
-            mods.landing_forces_mod.merge(2.0, 100.0);
-        }
+        ship.ship_type = ShipType::DD;
+        let mods = special_enemy_modifiers(defs, &ship, SpecialEnemyType::PtImp, shelling);
+        assert_eq!(mods.pt_ship_type_accuracy_mod.a, 1.0);
 
-        if landing_forces_count >= 2 {
-            if army_infantry_chiha_kai_count >= 1
-                || army_infantry_count
-                    + t97_tank_chiha_count
-                    + t97_tank_chiha_kai_count
-                    + t2_tank_count
-                    + t4_tank_count
-                    + t4_tank_kai_count
-                    >= 3
-            {
-                mods.landing_forces_group_mod.merge(3.0, 150.0);
-            }
-        }
+        ship.ship_type = ShipType::BB;
+        let mods = special_enemy_modifiers(defs, &ship, SpecialEnemyType::PtImp, shelling);
+        assert_eq!(mods.pt_ship_type_accuracy_mod.a, 0.7);
 
-        if landing_forces_count >= 2 && t4_tank_count >= 1 {
-            mods.landing_forces_t4_mod.merge(1.0, 100.0);
-        }
+        let mods = special_enemy_modifiers(defs, &ship, SpecialEnemyType::None, shelling);
+        assert_eq!(mods.pt_accuracy_mod, Default::default());
+    }
 
-        if landing_forces_count >= 2 && t4_tank_kai_count >= 1 {
-            mods.landing_forces_t4kai_mod.merge(1.0, 172.0);
-        }
+    #[test]
+    fn test_land_base_special_enemy_modifiers() {
+        let defs: Vec<SpecialEnemyModDef> = serde_json::from_value(serde_json::json!([
+            {
+                "targets": ["SupplyDepot"],
+                "attack_types": ["LandBase"],
+                "count": "count_gear_type(47)",
+                "field": "postcap_general_mod",
+                "a": [1.2],
+            },
+            {
+                "targets": ["SupplyDepot"],
+                "attack_types": [],
+                "field": "precap_general_mod",
+                "a": [1.5],
+            },
+        ]))
+        .unwrap();
+
+        let plane = Gear {
+            gear_type: GearType::LbAttacker,
+            ..Default::default()
+        };
 
-        if t4_tank_count + t4_tank_kai_count >= 1 {
-            mods.t4_tank_group_mod.merge(1.2, 42.0);
-        }
+        let mods = land_base_special_enemy_modifiers(&defs, &plane, SpecialEnemyType::SupplyDepot);
+        assert_eq!(mods.postcap_general_mod, (1.2, 0.0).into());
+        assert_eq!(mods.precap_general_mod, Default::default());
 
-        if t4_tank_kai_count >= 1 {
-            mods.t4_tank_kai_mod.merge(1.1, 28.0);
-        }
+        let mods = land_base_special_enemy_modifiers(&defs, &plane, SpecialEnemyType::PtImp);
+        assert_eq!(mods, Default::default());
 
-        if panzer2_count >= 1 {
-            mods.panzer2_mod.merge(1.15, 15.0);
-        }
+        let mods = special_enemy_modifiers(
+            &defs,
+            &Ship::default(),
+            SpecialEnemyType::SupplyDepot,
+            AttackType::Shelling(Default::default()),
+        );
+        assert_eq!(mods.postcap_general_mod, Default::default());
+    }
 
-        if r35_french_count >= 1 {
-            mods.r35_french_mod.merge(1.2, 20.0);
-        }
+    #[test]
+    fn test_land_base_plane_namespace() {
+        let defs: Vec<SpecialEnemyModDef> = serde_json::from_value(serde_json::json!([
+            {
+                "targets": ["SupplyDepot"],
+                "attack_types": ["LandBase"],
+                "count": "ship_type_in(2)",
+                "field": "precap_general_mod",
+                "a": [2.0],
+            },
+            {
+                "targets": ["SupplyDepot"],
+                "attack_types": ["LandBase"],
+                "count": "is_aerial * count(168)",
+                "field": "postcap_general_mod",
+                "a": [1.2],
+                "stars_bonuses": [[{ "gear_types": ["LbAttacker"], "divisor": 50 }]],
+            },
+        ]))
+        .unwrap();
+
+        let mut plane = Gear::default();
+        plane.gear_id = 168;
+        plane.gear_type = GearType::LbAttacker;
+        plane.stars = 10;
+
+        // 艦に関する規則は適用されず、改修値は機体のものを使う
+        let mods = land_base_special_enemy_modifiers(&defs, &plane, SpecialEnemyType::SupplyDepot);
+        assert_eq!(mods.precap_general_mod, Default::default());
+        assert_eq!(mods.postcap_general_mod, (1.2 * 1.2, 0.0).into());
     }
 
-    // 上陸用舟艇シナジー
-    let landing_craft_synergy_mod = {
-        let daihatsu_count = gears.count(gear_id!("大発動艇"));
-        let a = armed_count;
-        let b = ab_count;
-        let c = daihatsu_count
-            + toku_dlc_count
-            + t89_tank_count
-            + panzer2_count
-            + honi1_count
-            + t4_tank_count
-            + t4_tank_kai_count
-            + r35_french_count;
-        let d =
-            shikon_count + toku_dlc_panzer3_count + chiha_count + chiha_kai_count + t2_tank_count;
-
-        match (a, b, c, d) {
-            (1, 0, _, _) | (0, 1, _, _) if c + d >= 1 => Some((1.2, 10.0)),
-            (1, 1, 1, 0) => Some((1.3, 15.0)),
-            (1, 1, 0, 1) => Some((1.4, 20.0)),
-            (1, 1, _, _) if c + d >= 2 => Some((1.5, 25.0)),
-            _ => None,
-        }
-    };
+    #[test]
+    fn test_reference_land_base_special_enemy_modifiers() {
+        let defs = reference_special_enemy_mod_defs();
+        let plane = |gear_type: GearType| {
+            let mut plane = Gear::default();
+            plane.gear_type = gear_type;
+            plane
+        };
 
-    if let Some(v) = landing_craft_synergy_mod {
-        mods.landing_craft_synergy_mod = v.into();
-    }
+        let mods = |plane: &Gear, target: SpecialEnemyType| {
+            land_base_special_enemy_modifiers(defs, plane, target).precap_general_mod
+        };
 
-    match special_enemy_type {
-        SpecialEnemyType::Pillbox => {
-            apply_mod!(mods.precap_general_mod, a, has_ap_shell, 1.85);
-            apply_mod!(mods.precap_general_mod, a, wg42_count, [1.6, 1.6 * 1.7]);
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                type4_rocket_group_count,
-                [1.5, 1.5 * 1.8]
-            );
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                mortar_group_count,
-                [1.3, 1.3 * 1.5]
-            );
-            apply_mod!(mods.precap_general_mod, a, has_seaplane, 1.5);
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                dive_bomber_count,
-                [1.5, 1.5 * 2.0]
-            );
-
-            let ship_is_dd_or_cl = matches!(attacker.ship_type, ShipType::DD | ShipType::CL);
-            apply_mod!(mods.precap_general_mod, a, ship_is_dd_or_cl, 1.4);
-        }
-        SpecialEnemyType::IsolatedIsland => {
-            apply_mod!(mods.precap_general_mod, a, has_aa_shell, 1.75);
-            apply_mod!(mods.precap_general_mod, a, wg42_count, [1.4, 1.4 * 1.5]);
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                type4_rocket_group_count,
-                [1.3, 1.3 * 1.65]
-            );
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                mortar_group_count,
-                [1.2, 1.2 * 1.4]
-            );
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                dive_bomber_count,
-                [1.4, 1.4 * 1.75]
-            );
-        }
-        SpecialEnemyType::HarbourSummerPrincess => {
-            apply_mod!(mods.precap_general_mod, a, has_aa_shell, 1.75);
-            apply_mod!(mods.precap_general_mod, a, has_ap_shell, 1.3);
-            apply_mod!(mods.precap_general_mod, a, wg42_count, [1.4, 1.4 * 1.2]);
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                type4_rocket_group_count,
-                [1.25, 1.25 * 1.4]
-            );
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                mortar_group_count,
-                [1.1, 1.1 * 1.15]
-            );
-            apply_mod!(mods.precap_general_mod, a, has_seaplane, 1.3);
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                dive_bomber_count,
-                [1.3, 1.3 * 1.2]
-            );
-        }
-        SpecialEnemyType::SoftSkinned | SpecialEnemyType::SupplyDepot => {
-            apply_mod!(mods.precap_general_mod, a, has_aa_shell, 2.5);
-            apply_mod!(mods.precap_general_mod, a, wg42_count, [1.3, 1.3 * 1.4]);
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                type4_rocket_group_count,
-                [1.25, 1.25 * 1.5]
-            );
-            apply_mod!(
-                mods.precap_general_mod,
-                a,
-                mortar_group_count,
-                [1.2, 1.2 * 1.3]
-            );
-            apply_mod!(mods.precap_general_mod, a, has_seaplane, 1.2);
-        }
-        _ => (),
+        let lb_attacker = plane(GearType::LbAttacker);
+        assert_eq!(
+            mods(&lb_attacker, SpecialEnemyType::Pillbox),
+            (1.6, 0.0).into()
+        );
+        assert_eq!(
+            mods(&lb_attacker, SpecialEnemyType::IsolatedIsland),
+            (1.18, 0.0).into()
+        );
+        assert_eq!(
+            mods(&plane(GearType::LargeLbAircraft), SpecialEnemyType::Pillbox),
+            (1.6, 0.0).into()
+        );
+        assert_eq!(
+            mods(&plane(GearType::CbTorpedoBomber), SpecialEnemyType::Pillbox),
+            Default::default()
+        );
     }
 
-    mods
-}
+    /// 規則の結果が、規則で表す前の実装と一致するか
+    ///
+    /// 装備をランダムに組み合わせて、特殊敵種別と攻撃種別ごとに比較する
+    #[test]
+    fn test_reference_special_enemy_mod_defs_match_baseline() {
+        use GearType::*;
+
+        let gear = |gear_id: u16, gear_type: GearType| Gear {
+            gear_id,
+            gear_type,
+            ..Default::default()
+        };
+        let with_attr = |gear: Gear, attr: GearAttr| Gear {
+            attrs: attr.into(),
+            ..gear
+        };
 
-/// 砲撃支援特効補正
-fn anti_inst_support_shelling_modifiers(
-    attacker: &Ship,
-    special_enemy_type: SpecialEnemyType,
-) -> SpecialEnemyModifiers {
-    let mut mods = SpecialEnemyModifiers::new();
+        let landing_crafts = [
+            gear(gear_id!("大発動艇"), LandingCraft),
+            gear(gear_id!("特大発動艇"), LandingCraft),
+            gear(gear_id!("大発動艇(八九式中戦車&陸戦隊)"), LandingCraft),
+            gear(gear_id!("大発動艇(II号戦車/北アフリカ仕様)"), LandingCraft),
+            gear(gear_id!("M4A1 DD"), LandingCraft),
+            gear(gear_id!("装甲艇(AB艇)"), LandingCraft),
+            gear(gear_id!("武装大発"), LandingCraft),
+            gear(gear_id!("特大発動艇+戦車第11連隊"), LandingCraft),
+            gear(gear_id!("特大発動艇+一式砲戦車"), LandingCraft),
+            gear(gear_id!("特大発動艇+Ⅲ号戦車(北アフリカ仕様)"), LandingCraft),
+            gear(gear_id!("特大発動艇+Ⅲ号戦車J型"), LandingCraft),
+            gear(gear_id!("特大発動艇+チハ"), LandingCraft),
+            gear(gear_id!("特大発動艇+チハ改"), LandingCraft),
+            gear(gear_id!("大発動艇(R35&フランス兵)"), LandingCraft),
+            gear(gear_id!("特二式内火艇"), AmphibiousTank),
+            gear(gear_id!("特四式内火艇"), AmphibiousTank),
+            gear(gear_id!("特四式内火艇改"), AmphibiousTank),
+            gear(gear_id!("陸軍歩兵部隊"), LandingForce),
+            gear(gear_id!("九七式中戦車(チハ)"), LandingForce),
+            gear(gear_id!("九七式中戦車 新砲塔(チハ改)"), LandingForce),
+            gear(gear_id!("陸軍歩兵部隊+チハ改"), LandingForce),
+        ];
+
+        let others = [
+            gear(gear_id!("WG42 (Wurfgerät 42)"), AntiGroundEquipment),
+            gear(gear_id!("艦載型 四式20cm対地噴進砲"), AntiGroundEquipment),
+            gear(gear_id!("四式20cm対地噴進砲 集中配備"), AntiGroundEquipment),
+            gear(gear_id!("二式12cm迫撃砲改"), AntiGroundEquipment),
+            gear(gear_id!("二式12cm迫撃砲改 集中配備"), AntiGroundEquipment),
+            gear(gear_id!("Laté 298B"), SeaplaneBomber),
+            gear(0, SmallMainGun),
+            gear(0, SecondaryGun),
+            gear(0, AntiAirGun),
+            gear(0, ShipPersonnel),
+            gear(0, ApShell),
+            gear(0, AntiAirShell),
+            gear(0, SeaplaneBomber),
+            gear(0, SeaplaneFighter),
+            gear(0, CbDiveBomber),
+            with_attr(gear(0, CbDiveBomber), GearAttr::AntiInstDiveBomber),
+            gear(0, JetFighterBomber),
+            with_attr(gear(0, CbTorpedoBomber), GearAttr::CbSwordfish),
+        ];
+
+        let ships = [
+            (ShipType::DD, 0, 0),
+            (ShipType::CL, 0, 0),
+            (ShipType::CT, 0, 31),
+            (ShipType::CA, ctype!("Admiral Hipper級"), 0),
+            (ShipType::BB, ctype!("大和型"), 0),
+            (ShipType::BB, ctype!("長門型"), 0),
+            (ShipType::FBB, ctype!("Nelson級"), 34),
+            (ShipType::CV, ctype!("Ark Royal級"), 0),
+            (ShipType::SS, 0, 0),
+            (ShipType::SSV, 0, 0),
+        ];
+
+        let targets = [
+            SpecialEnemyType::None,
+            SpecialEnemyType::SoftSkinned,
+            SpecialEnemyType::Pillbox,
+            SpecialEnemyType::IsolatedIsland,
+            SpecialEnemyType::SupplyDepot,
+            SpecialEnemyType::NewSupplyDepot,
+            SpecialEnemyType::HarbourSummerPrincess,
+            SpecialEnemyType::PtImp,
+            SpecialEnemyType::BattleshipSummerPrincess,
+            SpecialEnemyType::HeavyCruiserSummerPrincess,
+            SpecialEnemyType::FrenchBattleshipPrincess,
+            SpecialEnemyType::AnchorageWaterDemonVacationMode,
+            SpecialEnemyType::DockPrincess,
+            SpecialEnemyType::SummerAircraftCarrierDemon,
+            SpecialEnemyType::EuropeanWaterPrincess,
+        ];
+
+        let attack_types = [
+            AttackType::Shelling(ShellingType::Normal),
+            AttackType::Shelling(ShellingType::Aerial),
+            AttackType::Night(NightAttackType::Normal),
+            AttackType::Night(NightAttackType::Swordfish),
+            AttackType::Night(NightAttackType::Aerial),
+            AttackType::Torpedo,
+            AttackType::Asw(AswAttackType::DepthCharge),
+            AttackType::Asw(AswAttackType::Aerial),
+            AttackType::SupportShelling(SupportShellingType(ShellingType::Normal)),
+            AttackType::SupportShelling(SupportShellingType(ShellingType::Aerial)),
+        ];
+
+        let assert_close = |actual: &SpecialEnemyModifiers,
+                            expected: &SpecialEnemyModifiers,
+                            context: &dyn Fn() -> String| {
+            let actual = serde_json::to_value(actual).unwrap();
+            let expected = serde_json::to_value(expected).unwrap();
+
+            for (field, expected) in expected.as_object().unwrap() {
+                let actual = &actual[field];
+
+                if expected.is_null() || actual.is_null() {
+                    assert_eq!(actual, expected, "{field}: {}", context());
+                    continue;
+                }
+
+                for key in ["a", "b"] {
+                    let (a, e) = (
+                        actual[key].as_f64().unwrap(),
+                        expected[key].as_f64().unwrap(),
+                    );
+                    assert!(
+                        (a - e).abs() < 1e-9,
+                        "{field}.{key}: {a} != {e}, {}",
+                        context()
+                    );
+                }
+            }
+        };
 
-    if attacker.gears.has_type(GearType::AntiAirShell) {
-        let aa_shell_mod = match special_enemy_type {
-            SpecialEnemyType::IsolatedIsland | SpecialEnemyType::HarbourSummerPrincess => 1.75,
-            SpecialEnemyType::SoftSkinned | SpecialEnemyType::SupplyDepot => 2.5,
-            _ => 1.0,
+        let defs = reference_special_enemy_mod_defs();
+        let mut rng = rng(0);
+        let all_gears = [landing_crafts.as_slice(), others.as_slice()].concat();
+        let by_id = |gear_id: u16| {
+            all_gears
+                .iter()
+                .find(|gear| gear.gear_id == gear_id)
+                .unwrap()
+                .clone()
         };
 
-        mods.precap_general_mod.a *= aa_shell_mod;
-    }
+        // ランダムには出にくい上陸用舟艇シナジーなどの組み合わせ
+        let fixed = [
+            vec![gear_id!("武装大発"), gear_id!("大発動艇")],
+            vec![
+                gear_id!("武装大発"),
+                gear_id!("装甲艇(AB艇)"),
+                gear_id!("大発動艇"),
+            ],
+            vec![
+                gear_id!("武装大発"),
+                gear_id!("装甲艇(AB艇)"),
+                gear_id!("特大発動艇+チハ"),
+            ],
+            vec![
+                gear_id!("武装大発"),
+                gear_id!("装甲艇(AB艇)"),
+                gear_id!("大発動艇"),
+                gear_id!("特二式内火艇"),
+            ],
+            vec![
+                gear_id!("装甲艇(AB艇)"),
+                gear_id!("特二式内火艇"),
+                gear_id!("特二式内火艇"),
+            ],
+            vec![
+                gear_id!("陸軍歩兵部隊"),
+                gear_id!("九七式中戦車(チハ)"),
+                gear_id!("九七式中戦車 新砲塔(チハ改)"),
+            ],
+            vec![
+                gear_id!("陸軍歩兵部隊"),
+                gear_id!("九七式中戦車(チハ)"),
+                gear_id!("特四式内火艇改"),
+            ],
+            vec![gear_id!("陸軍歩兵部隊+チハ改"), gear_id!("特四式内火艇")],
+            vec![
+                gear_id!("大発動艇(II号戦車/北アフリカ仕様)"),
+                gear_id!("大発動艇(II号戦車/北アフリカ仕様)"),
+            ],
+            vec![gear_id!("特四式内火艇"), gear_id!("特四式内火艇改")],
+            vec![
+                gear_id!("特大発動艇+一式砲戦車"),
+                gear_id!("特大発動艇+Ⅲ号戦車J型"),
+                gear_id!("M4A1 DD"),
+                gear_id!("大発動艇(R35&フランス兵)"),
+            ],
+            vec![
+                gear_id!("WG42 (Wurfgerät 42)"),
+                gear_id!("WG42 (Wurfgerät 42)"),
+                gear_id!("WG42 (Wurfgerät 42)"),
+                gear_id!("WG42 (Wurfgerät 42)"),
+                gear_id!("二式12cm迫撃砲改 集中配備"),
+            ],
+            vec![
+                gear_id!("艦載型 四式20cm対地噴進砲"),
+                gear_id!("艦載型 四式20cm対地噴進砲"),
+                gear_id!("四式20cm対地噴進砲 集中配備"),
+                gear_id!("四式20cm対地噴進砲 集中配備"),
+                gear_id!("二式12cm迫撃砲改"),
+            ],
+        ]
+        .map(|gear_ids| gear_ids.into_iter().map(by_id).collect::<Vec<_>>());
+
+        // 上陸用舟艇の組み合わせを多く試すため、半分は上陸用舟艇のみから選ぶ
+        let random = (0..300)
+            .map(|_| {
+                let pool = if rng.gen_bool(0.5) {
+                    landing_crafts.as_slice()
+                } else {
+                    all_gears.as_slice()
+                };
+
+                (0..rng.gen_range(1..=5))
+                    .map(|_| pool.choose(&mut rng).unwrap().clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for loadout in fixed.into_iter().chain(random) {
+            let (ship_type, ctype, nationality) = *ships.choose(&mut rng).unwrap();
+            let mut ship = Ship {
+                ship_type,
+                ctype,
+                ..Default::default()
+            };
+            ship.master.nationality = nationality;
+
+            for gear in loadout {
+                ship.gears.push(Gear {
+                    stars: rng.gen_range(0..=10),
+                    ..gear
+                });
+            }
 
-    mods
+            for target in targets {
+                for attack_type in attack_types {
+                    let actual = special_enemy_modifiers(defs, &ship, target, attack_type);
+
+                    let mut expected =
+                        baseline::special_enemy_modifiers(&ship, target, attack_type);
+                    if target == SpecialEnemyType::PtImp {
+                        let accuracy = baseline::anti_pt_imp_accuracy_modifiers(&ship, attack_type);
+                        expected.pt_accuracy_mod = accuracy.pt_accuracy_mod;
+                        expected.pt_ship_type_accuracy_mod = accuracy.pt_ship_type_accuracy_mod;
+                        expected.pt_equipment_accuracy_mod = accuracy.pt_equipment_accuracy_mod;
+                        expected.pt_night_accuracy_mod = accuracy.pt_night_accuracy_mod;
+                    }
+
+                    assert_close(&actual, &expected, &|| {
+                        let gears = ship
+                            .gears
+                            .iter()
+                            .map(|(_, gear)| (gear.gear_id, gear.gear_type, gear.stars))
+                            .collect::<Vec<_>>();
+                        format!("{target:?} {attack_type:?} {ship_type:?} {ctype} {gears:?}")
+                    });
+                }
+            }
+        }
+    }
 }
@@ -1,6 +1,125 @@
 mod air_battle;
+mod air_raid_simulator;
 mod battle_logger;
+mod battle_simulator;
+mod land_base_simulator;
+mod sortie_simulator;
 mod support_shelling_simulator;
+mod support_simulator;
 
+pub(crate) use air_battle::AerialCombat;
+pub use air_raid_simulator::*;
 pub use battle_logger::*;
+pub use battle_simulator::*;
+pub use land_base_simulator::*;
+pub use sortie_simulator::*;
 pub use support_shelling_simulator::*;
+pub use support_simulator::*;
+
+use rand::prelude::*;
+
+/// シード値から乱数生成器を作成する
+///
+/// 省略した場合は JS の Number で扱える範囲のシード値をランダムに生成する
+pub fn seeded_rng(seed: Option<u64>) -> (SmallRng, u64) {
+    const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+    let seed = seed.unwrap_or_else(|| SmallRng::from_entropy().r#gen::<u64>() & MAX_SAFE_INTEGER);
+    (SmallRng::seed_from_u64(seed), seed)
+}
+
+/// 試行回数を重みに比例して割り振る
+///
+/// 端数は剰余の大きい順に割り当てるため、合計は`times`と一致する
+pub(crate) fn allocate_times(weights: &[f64], times: usize) -> Vec<usize> {
+    let total = weights.iter().map(|w| w.max(0.0)).sum::<f64>();
+
+    if total <= 0.0 {
+        return vec![0; weights.len()];
+    }
+
+    let exact = weights
+        .iter()
+        .map(|w| w.max(0.0) / total * times as f64)
+        .collect::<Vec<_>>();
+    let mut allocated = exact.iter().map(|v| v.floor() as usize).collect::<Vec<_>>();
+
+    let rest = times - allocated.iter().sum::<usize>();
+    let mut order = (0..weights.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+
+    order
+        .into_iter()
+        .take(rest)
+        .for_each(|index| allocated[index] += 1);
+
+    allocated
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        battle::BattleComp,
+        test::{battle_defs, comp, ship},
+        types::{BattleType, Engagement, Formation, OrgType, ShipType},
+    };
+
+    fn simulate_battle(rng: &mut SmallRng) -> serde_json::Value {
+        let battle_defs = battle_defs();
+        let mut player = BattleComp::new(
+            comp(
+                OrgType::Single,
+                vec![ship("p1", ShipType::BB, 80), ship("p2", ShipType::DD, 30)],
+            ),
+            Formation::LINE_AHEAD,
+        );
+        let mut enemy = BattleComp::new(
+            comp(
+                OrgType::EnemySingle,
+                vec![ship("e1", ShipType::BB, 80), ship("e2", ShipType::DD, 30)],
+            ),
+            Formation::LINE_AHEAD,
+        );
+
+        let result = BattleSimulator::new(
+            rng,
+            &battle_defs,
+            &mut player,
+            &mut enemy,
+            BattleType::DayAndNight,
+            Engagement::Parallel,
+            Default::default(),
+        )
+        .run(100)
+        .unwrap();
+
+        serde_json::to_value(result).unwrap()
+    }
+
+    #[test]
+    fn test_seeded_rng() {
+        let (mut rng, seed) = seeded_rng(Some(42));
+        assert_eq!(seed, 42);
+        let expected = simulate_battle(&mut rng);
+
+        let (mut rng, _) = seeded_rng(Some(42));
+        assert_eq!(simulate_battle(&mut rng), expected);
+
+        let (mut rng, _) = seeded_rng(Some(43));
+        assert_ne!(simulate_battle(&mut rng), expected);
+
+        // 省略した場合も返されたシード値で再現できる
+        let (mut rng, seed) = seeded_rng(None);
+        assert!(seed < 1 << 53);
+        let result = simulate_battle(&mut rng);
+        assert_eq!(simulate_battle(&mut seeded_rng(Some(seed)).0), result);
+    }
+
+    #[test]
+    fn test_allocate_times() {
+        assert_eq!(allocate_times(&[0.25, 0.25, 0.5], 10), vec![3, 2, 5]);
+        assert_eq!(allocate_times(&[1.0, 1.0, 1.0], 10), vec![4, 3, 3]);
+        assert_eq!(allocate_times(&[0.0, 0.0], 10), vec![0, 0]);
+    }
+}
@@ -1,10 +1,11 @@
-#![allow(dead_code, unused_variables)]
-
 use anyhow::Result;
 use rand::prelude::*;
 
 use crate::{
-    attack::create_airstrike_params,
+    air_squadron::AirSquadron,
+    attack::{
+        create_airstrike_params, create_land_base_airstrike_params, create_support_airstrike_params,
+    },
     comp::Comp,
     error::CalculationError,
     plane::{PlaneImpl, PlaneMut, PlaneVec},
@@ -33,12 +34,11 @@ fn try_fighter_combat<R: Rng + ?Sized>(
     Ok(air_state)
 }
 
-fn try_air_defense<R: Rng + ?Sized>(
+fn try_air_defense<'p, R: Rng + ?Sized>(
     rng: &mut R,
     config: &BattleDefinitions,
-    attacker_comp: &mut Comp,
+    planes: impl Iterator<Item = PlaneMut<'p>>,
     target_comp: &Comp,
-    escort_participates: bool,
     formation: Formation,
 ) -> Result<(), CalculationError> {
     let formation_mod = config.get_formation_fleet_anti_air_mod(formation);
@@ -55,8 +55,7 @@ fn try_air_defense<R: Rng + ?Sized>(
         return Ok(());
     }
 
-    attacker_comp
-        .planes_mut(escort_participates)
+    planes
         .filter(|plane| plane.is_attacker())
         .try_for_each(|mut plane| {
             let member = member_vec.choose(rng).expect("member_vec.len() > 0");
@@ -73,10 +72,11 @@ fn try_airstrike<R: Rng + ?Sized>(
     target_comp: &mut Comp,
     escort_participates: bool,
     contact_rank: Option<ContactRank>,
+    air_type: AirWaveType,
 ) -> anyhow::Result<()> {
     let mut target_vec = target_comp
         .members_mut()
-        .filter(|member| member.remains())
+        .filter(|member| member.remains() && !member.is_submarine())
         .collect::<Vec<_>>();
 
     if target_vec.is_empty() {
@@ -92,7 +92,11 @@ fn try_airstrike<R: Rng + ?Sized>(
 
             attacker
                 .planes()
-                .filter(|plane| plane.remains() && plane.is_attacker())
+                .filter(|plane| {
+                    plane.remains()
+                        && plane.is_attacker()
+                        && (!matches!(air_type, AirWaveType::Jet) || plane.is_jet_plane())
+                })
                 .try_for_each(|plane| {
                     let target = target_vec.choose_mut(rng).expect("member_vec.len() > 0");
 
@@ -111,24 +115,200 @@ fn try_airstrike<R: Rng + ?Sized>(
         })
 }
 
-struct AerialCombat<'a, R>
+/// 基地航空隊1波分の航空戦
+pub(crate) fn try_land_base_wave<R: Rng + ?Sized>(
+    rng: &mut R,
+    battle_defs: &BattleDefinitions,
+    squadron: &mut AirSquadron,
+    enemy_comp: &mut Comp,
+    enemy_formation: Formation,
+) -> Result<AirState> {
+    let player_fp = squadron.fighter_power();
+
+    let mut enemy_planes = enemy_comp.planes_mut(true).collect::<PlaneVec<_>>();
+    let enemy_fp = enemy_planes.fighter_power(AirWaveType::LandBase)?;
+    let air_state = AirState::new(player_fp, enemy_fp);
+
+    squadron
+        .planes_mut()
+        .filter(|plane| plane.participates_in_fighter_combat(AirWaveType::LandBase))
+        .for_each(|mut plane| plane.suffer_in_fighter_combat(rng, air_state, Side::Player));
+    enemy_planes
+        .iter_mut()
+        .filter(|plane| plane.participates_in_fighter_combat(AirWaveType::LandBase))
+        .for_each(|plane| plane.suffer_in_fighter_combat(rng, air_state, Side::Enemy));
+
+    drop(enemy_planes);
+
+    try_air_defense(
+        rng,
+        battle_defs,
+        squadron.planes_mut(),
+        enemy_comp,
+        enemy_formation,
+    )?;
+
+    let mut target_vec = enemy_comp
+        .members_mut()
+        .filter(|member| member.remains() && !member.is_submarine())
+        .collect::<Vec<_>>();
+
+    if target_vec.is_empty() {
+        return Ok(air_state);
+    }
+
+    let recon_mod = squadron.land_base_recon_mod();
+
+    squadron
+        .planes()
+        .filter(|plane| plane.remains() && plane.is_attacker())
+        .try_for_each(|plane| {
+            let target = target_vec.choose_mut(rng).expect("target_vec.len() > 0");
+
+            let special_enemy_mods =
+                battle_defs.get_land_base_special_enemy_mods(&plane, target.special_enemy_type());
+
+            let attack = create_land_base_airstrike_params(
+                rng,
+                plane,
+                recon_mod,
+                None,
+                special_enemy_mods,
+                &target.as_ref(),
+            )
+            .into_attack();
+
+            attack.apply(rng, target)
+        })?;
+
+    Ok(air_state)
+}
+
+/// 支援艦隊の航空支援
+///
+/// `support_fleet`は攻撃する支援艦隊 (`RouteSup`か`BossSup`)
+pub(crate) fn try_support_airstrike<R: Rng + ?Sized>(
+    rng: &mut R,
+    battle_defs: &BattleDefinitions,
+    support_fleet: FleetType,
+    player_comp: &mut Comp,
+    enemy_comp: &mut Comp,
+    enemy_formation: Formation,
+) -> Result<()> {
+    let Some(support_fleet) = player_comp.get_fleet_mut(support_fleet) else {
+        return Ok(());
+    };
+
+    let mut player_planes = support_fleet
+        .ships
+        .values_mut()
+        .flat_map(|ship| ship.planes_mut())
+        .collect::<PlaneVec<_>>();
+    let mut enemy_planes = enemy_comp.planes_mut(true).collect::<PlaneVec<_>>();
+
+    try_fighter_combat(
+        rng,
+        &mut player_planes,
+        &mut enemy_planes,
+        AirWaveType::Carrier,
+    )?;
+
+    drop(enemy_planes);
+
+    try_air_defense(
+        rng,
+        battle_defs,
+        player_planes.into_iter(),
+        enemy_comp,
+        enemy_formation,
+    )?;
+
+    let mut target_vec = enemy_comp
+        .members_mut()
+        .filter(|member| member.remains() && !member.is_submarine())
+        .collect::<Vec<_>>();
+
+    if target_vec.is_empty() {
+        return Ok(());
+    }
+
+    support_fleet.ships.values().try_for_each(|ship| {
+        let remaining_ammo_mod = ship.remaining_ammo_mod();
+
+        ship.planes()
+            .filter(|plane| plane.remains() && plane.is_attacker())
+            .try_for_each(|plane| {
+                let target = target_vec.choose_mut(rng).expect("target_vec.len() > 0");
+
+                let attack = create_support_airstrike_params(
+                    rng,
+                    plane,
+                    remaining_ammo_mod,
+                    &target.as_ref(),
+                )
+                .into_attack();
+
+                attack.apply(rng, target)
+            })
+    })
+}
+
+/// 敵基地空襲に対する防空戦
+///
+/// `squadrons`は防空状態の基地航空隊
+pub(crate) fn try_air_raid_defense<R: Rng + ?Sized>(
+    rng: &mut R,
+    squadrons: &mut [AirSquadron],
+    enemy_comp: &mut Comp,
+    high_altitude: bool,
+) -> Result<AirState> {
+    let interception_power = squadrons
+        .iter()
+        .map(|squadron| squadron.interception_power())
+        .sum::<i32>();
+
+    let player_fp = if high_altitude {
+        let modifier = AirSquadron::high_altitude_interception_mod(squadrons.iter());
+        (interception_power as f64 * modifier).floor() as i32
+    } else {
+        interception_power
+    };
+
+    let mut enemy_planes = enemy_comp.planes_mut(true).collect::<PlaneVec<_>>();
+    let enemy_fp = enemy_planes.fighter_power(AirWaveType::LandBase)?;
+    let air_state = AirState::new(player_fp, enemy_fp);
+
+    squadrons
+        .iter_mut()
+        .flat_map(|squadron| squadron.planes_mut())
+        .filter(|plane| plane.participates_in_fighter_combat(AirWaveType::LandBase))
+        .for_each(|mut plane| plane.suffer_in_fighter_combat(rng, air_state, Side::Player));
+    enemy_planes
+        .iter_mut()
+        .filter(|plane| plane.participates_in_fighter_combat(AirWaveType::LandBase))
+        .for_each(|plane| plane.suffer_in_fighter_combat(rng, air_state, Side::Enemy));
+
+    Ok(air_state)
+}
+
+pub(crate) struct AerialCombat<'a, R>
 where
     R: Rng + ?Sized,
 {
-    rng: &'a mut R,
-    battle_defs: &'a BattleDefinitions,
-    player_comp: &'a mut Comp,
-    enemy_comp: &'a mut Comp,
-    escort_participates: bool,
-    player_formation: Formation,
-    enemy_formation: Formation,
+    pub rng: &'a mut R,
+    pub battle_defs: &'a BattleDefinitions,
+    pub player_comp: &'a mut Comp,
+    pub enemy_comp: &'a mut Comp,
+    pub escort_participates: bool,
+    pub player_formation: Formation,
+    pub enemy_formation: Formation,
 }
 
 impl<'a, R> AerialCombat<'a, R>
 where
     R: Rng + ?Sized,
 {
-    fn try_jet_assault_phase(&mut self) -> Result<()> {
+    pub fn try_jet_assault_phase(&mut self) -> Result<()> {
         let escort_participates = self.escort_participates;
 
         let mut player_planes = self
@@ -159,6 +339,7 @@ where
             self.enemy_comp,
             escort_participates,
             player_contact_rank,
+            AirWaveType::Jet,
         )?;
 
         try_airstrike(
@@ -167,12 +348,13 @@ where
             self.player_comp,
             escort_participates,
             enemy_contact_rank,
+            AirWaveType::Jet,
         )?;
 
         Ok(())
     }
 
-    fn try_aerial_combat(&mut self) -> Result<AirState> {
+    pub fn try_aerial_combat(&mut self) -> Result<AirState> {
         let escort_participates = self.escort_participates;
 
         let mut player_planes = self
@@ -198,18 +380,16 @@ where
         try_air_defense(
             self.rng,
             self.battle_defs,
-            self.player_comp,
+            self.player_comp.planes_mut(escort_participates),
             self.enemy_comp,
-            escort_participates,
             self.enemy_formation,
         )?;
 
         try_air_defense(
             self.rng,
             self.battle_defs,
-            self.enemy_comp,
+            self.enemy_comp.planes_mut(escort_participates),
             self.player_comp,
-            escort_participates,
             self.player_formation,
         )?;
 
@@ -219,6 +399,7 @@ where
             self.enemy_comp,
             escort_participates,
             player_contact_rank,
+            AirWaveType::Carrier,
         )?;
 
         try_airstrike(
@@ -227,8 +408,98 @@ where
             self.player_comp,
             escort_participates,
             enemy_contact_rank,
+            AirWaveType::Carrier,
         )?;
 
         Ok(air_state)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gear::Gear,
+        ship::Ship,
+        test::{battle_defs, comp, rng, ship},
+        types::{GearAttr, GearType, OrgType, ShipType},
+    };
+
+    fn carrier(gear_type: GearType, attr: GearAttr) -> Ship {
+        let mut gear = Gear::default();
+        gear.gear_type = gear_type;
+        gear.attrs = attr.into();
+        gear.bombing = 10;
+
+        let mut ship = ship("cv", ShipType::CV, 60);
+        ship.gears.push(gear);
+        ship.slots = [Some(30)].into_iter().collect();
+        ship
+    }
+
+    fn enemy_hps(seed: u64, carrier: &Ship, is_jet_assault: bool) -> (u16, u16) {
+        let battle_defs = battle_defs();
+        let mut rng = rng(seed);
+        let mut player = comp(OrgType::Single, vec![carrier.clone()]);
+        let mut enemy = comp(
+            OrgType::EnemySingle,
+            vec![
+                ship("e_dd", ShipType::DD, 40),
+                ship("e_ss", ShipType::SS, 40),
+            ],
+        );
+
+        let mut aerial_combat = AerialCombat {
+            rng: &mut rng,
+            battle_defs: &battle_defs,
+            player_comp: &mut player,
+            enemy_comp: &mut enemy,
+            escort_participates: true,
+            player_formation: Formation::LINE_AHEAD,
+            enemy_formation: Formation::LINE_AHEAD,
+        };
+
+        if is_jet_assault {
+            aerial_combat.try_jet_assault_phase().unwrap();
+        } else {
+            aerial_combat.try_aerial_combat().unwrap();
+        }
+
+        let hp = |index: usize| enemy.main.ships.get(index).unwrap().current_hp;
+        (hp(0), hp(1))
+    }
+
+    #[test]
+    fn test_airstrike_ignores_submarines() {
+        let carrier = carrier(GearType::CbDiveBomber, GearAttr::CbAircraft);
+
+        let damaged = (0..50)
+            .filter(|&seed| {
+                let (dd_hp, ss_hp) = enemy_hps(seed, &carrier, false);
+                assert_eq!(ss_hp, 40);
+                dd_hp < 40
+            })
+            .count();
+
+        assert!(damaged > 0);
+    }
+
+    #[test]
+    fn test_jet_assault_phase() {
+        // 噴式機以外は噴式強襲に参加しない
+        let bomber_carrier = carrier(GearType::CbDiveBomber, GearAttr::CbAircraft);
+        assert!((0..50).all(|seed| enemy_hps(seed, &bomber_carrier, true) == (40, 40)));
+
+        let jet_carrier = carrier(GearType::JetFighterBomber, GearAttr::JetAircraft);
+
+        let damaged = (0..50)
+            .filter(|&seed| {
+                let (dd_hp, ss_hp) = enemy_hps(seed, &jet_carrier, true);
+                assert_eq!(ss_hp, 40);
+                dd_hp < 40
+            })
+            .count();
+
+        assert!(damaged > 0);
+    }
+}
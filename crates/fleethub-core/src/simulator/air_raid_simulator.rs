@@ -0,0 +1,282 @@
+use anyhow::{Result, ensure};
+use hashbrown::HashMap;
+use rand::prelude::*;
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::{
+    air_squadron::AirSquadron, comp::Comp, plane::PlaneImpl, types::AirState, utils::Histogram,
+};
+
+use super::air_battle::try_air_raid_defense;
+
+/// 空襲による基地の被害
+///
+/// 資源の減少量は計算式が不明なため推定しない
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Tsify)]
+pub enum AirRaidDamage {
+    /// 損害なし
+    NoDamage,
+    /// 軽微な損害
+    Minor,
+    /// 資源の損害
+    Moderate,
+    /// 甚大な損害
+    Severe,
+}
+
+impl AirRaidDamage {
+    /// 防空戦の制空状態から被害の程度を推定する
+    ///
+    /// 敵攻撃機が全滅した場合は損害なし
+    pub fn estimate(air_state: AirState, enemy_attacker_slots: usize) -> Self {
+        if enemy_attacker_slots == 0 {
+            return Self::NoDamage;
+        }
+
+        match air_state {
+            AirState::AirSupremacy => Self::NoDamage,
+            AirState::AirSuperiority => Self::Minor,
+            AirState::AirParity | AirState::AirDenial => Self::Moderate,
+            AirState::AirIncapability => Self::Severe,
+        }
+    }
+
+    /// 資源が減少する被害か
+    pub fn causes_resource_loss(self) -> bool {
+        matches!(self, Self::Moderate | Self::Severe)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct AirRaidSquadronResult {
+    pub squadron_id: String,
+    /// 防空戦終了時の平均搭載数
+    pub slots: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct AirRaidSimulatorResult {
+    pub air_state_map: HashMap<AirState, f64>,
+    pub squadrons: Vec<AirRaidSquadronResult>,
+    /// 防空戦を突破した敵攻撃機の平均機数
+    pub enemy_attacker_slots: f64,
+    /// 敵攻撃機を全滅させた割合
+    pub enemy_attacker_annihilation_rate: f64,
+    /// 基地の被害の割合
+    pub base_damage_map: HashMap<AirRaidDamage, f64>,
+    /// 資源が減少する被害を受ける割合
+    pub resource_loss_rate: f64,
+    /// 乱数のシード値
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+pub struct AirRaidSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    rng: &'a mut R,
+    squadrons: Vec<AirSquadron>,
+    enemy_comp: &'a mut Comp,
+    high_altitude: bool,
+}
+
+impl<'a, R> AirRaidSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    /// 防空状態の基地航空隊のみを対象とする
+    pub fn new(
+        rng: &'a mut R,
+        squadrons: Vec<AirSquadron>,
+        enemy_comp: &'a mut Comp,
+        high_altitude: bool,
+    ) -> Self {
+        let squadrons = squadrons
+            .into_iter()
+            .filter(|squadron| squadron.mode.is_air_defense())
+            .collect();
+
+        Self {
+            rng,
+            squadrons,
+            enemy_comp,
+            high_altitude,
+        }
+    }
+
+    pub fn run(&mut self, times: usize) -> Result<AirRaidSimulatorResult> {
+        ensure!(times > 0, "試行回数が0です");
+
+        let mut air_state_counter = Histogram::<AirState, usize>::new();
+        let mut base_damage_counter = Histogram::<AirRaidDamage, usize>::new();
+        let mut slot_sums = self
+            .squadrons
+            .iter()
+            .map(|squadron| vec![0_usize; squadron.slots.len()])
+            .collect::<Vec<_>>();
+        let mut enemy_attacker_total = 0_usize;
+        let mut annihilation_count = 0_usize;
+
+        (0..times).try_for_each(|_| -> Result<()> {
+            let mut squadrons = self.squadrons.clone();
+
+            let air_state = try_air_raid_defense(
+                self.rng,
+                &mut squadrons,
+                self.enemy_comp,
+                self.high_altitude,
+            )?;
+
+            air_state_counter += (air_state, 1);
+
+            slot_sums
+                .iter_mut()
+                .zip(squadrons.iter())
+                .for_each(|(sums, squadron)| {
+                    sums.iter_mut()
+                        .zip(squadron.slots.iter())
+                        .for_each(|(sum, &slot)| *sum += slot.unwrap_or_default() as usize);
+                });
+
+            let enemy_attacker_slots = self
+                .enemy_comp
+                .planes(true)
+                .filter(|plane| plane.is_attacker())
+                .map(|plane| plane.slot_size().unwrap_or_default() as usize)
+                .sum::<usize>();
+
+            enemy_attacker_total += enemy_attacker_slots;
+
+            if enemy_attacker_slots == 0 {
+                annihilation_count += 1;
+            }
+
+            base_damage_counter += (AirRaidDamage::estimate(air_state, enemy_attacker_slots), 1);
+
+            self.enemy_comp.reset_battle_state();
+            Ok(())
+        })?;
+
+        let times_f64 = times as f64;
+
+        let air_state_map = air_state_counter
+            .into_iter()
+            .map(|(air_state, count)| (air_state, count as f64 / times_f64))
+            .collect();
+
+        let base_damage_map = base_damage_counter
+            .into_iter()
+            .map(|(damage, count)| (damage, count as f64 / times_f64))
+            .collect::<HashMap<_, _>>();
+
+        let resource_loss_rate = base_damage_map
+            .iter()
+            .filter(|(damage, _)| damage.causes_resource_loss())
+            .map(|(_, rate)| rate)
+            .sum();
+
+        let squadrons = self
+            .squadrons
+            .iter()
+            .zip(slot_sums)
+            .map(|(squadron, sums)| AirRaidSquadronResult {
+                squadron_id: squadron.id.clone(),
+                slots: sums.into_iter().map(|sum| sum as f64 / times_f64).collect(),
+            })
+            .collect();
+
+        Ok(AirRaidSimulatorResult {
+            air_state_map,
+            squadrons,
+            enemy_attacker_slots: enemy_attacker_total as f64 / times_f64,
+            enemy_attacker_annihilation_rate: annihilation_count as f64 / times_f64,
+            base_damage_map,
+            resource_loss_rate,
+            seed: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        gear::Gear,
+        test::{comp, ship},
+        types::{GearAttr, GearType, OrgType, ShipType},
+    };
+
+    #[test]
+    fn test_air_raid_damage() {
+        use AirRaidDamage::*;
+
+        assert_eq!(
+            AirRaidDamage::estimate(AirState::AirIncapability, 0),
+            NoDamage
+        );
+        assert_eq!(
+            AirRaidDamage::estimate(AirState::AirSupremacy, 10),
+            NoDamage
+        );
+        assert_eq!(AirRaidDamage::estimate(AirState::AirSuperiority, 10), Minor);
+        assert_eq!(AirRaidDamage::estimate(AirState::AirDenial, 10), Moderate);
+        assert_eq!(
+            AirRaidDamage::estimate(AirState::AirIncapability, 10),
+            Severe
+        );
+
+        assert!(!NoDamage.causes_resource_loss());
+        assert!(!Minor.causes_resource_loss());
+        assert!(Moderate.causes_resource_loss());
+        assert!(Severe.causes_resource_loss());
+    }
+
+    #[test]
+    fn test_resource_loss_rate() {
+        let plane = |gear_type: GearType, anti_air: i16, bombing: i16| {
+            let mut gear = Gear::default();
+            gear.gear_type = gear_type;
+            gear.attrs = GearAttr::CbAircraft.into();
+            gear.anti_air = anti_air;
+            gear.bombing = bombing;
+            gear
+        };
+
+        let mut enemy_ship = ship("e1", ShipType::CV, 80);
+        enemy_ship.gears.push(plane(GearType::CbFighter, 10, 0));
+        enemy_ship.gears.push(plane(GearType::CbDiveBomber, 0, 10));
+        enemy_ship.master.slotnum = 2;
+        enemy_ship.master.slots = [Some(20), Some(20)].into_iter().collect();
+        enemy_ship.reset_battle_state();
+        let mut enemy = comp(OrgType::EnemySingle, vec![enemy_ship]);
+
+        // 防空する基地航空隊がない場合は制空権を喪失し、資源の損害を受ける
+        let mut rng = SmallRng::seed_from_u64(0);
+        let result = AirRaidSimulator::new(&mut rng, vec![], &mut enemy, false)
+            .run(10)
+            .unwrap();
+        assert_eq!(result.air_state_map[&AirState::AirIncapability], 1.0);
+        assert!(result.enemy_attacker_slots > 0.0);
+        assert_eq!(result.base_damage_map[&AirRaidDamage::Severe], 1.0);
+        assert_eq!(result.resource_loss_rate, 1.0);
+    }
+
+    #[test]
+    fn test_zero_times() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut enemy = Comp {
+            org_type: Default::default(),
+            hq_level: 120,
+            main: Default::default(),
+            escort: None,
+            route_sup: None,
+            boss_sup: None,
+        };
+
+        let result = AirRaidSimulator::new(&mut rng, vec![], &mut enemy, false).run(0);
+        assert!(result.is_err());
+    }
+}
@@ -5,15 +5,29 @@ use tsify::Tsify;
 
 use crate::{
     comp::Comp,
-    types::{DamageState, FleetType},
+    types::{BattleRank, BattleRankSide, DamageState, FleetType},
     utils::Histogram,
 };
 
+/// 勝敗判定の対象となる艦の耐久
+pub(crate) fn battle_hps(comp: &Comp) -> Vec<u16> {
+    comp.members_by(FleetType::Main | FleetType::Escort)
+        .map(|member| member.ship.current_hp)
+        .collect()
+}
+
+pub(crate) fn rank_side(comp: &Comp, hps_before: &[u16]) -> BattleRankSide {
+    BattleRankSide::new(hps_before.iter().copied().zip(battle_hps(comp)))
+}
+
 #[derive(Debug, Default)]
 pub struct BattleLogger {
     times: usize,
     sunk_counter: Histogram<usize, usize>,
     damage_map: HashMap<String, Histogram<DamageState, usize>>,
+    rank_counter: HashMap<BattleRank, usize>,
+    flagship_sunk_count: usize,
+    fleet_cutin_skipped_count: usize,
 }
 
 impl BattleLogger {
@@ -24,6 +38,20 @@ impl BattleLogger {
         }
     }
 
+    /// 記録した回数
+    pub fn count(&self) -> usize {
+        self.sunk_counter.values().sum()
+    }
+
+    /// 自艦隊から見た勝敗を記録する
+    pub fn write_rank(&mut self, player: &BattleRankSide, enemy: &BattleRankSide) {
+        *self
+            .rank_counter
+            .entry(BattleRank::new(player, enemy))
+            .or_default() += 1;
+        self.flagship_sunk_count += enemy.flagship_sunk as usize;
+    }
+
     pub fn write(&mut self, comp: &Comp) {
         let sunk_count = comp
             .ships()
@@ -46,6 +74,19 @@ impl BattleLogger {
             .count();
 
         self.sunk_counter += (sunk_count, 1);
+
+        let fleet_cutin_skipped = comp.main.fleet_cutin_skipped
+            || comp
+                .escort
+                .as_ref()
+                .is_some_and(|fleet| fleet.fleet_cutin_skipped);
+        self.fleet_cutin_skipped_count += fleet_cutin_skipped as usize;
+    }
+
+    /// 記録した回数を試行回数として結果を作成する
+    pub fn create_result_by_count(mut self, comp: &Comp) -> SimulatorResult {
+        self.times = self.count();
+        self.create_result(comp)
     }
 
     pub fn create_result(self, comp: &Comp) -> SimulatorResult {
@@ -93,7 +134,73 @@ impl BattleLogger {
             })
             .collect();
 
-        SimulatorResult { items, sunk_vec }
+        let rank = (!self.rank_counter.is_empty()).then(|| {
+            let rank_map = self
+                .rank_counter
+                .into_iter()
+                .map(|(rank, count)| (rank, count as f64 / times_f64))
+                .collect();
+
+            BattleRankResult::new(rank_map, self.flagship_sunk_count as f64 / times_f64)
+        });
+
+        SimulatorResult {
+            items,
+            sunk_vec,
+            rank,
+            fleet_cutin_skipped_rate: self.fleet_cutin_skipped_count as f64 / times_f64,
+            seed: None,
+        }
+    }
+}
+
+/// 勝敗の分布
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct BattleRankResult {
+    pub rank_map: HashMap<BattleRank, f64>,
+    /// S勝利以上の確率
+    pub s_rate: f64,
+    /// B勝利以上の確率
+    pub victory_rate: f64,
+    /// 敵旗艦を撃沈した確率
+    pub flagship_sunk_rate: f64,
+}
+
+impl BattleRankResult {
+    fn new(rank_map: HashMap<BattleRank, f64>, flagship_sunk_rate: f64) -> Self {
+        let sum_by = |f: fn(BattleRank) -> bool| {
+            rank_map
+                .iter()
+                .filter(|(rank, _)| f(**rank))
+                .map(|(_, rate)| rate)
+                .sum()
+        };
+
+        Self {
+            s_rate: sum_by(BattleRank::is_s_or_better),
+            victory_rate: sum_by(BattleRank::is_victory),
+            rank_map,
+            flagship_sunk_rate,
+        }
+    }
+
+    /// 試行回数で重み付けして複数の結果を結合する
+    fn merge(results: Vec<(BattleRankResult, f64)>) -> Option<Self> {
+        if results.is_empty() {
+            return None;
+        }
+
+        let mut rank_map = HashMap::<BattleRank, f64>::new();
+        let mut flagship_sunk_rate = 0.0;
+
+        for (result, weight) in results {
+            for (rank, rate) in result.rank_map {
+                *rank_map.entry(rank).or_default() += rate * weight;
+            }
+            flagship_sunk_rate += result.flagship_sunk_rate * weight;
+        }
+
+        Some(Self::new(rank_map, flagship_sunk_rate))
     }
 }
 
@@ -110,4 +217,72 @@ pub struct SimulatorResultItem {
 pub struct SimulatorResult {
     pub items: Vec<SimulatorResultItem>,
     pub sunk_vec: Vec<(usize, f64, f64)>,
+    /// 勝敗を記録した場合の分布
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<BattleRankResult>,
+    /// 発動率が不明なため艦隊特殊攻撃の判定を省いた割合
+    pub fleet_cutin_skipped_rate: f64,
+    /// 乱数のシード値
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl SimulatorResult {
+    /// 試行回数で重み付けして複数の結果を結合する
+    pub fn merge(results: Vec<(SimulatorResult, usize)>) -> Self {
+        let total = results.iter().map(|(_, times)| *times).sum::<usize>() as f64;
+
+        let mut items: Vec<SimulatorResultItem> = vec![];
+        let mut sunk_map = HashMap::<usize, f64>::new();
+        let mut ranks = vec![];
+        let mut fleet_cutin_skipped_rate = 0.0;
+
+        for (result, times) in results {
+            let weight = times as f64 / total;
+
+            for item in result.items {
+                let entry = match items.iter_mut().find(|current| current.id == item.id) {
+                    Some(entry) => entry,
+                    None => {
+                        items.push(SimulatorResultItem {
+                            damage_state_map: HashMap::new(),
+                            ..item.clone()
+                        });
+                        items.last_mut().unwrap_or_else(|| unreachable!())
+                    }
+                };
+
+                for (ds, rate) in item.damage_state_map {
+                    *entry.damage_state_map.entry(ds).or_default() += rate * weight;
+                }
+            }
+
+            for (n, rate, _) in result.sunk_vec {
+                *sunk_map.entry(n).or_default() += rate * weight;
+            }
+
+            if let Some(rank) = result.rank {
+                ranks.push((rank, weight));
+            }
+
+            fleet_cutin_skipped_rate += result.fleet_cutin_skipped_rate * weight;
+        }
+
+        let sunk_vec = sunk_map
+            .into_iter()
+            .sorted_by(|a, b| b.0.cmp(&a.0))
+            .scan(0.0, |acc, (n, rate)| {
+                *acc += rate;
+                Some((n, rate, *acc))
+            })
+            .collect();
+
+        Self {
+            items,
+            sunk_vec,
+            rank: BattleRankResult::merge(ranks),
+            fleet_cutin_skipped_rate,
+            seed: None,
+        }
+    }
 }
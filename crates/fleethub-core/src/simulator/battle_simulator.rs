@@ -0,0 +1,233 @@
+use anyhow::Result;
+use rand::prelude::*;
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::{
+    battle::{Battle, BattleComp},
+    types::{BattleDefinitions, BattleType, Engagement, NodeState, Phase},
+};
+
+use super::{
+    BattleLogger, SimulatorResult,
+    battle_logger::{battle_hps, rank_side},
+};
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct PhaseSimulatorResult {
+    pub phase: Phase,
+    pub player: SimulatorResult,
+    pub enemy: SimulatorResult,
+}
+
+/// 交戦形態ごとの結果
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct EngagementSimulatorResult {
+    pub engagement: Engagement,
+    pub times: usize,
+    /// 全体の試行回数に対する割合
+    pub rate: f64,
+    #[serde(flatten)]
+    pub result: BattleSimulatorResult,
+}
+
+/// `items`と`sunk_vec`は戦闘終了時の敵艦隊の結果
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct BattleSimulatorResult {
+    #[serde(flatten)]
+    pub enemy: SimulatorResult,
+    pub player: SimulatorResult,
+    pub phases: Vec<PhaseSimulatorResult>,
+    /// 交戦形態の発生率を指定した場合の交戦形態ごとの結果
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub engagements: Vec<EngagementSimulatorResult>,
+    /// 乱数のシード値
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl BattleSimulatorResult {
+    /// 試行回数で重み付けして複数の結果を結合する
+    pub fn merge(results: Vec<(BattleSimulatorResult, usize)>) -> Self {
+        let total = results.iter().map(|(_, times)| *times).sum::<usize>();
+
+        let mut enemy = vec![];
+        let mut player = vec![];
+        let mut phases: Vec<(Phase, Vec<_>, Vec<_>)> = vec![];
+        let mut engagements: Vec<(Engagement, Vec<_>)> = vec![];
+
+        for (result, times) in results {
+            for item in result.engagements {
+                let entry = match engagements
+                    .iter_mut()
+                    .find(|(engagement, _)| *engagement == item.engagement)
+                {
+                    Some(entry) => entry,
+                    None => {
+                        engagements.push((item.engagement, vec![]));
+                        engagements.last_mut().unwrap_or_else(|| unreachable!())
+                    }
+                };
+
+                entry.1.push((item.result, item.times));
+            }
+
+            enemy.push((result.enemy, times));
+            player.push((result.player, times));
+
+            for (index, phase) in result.phases.into_iter().enumerate() {
+                if phases.len() <= index {
+                    phases.push((phase.phase, vec![], vec![]));
+                }
+
+                phases[index].1.push((phase.player, times));
+                phases[index].2.push((phase.enemy, times));
+            }
+        }
+
+        Self {
+            enemy: SimulatorResult::merge(enemy),
+            player: SimulatorResult::merge(player),
+            phases: phases
+                .into_iter()
+                .map(|(phase, player, enemy)| PhaseSimulatorResult {
+                    phase,
+                    player: SimulatorResult::merge(player),
+                    enemy: SimulatorResult::merge(enemy),
+                })
+                .collect(),
+            engagements: engagements
+                .into_iter()
+                .map(|(engagement, results)| {
+                    let times = results.iter().map(|(_, times)| *times).sum::<usize>();
+
+                    EngagementSimulatorResult {
+                        engagement,
+                        times,
+                        rate: times as f64 / total.max(1) as f64,
+                        result: Self::merge(results),
+                    }
+                })
+                .collect(),
+            seed: None,
+        }
+    }
+}
+
+struct PhaseLogger {
+    phase: Phase,
+    player: BattleLogger,
+    enemy: BattleLogger,
+}
+
+pub struct BattleSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    rng: &'a mut R,
+    battle_defs: &'a BattleDefinitions,
+    player_comp: &'a mut BattleComp,
+    enemy_comp: &'a mut BattleComp,
+    battle_type: BattleType,
+    engagement: Engagement,
+    node_state: NodeState,
+}
+
+impl<'a, R> BattleSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub fn new(
+        rng: &'a mut R,
+        battle_defs: &'a BattleDefinitions,
+        player_comp: &'a mut BattleComp,
+        enemy_comp: &'a mut BattleComp,
+        battle_type: BattleType,
+        engagement: Engagement,
+        node_state: NodeState,
+    ) -> Self {
+        Self {
+            rng,
+            battle_defs,
+            player_comp,
+            enemy_comp,
+            battle_type,
+            engagement,
+            node_state,
+        }
+    }
+
+    fn battle(&mut self) -> Battle<'_, R> {
+        Battle::new(
+            self.rng,
+            self.battle_defs,
+            self.battle_type,
+            self.engagement,
+            self.node_state,
+            self.player_comp,
+            self.enemy_comp,
+        )
+    }
+
+    pub fn run(&mut self, times: usize) -> Result<BattleSimulatorResult> {
+        let mut player_logger = BattleLogger::new(times);
+        let mut enemy_logger = BattleLogger::new(times);
+
+        let mut phase_loggers = self
+            .battle()
+            .phases()?
+            .into_iter()
+            .map(|phase| PhaseLogger {
+                phase,
+                player: BattleLogger::new(times),
+                enemy: BattleLogger::new(times),
+            })
+            .collect::<Vec<_>>();
+
+        (0..times).try_for_each(|_| -> Result<()> {
+            let player_hps = battle_hps(self.player_comp);
+            let enemy_hps = battle_hps(self.enemy_comp);
+
+            let mut battle = self.battle();
+
+            phase_loggers
+                .iter_mut()
+                .try_for_each(|logger| -> Result<()> {
+                    battle.try_phase(logger.phase)?;
+                    logger.player.write(battle.player_comp);
+                    logger.enemy.write(battle.enemy_comp);
+                    Ok(())
+                })?;
+
+            player_logger.write(self.player_comp);
+            enemy_logger.write(self.enemy_comp);
+
+            let player_side = rank_side(self.player_comp, &player_hps);
+            let enemy_side = rank_side(self.enemy_comp, &enemy_hps);
+            player_logger.write_rank(&player_side, &enemy_side);
+            enemy_logger.write_rank(&player_side, &enemy_side);
+
+            self.player_comp.reset_battle_state();
+            self.enemy_comp.reset_battle_state();
+            Ok(())
+        })?;
+
+        let phases = phase_loggers
+            .into_iter()
+            .map(|logger| PhaseSimulatorResult {
+                phase: logger.phase,
+                player: logger.player.create_result(self.player_comp),
+                enemy: logger.enemy.create_result(self.enemy_comp),
+            })
+            .collect();
+
+        Ok(BattleSimulatorResult {
+            enemy: enemy_logger.create_result(self.enemy_comp),
+            player: player_logger.create_result(self.player_comp),
+            phases,
+            engagements: vec![],
+            seed: None,
+        })
+    }
+}
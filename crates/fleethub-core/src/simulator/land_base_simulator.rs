@@ -0,0 +1,262 @@
+use anyhow::Result;
+use hashbrown::HashMap;
+use rand::prelude::*;
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::{
+    air_squadron::AirSquadron,
+    battle::BattleComp,
+    types::{AirState, BattleDefinitions},
+    utils::Histogram,
+};
+
+use super::{BattleLogger, SimulatorResult, air_battle::try_land_base_wave};
+
+/// 基地航空隊1波ごとの結果
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct LandBaseWaveResult {
+    pub squadron_id: String,
+    /// 1波目が1、2波目が2
+    pub wave: usize,
+    pub air_state_map: HashMap<AirState, f64>,
+    /// 波終了時の平均搭載数
+    pub slots: Vec<f64>,
+    pub enemy: SimulatorResult,
+}
+
+/// `items`と`sunk_vec`は全ての波が終了した時点の敵艦隊の結果
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct LandBaseSimulatorResult {
+    #[serde(flatten)]
+    pub enemy: SimulatorResult,
+    pub waves: Vec<LandBaseWaveResult>,
+    /// 乱数のシード値
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+struct WaveLogger {
+    squadron_index: usize,
+    wave: usize,
+    air_state_counter: Histogram<AirState, usize>,
+    slot_sums: Vec<usize>,
+    enemy: BattleLogger,
+}
+
+pub struct LandBaseSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    rng: &'a mut R,
+    battle_defs: &'a BattleDefinitions,
+    squadrons: Vec<AirSquadron>,
+    enemy_comp: &'a mut BattleComp,
+}
+
+impl<'a, R> LandBaseSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    /// 出撃状態の基地航空隊のみを対象とする
+    pub fn new(
+        rng: &'a mut R,
+        battle_defs: &'a BattleDefinitions,
+        squadrons: Vec<AirSquadron>,
+        enemy_comp: &'a mut BattleComp,
+    ) -> Self {
+        let squadrons = squadrons
+            .into_iter()
+            .filter(|squadron| !squadron.mode.is_air_defense())
+            .collect();
+
+        Self {
+            rng,
+            battle_defs,
+            squadrons,
+            enemy_comp,
+        }
+    }
+
+    pub fn run(&mut self, times: usize) -> Result<LandBaseSimulatorResult> {
+        let mut enemy_logger = BattleLogger::new(times);
+
+        let mut wave_loggers = self
+            .squadrons
+            .iter()
+            .enumerate()
+            .flat_map(|(squadron_index, squadron)| {
+                (1..=2).map(move |wave| WaveLogger {
+                    squadron_index,
+                    wave,
+                    air_state_counter: Histogram::new(),
+                    slot_sums: vec![0; squadron.slots.len()],
+                    enemy: BattleLogger::new(times),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        (0..times).try_for_each(|_| -> Result<()> {
+            let mut squadrons = self.squadrons.clone();
+
+            wave_loggers
+                .iter_mut()
+                .try_for_each(|logger| -> Result<()> {
+                    let squadron = &mut squadrons[logger.squadron_index];
+                    let formation = self.enemy_comp.formation;
+
+                    let air_state = try_land_base_wave(
+                        self.rng,
+                        self.battle_defs,
+                        squadron,
+                        self.enemy_comp,
+                        formation,
+                    )?;
+
+                    logger.air_state_counter += (air_state, 1);
+                    logger
+                        .slot_sums
+                        .iter_mut()
+                        .zip(squadron.slots.iter())
+                        .for_each(|(sum, &slot)| *sum += slot.unwrap_or_default() as usize);
+                    logger.enemy.write(self.enemy_comp);
+                    Ok(())
+                })?;
+
+            enemy_logger.write(self.enemy_comp);
+            self.enemy_comp.reset_battle_state();
+            Ok(())
+        })?;
+
+        let times_f64 = times as f64;
+
+        let waves = wave_loggers
+            .into_iter()
+            .map(|logger| LandBaseWaveResult {
+                squadron_id: self.squadrons[logger.squadron_index].id.clone(),
+                wave: logger.wave,
+                air_state_map: logger
+                    .air_state_counter
+                    .into_iter()
+                    .map(|(air_state, count)| (air_state, count as f64 / times_f64))
+                    .collect(),
+                slots: logger
+                    .slot_sums
+                    .into_iter()
+                    .map(|sum| sum as f64 / times_f64)
+                    .collect(),
+                enemy: logger.enemy.create_result(self.enemy_comp),
+            })
+            .collect();
+
+        Ok(LandBaseSimulatorResult {
+            enemy: enemy_logger.create_result(self.enemy_comp),
+            waves,
+            seed: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        comp::Comp,
+        fleet::Fleet,
+        gear::Gear,
+        ship::Ship,
+        test::rng,
+        types::{AirSquadronMode, DamageState, Formation, GearAttr, GearType},
+    };
+
+    fn lb_attacker_squadron(mode: AirSquadronMode) -> AirSquadron {
+        let mut gear = Gear::default();
+        gear.gear_type = GearType::LbAttacker;
+        gear.attrs = GearAttr::LbAircraft.into();
+        gear.torpedo = 14;
+
+        let mut squadron = AirSquadron {
+            id: "squadron".into(),
+            mode,
+            slots: [Some(18)].into_iter().collect(),
+            max_slots: [Some(18)].into_iter().collect(),
+            ..Default::default()
+        };
+        squadron.gears.push(gear);
+        squadron
+    }
+
+    fn enemy_comp() -> BattleComp {
+        let mut ship = Ship::default();
+        ship.id = "enemy".into();
+        ship.current_hp = 30;
+        ship.master.max_hp.0 = Some(30);
+        ship.master.armor.1 = Some(10);
+        ship.master.anti_air.1 = Some(0);
+        ship.master.basic_evasion_term = Some(0.0);
+
+        let mut fleet = Fleet::default();
+        fleet.ships.push(ship);
+
+        let comp = Comp {
+            org_type: Default::default(),
+            hq_level: 120,
+            main: fleet,
+            escort: None,
+            route_sup: None,
+            boss_sup: None,
+        };
+
+        BattleComp::new(comp, Formation::LINE_AHEAD)
+    }
+
+    #[test]
+    fn test_land_base_simulator() {
+        let mut rng = rng(0);
+        let mut battle_defs = BattleDefinitions::default();
+        battle_defs
+            .formation
+            .insert(Formation::LINE_AHEAD, Default::default());
+        let mut enemy_comp = enemy_comp();
+
+        let squadrons = vec![
+            lb_attacker_squadron(AirSquadronMode::Sortie),
+            lb_attacker_squadron(AirSquadronMode::AirDefense),
+        ];
+        let result = LandBaseSimulator::new(&mut rng, &battle_defs, squadrons, &mut enemy_comp)
+            .run(100)
+            .unwrap();
+
+        // 防空の基地航空隊は出撃しない
+        assert_eq!(result.waves.len(), 2);
+        assert_eq!(
+            result
+                .waves
+                .iter()
+                .map(|wave| wave.wave)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+
+        // 波ごとの結果はその時点までの累計になる
+        let normal_rate = |enemy: &SimulatorResult| {
+            enemy.items[0]
+                .damage_state_map
+                .get(&DamageState::Normal)
+                .copied()
+                .unwrap_or_default()
+        };
+        let first = normal_rate(&result.waves[0].enemy);
+        let second = normal_rate(&result.waves[1].enemy);
+        assert!(first < 1.0 && second <= first);
+        assert_eq!(normal_rate(&result.enemy), second);
+
+        let first_slot = result.waves[0].slots[0];
+        let second_slot = result.waves[1].slots[0];
+        assert!(first_slot < 18.0 && second_slot <= first_slot);
+
+        // 試行ごとに敵艦隊の状態を戻す
+        assert_eq!(enemy_comp.main.ships[0].as_ref().unwrap().current_hp, 30);
+    }
+}
@@ -0,0 +1,506 @@
+use anyhow::{Result, ensure};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{
+    battle::{Battle, BattleComp},
+    comp::Comp,
+    types::{
+        BattleDefinitions, BattleType, DamageState, EngagementRates, FleetType, Formation,
+        FormationRate, NodeState, OrgState,
+    },
+};
+
+use super::{
+    BattleLogger, SimulatorResult,
+    battle_logger::{battle_hps, rank_side},
+};
+
+/// 各マスの戦闘の設定
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct SortieNodeConfig {
+    pub node_state: NodeState,
+    pub battle_type: BattleType,
+    pub player_formation: Formation,
+    pub enemy_formation: Formation,
+    /// 指定した場合は`enemy_formation`の代わりに使用する発生率
+    pub enemy_formation_rates: Vec<FormationRate>,
+    pub engagement: EngagementRates,
+    /// 戦闘後に消費する燃料の最大値に対する割合
+    pub fuel_consumption: f64,
+    /// 戦闘後に消費する弾薬の最大値に対する割合
+    pub ammo_consumption: f64,
+}
+
+impl Default for SortieNodeConfig {
+    fn default() -> Self {
+        Self {
+            node_state: Default::default(),
+            battle_type: Default::default(),
+            player_formation: Default::default(),
+            enemy_formation: Default::default(),
+            enemy_formation_rates: Default::default(),
+            engagement: Default::default(),
+            fuel_consumption: 0.2,
+            ammo_consumption: 0.2,
+        }
+    }
+}
+
+pub struct SortieNode {
+    pub enemy: Comp,
+    pub config: SortieNodeConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+pub struct SortieNodeInput {
+    pub enemy: OrgState,
+    #[serde(flatten)]
+    pub config: SortieNodeConfig,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+#[tsify(from_wasm_abi)]
+pub struct SortieInput {
+    pub nodes: Vec<SortieNodeInput>,
+    #[serde(flatten)]
+    pub config: SortieConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct SortieConfig {
+    /// 最終マス以外で大破艦が発生した場合に撤退する
+    pub retreat_on_heavy_damage: bool,
+    /// 大破艦が全て未使用の応急修理を持つ場合は撤退しない
+    pub advance_with_damage_control: bool,
+}
+
+impl Default for SortieConfig {
+    fn default() -> Self {
+        Self {
+            retreat_on_heavy_damage: true,
+            advance_with_damage_control: true,
+        }
+    }
+}
+
+/// 到達した場合の各マスの結果
+#[derive(Debug, Clone, Serialize, Tsify)]
+pub struct SortieNodeResult {
+    /// このマスに到達した割合
+    pub reach_rate: f64,
+    /// 戦闘後にこのマスで撤退した割合
+    pub retreat_rate: f64,
+    /// このマスの戦闘で応急修理が発動した割合
+    pub damage_control_rate: f64,
+    /// 戦闘前の自艦隊
+    pub arrival: SimulatorResult,
+    /// 戦闘後の自艦隊
+    pub player: SimulatorResult,
+    /// 戦闘後の敵艦隊
+    pub enemy: SimulatorResult,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct SortieSimulatorResult {
+    pub nodes: Vec<SortieNodeResult>,
+    /// 最終マスに到達した割合
+    pub boss_reach_rate: f64,
+    /// 最終マスに到達してS勝利以上となる割合
+    pub boss_s_rate: f64,
+    /// 最終マスに到達して敵旗艦を撃沈する割合
+    pub boss_kill_rate: f64,
+    /// 出撃中に応急修理が発動した割合
+    pub damage_control_rate: f64,
+    /// 乱数のシード値
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// 戦闘開始時に大破している旗艦以外の艦は轟沈し得る
+fn update_sinkable(comp: &mut BattleComp) {
+    comp.members_mut_by(FleetType::Main | FleetType::Escort)
+        .for_each(|member| {
+            let is_flagship = member.position.is_flagship();
+            let ship = member.ship;
+            ship.sinkable = !is_flagship && ship.damage_state() == DamageState::Taiha;
+        });
+}
+
+fn used_damage_controls(comp: &BattleComp) -> usize {
+    comp.members_by(FleetType::Main | FleetType::Escort)
+        .map(|member| usize::from(member.used_damage_controls))
+        .sum()
+}
+
+struct NodeLogger {
+    reached: usize,
+    retreated: usize,
+    damage_control_used: usize,
+    arrival: BattleLogger,
+    player: BattleLogger,
+    enemy: BattleLogger,
+}
+
+/// 複数のマスを続けて戦闘する
+///
+/// 耐久、燃料、弾薬、艦載機の損失はマス間で引き継ぎ、出撃ごとに初期化する
+pub struct SortieSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    rng: &'a mut R,
+    battle_defs: &'a BattleDefinitions,
+    player_comp: &'a mut BattleComp,
+    nodes: Vec<(&'a SortieNodeConfig, BattleComp)>,
+    config: &'a SortieConfig,
+}
+
+impl<'a, R> SortieSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub fn new(
+        rng: &'a mut R,
+        battle_defs: &'a BattleDefinitions,
+        player_comp: &'a mut BattleComp,
+        nodes: &'a [SortieNode],
+        config: &'a SortieConfig,
+    ) -> Self {
+        let nodes = nodes
+            .iter()
+            .map(|node| {
+                let enemy = BattleComp::new(node.enemy.clone(), node.config.enemy_formation);
+                (&node.config, enemy)
+            })
+            .collect();
+
+        Self {
+            rng,
+            battle_defs,
+            player_comp,
+            nodes,
+            config,
+        }
+    }
+
+    fn consume_supplies(&mut self, node_config: &SortieNodeConfig) {
+        self.player_comp
+            .members_mut_by(FleetType::Main | FleetType::Escort)
+            .for_each(|member| {
+                let ship = member.ship;
+                ship.fuel = ship.get_remaining_fuel(node_config.fuel_consumption, false);
+                ship.ammo = ship.get_remaining_ammo(node_config.ammo_consumption, false);
+            });
+    }
+
+    fn should_retreat(&self) -> bool {
+        self.config.retreat_on_heavy_damage
+            && self
+                .player_comp
+                .members_by(FleetType::Main | FleetType::Escort)
+                .filter(|member| member.damage_state() == DamageState::Taiha)
+                .any(|member| {
+                    !self.config.advance_with_damage_control
+                        || member.available_damage_control().is_none()
+                })
+    }
+
+    pub fn run(&mut self, times: usize) -> Result<SortieSimulatorResult> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, (node_config, _))| {
+                let rates = &node_config.enemy_formation_rates;
+
+                ensure!(
+                    rates.is_empty() || rates.iter().any(|rate| rate.rate > 0.0),
+                    "{}マス目の敵陣形の発生率が全て0です",
+                    index + 1
+                );
+                Ok(())
+            })?;
+
+        let mut loggers = self
+            .nodes
+            .iter()
+            .map(|_| NodeLogger {
+                reached: 0,
+                retreated: 0,
+                damage_control_used: 0,
+                arrival: BattleLogger::new(0),
+                player: BattleLogger::new(0),
+                enemy: BattleLogger::new(0),
+            })
+            .collect::<Vec<_>>();
+
+        let last_index = self.nodes.len().saturating_sub(1);
+        let mut damage_control_used = 0;
+
+        for _ in 0..times {
+            for (index, logger) in loggers.iter_mut().enumerate() {
+                logger.reached += 1;
+                logger.arrival.write(self.player_comp);
+
+                let node_config = self.nodes[index].0;
+                let engagement = node_config
+                    .engagement
+                    .with_saiun(self.player_comp.has_saiun())
+                    .choose(self.rng);
+                self.player_comp.formation = node_config.player_formation;
+
+                let enemy_comp = &mut self.nodes[index].1;
+
+                if !node_config.enemy_formation_rates.is_empty() {
+                    enemy_comp.formation = node_config
+                        .enemy_formation_rates
+                        .choose_weighted(self.rng, |rate| rate.rate.max(0.0))?
+                        .formation;
+                }
+                let player_hps = battle_hps(self.player_comp);
+                let enemy_hps = battle_hps(enemy_comp);
+                let used_before = used_damage_controls(self.player_comp);
+                update_sinkable(self.player_comp);
+
+                Battle::new(
+                    self.rng,
+                    self.battle_defs,
+                    node_config.battle_type,
+                    engagement,
+                    node_config.node_state,
+                    self.player_comp,
+                    enemy_comp,
+                )
+                .try_battle()?;
+
+                if used_damage_controls(self.player_comp) > used_before {
+                    logger.damage_control_used += 1;
+                }
+
+                logger.player.write(self.player_comp);
+                logger.enemy.write(enemy_comp);
+
+                let player_side = rank_side(self.player_comp, &player_hps);
+                let enemy_side = rank_side(enemy_comp, &enemy_hps);
+                logger.player.write_rank(&player_side, &enemy_side);
+                logger.enemy.write_rank(&player_side, &enemy_side);
+                enemy_comp.reset_battle_state();
+
+                self.consume_supplies(node_config);
+
+                if index != last_index && self.should_retreat() {
+                    logger.retreated += 1;
+                    break;
+                }
+            }
+
+            if used_damage_controls(self.player_comp) > 0 {
+                damage_control_used += 1;
+            }
+
+            self.player_comp.reset_battle_state();
+        }
+
+        let times_f64 = times.max(1) as f64;
+
+        let nodes = loggers
+            .into_iter()
+            .zip(self.nodes.iter())
+            .map(|(logger, (_, enemy_comp))| SortieNodeResult {
+                reach_rate: logger.reached as f64 / times_f64,
+                retreat_rate: logger.retreated as f64 / times_f64,
+                damage_control_rate: logger.damage_control_used as f64
+                    / logger.reached.max(1) as f64,
+                arrival: logger.arrival.create_result_by_count(self.player_comp),
+                player: logger.player.create_result_by_count(self.player_comp),
+                enemy: logger.enemy.create_result_by_count(enemy_comp),
+            })
+            .collect::<Vec<_>>();
+
+        let boss = nodes.last();
+        let boss_reach_rate = boss.map_or(0.0, |node| node.reach_rate);
+        let boss_rank = boss.and_then(|node| node.player.rank.as_ref());
+        let boss_s_rate = boss_rank.map_or(0.0, |rank| rank.s_rate) * boss_reach_rate;
+        let boss_kill_rate =
+            boss_rank.map_or(0.0, |rank| rank.flagship_sunk_rate) * boss_reach_rate;
+
+        Ok(SortieSimulatorResult {
+            nodes,
+            boss_reach_rate,
+            boss_s_rate,
+            boss_kill_rate,
+            damage_control_rate: damage_control_used as f64 / times_f64,
+            seed: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        ship::Ship,
+        test::{battle_defs, comp, damaged_ship, rng, ship},
+        types::{OrgType, ShipType},
+    };
+
+    fn enemy(firepower: u16) -> Comp {
+        let mut ship = ship("e1", ShipType::BB, 300);
+        ship.master.firepower.1 = Some(firepower);
+        comp(OrgType::EnemySingle, vec![ship])
+    }
+
+    fn node(enemy: Comp) -> SortieNode {
+        SortieNode {
+            enemy,
+            config: SortieNodeConfig {
+                player_formation: Formation::LINE_AHEAD,
+                enemy_formation: Formation::LINE_AHEAD,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn simulate(
+        ships: Vec<Ship>,
+        nodes: &[SortieNode],
+        config: &SortieConfig,
+    ) -> SortieSimulatorResult {
+        let battle_defs = battle_defs();
+        let mut rng = rng(0);
+        let mut player = BattleComp::new(comp(OrgType::Single, ships), Formation::LINE_AHEAD);
+
+        SortieSimulator::new(&mut rng, &battle_defs, &mut player, nodes, config)
+            .run(200)
+            .unwrap()
+    }
+
+    fn damage_state_rate(result: &SimulatorResult, id: &str, damage_state: DamageState) -> f64 {
+        result
+            .items
+            .iter()
+            .find(|item| item.id == id)
+            .and_then(|item| item.damage_state_map.get(&damage_state).copied())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_retreat_on_heavy_damage() {
+        // 大破した随伴艦は攻撃力が装甲を下回る敵からは撃沈されない
+        let ships = vec![
+            ship("p1", ShipType::BB, 80),
+            damaged_ship("p2", ShipType::DD, 40, 8),
+        ];
+        let nodes = [node(enemy(0)), node(enemy(0))];
+
+        let result = simulate(ships.clone(), &nodes, &Default::default());
+        assert_eq!(result.nodes[0].reach_rate, 1.0);
+        assert_eq!(result.nodes[0].retreat_rate, 1.0);
+        assert_eq!(result.nodes[1].reach_rate, 0.0);
+        assert_eq!(result.boss_reach_rate, 0.0);
+        assert_eq!(result.boss_s_rate, 0.0);
+
+        let config = SortieConfig {
+            retreat_on_heavy_damage: false,
+            ..Default::default()
+        };
+        let result = simulate(ships, &nodes, &config);
+        assert_eq!(result.nodes[0].retreat_rate, 0.0);
+        assert_eq!(result.nodes[1].reach_rate, 1.0);
+        assert_eq!(result.boss_reach_rate, 1.0);
+        assert_eq!(
+            damage_state_rate(&result.nodes[1].arrival, "p2", DamageState::Taiha),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_sortie_carry_over() {
+        let ships = || vec![ship("p1", ShipType::BB, 80), ship("p2", ShipType::DD, 40)];
+        let nodes = [node(enemy(200)), node(enemy(0))];
+
+        let config = SortieConfig {
+            retreat_on_heavy_damage: false,
+            ..Default::default()
+        };
+        let result = simulate(ships(), &nodes, &config);
+        let [first, boss] = &result.nodes[..] else {
+            unreachable!()
+        };
+
+        // 2マス目の到達時の状態は1マス目の戦闘後の状態と一致する
+        assert_eq!(
+            damage_state_rate(&first.arrival, "p2", DamageState::Normal),
+            1.0
+        );
+        ["p1", "p2"].into_iter().for_each(|id| {
+            [
+                DamageState::Normal,
+                DamageState::Shouha,
+                DamageState::Chuuha,
+                DamageState::Taiha,
+            ]
+            .into_iter()
+            .for_each(|ds| {
+                assert_eq!(
+                    damage_state_rate(&boss.arrival, id, ds),
+                    damage_state_rate(&first.player, id, ds)
+                );
+            });
+        });
+        assert!(damage_state_rate(&boss.arrival, "p2", DamageState::Normal) < 1.0);
+
+        let result = simulate(ships(), &nodes, &Default::default());
+        let [first, boss] = &result.nodes[..] else {
+            unreachable!()
+        };
+
+        // 大破した艦がいる場合のみ撤退する
+        let heavily_damaged = ["p1", "p2"]
+            .into_iter()
+            .map(|id| damage_state_rate(&first.player, id, DamageState::Taiha))
+            .fold(0.0, f64::max);
+        assert!(first.retreat_rate > 0.0);
+        assert!(first.retreat_rate >= heavily_damaged);
+        assert_eq!(boss.reach_rate, 1.0 - first.retreat_rate);
+        assert_eq!(result.boss_reach_rate, boss.reach_rate);
+    }
+
+    #[test]
+    fn test_enemy_formation_rates() {
+        let battle_defs = battle_defs();
+        let mut rng = rng(0);
+        let mut player = BattleComp::new(
+            comp(OrgType::Single, vec![ship("p1", ShipType::BB, 80)]),
+            Formation::LINE_AHEAD,
+        );
+
+        let with_rates = |rates: &[f64]| {
+            let mut boss = node(enemy(0));
+            boss.config.enemy_formation_rates = rates
+                .iter()
+                .map(|&rate| FormationRate {
+                    formation: Formation::DIAMOND,
+                    rate,
+                })
+                .collect();
+            [node(enemy(0)), boss]
+        };
+
+        let config = SortieConfig::default();
+        let mut run = |nodes: &[SortieNode]| {
+            SortieSimulator::new(&mut rng, &battle_defs, &mut player, nodes, &config).run(10)
+        };
+
+        assert!(run(&with_rates(&[])).is_ok());
+        assert!(run(&with_rates(&[1.0, 0.0])).is_ok());
+
+        let err = run(&with_rates(&[0.0, -1.0])).unwrap_err();
+        assert_eq!(err.to_string(), "2マス目の敵陣形の発生率が全て0です");
+    }
+}
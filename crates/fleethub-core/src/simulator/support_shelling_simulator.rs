@@ -5,7 +5,7 @@ use tsify::Tsify;
 
 use crate::{
     battle::{support_shelling_phase::SupportShellingPhase, BattleComp},
-    types::{BattleDefinitions, Engagement, Formation, NodeState},
+    types::{BattleDefinitions, Engagement, FleetType, Formation, NodeState},
 };
 
 use super::{BattleLogger, SimulatorResult};
@@ -45,6 +45,7 @@ where
             battle_defs,
             engagement,
             node_state,
+            support_fleet: FleetType::RouteSup,
             player_comp,
             enemy_comp,
         };
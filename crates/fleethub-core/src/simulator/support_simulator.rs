@@ -0,0 +1,145 @@
+use anyhow::{Result, anyhow, ensure};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{
+    battle::{
+        BattleComp, support_shelling_phase::SupportShellingPhase,
+        support_torpedo_phase::SupportTorpedoPhase,
+    },
+    types::{BattleDefinitions, Engagement, FleetType, NodeState, SupportType},
+};
+
+use super::{BattleLogger, SimulatorResult, air_battle::try_support_airstrike};
+
+/// 支援艦隊の設定
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub struct SupportConfig {
+    /// 道中支援か決戦支援のいずれか
+    pub support_fleet: FleetType,
+    /// 省略した場合は支援艦隊の編成から判定する
+    #[serde(default)]
+    pub support_type: Option<SupportType>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct SupportSimulatorResult {
+    #[serde(flatten)]
+    pub enemy: SimulatorResult,
+    pub support_type: SupportType,
+    /// 乱数のシード値
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+pub struct SupportSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    rng: &'a mut R,
+    battle_defs: &'a BattleDefinitions,
+    player_comp: &'a mut BattleComp,
+    enemy_comp: &'a mut BattleComp,
+    engagement: Engagement,
+    node_state: NodeState,
+    support_fleet: FleetType,
+    support_type: SupportType,
+}
+
+impl<'a, R> SupportSimulator<'a, R>
+where
+    R: Rng + ?Sized,
+{
+    pub fn new(
+        rng: &'a mut R,
+        battle_defs: &'a BattleDefinitions,
+        player_comp: &'a mut BattleComp,
+        enemy_comp: &'a mut BattleComp,
+        engagement: Engagement,
+        node_state: NodeState,
+        config: SupportConfig,
+    ) -> Result<Self> {
+        let SupportConfig {
+            support_fleet,
+            support_type,
+        } = config;
+
+        ensure!(
+            matches!(support_fleet, FleetType::RouteSup | FleetType::BossSup),
+            "支援艦隊ではありません: {support_fleet:?}"
+        );
+
+        let support_type = support_type
+            .or_else(|| player_comp.get_fleet(support_fleet)?.support_type())
+            .ok_or_else(|| anyhow!("支援艦隊の種別を判定できません"))?;
+
+        Ok(Self {
+            rng,
+            battle_defs,
+            player_comp,
+            enemy_comp,
+            engagement,
+            node_state,
+            support_fleet,
+            support_type,
+        })
+    }
+
+    fn try_combat(&mut self) -> Result<()> {
+        match self.support_type {
+            SupportType::Shelling => SupportShellingPhase {
+                rng: self.rng,
+                battle_defs: self.battle_defs,
+                engagement: self.engagement,
+                node_state: self.node_state,
+                support_fleet: self.support_fleet,
+                player_comp: self.player_comp,
+                enemy_comp: self.enemy_comp,
+            }
+            .try_combat(),
+            SupportType::Torpedo => SupportTorpedoPhase {
+                rng: self.rng,
+                battle_defs: self.battle_defs,
+                engagement: self.engagement,
+                node_state: self.node_state,
+                support_fleet: self.support_fleet,
+                player_comp: self.player_comp,
+                enemy_comp: self.enemy_comp,
+            }
+            .try_combat(),
+            SupportType::Aerial => {
+                let enemy_formation = self.enemy_comp.formation;
+
+                try_support_airstrike(
+                    self.rng,
+                    self.battle_defs,
+                    self.support_fleet,
+                    self.player_comp,
+                    self.enemy_comp,
+                    enemy_formation,
+                )
+            }
+        }
+    }
+
+    pub fn run(&mut self, times: usize) -> Result<SupportSimulatorResult> {
+        let mut logger = BattleLogger::new(times);
+
+        (0..times).try_for_each(|_| -> Result<()> {
+            self.try_combat()?;
+            logger.write(self.enemy_comp);
+            self.player_comp.reset_battle_state();
+            self.enemy_comp.reset_battle_state();
+            Ok(())
+        })?;
+
+        Ok(SupportSimulatorResult {
+            enemy: logger.create_result(self.enemy_comp),
+            support_type: self.support_type,
+            seed: None,
+        })
+    }
+}
@@ -16,11 +16,13 @@ export_types!(
     attack_type,
     battle_conditions,
     battle_definitions,
+    battle_rank,
     comp_type,
     compiled_evaler,
     const_id,
     contact_rank,
     cutin,
+    damage_control,
     damage_state,
     defense_power,
     ebonus,
@@ -52,4 +54,5 @@ export_types!(
     slot_size_vec,
     special_enemy_type,
     speed,
+    support_type,
 );
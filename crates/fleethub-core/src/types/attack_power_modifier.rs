@@ -90,6 +90,14 @@ pub struct SpecialEnemyModifiers {
     pub r35_french_mod: AttackPowerModifier,
     pub postcap_general_mod: AttackPowerModifier,
     pub pt_mod: Option<AttackPowerModifier>,
+    /// 対PT小鬼群の命中補正、陣形補正の後に`a`倍して`b`を加算する
+    pub pt_accuracy_mod: AttackPowerModifier,
+    /// 対PT小鬼群の艦種命中補正
+    pub pt_ship_type_accuracy_mod: AttackPowerModifier,
+    /// 対PT小鬼群の装備命中補正
+    pub pt_equipment_accuracy_mod: AttackPowerModifier,
+    /// 対PT小鬼群の夜戦命中補正
+    pub pt_night_accuracy_mod: AttackPowerModifier,
 }
 
 impl SpecialEnemyModifiers {
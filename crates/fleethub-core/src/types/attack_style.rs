@@ -151,6 +151,15 @@ pub enum NightPhaseAttackStyle {
     Asw(AswAttackStyle),
 }
 
+impl NightPhaseAttackStyle {
+    pub fn to_attack_type(&self) -> AttackType {
+        match self {
+            NightPhaseAttackStyle::Asw(style) => style.attack_type.into(),
+            NightPhaseAttackStyle::Night(style) => style.attack_type.into(),
+        }
+    }
+}
+
 impl From<AswAttackType> for NightPhaseAttackStyle {
     fn from(attack_type: AswAttackType) -> Self {
         Self::Asw(AswAttackStyle { attack_type })
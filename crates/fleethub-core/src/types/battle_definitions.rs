@@ -7,11 +7,21 @@ use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DefaultOnError};
 use tsify::Tsify;
 
-use crate::member::BattleMemberRef;
+use crate::{
+    attack::{FleetCutinEffect, reference_fleet_cutin_defs},
+    fleet::Fleet,
+    gear::Gear,
+    member::BattleMemberRef,
+    ship::{
+        Ship, gunfit_accuracy, gunfit_accuracy_info, land_base_special_enemy_modifiers,
+        reference_gunfit_accuracy_defs, reference_special_enemy_mod_defs, special_enemy_modifiers,
+    },
+};
 
 use super::{
-    AttackPowerModifier, AttackType, CompiledEvaler, DayCutin, DayCutinLike, Formation, NightCutin,
-    NightCutinLike, NodeState, ShipConditions,
+    AttackPowerModifier, AttackType, CompiledEvaler, DayCutin, DayCutinLike, Engagement,
+    FleetCutinTag, Formation, GearType, NightCutin, NightCutinLike, NodeState, ShipConditions,
+    ShipType, SpecialEnemyModifiers, SpecialEnemyType, Time,
 };
 
 #[serde_as]
@@ -163,6 +173,7 @@ impl DayCutinDef {
 #[derive(Debug, Default, Clone, Deserialize, Tsify)]
 #[serde(default)]
 pub struct HistoricalBonusDef {
+    pub name: String,
     map: i16,
     node: CompiledEvaler,
     phase: u8,
@@ -193,6 +204,27 @@ impl HistoricalBonusDef {
     }
 }
 
+/// マスターデータとは別に追加する特効の定義
+#[derive(Debug, Default, Clone, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+#[serde(default)]
+pub struct HistoricalBonusRuleSet {
+    pub name: String,
+    pub rules: Vec<HistoricalBonusDef>,
+}
+
+/// 適用された特効の参照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+pub struct HistoricalBonusRef {
+    /// マスターデータの定義の場合は`None`
+    pub rule_set: Option<String>,
+    pub index: usize,
+    pub name: String,
+}
+
+/// (追加の定義名, 定義内の位置, 定義)
+type HistoricalBonusEntry<'a> = (Option<&'a str>, usize, &'a HistoricalBonusDef);
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Tsify)]
 pub struct HistoricalParams {
     pub power_mod: AttackPowerModifier,
@@ -245,6 +277,225 @@ impl NightCutinDef {
     }
 }
 
+/// 艦隊特殊攻撃の装備補正
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct FleetCutinEquipmentModDef {
+    pub requires: CompiledEvaler,
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct FleetCutinAttackDef {
+    /// 攻撃する艦の位置
+    pub index: usize,
+    /// 順に掛け合わせる火力補正
+    pub power_mods: Vec<CompiledEvaler>,
+    #[serde(default = "num_traits::one")]
+    pub accuracy_mod: f64,
+    #[serde(default)]
+    pub equipment_mods: Vec<FleetCutinEquipmentModDef>,
+}
+
+/// 艦隊特殊攻撃の攻撃回数の上限
+pub const FLEET_CUTIN_ATTACK_CAPACITY: usize = 3;
+
+fn deserialize_fleet_cutin_attacks<'de, D>(
+    deserializer: D,
+) -> Result<Vec<FleetCutinAttackDef>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let attacks = Vec::<FleetCutinAttackDef>::deserialize(deserializer)?;
+
+    if attacks.len() > FLEET_CUTIN_ATTACK_CAPACITY {
+        return Err(D::Error::invalid_length(
+            attacks.len(),
+            &"at most 3 fleet cutin attacks",
+        ));
+    }
+
+    Ok(attacks)
+}
+
+/// 艦隊特殊攻撃の定義
+///
+/// 式では`ship_id(1)`のように艦の位置を1から始まる番号で指定する
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct FleetCutinDef {
+    pub tag: FleetCutinTag,
+    pub formations: Vec<Formation>,
+    #[serde(default)]
+    pub night_only: bool,
+    pub requires: CompiledEvaler,
+    /// 発動率(%)、空の場合は不明
+    #[serde(default)]
+    pub rate: CompiledEvaler,
+    /// 最大`FLEET_CUTIN_ATTACK_CAPACITY`回
+    #[serde(deserialize_with = "deserialize_fleet_cutin_attacks")]
+    pub attacks: Vec<FleetCutinAttackDef>,
+}
+
+/// 特効補正の対象となる攻撃種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Tsify)]
+pub enum SpecialEnemyAttackCategory {
+    Asw,
+    Shelling,
+    Night,
+    Torpedo,
+    SupportShelling,
+    /// 基地航空隊の航空攻撃、明示した規則のみ適用する
+    LandBase,
+}
+
+impl SpecialEnemyAttackCategory {
+    pub fn matches(self, attack_type: AttackType) -> bool {
+        matches!(
+            (self, attack_type),
+            (Self::Asw, AttackType::Asw(_))
+                | (Self::Shelling, AttackType::Shelling(_))
+                | (Self::Night, AttackType::Night(_))
+                | (Self::Torpedo, AttackType::Torpedo)
+                | (Self::SupportShelling, AttackType::SupportShelling(_))
+        )
+    }
+}
+
+/// 特効補正を適用する項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecialEnemyModField {
+    PrecapGeneralMod,
+    StypeMod,
+    LandingCraftSynergyMod,
+    TokuDaihatsuTankMod,
+    M4a1ddMod,
+    HoniMod,
+    TokuDaihatsuChihaMod,
+    TokuDaihatsuChihaKaiMod,
+    ArmyInfantryMod,
+    ArmyInfantryChihaKaiMod,
+    T97TankChihaMod,
+    T97TankChihaKaiMod,
+    LandingForcesMod,
+    LandingForcesGroupMod,
+    LandingForcesT4Mod,
+    LandingForcesT4kaiMod,
+    T4TankGroupMod,
+    T4TankKaiMod,
+    Panzer2Mod,
+    R35FrenchMod,
+    PostcapGeneralMod,
+    PtMod,
+    PtAccuracyMod,
+    PtShipTypeAccuracyMod,
+    PtEquipmentAccuracyMod,
+    PtNightAccuracyMod,
+}
+
+/// 平均改修値による補正の項
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct StarsBonusTermDef {
+    #[serde(default)]
+    pub gear_ids: Vec<u16>,
+    #[serde(default)]
+    pub gear_types: Vec<GearType>,
+    pub divisor: f64,
+}
+
+/// 特効補正の規則
+///
+/// 対象に該当する規則を順に適用する
+#[derive(Debug, Clone, Deserialize, Tsify)]
+pub struct SpecialEnemyModDef {
+    /// 規則のグループ、マスターデータで同じグループの規則を定義すると組み込みの規則を置き換える
+    #[serde(default)]
+    pub id: String,
+    pub targets: Vec<SpecialEnemyType>,
+    /// 空の場合は全ての攻撃種別
+    #[serde(default)]
+    pub attack_types: Vec<SpecialEnemyAttackCategory>,
+    #[serde(default)]
+    pub requires: CompiledEvaler,
+    /// 補正値を選ぶための個数、空の場合は1
+    #[serde(default)]
+    pub count: CompiledEvaler,
+    pub field: SpecialEnemyModField,
+    /// 個数に対応する乗算補正、個数が多い場合は最後の値を使う
+    #[serde(default)]
+    pub a: Vec<f64>,
+    /// 個数に対応する加算補正
+    #[serde(default)]
+    pub b: Vec<f64>,
+    /// 乗算補正に順に掛ける 1 + Σ(平均改修値 / divisor)
+    #[serde(default)]
+    pub stars_bonuses: Vec<Vec<StarsBonusTermDef>>,
+}
+
+/// フィット砲補正の規則で装備数から補正値を求める方法
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum GunfitScale {
+    /// 補正値 × √個数
+    #[default]
+    Sqrt,
+    /// 補正値 × 個数
+    Linear,
+    /// 1個以上で補正値
+    Any,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Tsify)]
+#[serde(default)]
+pub struct GunfitRuleDef {
+    pub gear_ids: Vec<u16>,
+    pub gear_types: Vec<GearType>,
+    /// いずれかを装備している場合は適用しない
+    pub unless_gear_ids: Vec<u16>,
+    /// 省略した場合は昼戦と夜戦の両方
+    pub night: Option<bool>,
+    pub scale: GunfitScale,
+    pub value: f64,
+    /// ケッコンカッコカリ時の補正値、省略した場合は`value`
+    pub married_value: Option<f64>,
+}
+
+/// フィット砲補正の定義
+///
+/// 条件は空の場合に全ての艦に一致し、一致した定義の規則を全て加算する
+#[derive(Debug, Default, Clone, Deserialize, Tsify)]
+#[serde(default)]
+pub struct GunfitAccuracyDef {
+    /// 組み込みの定義と同じ名前の場合は置き換える
+    pub name: String,
+    pub ship_types: Vec<ShipType>,
+    pub ctypes: Vec<u16>,
+    pub ship_ids: Vec<u16>,
+    pub exclude_ctypes: Vec<u16>,
+    pub exclude_ship_ids: Vec<u16>,
+    /// 深海棲艦に限定する場合は`true`、艦娘に限定する場合は`false`
+    pub abyssal: Option<bool>,
+    pub rules: Vec<GunfitRuleDef>,
+}
+
+/// 装備ごとのフィット砲補正
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+pub struct GunfitGearContribution {
+    pub index: usize,
+    pub gear_id: u16,
+    pub value: f64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct GunfitAccuracyInfo {
+    pub value: f64,
+    /// `ShipState`で指定された値を使っている
+    pub overridden: bool,
+    pub gears: Vec<GunfitGearContribution>,
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Tsify)]
 pub struct BattleDefinitions {
     pub formation: HashMap<Formation, NestedFormationDef>,
@@ -252,9 +503,113 @@ pub struct BattleDefinitions {
     pub day_cutin: HashMap<DayCutin, DayCutinDef>,
     pub night_cutin: HashMap<NightCutin, NightCutinDef>,
     pub historical_bonuses: Vec<HistoricalBonusDef>,
+    #[serde(default)]
+    pub fleet_cutin: Vec<FleetCutinDef>,
+    #[serde(default)]
+    pub special_enemy: Vec<SpecialEnemyModDef>,
+    #[serde(default)]
+    pub historical_bonus_rule_sets: Vec<HistoricalBonusRuleSet>,
+    #[serde(default)]
+    pub gunfit_accuracy: Vec<GunfitAccuracyDef>,
 }
 
 impl BattleDefinitions {
+    /// 組み込みの定義に、タグごとに定義を上書き・追加したもの
+    pub fn fleet_cutin_defs(&self) -> Vec<&FleetCutinDef> {
+        let find = |tag: &FleetCutinTag| self.fleet_cutin.iter().find(|def| &def.tag == tag);
+
+        let reference = reference_fleet_cutin_defs()
+            .iter()
+            .map(|def| find(&def.tag).unwrap_or(def));
+
+        let added = self.fleet_cutin.iter().filter(|def| {
+            !reference_fleet_cutin_defs()
+                .iter()
+                .any(|reference| reference.tag == def.tag)
+        });
+
+        reference.chain(added).collect()
+    }
+
+    pub fn get_possible_fleet_cutin_effect_vec(
+        &self,
+        fleet: &Fleet,
+        formation: Formation,
+        engagement: Engagement,
+        time: Time,
+    ) -> Vec<FleetCutinEffect> {
+        self.fleet_cutin_defs()
+            .into_iter()
+            .filter_map(|def| def.get_effect(fleet, formation, engagement, time))
+            .collect()
+    }
+
+    pub fn calc_fleet_cutin_rate(&self, fleet: &Fleet, tag: &FleetCutinTag) -> Option<f64> {
+        self.fleet_cutin_defs()
+            .into_iter()
+            .find(|def| &def.tag == tag)?
+            .calc_rate(fleet)
+    }
+
+    /// 組み込みの規則に、グループごとに規則を置き換え・追加したもの
+    pub fn special_enemy_mod_defs(&self) -> Vec<&SpecialEnemyModDef> {
+        let overridden =
+            |id: &str| !id.is_empty() && self.special_enemy.iter().any(|def| def.id == id);
+
+        reference_special_enemy_mod_defs()
+            .iter()
+            .filter(|def| !overridden(&def.id))
+            .chain(self.special_enemy.iter())
+            .collect()
+    }
+
+    pub fn get_special_enemy_mods<T: Into<AttackType>>(
+        &self,
+        attacker: &Ship,
+        target_type: SpecialEnemyType,
+        attack_type: T,
+    ) -> SpecialEnemyModifiers {
+        special_enemy_modifiers(
+            self.special_enemy_mod_defs(),
+            attacker,
+            target_type,
+            attack_type.into(),
+        )
+    }
+
+    pub fn get_land_base_special_enemy_mods(
+        &self,
+        plane: &Gear,
+        target_type: SpecialEnemyType,
+    ) -> SpecialEnemyModifiers {
+        land_base_special_enemy_modifiers(self.special_enemy_mod_defs(), plane, target_type)
+    }
+
+    /// 組み込みの定義に、名前ごとに定義を上書き・追加したもの
+    pub fn gunfit_accuracy_defs(&self) -> Vec<&GunfitAccuracyDef> {
+        let find = |name: &str| self.gunfit_accuracy.iter().find(|def| def.name == name);
+
+        let reference = reference_gunfit_accuracy_defs()
+            .iter()
+            .map(|def| find(&def.name).unwrap_or(def));
+
+        let added = self.gunfit_accuracy.iter().filter(|def| {
+            !reference_gunfit_accuracy_defs()
+                .iter()
+                .any(|reference| reference.name == def.name)
+        });
+
+        reference.chain(added).collect()
+    }
+
+    pub fn get_gunfit_accuracy(&self, ship: &Ship, is_night: bool) -> f64 {
+        gunfit_accuracy(&self.gunfit_accuracy_defs(), ship, is_night)
+    }
+
+    pub fn get_gunfit_accuracy_info(&self, ship: &Ship, is_night: bool) -> GunfitAccuracyInfo {
+        gunfit_accuracy_info(&self.gunfit_accuracy_defs(), ship, is_night)
+    }
+
     pub fn get_formation_def(
         &self,
         formation: Formation,
@@ -268,6 +623,45 @@ impl BattleDefinitions {
         nfd.get_def(fleet_len, ship_index)
     }
 
+    /// マスターデータの定義の後に追加の定義を順に返す
+    fn historical_bonus_defs(&self) -> impl Iterator<Item = HistoricalBonusEntry<'_>> {
+        let master = self
+            .historical_bonuses
+            .iter()
+            .enumerate()
+            .map(|(index, def)| (None, index, def));
+
+        let custom = self.historical_bonus_rule_sets.iter().flat_map(|rule_set| {
+            rule_set
+                .rules
+                .iter()
+                .enumerate()
+                .map(move |(index, def)| (Some(rule_set.name.as_str()), index, def))
+        });
+
+        master.chain(custom)
+    }
+
+    fn matched_historical_bonus_defs<'a>(
+        &'a self,
+        node_state: NodeState,
+        attacker: &'a BattleMemberRef,
+        target: &'a BattleMemberRef,
+    ) -> impl Iterator<Item = HistoricalBonusEntry<'a>> {
+        let disabled = node_state.map == 0 || node_state.disable_historical_mod;
+        let attacker_is_player = attacker.position.org_type.is_player();
+
+        self.historical_bonus_defs()
+            .filter(move |_| !disabled)
+            .filter(move |(_, _, def)| {
+                if attacker_is_player {
+                    def.matches(node_state, attacker, target)
+                } else {
+                    def.matches(node_state, target, attacker)
+                }
+            })
+    }
+
     pub fn get_historical_params(
         &self,
         node_state: NodeState,
@@ -275,32 +669,37 @@ impl BattleDefinitions {
         target: &BattleMemberRef,
     ) -> HistoricalParams {
         let mut params = HistoricalParams::default();
+        let attacker_is_player = attacker.position.org_type.is_player();
 
-        if node_state.map == 0 || node_state.disable_historical_mod {
-            return params;
-        }
-
-        if attacker.position.org_type.is_player() {
-            self.historical_bonuses
-                .iter()
-                .filter(|def| def.matches(node_state, attacker, target))
-                .for_each(|def| {
+        self.matched_historical_bonus_defs(node_state, attacker, target)
+            .for_each(|(_, _, def)| {
+                if attacker_is_player {
                     params.power_mod.merge(def.power_mod.a, def.power_mod.b);
                     params.armor_penetration += def.armor_penetration;
                     params.accuracy_mod *= def.accuracy_mod;
-                })
-        } else {
-            self.historical_bonuses
-                .iter()
-                .filter(|def| def.matches(node_state, target, attacker))
-                .for_each(|def| {
+                } else {
                     params.target_evasion_mod *= def.evasion_mod;
-                })
-        };
+                }
+            });
 
         params
     }
 
+    pub fn get_historical_bonus_refs(
+        &self,
+        node_state: NodeState,
+        attacker: &BattleMemberRef,
+        target: &BattleMemberRef,
+    ) -> Vec<HistoricalBonusRef> {
+        self.matched_historical_bonus_defs(node_state, attacker, target)
+            .map(|(rule_set, index, def)| HistoricalBonusRef {
+                rule_set: rule_set.map(ToString::to_string),
+                index,
+                name: def.name.clone(),
+            })
+            .collect()
+    }
+
     pub fn get_formation_fleet_anti_air_mod(&self, formation: Formation) -> f64 {
         self.get_formation_def(formation, 0, 6).fleet_anti_air_mod
     }
@@ -468,6 +867,7 @@ fn cancels_formation_modifier(
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::{OrgType, ShipPosition};
 
     #[test]
     fn test_cancels_formation_modifier() {
@@ -510,4 +910,145 @@ mod test {
                 )
             });
     }
+
+    #[test]
+    fn test_historical_bonus_rule_sets() {
+        let rule_set: HistoricalBonusRuleSet = serde_json::from_value(serde_json::json!({
+            "name": "custom",
+            "rules": [
+                { "name": "A", "map": 551, "node": "A", "power_mod": { "a": 1.2 }, "evasion_mod": 0.8 },
+                { "name": "B", "map": 551, "node": "B", "accuracy_mod": 1.1 },
+            ]
+        }))
+        .unwrap();
+
+        let mut defs = BattleDefinitions::default();
+        defs.historical_bonus_rule_sets.push(rule_set);
+
+        let player_ship = Ship::default();
+        let enemy_ship = Ship::default();
+        let enemy_position = ShipPosition {
+            org_type: OrgType::EnemySingle,
+            ..Default::default()
+        };
+        let player =
+            BattleMemberRef::new(&player_ship, Default::default(), Default::default(), None);
+        let enemy = BattleMemberRef::new(&enemy_ship, enemy_position, Default::default(), None);
+
+        let node_state = NodeState {
+            map: 551,
+            node: "A".parse().unwrap(),
+            ..Default::default()
+        };
+
+        let params = defs.get_historical_params(node_state, &player, &enemy);
+        assert_eq!(params.power_mod.a, 1.2);
+        assert_eq!(params.accuracy_mod, 1.0);
+        assert_eq!(
+            defs.get_historical_bonus_refs(node_state, &player, &enemy),
+            vec![HistoricalBonusRef {
+                rule_set: Some("custom".into()),
+                index: 0,
+                name: "A".into(),
+            }]
+        );
+
+        let params = defs.get_historical_params(node_state, &enemy, &player);
+        assert_eq!(params.target_evasion_mod, 0.8);
+
+        let disabled = NodeState {
+            disable_historical_mod: true,
+            ..node_state
+        };
+        assert!(
+            defs.get_historical_bonus_refs(disabled, &player, &enemy)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_special_enemy_mod_defs() {
+        let reference = reference_special_enemy_mod_defs();
+        let count_id =
+            |defs: &[&SpecialEnemyModDef], id: &str| defs.iter().filter(|def| def.id == id).count();
+
+        let mut defs = BattleDefinitions::default();
+        assert_eq!(defs.special_enemy_mod_defs().len(), reference.len());
+
+        defs.special_enemy = serde_json::from_value(serde_json::json!([
+            {
+                "id": "event",
+                "targets": ["SupplyDepot"],
+                "field": "postcap_general_mod",
+                "a": [1.1],
+            },
+        ]))
+        .unwrap();
+
+        let merged = defs.special_enemy_mod_defs();
+        assert_eq!(merged.len(), reference.len() + 1);
+        assert_eq!(count_id(&merged, "event"), 1);
+
+        defs.special_enemy = serde_json::from_value(serde_json::json!([
+            {
+                "id": "anti_pt_imp",
+                "targets": ["PtImp"],
+                "field": "pt_mod",
+                "a": [1.5],
+            },
+        ]))
+        .unwrap();
+
+        let merged = defs.special_enemy_mod_defs();
+        let reference_pt = reference
+            .iter()
+            .filter(|def| def.id == "anti_pt_imp")
+            .count();
+        assert!(reference_pt > 1);
+        assert_eq!(merged.len(), reference.len() - reference_pt + 1);
+        assert_eq!(count_id(&merged, "anti_pt_imp"), 1);
+        assert!(count_id(&merged, "anti_installation") > 0);
+    }
+
+    #[test]
+    fn test_gunfit_accuracy_defs() {
+        use crate::types::{ctype, gear_id};
+
+        let gunfit_accuracy = serde_json::from_value(serde_json::json!([
+            {
+                "name": "長門型",
+                "ship_types": ["BB"],
+                "ctypes": [ctype!("長門型")],
+                "rules": [
+                    { "gear_ids": [gear_id!("46cm三連装砲")], "value": -7 },
+                    { "gear_ids": [gear_id!("46cm三連装砲")], "night": true, "value": 3 },
+                ],
+            },
+        ]))
+        .unwrap();
+        let defs = BattleDefinitions {
+            gunfit_accuracy,
+            ..Default::default()
+        };
+
+        let reference = reference_gunfit_accuracy_defs();
+        let merged = defs.gunfit_accuracy_defs();
+        assert_eq!(merged.len(), reference.len());
+
+        let mut nagato = Ship::default();
+        nagato.ship_type = ShipType::BB;
+        nagato.ctype = ctype!("長門型");
+        nagato.gears.push(Gear {
+            gear_id: gear_id!("46cm三連装砲"),
+            ..Default::default()
+        });
+
+        let info = defs.get_gunfit_accuracy_info(&nagato, true);
+        assert_eq!(info.value, -7.0 + 3.0);
+
+        let mut kongou = nagato.clone();
+        kongou.ctype = ctype!("金剛型");
+        assert_eq!(defs.get_gunfit_accuracy(&kongou, false), -10.0);
+        assert_eq!(defs.get_gunfit_accuracy(&kongou, true), 0.0);
+    }
 }
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// 戦闘の勝敗
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Tsify,
+)]
+pub enum BattleRank {
+    /// 完全勝利S
+    SS,
+    S,
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl BattleRank {
+    pub fn is_s_or_better(self) -> bool {
+        matches!(self, Self::SS | Self::S)
+    }
+
+    pub fn is_victory(self) -> bool {
+        self <= Self::B
+    }
+}
+
+/// 勝敗判定に使う片方の艦隊の戦闘前後の耐久
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BattleRankSide {
+    pub ship_count: usize,
+    pub sunk_count: usize,
+    pub flagship_sunk: bool,
+    /// 戦闘前の耐久の合計
+    pub hp_before: u32,
+    /// 戦闘で失った耐久の合計
+    pub hp_lost: u32,
+}
+
+impl BattleRankSide {
+    /// `hps`は戦闘前と戦闘後の耐久の組で、先頭を旗艦とする
+    ///
+    /// 戦闘前に撃沈されている艦は含めない
+    pub fn new(hps: impl IntoIterator<Item = (u16, u16)>) -> Self {
+        hps.into_iter()
+            .filter(|(before, _)| *before > 0)
+            .enumerate()
+            .fold(Self::default(), |mut acc, (index, (before, after))| {
+                let sunk = after == 0;
+
+                acc.ship_count += 1;
+                acc.sunk_count += sunk as usize;
+                acc.flagship_sunk |= index == 0 && sunk;
+                acc.hp_before += before as u32;
+                acc.hp_lost += before.saturating_sub(after) as u32;
+                acc
+            })
+    }
+
+    /// 戦果ゲージ (%)
+    pub fn damage_ratio(&self) -> u32 {
+        if self.hp_before == 0 {
+            return 0;
+        }
+
+        self.hp_lost * 100 / self.hp_before
+    }
+
+    fn all_sunk(&self) -> bool {
+        self.ship_count > 0 && self.sunk_count == self.ship_count
+    }
+}
+
+impl BattleRank {
+    /// 通常戦闘の勝敗判定
+    ///
+    /// A勝利に必要な撃沈数は敵艦隊の2/3(切り捨て)、E敗北は自艦隊の半数以上が撃沈された場合とする
+    pub fn new(player: &BattleRankSide, enemy: &BattleRankSide) -> Self {
+        let player_ratio = player.damage_ratio();
+        let enemy_ratio = enemy.damage_ratio();
+
+        if player.sunk_count == 0 {
+            if enemy.all_sunk() {
+                return if player.hp_lost == 0 {
+                    Self::SS
+                } else {
+                    Self::S
+                };
+            }
+
+            let required_for_a = (enemy.ship_count * 2 / 3).max(1);
+
+            if enemy.ship_count > 1 && enemy.sunk_count >= required_for_a {
+                return Self::A;
+            }
+        }
+
+        if enemy.flagship_sunk && player.sunk_count < enemy.sunk_count {
+            return Self::B;
+        }
+
+        if enemy_ratio * 2 > player_ratio * 5 {
+            return Self::B;
+        }
+
+        if enemy_ratio > player_ratio {
+            return Self::C;
+        }
+
+        if player.sunk_count > 0 && player.sunk_count * 2 >= player.ship_count {
+            return Self::E;
+        }
+
+        Self::D
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_battle_rank() {
+        let side = |hps: &[(u16, u16)]| BattleRankSide::new(hps.iter().copied());
+
+        let full = side(&[(40, 40), (40, 40), (40, 40)]);
+        let damaged = side(&[(40, 30), (40, 40), (40, 40)]);
+        let sunk6 = side(&[(10, 0); 6]);
+
+        assert_eq!(BattleRank::new(&full, &sunk6), BattleRank::SS);
+        assert_eq!(BattleRank::new(&damaged, &sunk6), BattleRank::S);
+
+        let four_sunk = side(&[(10, 5), (10, 10), (10, 0), (10, 0), (10, 0), (10, 0)]);
+        assert_eq!(BattleRank::new(&damaged, &four_sunk), BattleRank::A);
+
+        let flagship_sunk = side(&[(10, 0), (10, 10), (10, 10), (10, 10), (10, 10), (10, 10)]);
+        assert_eq!(BattleRank::new(&damaged, &flagship_sunk), BattleRank::B);
+
+        let scratched = side(&[(100, 99)]);
+        assert_eq!(BattleRank::new(&full, &scratched), BattleRank::B);
+        assert_eq!(BattleRank::new(&full, &side(&[(100, 100)])), BattleRank::D);
+        assert_eq!(
+            BattleRank::new(&damaged, &side(&[(100, 80)])),
+            BattleRank::C
+        );
+
+        let lost = side(&[(40, 0), (40, 40)]);
+        assert_eq!(BattleRank::new(&lost, &side(&[(100, 100)])), BattleRank::E);
+
+        assert!(BattleRank::SS.is_s_or_better());
+        assert!(BattleRank::B.is_victory());
+        assert!(!BattleRank::C.is_victory());
+    }
+}
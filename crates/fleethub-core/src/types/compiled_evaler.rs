@@ -26,7 +26,8 @@ impl CompiledEvaler {
         }
 
         let parser = Parser::new();
-        let mut slab = Slab::new();
+        // 既定の容量では長い式を扱えない
+        let mut slab = Slab::with_capacity(expr.len().max(64));
 
         let instruction = parser
             .parse(expr.as_str(), &mut slab.ps)?
@@ -51,6 +52,10 @@ impl CompiledEvaler {
     pub fn is_empty(&self) -> bool {
         self.expr.is_empty()
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
 }
 
 impl Clone for CompiledEvaler {
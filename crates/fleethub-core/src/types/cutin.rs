@@ -101,6 +101,33 @@ pub enum FleetCutin {
     Yamato3ShipCutin,
     QueenElizabethClassCutin,
     RichelieuClassCutin,
+    /// マスターデータで追加された艦隊特殊攻撃
+    Other,
+}
+
+/// 艦隊特殊攻撃の定義を識別するタグ
+///
+/// 組み込みの種別にないものは`Other`として扱う
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
+#[serde(untagged)]
+pub enum FleetCutinTag {
+    Known(FleetCutin),
+    Other(String),
+}
+
+impl FleetCutinTag {
+    pub fn cutin(&self) -> FleetCutin {
+        match self {
+            Self::Known(cutin) => *cutin,
+            Self::Other(_) => FleetCutin::Other,
+        }
+    }
+}
+
+impl From<FleetCutin> for FleetCutinTag {
+    fn from(value: FleetCutin) -> Self {
+        Self::Known(value)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use super::gear_id;
+
+/// 応急修理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum DamageControl {
+    /// 応急修理要員
+    Personnel,
+    /// 応急修理女神
+    Goddess,
+}
+
+impl DamageControl {
+    pub fn from_gear_id(gear_id: u16) -> Option<Self> {
+        match gear_id {
+            gear_id!("応急修理要員") => Some(Self::Personnel),
+            gear_id!("応急修理女神") => Some(Self::Goddess),
+            _ => None,
+        }
+    }
+
+    /// 発動後の耐久
+    pub fn revived_hp(self, max_hp: u16) -> u16 {
+        match self {
+            Self::Personnel => (max_hp / 5).max(1),
+            Self::Goddess => max_hp,
+        }
+    }
+
+    /// 燃料と弾薬も全回復する
+    pub fn restores_supplies(self) -> bool {
+        self == Self::Goddess
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_revived_hp() {
+        assert_eq!(DamageControl::Personnel.revived_hp(37), 7);
+        assert_eq!(DamageControl::Personnel.revived_hp(3), 1);
+        assert_eq!(DamageControl::Goddess.revived_hp(37), 37);
+    }
+}
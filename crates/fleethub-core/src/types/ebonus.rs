@@ -7,7 +7,7 @@ use wasm_bindgen::prelude::*;
 use crate::{
     gear::Gear,
     gear_array::GearArray,
-    master_data::MasterShip,
+    master_data::{MasterEBonuses, MasterShip},
     types::{ctype, gear_id, ShipAttr, SpeedGroup},
 };
 
@@ -243,16 +243,15 @@ fn get_speed_synergy(
     }
 }
 
-fn get_aerial_power(ship: &MasterShip, gears: &GearArray) -> i16 {
-    let ship_input = ShipInput::new(ship);
-
+/// 艦載機の雷装と爆装のボーナスは搭載している機体のうち最小のものを適用する
+fn get_aerial_power<F>(gears: &GearArray, create_ebonuses: F) -> i16
+where
+    F: Fn(Vec<&Gear>) -> EBonuses,
+{
     let plane_bonuses = gears
         .values()
         .filter(|gear| gear.has_proficiency())
-        .map(|gear| {
-            let gears_input = GearVecInput(vec![GearInput::new(gear)]);
-            create_equipment_bonuses(ship_input.clone(), gears_input)
-        })
+        .map(|gear| create_ebonuses(vec![gear]))
         .collect::<Vec<_>>();
 
     let min_torpedo = plane_bonuses
@@ -272,10 +271,9 @@ fn get_aerial_power(ship: &MasterShip, gears: &GearArray) -> i16 {
     let other = gears
         .values()
         .filter(|gear| !gear.has_proficiency())
-        .map(GearInput::new)
         .collect::<Vec<_>>();
 
-    let other_bonus = create_equipment_bonuses(ship_input, GearVecInput(other));
+    let other_bonus = create_ebonuses(other);
 
     min_torpedo + min_bombing + other_bonus.torpedo + other_bonus.bombing
 }
@@ -286,8 +284,22 @@ impl EBonuses {
         let ship_input = ShipInput::new(ship);
         let gears_input = GearVecInput(gears.values().map(GearInput::new).collect::<Vec<_>>());
 
-        let mut ebonuses = create_equipment_bonuses(ship_input, gears_input);
-        ebonuses.aerial_power = get_aerial_power(ship, gears);
+        let mut ebonuses = create_equipment_bonuses(ship_input.clone(), gears_input);
+        ebonuses.aerial_power = get_aerial_power(gears, |gears| {
+            let gears_input = GearVecInput(gears.into_iter().map(GearInput::new).collect());
+            create_equipment_bonuses(ship_input.clone(), gears_input)
+        });
+        ebonuses.speed = get_speed_bonus(ship, gears);
+
+        ebonuses
+    }
+
+    /// マスターデータの装備ボーナスの定義から計算する
+    pub fn from_master(ship: &MasterShip, gears: &GearArray, master: &MasterEBonuses) -> Self {
+        let eval = |gears: Vec<&Gear>| master.eval(ship, &gears);
+
+        let mut ebonuses = eval(gears.values().collect());
+        ebonuses.aerial_power = get_aerial_power(gears, eval);
         ebonuses.speed = get_speed_bonus(ship, gears);
 
         ebonuses
@@ -1,3 +1,4 @@
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 use tsify::Tsify;
@@ -28,3 +29,153 @@ impl Engagement {
         }
     }
 }
+
+/// 交戦形態の発生率
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(default)]
+pub struct EngagementRates {
+    pub green_t: f64,
+    pub parallel: f64,
+    pub head_on: f64,
+    pub red_t: f64,
+    /// 自艦隊に彩雲を装備している場合のT不利の発生率の倍率
+    ///
+    /// 指定しない場合は彩雲による補正を行わない
+    pub saiun_red_t_modifier: Option<f64>,
+}
+
+impl Default for EngagementRates {
+    fn default() -> Self {
+        Self {
+            green_t: 0.15,
+            parallel: 0.45,
+            head_on: 0.3,
+            red_t: 0.1,
+            saiun_red_t_modifier: None,
+        }
+    }
+}
+
+impl EngagementRates {
+    /// 指定した交戦形態のみが発生する
+    pub fn only(engagement: Engagement) -> Self {
+        let mut rates = Self {
+            green_t: 0.0,
+            parallel: 0.0,
+            head_on: 0.0,
+            red_t: 0.0,
+            ..Default::default()
+        };
+        *rates.get_mut(engagement) = 1.0;
+        rates
+    }
+
+    pub fn get(&self, engagement: Engagement) -> f64 {
+        match engagement {
+            Engagement::GreenT => self.green_t,
+            Engagement::Parallel => self.parallel,
+            Engagement::HeadOn => self.head_on,
+            Engagement::RedT => self.red_t,
+        }
+    }
+
+    fn get_mut(&mut self, engagement: Engagement) -> &mut f64 {
+        match engagement {
+            Engagement::GreenT => &mut self.green_t,
+            Engagement::Parallel => &mut self.parallel,
+            Engagement::HeadOn => &mut self.head_on,
+            Engagement::RedT => &mut self.red_t,
+        }
+    }
+
+    /// 彩雲の有無を反映した発生率、減少分は正規化により他の交戦形態に配分される
+    pub fn with_saiun(&self, has_saiun: bool) -> Self {
+        let mut rates = *self;
+
+        if let Some(modifier) = self.saiun_red_t_modifier.filter(|_| has_saiun) {
+            rates.red_t *= modifier;
+        }
+
+        rates
+    }
+
+    /// 発生率の合計で正規化した値
+    pub fn normalized(&self) -> impl Iterator<Item = (Engagement, f64)> + '_ {
+        let total = self.iter().map(|(_, rate)| rate.max(0.0)).sum::<f64>();
+
+        self.iter().map(move |(engagement, rate)| {
+            let rate = if total > 0.0 {
+                rate.max(0.0) / total
+            } else {
+                (engagement == Engagement::default()).into()
+            };
+
+            (engagement, rate)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Engagement, f64)> + '_ {
+        use strum::IntoEnumIterator;
+        Engagement::iter().map(|engagement| (engagement, self.get(engagement)))
+    }
+
+    /// 発生率の合計で正規化して選択する
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Engagement {
+        let total = self.iter().map(|(_, rate)| rate.max(0.0)).sum::<f64>();
+
+        if total <= 0.0 {
+            return Engagement::default();
+        }
+
+        let mut value = rng.gen_range(0.0..total);
+
+        for (engagement, rate) in self.iter() {
+            let rate = rate.max(0.0);
+
+            if value < rate {
+                return engagement;
+            }
+
+            value -= rate;
+        }
+
+        Engagement::RedT
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::rng;
+
+    #[test]
+    fn test_engagement_rates() {
+        let rates = EngagementRates::only(Engagement::HeadOn);
+        let mut rng = rng(0);
+
+        assert_eq!(rates.get(Engagement::HeadOn), 1.0);
+        assert!((0..100).all(|_| rates.choose(&mut rng) == Engagement::HeadOn));
+
+        let total = EngagementRates::default()
+            .iter()
+            .map(|(_, rate)| rate)
+            .sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        assert_eq!(EngagementRates::default().with_saiun(true).red_t, 0.1);
+
+        let saiun = EngagementRates {
+            saiun_red_t_modifier: Some(0.5),
+            ..Default::default()
+        }
+        .with_saiun(true);
+        assert_eq!(saiun.red_t, 0.05);
+
+        let red_t = saiun
+            .normalized()
+            .find(|(engagement, _)| *engagement == Engagement::RedT)
+            .map(|(_, rate)| rate)
+            .unwrap();
+        assert!((red_t - 0.05 / 0.95).abs() < 1e-9);
+    }
+}
@@ -100,3 +100,10 @@ impl Default for Formation {
         Self::Single(Default::default())
     }
 }
+
+/// 陣形の発生率
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct FormationRate {
+    pub formation: Formation,
+    pub rate: f64,
+}
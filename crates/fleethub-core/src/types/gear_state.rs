@@ -62,3 +62,16 @@ impl IntoIterator for GearVecState {
         [g1, g2, g3, g4, g5, gx].into_iter()
     }
 }
+
+impl From<[Option<GearState>; 6]> for GearVecState {
+    fn from([g1, g2, g3, g4, g5, gx]: [Option<GearState>; 6]) -> Self {
+        Self {
+            g1,
+            g2,
+            g3,
+            g4,
+            g5,
+            gx,
+        }
+    }
+}
@@ -1,4 +1,30 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// 戦闘の種類
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub enum BattleType {
+    /// 昼戦のみ
+    #[default]
+    Day,
+    /// 昼戦後に夜戦
+    DayAndNight,
+    /// 夜戦マス
+    Night,
+}
+
+impl BattleType {
+    pub fn has_day_battle(self) -> bool {
+        matches!(self, Self::Day | Self::DayAndNight)
+    }
+
+    pub fn has_night_battle(self) -> bool {
+        matches!(self, Self::DayAndNight | Self::Night)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
 pub enum Phase {
     JetAssault,
     AerialCombat,
@@ -9,7 +35,7 @@ pub enum Phase {
     NightCombat,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
 pub enum DayCombatRound {
     Main1,
     Main2,
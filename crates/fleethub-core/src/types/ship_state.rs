@@ -3,7 +3,7 @@ use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 
-use super::{CustomPowerModifiers, GearVecState};
+use super::{CustomPowerModifiers, DamageControl, GearVecState};
 
 #[derive(Debug, Default, Clone, Hash, Serialize, Deserialize, Tsify)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
@@ -79,6 +79,10 @@ pub struct ShipState {
 
     #[serde(default)]
     pub custom_power_mods: CustomPowerModifiers,
+
+    /// 装備とは別に持たせる応急修理
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub damage_control: Option<DamageControl>,
 }
 
 #[derive(
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// 支援艦隊の種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum SupportType {
+    /// 砲撃支援
+    Shelling,
+    /// 航空支援
+    Aerial,
+    /// 長距離雷撃支援
+    Torpedo,
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use fleethub_core::{
+    gear::Gear,
+    master_data::MasterShip,
+    types::{EBonuses, GearState},
+};
+use rand::prelude::*;
+use serde_json::{Value, json};
+
+use common::*;
+
+fn ship_input(ship: &MasterShip) -> Value {
+    json!({
+        "ship_id": ship.ship_id,
+        "ctype": ship.ctype,
+        "stype": ship.stype,
+        "yomi": ship.yomi,
+    })
+}
+
+fn gear_input(gear: &Gear) -> Value {
+    json!({
+        "gear_id": gear.gear_id,
+        "types": gear.types,
+        "firepower": gear.firepower,
+        "torpedo": gear.torpedo,
+        "anti_air": gear.anti_air,
+        "armor": gear.armor,
+        "evasion": gear.evasion,
+        "asw": gear.asw,
+        "los": gear.los,
+        "bombing": gear.bombing,
+        "accuracy": gear.accuracy,
+        "range": gear.range,
+        "radius": gear.radius,
+        "stars": gear.stars,
+        "ace": gear.ace(),
+    })
+}
+
+/// equipment-bonus モジュールで全ての組み合わせを1度に計算する
+fn equipment_bonus_module(cases: &[(&MasterShip, Vec<Gear>)]) -> Vec<EBonuses> {
+    let input = cases
+        .iter()
+        .map(|(ship, gears)| {
+            json!([
+                ship_input(ship),
+                gears.iter().map(gear_input).collect::<Vec<_>>()
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let code = r#"
+        const { createEquipmentBonuses } = require("equipment-bonus");
+        const input = JSON.parse(require("fs").readFileSync(0, "utf8"));
+        const result = input.map(([ship, gears]) => createEquipmentBonuses(ship, gears));
+        console.log(JSON.stringify(result));
+    "#;
+
+    let mut child = Command::new("node")
+        .arg("-e")
+        .arg(code)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("node が必要");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(serde_json::to_string(&input).unwrap().as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "equipment-bonus モジュールが必要: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// マスターデータの装備ボーナスの定義が equipment-bonus モジュールと同じ値を返すか
+///
+/// 実行には実際のマスターデータと、node から読み込める equipment-bonus モジュールが必要
+#[test]
+#[ignore = "node と equipment-bonus モジュールが必要"]
+fn test_master_ebonuses_conformance() {
+    let master_data = FH_CORE.master_data();
+    let master_ebonuses = master_data
+        .ebonuses
+        .as_ref()
+        .expect("マスターデータに装備ボーナスの定義がない");
+
+    let ships = master_data
+        .ships
+        .iter()
+        .filter(|ship| !ship.is_abyssal())
+        .collect::<Vec<_>>();
+    let gears = master_data
+        .gears
+        .iter()
+        .filter(|gear| !gear.is_abyssal())
+        .collect::<Vec<_>>();
+
+    let create_gear = |gear_id: u16, stars: u8| {
+        FH_CORE
+            .create_gear(Some(GearState {
+                gear_id,
+                stars: Some(stars),
+                ..Default::default()
+            }))
+            .unwrap()
+    };
+
+    let mut count = 0;
+    let mut mismatches = vec![];
+
+    let mut compare = |cases: Vec<(&MasterShip, Vec<Gear>)>| {
+        count += cases.len();
+
+        cases
+            .iter()
+            .zip(equipment_bonus_module(&cases))
+            .for_each(|((ship, gears), expected)| {
+                let actual = master_ebonuses.eval(ship, &gears.iter().collect::<Vec<_>>());
+
+                let actual = serde_json::to_value(actual).unwrap();
+                let expected = serde_json::to_value(expected).unwrap();
+
+                if actual != expected {
+                    let gears = gears
+                        .iter()
+                        .map(|gear| (gear.gear_id, gear.stars))
+                        .collect::<Vec<_>>();
+                    mismatches.push(format!(
+                        "{} {gears:?}: {actual} != {expected}",
+                        ship.ship_id
+                    ));
+                }
+            });
+    };
+
+    // 全ての艦と装備の組み合わせ
+    ships.chunks(20).for_each(|chunk| {
+        compare(
+            chunk
+                .iter()
+                .flat_map(|ship| {
+                    gears
+                        .iter()
+                        .map(|gear| (*ship, vec![create_gear(gear.gear_id, 0)]))
+                })
+                .collect(),
+        )
+    });
+
+    // 複数の装備と改修値の条件を試すためのランダムな組み合わせ
+    let mut rng = rng(0);
+    let random = (0..20000)
+        .map(|_| {
+            let ship = *ships.choose(&mut rng).unwrap();
+            let loadout = (0..rng.gen_range(2..=5))
+                .map(|_| {
+                    let gear = gears.choose(&mut rng).unwrap();
+                    create_gear(gear.gear_id, rng.gen_range(0..=10))
+                })
+                .collect::<Vec<_>>();
+
+            (ship, loadout)
+        })
+        .collect();
+
+    compare(random);
+
+    assert!(
+        mismatches.is_empty(),
+        "{} / {} 件が一致しない\n{}",
+        mismatches.len(),
+        count,
+        mismatches
+            .iter()
+            .take(50)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
@@ -15,6 +15,7 @@ fn assert_fleet_cutin(
     expected: impl IntoIterator<Item = (usize, f64)>,
 ) {
     let expected_effect = FleetCutinEffect {
+        tag: cutin.into(),
         cutin,
         attacks: expected
             .into_iter()
@@ -30,7 +30,7 @@ fn get_special_enemy_mods(
         gx = gx
     };
 
-    ship.special_enemy_mods(special_enemy_type, attack_type)
+    common::battle_definitions().get_special_enemy_mods(&ship, special_enemy_type, attack_type)
 }
 
 macro_rules! table {
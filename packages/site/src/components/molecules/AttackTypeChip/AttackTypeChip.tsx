@@ -94,6 +94,7 @@ const FLEET_CUTINS = [
   "RichelieuClassCutin",
   "Yamato2ShipCutin",
   "Yamato3ShipCutin",
+  "Other",
 ] as const;
 
 const AttackTypeChip = React.forwardRef<HTMLDivElement, Props>(
@@ -24,9 +24,11 @@ const FleetCutinAnalysisTable: React.FCX<Props> = ({ className, data }) => {
   return (
     <Stack className={className} gap={2}>
       {data.map((report) => (
-        <div key={`${report.cutin}-${report.formation}`}>
+        <div key={`${report.tag}-${report.formation}`}>
           <Typography variant="subtitle1">
-            {t(`FleetCutin.${report.cutin}`)}{" "}
+            {report.cutin === "Other"
+              ? report.tag
+              : t(`FleetCutin.${report.cutin}`)}{" "}
             {t(`Formation.${report.formation}`)} {t(`ProcRate`)}{" "}
             {toPercent(report.rate)}
           </Typography>
@@ -5,7 +5,7 @@ import { Ship } from "fleethub-core";
 import React from "react";
 import { useTranslation } from "react-i18next";
 
-import { useShipName } from "../../../hooks";
+import { useFhCore, useShipName } from "../../../hooks";
 import { ShipEntity } from "../../../store";
 import { numstr } from "../../../utils";
 import { LabeledValue } from "../../atoms";
@@ -51,6 +51,7 @@ const ShipHeader: React.FCX<ShipHeaderProps> = ({
   onRemove,
 }) => {
   const { t } = useTranslation("common");
+  const { analyzer } = useFhCore();
 
   const displayName = useShipName(ship.ship_id, ship.is_abyssal());
 
@@ -70,11 +71,11 @@ const ShipHeader: React.FCX<ShipHeaderProps> = ({
           <Stack>
             <LabeledValue
               label={t("day_gunfit_accuracy")}
-              value={numstr(ship.gunfit_accuracy(false))}
+              value={numstr(analyzer.ship_gunfit_accuracy(ship, false))}
             />
             <LabeledValue
               label={t("night_gunfit_accuracy")}
-              value={numstr(ship.gunfit_accuracy(true))}
+              value={numstr(analyzer.ship_gunfit_accuracy(ship, true))}
             />
             <LabeledValue
               label={t("basic_accuracy_term")}